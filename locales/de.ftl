@@ -0,0 +1,328 @@
+# Deutsche Übersetzung. Fehlende Meldungen erscheinen auf Englisch.
+
+## Upgrade phases
+
+phase-refresh = Aktualisieren
+phase-self-update = Selbstaktualisierung
+phase-upgrade = Upgrade
+phase-casks = Casks
+phase-cleanup = Aufräumen
+phase-refresh-active = Aktualisiere
+phase-self-update-active = Aktualisiere sich selbst
+phase-upgrade-active = Upgrade läuft
+phase-casks-active = Aktualisiere Casks
+phase-tool-active = Aktualisiere { $name } ({ $current }/{ $total })
+phase-cleanup-active = Räume auf
+phase-skipped-condition = Übersprungen (Bedingung)
+phase-starting = Starte
+
+## Timeline entries
+
+timeline-queued = wartend
+timeline-stalled = hängt
+timeline-done = fertig
+timeline-failed = fehlgeschlagen
+timeline-unverified = Signaturprüfung fehlgeschlagen
+
+## spn upgrade
+
+upgrade-skip-native = Überspringe { $name }: wird von den automatischen Systemupdates erledigt
+upgrade-skip-held = Überspringe { $name }: zurückgehalten
+upgrade-skip-fresh = Überspringe { $name }: aktuell, vor { $ago } aktualisiert (min_interval { $interval })
+upgrade-skip-untrusted = Überspringe { $name }: seine sudo-Befehle sind neu oder geändert; bestätige sie mit spn upgrade im Terminal oder mit --accept-new-managers
+upgrade-skip-root = Überspringe { $name }: installiert in ein Home-Verzeichnis und spn läuft als root; run_as_user dafür setzen oder spn über sudo starten
+trust-new-manager = { $name } ist neu oder geändert und führt diese Befehle mit sudo aus:
+trust-prompt = { $name } erlauben, sie auszuführen? [y/N]
+upgrade-skip-offline = Überspringe { $name }: jeder Schritt braucht das Netzwerk (--offline)
+upgrade-hold-os = Halte Kernel-/Systemupdates für { $name } zurück: { $packages } (mit --include-os aktualisieren)
+upgrade-skip-os = Überspringe { $name }: Kernel-/Systemupdates ausstehend ({ $packages }); mit --include-os aktualisieren
+upgrade-none-detected = Auf diesem System wurden keine Paketmanager gefunden.
+upgrade-checked-for = Spine hat gesucht nach: { $managers }
+upgrade-detected = { $count } Paketmanager gefunden: { $managers }
+upgrade-starting = Starte Upgrade...
+upgrade-completed = Upgrade abgeschlossen.
+upgrade-error = Fehler beim Upgrade: { $error }
+upgrade-running = Führe Paketmanager-Upgrades aus...
+upgrade-prompt = Upgrade für { $name } ausführen (y/N)?
+upgrade-skipping = Überspringe { $name }
+spinner-starting = Starte { $name }
+spinner-success = ✓ { $name } erfolgreich abgeschlossen
+spinner-attention = ⚠ { $name } braucht Aufmerksamkeit: { $reason }
+spinner-failed = ✗ { $name } fehlgeschlagen: { $details }
+spinner-unverified = ⚠ { $name } angehalten, Sicherheitswarnung: { $details }
+spinner-unknown = ? { $name } mit unbekanntem Status beendet
+a11y-started = { $name }: GESTARTET
+a11y-step = { $name }: { $step }
+a11y-stalled = { $name }: HÄNGT, seit einer Weile keine Ausgabe
+a11y-success = { $name }: ERFOLGREICH nach { $duration }
+a11y-attention = { $name }: BRAUCHT AUFMERKSAMKEIT, { $reason }
+a11y-failed = { $name }: FEHLGESCHLAGEN nach { $duration }: { $error }
+a11y-unverified = { $name }: SICHERHEITSWARNUNG nach { $duration }, nichts aktualisiert: { $error }
+a11y-unfinished = { $name }: NICHT BEENDET
+
+## Notifications
+
+notify-complete-title = Spine-Update abgeschlossen
+notify-complete-body = Alle Paketmanager wurden erfolgreich aktualisiert.
+notify-failed-title = Spine-Update fehlgeschlagen
+notify-failed-body = Bei den Paketmanager-Updates sind Fehler aufgetreten.
+notify-pending-title =
+    { $count ->
+        [one] 1 Update verfügbar
+       *[other] { $count } Updates verfügbar
+    }
+notify-upgrade-action = Jetzt aktualisieren
+notify-url-title = Spine auf { $host }
+notify-url-run-started = Aktualisiere { $managers }
+notify-url-manager-finished = { $manager }: { $state } nach { $duration }
+notify-url-manager-failed = { $manager } fehlgeschlagen: { $error }
+notify-url-run-finished = { $managers } in { $duration } aktualisiert
+notify-url-run-failed = Fehlgeschlagen: { $failed } (Dauer { $duration })
+
+## Upgrade summary
+
+summary-title = SPINE UPGRADE-ÜBERSICHT
+summary-overall = Gesamtergebnis:
+summary-total = Paketmanager:
+summary-successful = Erfolgreich:
+summary-failed = Fehlgeschlagen:
+summary-unverified-count = Unverifiziert:
+summary-incomplete = Unvollständig:
+summary-details = Einzelergebnisse:
+summary-column-manager = Paketmanager
+summary-column-result = Ergebnis
+summary-column-duration = Dauer
+summary-column-packages = Pakete
+summary-column-phases = Schritte
+summary-success = Erfolgreich
+summary-failure = Fehlgeschlagen
+summary-unfinished = Unvollständig
+summary-unverified = Sicherheitswarnung
+summary-needs-attention = Braucht Aufmerksamkeit
+summary-timeline = Verlauf: { $timeline }
+summary-changes = Pakete: { $upgraded } aktualisiert, { $installed } installiert, { $removed } entfernt
+summary-deferred = Für einen interaktiven Lauf aufgehoben, da sie ein Passwort verlangen: { $casks }
+summary-needs-review = Manuelle Prüfung nötig, zuerst die Build-Dateien ansehen: { $packages }
+summary-kept-back = Zurückgehalten: { $packages }
+summary-attention = Braucht Aufmerksamkeit, unverändert gelassen: { $reason }
+summary-error = Fehler: { $error }
+cause-likely = Wahrscheinliche Ursache: { $title }. { $hint }
+cause-fix = Lösungsvorschlag: { $command }
+diagnosis-dpkg-interrupted = Unterbrochener dpkg-Lauf
+diagnosis-dpkg-interrupted-hint = Eine frühere Installation wurde abgebrochen und hat Pakete halb konfiguriert zurückgelassen. Konfiguriere sie fertig und versuche es dann erneut.
+diagnosis-apt-lock = apt-Sperre belegt
+diagnosis-apt-lock-hint = Ein anderer apt- oder dpkg-Prozess, oft unattended-upgrades, hält die Sperre. Warte, bis er fertig ist, und versuche es dann erneut.
+diagnosis-brew-shallow-tap = Flacher Homebrew-Tap
+diagnosis-brew-shallow-tap-hint = Ein Tap wurde ohne seine Historie geklont, daher kann brew update ihn nicht aktualisieren. Hole die vollständige Historie und versuche es dann erneut.
+diagnosis-brew-broken-checkout = Beschädigter Homebrew-Checkout
+diagnosis-brew-broken-checkout-hint = Homebrews Git-Checkout oder einer seiner Taps ist beschädigt. Setze sie auf den Upstream-Stand zurück und versuche es dann erneut.
+diagnosis-cargo-login = Anmeldung an der Cargo-Registry nötig
+diagnosis-cargo-login-hint = Die Registry braucht ein Token, das fehlt oder nicht mehr gültig ist. Melde dich erneut an und versuche es dann erneut.
+diagnosis-arch-keyring = Veralteter Arch-Schlüsselbund
+diagnosis-arch-keyring-hint = Pakete sind mit Schlüsseln signiert, denen der lokale Schlüsselbund noch nicht vertraut. Aktualisiere zuerst den Schlüsselbund und versuche es dann erneut.
+diagnosis-disk-full = Festplatte voll
+diagnosis-disk-full-hint = Schaffe Speicherplatz (z. B. Paket-Caches leeren) und versuche es dann erneut.
+diagnosis-database-locked = Paketdatenbank gesperrt
+diagnosis-database-locked-hint = Ein anderer Paketvorgang läuft. Warte, bis er fertig ist, und versuche es dann erneut.
+diagnosis-privileges = Fehlende Berechtigungen
+diagnosis-privileges-hint = Führe vor dem Aktualisieren 'sudo -v' aus oder richte für diesen Paketmanager sudo ohne Passwort ein (oder doas mit nopass oder persist).
+diagnosis-network = Netzwerkproblem
+diagnosis-network-hint = Prüfe deine Internetverbindung oder die Mirror-Konfiguration und versuche es dann erneut.
+diagnosis-signature = Signaturprüfung fehlgeschlagen
+diagnosis-signature-hint = Aktualisiere die Signaturschlüssel der Paketquellen und versuche es dann erneut.
+diagnosis-dependency-conflict = Abhängigkeitskonflikt
+diagnosis-dependency-conflict-hint = Löse die widersprüchlichen Pakete von Hand auf oder halte diesen Paketmanager an, bis upstream es behebt.
+diagnosis-out-of-memory = Beendet (möglicherweise kein Speicher mehr)
+diagnosis-out-of-memory-hint = Schließe speicherhungrige Anwendungen und versuche es erneut.
+diagnosis-waiting-for-input = Wartet auf Eingabe
+diagnosis-waiting-for-input-hint = Der Befehl hat eine Frage gestellt. Führe ihn von Hand aus oder füge noninteractive_args für ihn hinzu.
+diagnosis-timed-out = Zeitüberschreitung
+diagnosis-timed-out-hint = Der Befehl hat zu lange gedauert. Versuche es erneut, wenn das Netzwerk weniger ausgelastet ist.
+diagnosis-unknown = Unbekannt
+diagnosis-unknown-hint = Öffne die Logs, um zu sehen, was schiefgelaufen ist.
+recovery-retried = { $cause } behoben: { $command } ausgeführt und erneut versucht
+recovery-failed = Behebung von { $cause } versucht, aber { $command } schlug fehl
+summary-some-failed = Einige Paketmanager konnten nicht vollständig aktualisiert werden.
+summary-security-warning = Einige Paketquellen haben die Signaturprüfung nicht bestanden, daher wurde aus ihnen nichts aktualisiert.
+summary-allow-unverified-hint = Repariere die Schlüssel der Paketquellen oder führe 'spn upgrade --allow-unverified' aus, um trotzdem zu aktualisieren.
+summary-retry-hint = Prüfe die Fehlerdetails oben und führe 'spn upgrade' gegebenenfalls erneut aus.
+summary-sudo-hint = Eventuell müssen die fehlgeschlagenen Paketmanager manuell mit sudo ausgeführt werden.
+summary-all-succeeded = Alle Paketmanager wurden erfolgreich aktualisiert!
+summary-up-to-date = Dein System ist jetzt auf dem neuesten Stand.
+summary-kept-back-hint = { $count } Pakete wurden zurückgehalten (siehe oben); 'spn upgrade --full-upgrade' aktualisiert die durch Abhängigkeiten blockierten.
+
+## Service restarts
+
+restart-title = Dienste:
+restart-none = Keine Dienste verwenden veraltete Bibliotheken.
+restart-restarted = ✓ Neu gestartet: { $services }
+restart-failed = ✗ { $service } konnte nicht neu gestartet werden: { $error }
+restart-skipped = Verwenden weiter veraltete Bibliotheken (durch allow/deny ausgenommen): { $services }
+restart-check-failed = Warnung: Neu zu startende Dienste konnten nicht ermittelt werden: { $error }
+
+## Herunterfahren und Neustart nach dem Upgrade
+
+power-shutdown-countdown = Herunterfahren in { $seconds } s. Enter oder Strg+C bricht ab.
+power-reboot-countdown = Neustart in { $seconds } s. Enter oder Strg+C bricht ab.
+power-cancelled = Abgebrochen; der Rechner bleibt an.
+power-shutdown-skipped = Kein Herunterfahren, weil { $managers } fehlgeschlagen ist (--force fährt trotzdem herunter).
+power-reboot-skipped = Kein Neustart, weil { $managers } fehlgeschlagen ist (--force startet trotzdem neu).
+power-failed = Herunterfahren oder Neustart fehlgeschlagen: { $error }
+
+## Run statistics
+
+stats-summary = Läufe: { $runs } | Erfolgsquote: { $rate }% | Ø Dauer: { $duration }
+stats-last-failure = Letzter Fehler: { $error }
+stats-none = Keiner
+stats-no-runs = Noch keine Läufe aufgezeichnet
+
+## TUI
+
+tui-list-title = Paketmanager - Spine
+tui-list-title-next = Paketmanager - Spine - nächstes Auto-Update in { $countdown } ({ $time })
+tui-pending = Wartend
+tui-stalled = ⚠ Hängt ({ $operation })
+tui-complete = ✓ Fertig
+tui-attention = ⚠ Braucht Aufmerksamkeit
+tui-failed = ✗ Fehlgeschlagen
+tui-unverified = ⚠ Sicherheitswarnung
+tui-all-done = Alle Vorgänge abgeschlossen! 'q' zum Beenden, oder navigieren, um Details zu sehen.
+tui-status = Status
+tui-power-shutdown = Herunterfahren in { $seconds } s - 'c' bricht ab
+tui-power-reboot = Neustart in { $seconds } s - 'c' bricht ab
+tui-help = Hilfe
+tui-statistics = Statistik
+tui-config = Konfiguration
+tui-logs = Protokoll
+tui-help-navigate = Navigieren: ↑↓/j k
+tui-help-start = Starten: Leertaste
+tui-help-detail = Details: Enter
+tui-help-back = Zurück: Esc/h/←
+tui-help-logs = Protokoll: l
+tui-help-quit = Beenden: q
+tui-help-stalled = Hängt: Warten w | Beenden x
+tui-help-answer = Antworten y/n/Enter
+tui-help-input = Eingabe: i
+tui-help-view = Farben c · Umbruch W · Zeilennr. N · Nächster Fehler e · Folgen f
+tui-help-export = Kopieren y · Speichern S
+tui-help-scroll = Blättern: ↑↓/jk PgUp/PgDn Home/End ({ $position }/{ $total })
+tui-help-help = Hilfe: ?
+tui-help-resize = Größe: +/-
+tui-help-sort = Sortieren: s
+tui-help-palette = Befehle: :
+tui-help-scroll-keys = Blättern: ↑↓/jk PgUp/PgDn Home/End
+tui-help-close = Beliebige Taste zum Schließen
+tui-keys-title = Tasten
+tui-keys-everywhere = Überall
+tui-keys-list = Manager-Liste
+tui-keys-detail = Manager-Details
+tui-keys-logs = Protokoll
+tui-keys-commands = Befehle: : und einen Befehl eingeben, Tab ergänzt, Enter führt aus
+tui-command-quit = Die TUI verlassen
+tui-command-retry = Einen fehlgeschlagenen Manager erneut ausführen
+tui-command-cancel = Den laufenden Befehl eines Managers beenden
+tui-command-filter = Nur Manager zeigen, deren Name oder Status den Text enthält
+tui-command-logs = Das Protokoll eines Managers öffnen
+tui-command-help = Diese Hilfe anzeigen
+tui-command-target = Ohne Manager gilt der ausgewählte oder geöffnete.
+tui-palette-title = Befehl
+tui-palette-unknown = Unbekannter Befehl: { $command } (? listet sie auf)
+tui-palette-no-manager = Kein Manager namens { $name }
+tui-palette-not-failed = { $name } ist nicht fehlgeschlagen, es gibt nichts zu wiederholen
+tui-palette-retrying = { $name } wird wiederholt
+tui-palette-not-running = { $name } läuft nicht
+tui-palette-cancelling = Befehl von { $name } wird beendet
+tui-list-filter = Filter: { $filter }
+tui-list-no-match = Keine Manager passen zu "{ $filter }" - :filter zeigt wieder alle
+tui-list-sort = sortiert nach { $sort }
+tui-sort-config = Reihenfolge der Ausführung
+tui-sort-name = Name
+tui-sort-status = Status
+tui-sort-duration = Dauer
+tui-sort-pending = ausstehenden Updates
+tui-config-name = Name: { $value }
+tui-config-check = Prüfbefehl: { $value }
+tui-config-unset = –
+tui-status-line = Status: { $status }
+tui-status-stalled = ⚠ Hängt bei „{ $operation }“ - schon länger keine Ausgabe, wartet eventuell auf eine Eingabe
+tui-status-success = ✓ Alle Vorgänge erfolgreich abgeschlossen
+tui-status-attention = ⚠ Braucht Aufmerksamkeit, unverändert gelassen: { $reason }
+tui-status-failed = ✗ Fehlgeschlagen - { $details }
+tui-status-unverified = ⚠ Sicherheitswarnung - { $details }
+tui-timeline = Verlauf: { $timeline }
+tui-phases = Phasen:
+tui-tools = Werkzeuge:
+tui-logs-title = { $name } - Live-Protokoll
+tui-logs-title-dropped = { $name } - Live-Protokoll ({ $dropped } ältere Zeilen verworfen)
+tui-logs-not-started = Prozess noch nicht gestartet...
+tui-logs-no-output = Noch keine Ausgabe...
+tui-logs-success = Befehl erfolgreich abgeschlossen - keine Ausgabe
+tui-logs-no-errors = Keine Fehlerzeilen gefunden
+tui-logs-following = Folgt
+tui-logs-copied = Logs von { $name } in die Zwischenablage kopiert
+tui-logs-copy-failed = Kopieren fehlgeschlagen: { $error }
+tui-logs-saved = Logs gespeichert unter { $path }
+tui-logs-save-failed = { $path } konnte nicht gespeichert werden: { $error }
+tui-input-title = An stdin senden (Enter zum Senden, Esc zum Abbrechen)
+tui-save-title = Logs speichern unter (Enter zum Speichern, Esc zum Abbrechen)
+
+## spn list --interactive
+
+list-title = Paketmanager - Leertaste zum Aktivieren oder Deaktivieren
+list-detected = erkannt
+list-detected-version = erkannt ({ $version })
+list-not-installed = nicht installiert
+list-other-system = nicht für dieses System
+list-requires-sudo = Benötigt sudo: { $value }
+list-limited-to = Nur auf: { $value }
+list-help-toggle = Aktivieren/Deaktivieren: Leertaste
+list-enabled = { $name } aktiviert
+list-disabled = { $name } deaktiviert
+list-save-failed = { $name } konnte nicht gespeichert werden: { $error }
+
+## spn status
+
+status-last-run = Letzter Lauf:
+status-result = Ergebnis:
+status-auto-update = Auto-Update:
+status-managers = Paketmanager:
+status-on-hold = Zurückgehalten:
+status-pending = Ausstehend:
+status-never = nie
+status-all-succeeded = ✓ alle { $count } Paketmanager erfolgreich
+status-some-failed = ✗ { $failed } von { $ran } fehlgeschlagen ({ $managers })
+status-pending-count =
+    { $count ->
+        [one] 1 Update
+       *[other] { $count } Updates
+    }, geprüft { $time } ({ $age })
+status-pending-stale =
+    { $count ->
+        [one] 1 Update
+       *[other] { $count } Updates
+    }, geprüft { $time } ({ $age }, veraltet - 'spn check' ausführen)
+status-pending-unknown = unbekannt - 'spn check' ausführen, um es herauszufinden
+status-managers-detected = { $managers } (erkannt { $age })
+status-auto-not-scheduled = ⚠ in der Konfiguration aktiviert, aber nicht eingeplant; 'spn auto --enable' ausführen
+status-auto-off = ✗ aus
+status-auto-daily = täglich um { $time }
+status-auto-interval = alle { $hours } Stunde(n)
+status-auto-weekly = wöchentlich am { $day } um 18:00
+status-auto-drifted = { $schedule } (weicht von der Konfiguration ab; 'spn auto --repair' ausführen)
+status-auto-stale = ⚠ die eingeplanten Jobs stehen nicht mehr in der Konfiguration; 'spn auto --repair' ausführen
+status-auto-next = ✓ { $schedule }, nächster Lauf { $next }
+status-auto-on = ✓ { $schedule }
+status-auto-next-unknown = ✓ { $schedule } (nächster Lauf nicht bestimmbar: { $error })
+bar-checked = Geprüft { $age }
+bar-checked-stale = Geprüft { $age } (veraltet)
+bar-unknown = Ausstehende Updates unbekannt - 'spn check' ausführen
+bar-last-run-ok = Letzter Lauf { $age }: alle erfolgreich
+bar-last-run-failed = Letzter Lauf { $age }: { $managers } fehlgeschlagen
+bar-never-run = Noch nie gelaufen
+bar-on-hold = Zurückgehalten: { $managers }
+bar-upgrade = Jetzt aktualisieren
+bar-check = Nach Updates suchen
+age-just-now = gerade eben
+age-minutes = vor { $count } Min.
+age-hours = vor { $count } Std.
+age-days = vor { $count } T.
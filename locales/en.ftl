@@ -0,0 +1,330 @@
+# Spine's user-facing messages. English is the fallback for every other
+# language, so every message must be defined here.
+
+## Upgrade phases
+
+phase-refresh = Refresh
+phase-self-update = Self-update
+phase-upgrade = Upgrade
+phase-casks = Casks
+phase-cleanup = Cleanup
+phase-refresh-active = Refreshing
+phase-self-update-active = Self-updating
+phase-upgrade-active = Upgrading
+phase-casks-active = Upgrading casks
+phase-tool-active = Upgrading { $name } ({ $current }/{ $total })
+phase-cleanup-active = Cleaning
+phase-skipped-condition = Skipped (condition)
+phase-starting = Starting
+
+## Timeline entries, e.g. "queued 0s → refreshing 1s → done 1m 02s"
+
+timeline-queued = queued
+timeline-stalled = stalled
+timeline-done = done
+timeline-failed = failed
+timeline-unverified = signature check failed
+
+## spn upgrade
+
+upgrade-skip-native = Skipping { $name }: handled by the system's automatic updates
+upgrade-skip-held = Skipping { $name }: on hold
+upgrade-skip-fresh = Skipping { $name }: fresh, upgraded { $ago } ago (min_interval { $interval })
+upgrade-skip-untrusted = Skipping { $name }: its sudo commands are new or changed; accept them by running spn upgrade on a terminal or with --accept-new-managers
+upgrade-skip-root = Skipping { $name }: it installs into a home directory and spn runs as root; set run_as_user for it, or run spn through sudo
+trust-new-manager = { $name } is new or changed in the config and runs these commands with sudo:
+trust-prompt = Allow { $name } to run them? [y/N]
+upgrade-skip-offline = Skipping { $name }: every step needs the network (--offline)
+upgrade-hold-os = Holding kernel/OS updates for { $name }: { $packages } (upgrade with --include-os)
+upgrade-skip-os = Skipping { $name }: kernel/OS updates pending ({ $packages }); upgrade with --include-os
+upgrade-none-detected = No package managers detected on this system.
+upgrade-checked-for = Spine checked for: { $managers }
+upgrade-detected = Detected { $count } package manager(s): { $managers }
+upgrade-starting = Starting upgrade process...
+upgrade-completed = Upgrade process completed.
+upgrade-error = Error during upgrade process: { $error }
+upgrade-running = Running package manager upgrades...
+upgrade-prompt = Run upgrade for { $name } (y/N)?
+upgrade-skipping = Skipping { $name }
+spinner-starting = Starting { $name }
+spinner-success = ✓ { $name } completed successfully
+spinner-attention = ⚠ { $name } needs attention: { $reason }
+spinner-failed = ✗ { $name } failed: { $details }
+spinner-unverified = ⚠ { $name } stopped, security warning: { $details }
+spinner-unknown = ? { $name } finished with unknown status
+a11y-started = { $name }: STARTED
+a11y-step = { $name }: { $step }
+a11y-stalled = { $name }: STALLED, no output for a while
+a11y-success = { $name }: SUCCESS after { $duration }
+a11y-attention = { $name }: NEEDS ATTENTION, { $reason }
+a11y-failed = { $name }: FAILED after { $duration }: { $error }
+a11y-unverified = { $name }: SECURITY WARNING after { $duration }, nothing upgraded: { $error }
+a11y-unfinished = { $name }: DID NOT FINISH
+
+## Notifications
+
+notify-complete-title = Spine Update Complete
+notify-complete-body = All package managers have been updated successfully.
+notify-failed-title = Spine Update Failed
+notify-failed-body = Package manager updates encountered errors.
+notify-pending-title =
+    { $count ->
+        [one] 1 update available
+       *[other] { $count } updates available
+    }
+notify-upgrade-action = Upgrade now
+notify-url-title = Spine on { $host }
+notify-url-run-started = Upgrading { $managers }
+notify-url-manager-finished = { $manager }: { $state } after { $duration }
+notify-url-manager-failed = { $manager } failed: { $error }
+notify-url-run-finished = Upgraded { $managers } in { $duration }
+notify-url-run-failed = Failed: { $failed } (run took { $duration })
+
+## Upgrade summary
+
+summary-title = SPINE UPGRADE SUMMARY
+summary-overall = Overall Results:
+summary-total = Total Managers:
+summary-successful = Successful:
+summary-failed = Failed:
+summary-unverified-count = Unverified:
+summary-incomplete = Incomplete:
+summary-details = Detailed Results:
+summary-column-manager = Manager
+summary-column-result = Result
+summary-column-duration = Duration
+summary-column-packages = Packages
+summary-column-phases = Phases
+summary-success = Success
+summary-failure = Failed
+summary-unfinished = Incomplete
+summary-unverified = Security warning
+summary-needs-attention = Needs attention
+summary-timeline = Timeline: { $timeline }
+summary-changes = Packages: { $upgraded } upgraded, { $installed } installed, { $removed } removed
+summary-deferred = Left for an interactive run, they ask for a password: { $casks }
+summary-needs-review = Needs manual attention, review their build files first: { $packages }
+summary-kept-back = Kept back: { $packages }
+summary-attention = Needs attention, left alone: { $reason }
+summary-error = Error: { $error }
+cause-likely = Likely cause: { $title }. { $hint }
+cause-fix = Suggested fix: { $command }
+diagnosis-dpkg-interrupted = Interrupted dpkg run
+diagnosis-dpkg-interrupted-hint = An earlier install was cut off and left packages half-configured. Finish configuring them, then retry.
+diagnosis-apt-lock = apt lock held
+diagnosis-apt-lock-hint = Another apt or dpkg process, often unattended-upgrades, holds the lock. Wait for it to finish, then retry.
+diagnosis-brew-shallow-tap = Shallow Homebrew tap
+diagnosis-brew-shallow-tap-hint = A tap was cloned without its history, so brew update can't update it. Fetch the full history, then retry.
+diagnosis-brew-broken-checkout = Broken Homebrew checkout
+diagnosis-brew-broken-checkout-hint = Homebrew's git checkout or one of its taps is corrupted. Reset them to upstream, then retry.
+diagnosis-cargo-login = Cargo registry login required
+diagnosis-cargo-login-hint = The registry needs a token that is missing or no longer valid. Log in again, then retry.
+diagnosis-arch-keyring = Outdated Arch keyring
+diagnosis-arch-keyring-hint = Packages are signed with keys the local keyring doesn't trust yet. Update the keyring first, then retry.
+diagnosis-disk-full = Disk full
+diagnosis-disk-full-hint = Free up disk space (e.g. clear package caches), then retry.
+diagnosis-database-locked = Package database locked
+diagnosis-database-locked-hint = Another package operation is running. Wait for it to finish, then retry.
+diagnosis-privileges = Missing privileges
+diagnosis-privileges-hint = Run 'sudo -v' before upgrading, or configure passwordless sudo (or doas with nopass or persist) for this manager.
+diagnosis-network = Network problem
+diagnosis-network-hint = Check your internet connection or mirror configuration, then retry.
+diagnosis-signature = Signature verification failed
+diagnosis-signature-hint = Refresh the repository signing keys, then retry.
+diagnosis-dependency-conflict = Dependency conflict
+diagnosis-dependency-conflict-hint = Resolve the conflicting packages manually, or hold this manager until upstream fixes it.
+diagnosis-out-of-memory = Killed (possibly out of memory)
+diagnosis-out-of-memory-hint = Close memory-hungry applications and retry.
+diagnosis-waiting-for-input = Waiting for input
+diagnosis-waiting-for-input-hint = The command asked a question. Run it manually, or add noninteractive_args for it.
+diagnosis-timed-out = Timed out
+diagnosis-timed-out-hint = The command took too long. Retry when the network is less busy.
+diagnosis-unknown = Unknown
+diagnosis-unknown-hint = Open the logs to see what went wrong.
+recovery-retried = Recovered from { $cause }: ran { $command }, then retried
+recovery-failed = Tried to recover from { $cause }, but { $command } failed
+summary-some-failed = Some package managers failed to upgrade completely.
+summary-security-warning = Some repositories failed signature verification, so nothing was upgraded from them.
+summary-allow-unverified-hint = Fix the repository keys, or run 'spn upgrade --allow-unverified' to upgrade anyway.
+summary-retry-hint = Check the error details above and consider running 'spn upgrade' again.
+summary-sudo-hint = You may also need to run the failed managers manually with sudo privileges.
+summary-all-succeeded = All package managers upgraded successfully!
+summary-up-to-date = Your system is now up to date.
+summary-kept-back-hint = { $count } packages were kept back (see above); 'spn upgrade --full-upgrade' upgrades those held by dependencies.
+
+## Service restarts
+
+restart-title = Services:
+restart-none = No services are running outdated libraries.
+restart-restarted = ✓ Restarted { $services }
+restart-failed = ✗ Couldn't restart { $service }: { $error }
+restart-skipped = Left alone by allow/deny, still on outdated libraries: { $services }
+restart-check-failed = Warning: couldn't check for services to restart: { $error }
+
+## Shutdown and reboot after an upgrade
+
+power-shutdown-countdown = Shutting down in { $seconds }s. Press Enter or Ctrl+C to cancel.
+power-reboot-countdown = Rebooting in { $seconds }s. Press Enter or Ctrl+C to cancel.
+power-cancelled = Cancelled; the machine stays on.
+power-shutdown-skipped = Not shutting down because { $managers } failed (--force shuts down anyway).
+power-reboot-skipped = Not rebooting because { $managers } failed (--force reboots anyway).
+power-failed = Couldn't shut down or reboot: { $error }
+
+## Run statistics
+
+stats-summary = Runs: { $runs } | Success rate: { $rate }% | Avg duration: { $duration }
+stats-last-failure = Last failure: { $error }
+stats-none = None
+stats-no-runs = No previous runs recorded
+
+## TUI
+
+tui-list-title = Package Managers - Spine
+tui-list-title-next = Package Managers - Spine - next auto-update in { $countdown } ({ $time })
+tui-pending = Pending
+tui-running = { $operation }...
+tui-stalled = ⚠ Stalled ({ $operation })
+tui-complete = ✓ Complete
+tui-attention = ⚠ Needs attention
+tui-failed = ✗ Failed
+tui-unverified = ⚠ Security warning
+tui-all-done = All operations completed! Press 'q' to quit or navigate to view details.
+tui-status = Status
+tui-power-shutdown = Shutting down in { $seconds }s - press 'c' to cancel
+tui-power-reboot = Rebooting in { $seconds }s - press 'c' to cancel
+tui-help = Help
+tui-statistics = Statistics
+tui-config = Manager Configuration
+tui-logs = Logs
+tui-help-navigate = Navigate: ↑↓/j k
+tui-help-start = Start: Space
+tui-help-detail = Detail: Enter
+tui-help-back = Back: Esc/h/←
+tui-help-logs = Logs: l
+tui-help-quit = Quit: q
+tui-help-stalled = Stalled: Wait w | Kill x
+tui-help-answer = Answer y/n/Enter
+tui-help-input = Input: i
+tui-help-view = Colors c · Wrap W · Numbers N · Next error e · Follow f
+tui-help-export = Copy y · Save S
+tui-help-scroll = Scroll: ↑↓/jk PgUp/PgDn Home/End ({ $position }/{ $total })
+tui-help-help = Help: ?
+tui-help-resize = Resize: +/-
+tui-help-sort = Sort: s
+tui-help-palette = Commands: :
+tui-help-scroll-keys = Scroll: ↑↓/jk PgUp/PgDn Home/End
+tui-help-close = Press any key to close
+tui-keys-title = Keys
+tui-keys-everywhere = Everywhere
+tui-keys-list = Manager list
+tui-keys-detail = Manager details
+tui-keys-logs = Logs
+tui-keys-commands = Commands: type : and a command, Tab completes, Enter runs it
+tui-command-quit = Leave the TUI
+tui-command-retry = Run a failed manager again
+tui-command-cancel = Kill the command a manager is running
+tui-command-filter = List only managers whose name or state contains the text
+tui-command-logs = Open a manager's logs
+tui-command-help = Show this help
+tui-command-target = Without a manager, the one selected or open is used.
+tui-palette-title = Command
+tui-palette-unknown = Unknown command: { $command } (? lists them)
+tui-palette-no-manager = No manager named { $name }
+tui-palette-not-failed = { $name } hasn't failed, so there's nothing to retry
+tui-palette-retrying = Retrying { $name }
+tui-palette-not-running = { $name } isn't running
+tui-palette-cancelling = Killing { $name }'s command
+tui-list-filter = filter: { $filter }
+tui-list-no-match = No managers match "{ $filter }" - :filter shows all again
+tui-list-sort = sorted by { $sort }
+tui-sort-config = run order
+tui-sort-name = name
+tui-sort-status = status
+tui-sort-duration = duration
+tui-sort-pending = pending updates
+tui-config-name = Name: { $value }
+tui-config-check = Check Command: { $value }
+tui-config-unset = N/A
+tui-status-line = Status: { $status }
+tui-status-stalled = ⚠ Stalled while { $operation } - no output for a while, it may be waiting for input
+tui-status-success = ✓ All operations completed successfully
+tui-status-attention = ⚠ Needs attention, left alone: { $reason }
+tui-status-failed = ✗ Failed - { $details }
+tui-status-unverified = ⚠ Security warning - { $details }
+tui-timeline = Timeline: { $timeline }
+tui-phases = Phases:
+tui-tools = Tools:
+tui-logs-title = { $name } - Live Logs
+tui-logs-title-dropped = { $name } - Live Logs ({ $dropped } earlier lines dropped)
+tui-logs-not-started = Process not started yet...
+tui-logs-no-output = No output yet...
+tui-logs-success = Command completed successfully - no output captured
+tui-logs-no-errors = No error lines found
+tui-logs-following = Following
+tui-logs-copied = Copied { $name } logs to the clipboard
+tui-logs-copy-failed = Couldn't copy: { $error }
+tui-logs-saved = Saved logs to { $path }
+tui-logs-save-failed = Couldn't save { $path }: { $error }
+tui-input-title = Send to stdin (Enter to send, Esc to cancel)
+tui-save-title = Save logs to (Enter to save, Esc to cancel)
+
+## spn list --interactive
+
+list-title = Package Managers - Space to enable or disable
+list-detected = detected
+list-detected-version = detected ({ $version })
+list-not-installed = not installed
+list-other-system = not for this system
+list-requires-sudo = Requires sudo: { $value }
+list-limited-to = Only on: { $value }
+list-help-toggle = Enable/disable: Space
+list-enabled = Enabled { $name }
+list-disabled = Disabled { $name }
+list-save-failed = Couldn't save { $name }: { $error }
+
+## spn status
+
+status-last-run = Last run:
+status-result = Result:
+status-auto-update = Auto-update:
+status-managers = Managers:
+status-on-hold = On hold:
+status-pending = Pending:
+status-never = never
+status-all-succeeded = ✓ all { $count } manager(s) succeeded
+status-some-failed = ✗ { $failed } of { $ran } failed ({ $managers })
+status-pending-count =
+    { $count ->
+        [one] 1 update
+       *[other] { $count } updates
+    }, checked { $time } ({ $age })
+status-pending-stale =
+    { $count ->
+        [one] 1 update
+       *[other] { $count } updates
+    }, checked { $time } ({ $age }, stale - run 'spn check')
+status-pending-unknown = unknown - run 'spn check' to find out
+status-managers-detected = { $managers } (detected { $age })
+status-auto-not-scheduled = ⚠ enabled in the config but not scheduled; run 'spn auto --enable'
+status-auto-off = ✗ off
+status-auto-daily = daily at { $time }
+status-auto-interval = every { $hours } hour(s)
+status-auto-weekly = weekly on { $day } at 18:00
+status-auto-drifted = { $schedule } (doesn't match the config; run 'spn auto --repair')
+status-auto-stale = ⚠ the installed jobs aren't in the config any more; run 'spn auto --repair'
+status-auto-next = ✓ { $schedule }, next run { $next }
+status-auto-on = ✓ { $schedule }
+status-auto-next-unknown = ✓ { $schedule } (can't work out the next run: { $error })
+bar-checked = Checked { $age }
+bar-checked-stale = Checked { $age } (stale)
+bar-unknown = Pending updates unknown - run 'spn check'
+bar-last-run-ok = Last run { $age }: all succeeded
+bar-last-run-failed = Last run { $age }: { $managers } failed
+bar-never-run = Never run
+bar-on-hold = On hold: { $managers }
+bar-upgrade = Upgrade now
+bar-check = Check for updates
+age-just-now = just now
+age-minutes = { $count }m ago
+age-hours = { $count }h ago
+age-days = { $count }d ago
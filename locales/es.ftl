@@ -0,0 +1,328 @@
+# Traducción al español. Los mensajes que falten se muestran en inglés.
+
+## Upgrade phases
+
+phase-refresh = Actualizar índices
+phase-self-update = Autoactualización
+phase-upgrade = Actualización
+phase-casks = Casks
+phase-cleanup = Limpieza
+phase-refresh-active = Actualizando índices
+phase-self-update-active = Autoactualizando
+phase-upgrade-active = Actualizando
+phase-casks-active = Actualizando casks
+phase-tool-active = Actualizando { $name } ({ $current }/{ $total })
+phase-cleanup-active = Limpiando
+phase-skipped-condition = Omitido (condición)
+phase-starting = Iniciando
+
+## Timeline entries
+
+timeline-queued = en cola
+timeline-stalled = detenido
+timeline-done = hecho
+timeline-failed = falló
+timeline-unverified = verificación de firma fallida
+
+## spn upgrade
+
+upgrade-skip-native = Omitiendo { $name }: lo gestionan las actualizaciones automáticas del sistema
+upgrade-skip-held = Omitiendo { $name }: retenido
+upgrade-skip-fresh = Omitiendo { $name }: al día, actualizado hace { $ago } (min_interval { $interval })
+upgrade-skip-untrusted = Omitiendo { $name }: sus comandos con sudo son nuevos o han cambiado; acéptalos ejecutando spn upgrade en una terminal o con --accept-new-managers
+upgrade-skip-root = Omitiendo { $name }: instala en un directorio personal y spn se ejecuta como root; define run_as_user para él o ejecuta spn con sudo
+trust-new-manager = { $name } es nuevo o ha cambiado en la configuración y ejecuta estos comandos con sudo:
+trust-prompt = ¿Permitir que { $name } los ejecute? [y/N]
+upgrade-skip-offline = Omitiendo { $name }: todos los pasos necesitan la red (--offline)
+upgrade-hold-os = Reteniendo actualizaciones del kernel/sistema de { $name }: { $packages } (actualiza con --include-os)
+upgrade-skip-os = Omitiendo { $name }: hay actualizaciones del kernel/sistema pendientes ({ $packages }); actualiza con --include-os
+upgrade-none-detected = No se detectó ningún gestor de paquetes en este sistema.
+upgrade-checked-for = Spine buscó: { $managers }
+upgrade-detected = { $count } gestor(es) de paquetes detectado(s): { $managers }
+upgrade-starting = Iniciando la actualización...
+upgrade-completed = Actualización completada.
+upgrade-error = Error durante la actualización: { $error }
+upgrade-running = Ejecutando las actualizaciones de los gestores de paquetes...
+upgrade-prompt = ¿Actualizar { $name }? (y/N)
+upgrade-skipping = Omitiendo { $name }
+spinner-starting = Iniciando { $name }
+spinner-success = ✓ { $name } completado correctamente
+spinner-attention = ⚠ { $name } requiere atención: { $reason }
+spinner-failed = ✗ { $name } falló: { $details }
+spinner-unverified = ⚠ { $name } detenido, aviso de seguridad: { $details }
+spinner-unknown = ? { $name } terminó con un estado desconocido
+a11y-started = { $name }: INICIADO
+a11y-step = { $name }: { $step }
+a11y-stalled = { $name }: ATASCADO, sin salida desde hace un rato
+a11y-success = { $name }: ÉXITO tras { $duration }
+a11y-attention = { $name }: REQUIERE ATENCIÓN, { $reason }
+a11y-failed = { $name }: FALLÓ tras { $duration }: { $error }
+a11y-unverified = { $name }: AVISO DE SEGURIDAD tras { $duration }, nada actualizado: { $error }
+a11y-unfinished = { $name }: NO TERMINÓ
+
+## Notifications
+
+notify-complete-title = Actualización de Spine completada
+notify-complete-body = Todos los gestores de paquetes se actualizaron correctamente.
+notify-failed-title = La actualización de Spine falló
+notify-failed-body = Hubo errores al actualizar los gestores de paquetes.
+notify-pending-title =
+    { $count ->
+        [one] 1 actualización disponible
+       *[other] { $count } actualizaciones disponibles
+    }
+notify-upgrade-action = Actualizar ahora
+notify-url-title = Spine en { $host }
+notify-url-run-started = Actualizando { $managers }
+notify-url-manager-finished = { $manager }: { $state } tras { $duration }
+notify-url-manager-failed = { $manager } falló: { $error }
+notify-url-run-finished = { $managers } actualizados en { $duration }
+notify-url-run-failed = Fallaron: { $failed } (duración { $duration })
+
+## Upgrade summary
+
+summary-title = RESUMEN DE ACTUALIZACIÓN DE SPINE
+summary-overall = Resultados generales:
+summary-total = Gestores:
+summary-successful = Correctos:
+summary-failed = Fallidos:
+summary-unverified-count = Sin verificar:
+summary-incomplete = Incompletos:
+summary-details = Resultados detallados:
+summary-column-manager = Gestor
+summary-column-result = Resultado
+summary-column-duration = Duración
+summary-column-packages = Paquetes
+summary-column-phases = Pasos
+summary-success = Correcto
+summary-failure = Falló
+summary-unfinished = Incompleto
+summary-unverified = Aviso de seguridad
+summary-needs-attention = Requiere atención
+summary-timeline = Cronología: { $timeline }
+summary-changes = Paquetes: { $upgraded } actualizados, { $installed } instalados, { $removed } eliminados
+summary-deferred = Pendientes de una ejecución interactiva, piden una contraseña: { $casks }
+summary-needs-review = Requieren atención manual, revisa antes sus archivos de compilación: { $packages }
+summary-kept-back = Retenidos: { $packages }
+summary-attention = Requiere atención, se dejó sin tocar: { $reason }
+summary-error = Error: { $error }
+cause-likely = Causa probable: { $title }. { $hint }
+cause-fix = Solución sugerida: { $command }
+diagnosis-dpkg-interrupted = Ejecución de dpkg interrumpida
+diagnosis-dpkg-interrupted-hint = Una instalación anterior se cortó y dejó paquetes a medio configurar. Termina de configurarlos y vuelve a intentarlo.
+diagnosis-apt-lock = Bloqueo de apt ocupado
+diagnosis-apt-lock-hint = Otro proceso de apt o dpkg, a menudo unattended-upgrades, tiene el bloqueo. Espera a que termine y vuelve a intentarlo.
+diagnosis-brew-shallow-tap = Tap de Homebrew superficial
+diagnosis-brew-shallow-tap-hint = Un tap se clonó sin su historial, así que brew update no puede actualizarlo. Descarga el historial completo y vuelve a intentarlo.
+diagnosis-brew-broken-checkout = Copia de Homebrew dañada
+diagnosis-brew-broken-checkout-hint = La copia git de Homebrew o uno de sus taps está dañado. Restablécelos a upstream y vuelve a intentarlo.
+diagnosis-cargo-login = Hace falta iniciar sesión en el registro de Cargo
+diagnosis-cargo-login-hint = El registro necesita un token que falta o ya no es válido. Vuelve a iniciar sesión y vuelve a intentarlo.
+diagnosis-arch-keyring = Anillo de claves de Arch desactualizado
+diagnosis-arch-keyring-hint = Los paquetes están firmados con claves en las que el anillo local aún no confía. Actualiza primero el anillo de claves y vuelve a intentarlo.
+diagnosis-disk-full = Disco lleno
+diagnosis-disk-full-hint = Libera espacio en disco (p. ej. vaciando las cachés de paquetes) y vuelve a intentarlo.
+diagnosis-database-locked = Base de datos de paquetes bloqueada
+diagnosis-database-locked-hint = Hay otra operación de paquetes en curso. Espera a que termine y vuelve a intentarlo.
+diagnosis-privileges = Faltan privilegios
+diagnosis-privileges-hint = Ejecuta 'sudo -v' antes de actualizar o configura sudo sin contraseña (o doas con nopass o persist) para este gestor.
+diagnosis-network = Problema de red
+diagnosis-network-hint = Comprueba tu conexión a internet o la configuración de los mirrors y vuelve a intentarlo.
+diagnosis-signature = Falló la verificación de firmas
+diagnosis-signature-hint = Actualiza las claves de firma de los repositorios y vuelve a intentarlo.
+diagnosis-dependency-conflict = Conflicto de dependencias
+diagnosis-dependency-conflict-hint = Resuelve a mano los paquetes en conflicto o retén este gestor hasta que upstream lo arregle.
+diagnosis-out-of-memory = Terminado (posiblemente sin memoria)
+diagnosis-out-of-memory-hint = Cierra las aplicaciones que consumen mucha memoria y vuelve a intentarlo.
+diagnosis-waiting-for-input = Esperando una respuesta
+diagnosis-waiting-for-input-hint = El comando hizo una pregunta. Ejecútalo a mano o añade noninteractive_args para él.
+diagnosis-timed-out = Tiempo agotado
+diagnosis-timed-out-hint = El comando tardó demasiado. Vuelve a intentarlo cuando la red esté menos ocupada.
+diagnosis-unknown = Desconocida
+diagnosis-unknown-hint = Abre los registros para ver qué salió mal.
+recovery-retried = Recuperado de { $cause }: se ejecutó { $command } y se reintentó
+recovery-failed = Se intentó recuperar de { $cause }, pero { $command } falló
+summary-some-failed = Algunos gestores de paquetes no se actualizaron por completo.
+summary-security-warning = Algunos repositorios no superaron la verificación de firmas, así que no se actualizó nada desde ellos.
+summary-allow-unverified-hint = Corrige las claves de los repositorios o ejecuta 'spn upgrade --allow-unverified' para actualizar de todos modos.
+summary-retry-hint = Revisa los detalles del error y considera ejecutar 'spn upgrade' de nuevo.
+summary-sudo-hint = Puede que tengas que ejecutar manualmente con sudo los gestores que fallaron.
+summary-all-succeeded = ¡Todos los gestores de paquetes se actualizaron correctamente!
+summary-up-to-date = Tu sistema está al día.
+summary-kept-back-hint = Se retuvieron { $count } paquetes (ver arriba); 'spn upgrade --full-upgrade' actualiza los bloqueados por dependencias.
+
+## Service restarts
+
+restart-title = Servicios:
+restart-none = Ningún servicio usa bibliotecas obsoletas.
+restart-restarted = ✓ Reiniciados: { $services }
+restart-failed = ✗ No se pudo reiniciar { $service }: { $error }
+restart-skipped = Siguen usando bibliotecas obsoletas (excluidos por allow/deny): { $services }
+restart-check-failed = Aviso: no se pudo comprobar qué servicios reiniciar: { $error }
+
+## Apagado y reinicio tras la actualización
+
+power-shutdown-countdown = Apagando en { $seconds } s. Pulsa Enter o Ctrl+C para cancelar.
+power-reboot-countdown = Reiniciando en { $seconds } s. Pulsa Enter o Ctrl+C para cancelar.
+power-cancelled = Cancelado; el equipo sigue encendido.
+power-shutdown-skipped = No se apaga porque { $managers } falló (--force apaga de todos modos).
+power-reboot-skipped = No se reinicia porque { $managers } falló (--force reinicia de todos modos).
+power-failed = No se pudo apagar ni reiniciar: { $error }
+
+## Run statistics
+
+stats-summary = Ejecuciones: { $runs } | Tasa de éxito: { $rate }% | Duración media: { $duration }
+stats-last-failure = Último fallo: { $error }
+stats-none = Ninguno
+stats-no-runs = No hay ejecuciones anteriores registradas
+
+## TUI
+
+tui-list-title = Gestores de paquetes - Spine
+tui-list-title-next = Gestores de paquetes - Spine - próxima actualización automática en { $countdown } ({ $time })
+tui-pending = Pendiente
+tui-stalled = ⚠ Detenido ({ $operation })
+tui-complete = ✓ Completado
+tui-attention = ⚠ Requiere atención
+tui-failed = ✗ Falló
+tui-unverified = ⚠ Aviso de seguridad
+tui-all-done = ¡Todas las operaciones completadas! Pulsa 'q' para salir o navega para ver los detalles.
+tui-status = Estado
+tui-power-shutdown = Apagando en { $seconds } s - pulsa 'c' para cancelar
+tui-power-reboot = Reiniciando en { $seconds } s - pulsa 'c' para cancelar
+tui-help = Ayuda
+tui-statistics = Estadísticas
+tui-config = Configuración del gestor
+tui-logs = Registros
+tui-help-navigate = Navegar: ↑↓/j k
+tui-help-start = Iniciar: Espacio
+tui-help-detail = Detalle: Enter
+tui-help-back = Volver: Esc/h/←
+tui-help-logs = Registros: l
+tui-help-quit = Salir: q
+tui-help-stalled = Detenido: Esperar w | Matar x
+tui-help-answer = Responder y/n/Enter
+tui-help-input = Entrada: i
+tui-help-view = Colores c · Ajuste W · Números N · Siguiente error e · Seguir f
+tui-help-export = Copiar y · Guardar S
+tui-help-scroll = Desplazar: ↑↓/jk PgUp/PgDn Home/End ({ $position }/{ $total })
+tui-help-help = Ayuda: ?
+tui-help-resize = Tamaño: +/-
+tui-help-sort = Ordenar: s
+tui-help-palette = Comandos: :
+tui-help-scroll-keys = Desplazar: ↑↓/jk PgUp/PgDn Home/End
+tui-help-close = Pulsa cualquier tecla para cerrar
+tui-keys-title = Teclas
+tui-keys-everywhere = En todas partes
+tui-keys-list = Lista de gestores
+tui-keys-detail = Detalles del gestor
+tui-keys-logs = Registros
+tui-keys-commands = Comandos: escribe : y un comando, Tab completa, Enter lo ejecuta
+tui-command-quit = Salir de la TUI
+tui-command-retry = Volver a ejecutar un gestor que falló
+tui-command-cancel = Matar el comando que ejecuta un gestor
+tui-command-filter = Mostrar solo gestores cuyo nombre o estado contiene el texto
+tui-command-logs = Abrir los registros de un gestor
+tui-command-help = Mostrar esta ayuda
+tui-command-target = Sin gestor, se usa el seleccionado o abierto.
+tui-palette-title = Comando
+tui-palette-unknown = Comando desconocido: { $command } (? los lista)
+tui-palette-no-manager = No hay ningún gestor llamado { $name }
+tui-palette-not-failed = { $name } no ha fallado, no hay nada que reintentar
+tui-palette-retrying = Reintentando { $name }
+tui-palette-not-running = { $name } no se está ejecutando
+tui-palette-cancelling = Matando el comando de { $name }
+tui-list-filter = filtro: { $filter }
+tui-list-no-match = Ningún gestor coincide con "{ $filter }" - :filter vuelve a mostrar todos
+tui-list-sort = ordenado por { $sort }
+tui-sort-config = orden de ejecución
+tui-sort-name = nombre
+tui-sort-status = estado
+tui-sort-duration = duración
+tui-sort-pending = actualizaciones pendientes
+tui-config-name = Nombre: { $value }
+tui-config-check = Comando de comprobación: { $value }
+tui-config-unset = N/D
+tui-status-line = Estado: { $status }
+tui-status-stalled = ⚠ Detenido durante «{ $operation }» - lleva un rato sin salida, puede estar esperando una respuesta
+tui-status-success = ✓ Todas las operaciones se completaron correctamente
+tui-status-attention = ⚠ Requiere atención, se dejó sin tocar: { $reason }
+tui-status-failed = ✗ Falló - { $details }
+tui-status-unverified = ⚠ Aviso de seguridad - { $details }
+tui-timeline = Cronología: { $timeline }
+tui-phases = Fases:
+tui-tools = Herramientas:
+tui-logs-title = { $name } - Registros en vivo
+tui-logs-title-dropped = { $name } - Registros en vivo ({ $dropped } líneas anteriores descartadas)
+tui-logs-not-started = El proceso aún no ha empezado...
+tui-logs-no-output = Todavía no hay salida...
+tui-logs-success = El comando terminó correctamente - no se capturó salida
+tui-logs-no-errors = No se encontraron líneas de error
+tui-logs-following = Siguiendo
+tui-logs-copied = Registros de { $name } copiados al portapapeles
+tui-logs-copy-failed = No se pudo copiar: { $error }
+tui-logs-saved = Registros guardados en { $path }
+tui-logs-save-failed = No se pudo guardar { $path }: { $error }
+tui-input-title = Enviar a stdin (Enter para enviar, Esc para cancelar)
+tui-save-title = Guardar registros en (Enter para guardar, Esc para cancelar)
+
+## spn list --interactive
+
+list-title = Gestores de paquetes - Espacio para activar o desactivar
+list-detected = detectado
+list-detected-version = detectado ({ $version })
+list-not-installed = no instalado
+list-other-system = no es para este sistema
+list-requires-sudo = Requiere sudo: { $value }
+list-limited-to = Solo en: { $value }
+list-help-toggle = Activar/desactivar: Espacio
+list-enabled = { $name } activado
+list-disabled = { $name } desactivado
+list-save-failed = No se pudo guardar { $name }: { $error }
+
+## spn status
+
+status-last-run = Última vez:
+status-result = Resultado:
+status-auto-update = Automático:
+status-managers = Gestores:
+status-on-hold = Retenidos:
+status-pending = Pendientes:
+status-never = nunca
+status-all-succeeded = ✓ los { $count } gestores terminaron bien
+status-some-failed = ✗ fallaron { $failed } de { $ran } ({ $managers })
+status-pending-count =
+    { $count ->
+        [one] 1 actualización
+       *[other] { $count } actualizaciones
+    }, comprobado { $time } ({ $age })
+status-pending-stale =
+    { $count ->
+        [one] 1 actualización
+       *[other] { $count } actualizaciones
+    }, comprobado { $time } ({ $age }, desactualizado - ejecuta 'spn check')
+status-pending-unknown = desconocido - ejecuta 'spn check' para averiguarlo
+status-managers-detected = { $managers } (detectados { $age })
+status-auto-not-scheduled = ⚠ activado en la configuración pero no programado; ejecuta 'spn auto --enable'
+status-auto-off = ✗ desactivado
+status-auto-daily = a diario a las { $time }
+status-auto-interval = cada { $hours } hora(s)
+status-auto-weekly = semanal, el { $day } a las 18:00
+status-auto-drifted = { $schedule } (no coincide con la configuración; ejecuta 'spn auto --repair')
+status-auto-stale = ⚠ las tareas programadas ya no están en la configuración; ejecuta 'spn auto --repair'
+status-auto-next = ✓ { $schedule }, próxima ejecución { $next }
+status-auto-on = ✓ { $schedule }
+status-auto-next-unknown = ✓ { $schedule } (no se pudo calcular la próxima ejecución: { $error })
+bar-checked = Comprobado { $age }
+bar-checked-stale = Comprobado { $age } (desactualizado)
+bar-unknown = Actualizaciones pendientes desconocidas - ejecuta 'spn check'
+bar-last-run-ok = Última ejecución { $age }: todo correcto
+bar-last-run-failed = Última ejecución { $age }: falló { $managers }
+bar-never-run = Nunca ejecutado
+bar-on-hold = Retenidos: { $managers }
+bar-upgrade = Actualizar ahora
+bar-check = Buscar actualizaciones
+age-just-now = ahora mismo
+age-minutes = hace { $count } min
+age-hours = hace { $count } h
+age-days = hace { $count } d
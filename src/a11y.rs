@@ -0,0 +1,108 @@
+use crate::detect::{DetectedManager, RunState};
+use crate::execute::{execute_manager_workflow, ManagerEvent, RunOptions};
+use crate::i18n::t;
+use crate::stats::format_duration_secs;
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+
+/// Status updates buffered while a manager runs; only the latest matters.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Set by `--a11y`: output is read by a screen reader, so it has no
+/// spinners, box drawing or color, and states are spelled out.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether spn runs with `--a11y`.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A mark such as `✓ `, `⚠️  ` or `│ `, with the space after it, as it
+/// should be printed. Under `--a11y` a warning is spelled out and the other
+/// marks are left off, since the text after them says how things went.
+pub fn mark(symbol: &'static str) -> &'static str {
+    if !enabled() {
+        return symbol;
+    }
+    match symbol.trim_end() {
+        "⚠️" | "⚠" => "Warning: ",
+        "ℹ️" => "Note: ",
+        _ => "",
+    }
+}
+
+/// Run a manager's workflow, announcing each step it reaches and how it
+/// ended on lines of their own, in place of a spinner.
+pub async fn run_manager(manager: &mut DetectedManager, options: &RunOptions) -> Result<()> {
+    let name = manager.name.clone();
+    println!("{}", t!("a11y-started", name = name.as_str()));
+
+    let (events_tx, mut events_rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let manager_ref = Arc::new(Mutex::new(manager.clone()));
+    let workflow =
+        execute_manager_workflow(manager_ref.clone(), Some(events_tx), None, options.clone());
+    tokio::pin!(workflow);
+
+    let mut announced = None;
+    let result = loop {
+        tokio::select! {
+            result = &mut workflow => break result,
+            event = events_rx.recv() => {
+                let Ok(ManagerEvent::Status { status, .. }) = event else {
+                    continue;
+                };
+                // How it ended is announced once the workflow returns
+                let line = match status.state {
+                    RunState::Running => t!(
+                        "a11y-step",
+                        name = name.as_str(),
+                        step = status.operation()
+                    ),
+                    RunState::Stalled => t!("a11y-stalled", name = name.as_str()),
+                    _ => continue,
+                };
+                if announced.as_ref() != Some(&line) {
+                    println!("{line}");
+                    announced = Some(line);
+                }
+            }
+        }
+    };
+
+    *manager = manager_ref.lock().await.clone();
+    let duration = format_duration_secs(manager.duration.unwrap_or_default().as_secs_f64());
+    let error = manager.status.error.as_deref().unwrap_or_default();
+    println!(
+        "{}",
+        match manager.status.state {
+            RunState::Success => match &manager.status.attention {
+                Some(reason) => t!(
+                    "a11y-attention",
+                    name = name.as_str(),
+                    reason = reason.as_str()
+                ),
+                None => t!("a11y-success", name = name.as_str(), duration = duration),
+            },
+            RunState::Failed => t!(
+                "a11y-failed",
+                name = name.as_str(),
+                duration = duration,
+                error = error
+            ),
+            RunState::Unverified => t!(
+                "a11y-unverified",
+                name = name.as_str(),
+                duration = duration,
+                error = error
+            ),
+            _ => t!("a11y-unfinished", name = name.as_str()),
+        }
+    );
+    result
+}
@@ -0,0 +1,192 @@
+use crate::config::WebhookEvent;
+use crate::i18n::t;
+use crate::stats::format_duration_secs;
+use crate::webhook;
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long the apprise binary may take to deliver to every URL.
+const APPRISE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// URL schemes spine can send to without apprise installed.
+pub const NATIVE_SCHEMES: &[&str] = &[
+    "tgram", "discord", "slack", "pover", "ntfy", "ntfys", "gotify", "gotifys", "json", "jsons",
+];
+
+/// Title and message for an event, from the same payload webhooks get.
+pub fn message(event: WebhookEvent, payload: &Map<String, Value>) -> (String, String) {
+    let text = |key: &str| match payload.get(key) {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    };
+    let duration = || {
+        let secs = payload.get("duration_secs").and_then(Value::as_f64);
+        format_duration_secs(secs.unwrap_or_default())
+    };
+
+    let title = t!("notify-url-title", host = text("host"));
+    let message = match event {
+        WebhookEvent::RunStarted => t!("notify-url-run-started", managers = text("managers")),
+        WebhookEvent::ManagerFinished => t!(
+            "notify-url-manager-finished",
+            manager = text("manager"),
+            state = text("state"),
+            duration = duration()
+        ),
+        WebhookEvent::ManagerFailed => t!(
+            "notify-url-manager-failed",
+            manager = text("manager"),
+            error = text("error")
+        ),
+        WebhookEvent::RunFinished if text("failed").is_empty() => t!(
+            "notify-url-run-finished",
+            managers = text("managers"),
+            duration = duration()
+        ),
+        WebhookEvent::RunFinished => t!(
+            "notify-url-run-failed",
+            failed = text("failed"),
+            duration = duration()
+        ),
+    };
+    (title, message)
+}
+
+/// Deliver a notification to each Apprise-style URL. An installed `apprise`
+/// handles all of them, with every service it knows; without it the common
+/// services are posted to directly. Returns a message for each URL that
+/// failed.
+pub async fn send(urls: &[String], title: &str, message: &str) -> Vec<String> {
+    if which::which("apprise").is_ok() {
+        return match send_with_apprise(urls, title, message).await {
+            Ok(()) => Vec::new(),
+            Err(e) => vec![format!("apprise failed: {e:#}")],
+        };
+    }
+
+    let mut errors = Vec::new();
+    for url in urls {
+        if let Err(e) = send_native(url, title, message).await {
+            // The rest of the URL is usually a token
+            let scheme = url.split("://").next().unwrap_or_default();
+            errors.push(format!("{scheme}:// notification failed: {e:#}"));
+        }
+    }
+    errors
+}
+
+async fn send_with_apprise(urls: &[String], title: &str, message: &str) -> Result<()> {
+    // The URLs carry tokens, so they go in the environment, which only the
+    // user can read, rather than on the command line everyone can see
+    let output = Command::new("apprise")
+        .args(["--title", title, "--body", message])
+        .env("APPRISE_URLS", urls.join(" "))
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(APPRISE_TIMEOUT, output)
+        .await
+        .context("apprise didn't finish")?
+        .context("Couldn't run apprise")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "{}",
+            stderr
+                .lines()
+                .last()
+                .unwrap_or("exited with an error")
+                .trim()
+        );
+    }
+    Ok(())
+}
+
+/// The services most people use, following Apprise's URL formats:
+///
+/// - `tgram://bot_token/chat_id[/chat_id...]`
+/// - `discord://webhook_id/webhook_token`
+/// - `slack://token_a/token_b/token_c`
+/// - `pover://user_key@app_token`
+/// - `ntfy://topic` (ntfy.sh), `ntfy://host/topic`, `ntfys://host/topic`
+/// - `gotify://host/token`, `gotifys://host/token`
+/// - `json://host/path`, `jsons://host/path`
+async fn send_native(url: &str, title: &str, message: &str) -> Result<()> {
+    let (scheme, rest) = url.split_once("://").context("Not a service URL")?;
+    // Apprise options such as ?format= aren't supported here
+    let rest = rest
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('/');
+    let parts: Vec<&str> = rest.split('/').collect();
+    let text = format!("{title}\n{message}");
+
+    match (scheme, parts.as_slice()) {
+        ("tgram", [token, chats @ ..]) if !chats.is_empty() => {
+            for chat in chats {
+                let body = json!({ "chat_id": chat, "text": text });
+                let endpoint = format!("https://api.telegram.org/bot{token}/sendMessage");
+                webhook::request("POST", &endpoint, &[], &body.to_string()).await?;
+            }
+            Ok(())
+        }
+        ("discord", [id, token]) => {
+            let endpoint = format!("https://discord.com/api/webhooks/{id}/{token}");
+            let body = json!({ "content": text });
+            webhook::request("POST", &endpoint, &[], &body.to_string()).await
+        }
+        ("slack", [a, b, c]) => {
+            let endpoint = format!("https://hooks.slack.com/services/{a}/{b}/{c}");
+            let body = json!({ "text": text });
+            webhook::request("POST", &endpoint, &[], &body.to_string()).await
+        }
+        ("pover", [credentials]) => {
+            let (user, token) = credentials
+                .split_once('@')
+                .context("Expected pover://user_key@app_token")?;
+            let body = json!({ "token": token, "user": user, "title": title, "message": message });
+            let endpoint = "https://api.pushover.net/1/messages.json";
+            webhook::request("POST", endpoint, &[], &body.to_string()).await
+        }
+        ("ntfy" | "ntfys", [topic]) => {
+            let endpoint = format!("https://ntfy.sh/{topic}");
+            let headers = [("Title", title), ("Content-Type", "text/plain")];
+            webhook::request("POST", &endpoint, &headers, message).await
+        }
+        ("ntfy" | "ntfys", [host, path @ ..]) => {
+            let protocol = if scheme == "ntfys" { "https" } else { "http" };
+            let endpoint = format!("{protocol}://{host}/{}", path.join("/"));
+            let headers = [("Title", title), ("Content-Type", "text/plain")];
+            webhook::request("POST", &endpoint, &headers, message).await
+        }
+        ("gotify" | "gotifys", [host, path @ .., token]) => {
+            let protocol = if scheme == "gotifys" { "https" } else { "http" };
+            let mut endpoint = format!("{protocol}://{host}");
+            for segment in path {
+                endpoint.push('/');
+                endpoint.push_str(segment);
+            }
+            endpoint.push_str("/message");
+            let body = json!({ "title": title, "message": message });
+            let headers = [("X-Gotify-Key", *token)];
+            webhook::request("POST", &endpoint, &headers, &body.to_string()).await
+        }
+        ("json" | "jsons", _) if !rest.is_empty() => {
+            let protocol = if scheme == "jsons" { "https" } else { "http" };
+            let endpoint = format!("{protocol}://{rest}");
+            let body =
+                json!({ "version": "1.0", "title": title, "message": message, "type": "info" });
+            webhook::request("POST", &endpoint, &[], &body.to_string()).await
+        }
+        _ if NATIVE_SCHEMES.contains(&scheme) => anyhow::bail!("Malformed {scheme}:// URL"),
+        _ => anyhow::bail!("Install apprise to send to {scheme}:// URLs"),
+    }
+}
@@ -0,0 +1,137 @@
+use crate::a11y;
+use crate::config::Config;
+use crate::detect;
+use crate::execute::{run_captured, RunAs};
+use anyhow::Result;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// Advisory tools such as debsecan download their database on every run.
+const AUDIT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A known vulnerability affecting an installed package.
+struct Advisory {
+    package: String,
+    id: String,
+    /// A pending update fixes it.
+    fixed: bool,
+    details: String,
+}
+
+/// One `package advisory status [details]` line per advisory; lines with
+/// fewer fields are ignored.
+fn parse_advisories(output: &str) -> Vec<Advisory> {
+    let mut advisories: Vec<Advisory> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let package = fields.next()?.to_string();
+            let id = fields.next()?.to_string();
+            let fixed = fields.next()? == "fixed";
+            Some(Advisory {
+                package,
+                id,
+                fixed,
+                details: fields.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect();
+    // Fixable ones first, since they only need an upgrade
+    advisories.sort_by(|a, b| (!a.fixed, &a.package, &a.id).cmp(&(!b.fixed, &b.package, &b.id)));
+    advisories
+}
+
+/// `spn audit`: list installed packages with known advisories for every
+/// manager with an advisory_command, marking those a pending update fixes.
+/// Returns the number of managers that couldn't be audited.
+pub async fn run_audit(config: &Config, fixable_only: bool) -> Result<usize> {
+    let managers: Vec<_> = detect::detect_package_managers(config)
+        .await?
+        .into_iter()
+        .filter(|m| m.config.advisory_command.is_some())
+        .collect();
+    if managers.is_empty() {
+        println!("No detected package manager has an advisory_command configured.");
+        return Ok(0);
+    }
+
+    let mut audits = JoinSet::new();
+    for manager in managers {
+        audits.spawn(async move {
+            let command = manager
+                .config
+                .advisory_command
+                .as_deref()
+                .unwrap_or_default();
+            let result = run_captured(
+                command,
+                RunAs::manager_query(&manager.config),
+                AUDIT_TIMEOUT,
+            )
+            .await;
+            (manager.name, result)
+        });
+    }
+    let mut results = Vec::new();
+    while let Some(joined) = audits.join_next().await {
+        results.push(joined?);
+    }
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut failures = 0;
+    let (mut total, mut fixable) = (0, 0);
+    for (name, result) in results {
+        let advisories = match result {
+            Ok(output) => parse_advisories(&output),
+            Err(e) => {
+                eprintln!("{name}: audit failed: {e}");
+                failures += 1;
+                continue;
+            }
+        };
+        if advisories.is_empty() {
+            println!("{name}: no known advisories");
+            continue;
+        }
+
+        let fixed = advisories.iter().filter(|a| a.fixed).count();
+        let mut packages: Vec<&str> = advisories.iter().map(|a| a.package.as_str()).collect();
+        packages.sort_unstable();
+        packages.dedup();
+        println!(
+            "{name}: {} advisories in {} packages, {fixed} fixed by pending updates",
+            advisories.len(),
+            packages.len()
+        );
+        for advisory in advisories.iter().filter(|a| a.fixed || !fixable_only) {
+            // Spelled out under --a11y, where the arrow means nothing read aloud
+            if a11y::enabled() {
+                let mut line = format!("  {} {}", advisory.package, advisory.id);
+                if advisory.fixed {
+                    line.push_str(", fixed by pending update");
+                }
+                if !advisory.details.trim().is_empty() {
+                    line.push_str(&format!(": {}", advisory.details.trim()));
+                }
+                println!("{line}");
+                continue;
+            }
+            let marker = if advisory.fixed { "↑" } else { " " };
+            let line = format!(
+                "  {marker} {:<24} {:<20} {}",
+                advisory.package, advisory.id, advisory.details
+            );
+            println!("{}", line.trim_end());
+        }
+        total += advisories.len();
+        fixable += fixed;
+    }
+
+    if fixable > 0 {
+        println!(
+            "\n{}{fixable} of {total} advisories are fixed by pending updates; run 'spn upgrade' to apply them.",
+            a11y::mark("↑ ")
+        );
+    }
+    Ok(failures)
+}
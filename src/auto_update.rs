@@ -0,0 +1,1382 @@
+use crate::a11y;
+use crate::config::{self, AutoUpdateConfig};
+use crate::execute;
+use crate::native::{self, NativePolicy};
+use crate::schedule;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::path::Path;
+
+#[cfg(target_os = "macos")]
+const LAUNCHD_LABEL: &str = "com.spine.auto-update";
+#[cfg(target_os = "macos")]
+const PREFETCH_LABEL: &str = "com.spine.prefetch";
+
+pub async fn manage_auto_update(
+    enable: bool,
+    disable: bool,
+    status_only: bool,
+    verify: bool,
+    repair: bool,
+) -> Result<()> {
+    let config = config::load_config().await?;
+
+    // The system scheduler only takes jobs from root
+    if config::system_mode() && (enable || disable || repair) && !execute::is_root() {
+        anyhow::bail!("spn --system auto changes the system's schedule; run it with sudo");
+    }
+
+    if verify {
+        return verify_auto_update_schedule(&config.auto_update);
+    }
+
+    if repair {
+        return repair_auto_update(&config);
+    }
+
+    if status_only {
+        print_auto_update_status(&config);
+        return Ok(());
+    }
+
+    if !enable && !disable {
+        print_auto_update_status(&config);
+        eprintln!("\nUse --enable or --disable to change settings");
+        eprintln!(
+            "Edit {} to configure schedule",
+            config::config_file().await?.display()
+        );
+        return Ok(());
+    }
+
+    if enable {
+        enable_auto_update(&config).await?;
+    } else if disable {
+        disable_auto_update().await?;
+    }
+
+    Ok(())
+}
+
+/// One job the scheduler runs: the `[auto_update]` settings with a job's own
+/// timing, limited to its managers.
+pub struct ScheduledJob {
+    /// None for the single job of a config without `[auto_update.jobs]`.
+    pub name: Option<String>,
+    pub config: AutoUpdateConfig,
+    /// Managers the job upgrades; empty means all of them.
+    pub managers: Vec<String>,
+    /// Runs `spn prefetch` at `prefetch_schedule` instead of upgrading.
+    pub prefetch: bool,
+}
+
+impl ScheduledJob {
+    /// What the scheduler runs after the path to spn.
+    fn arguments(&self) -> String {
+        let system = if config::system_mode() {
+            "--system "
+        } else {
+            ""
+        };
+        let mut arguments = if self.prefetch {
+            format!("{system}prefetch --scheduled")
+        } else {
+            let mut arguments = format!("{system}upgrade --no-tui --scheduled");
+            if let Some(name) = &self.name {
+                arguments.push_str(&format!(" --job {name}"));
+            }
+            if self.config.notify {
+                arguments.push_str(" --notify");
+            }
+            arguments
+        };
+        if self.config.fallback_to_defaults {
+            arguments.push_str(" --defaults");
+        }
+        arguments
+    }
+
+    /// What messages call the job; None for the single upgrade job.
+    fn title(&self) -> Option<&str> {
+        if self.prefetch {
+            Some("prefetch")
+        } else {
+            self.name.as_deref()
+        }
+    }
+
+    /// Start of a message about this job, empty for the single job.
+    pub fn prefix(&self) -> String {
+        match self.title() {
+            Some(title) => format!("{title}: "),
+            None => String::new(),
+        }
+    }
+
+    /// The managers in words, for messages.
+    fn describe_managers(&self) -> String {
+        if self.managers.is_empty() {
+            "all managers".to_string()
+        } else {
+            self.managers.join(", ")
+        }
+    }
+}
+
+/// The jobs `spn auto --enable` installs: one per `[auto_update.jobs.NAME]`,
+/// or just `[auto_update]` itself when there are none, plus one for
+/// `prefetch_schedule` if it's set.
+pub fn scheduled_jobs(config: &AutoUpdateConfig) -> Vec<ScheduledJob> {
+    let mut jobs = upgrade_jobs(config);
+    if !config.prefetch_schedule.is_empty() {
+        let mut resolved = config.clone();
+        resolved.jobs.clear();
+        resolved.schedule = "daily".to_string();
+        resolved.time = config.prefetch_schedule.clone();
+        resolved.notify = false;
+        resolved.run_at_load = false;
+        jobs.push(ScheduledJob {
+            name: None,
+            config: resolved,
+            managers: Vec::new(),
+            prefetch: true,
+        });
+    }
+    jobs
+}
+
+fn upgrade_jobs(config: &AutoUpdateConfig) -> Vec<ScheduledJob> {
+    if config.jobs.is_empty() {
+        return vec![ScheduledJob {
+            name: None,
+            config: config.clone(),
+            managers: Vec::new(),
+            prefetch: false,
+        }];
+    }
+
+    config
+        .jobs
+        .iter()
+        .map(|(name, job)| {
+            let mut resolved = config.clone();
+            resolved.jobs.clear();
+            if let Some(schedule) = &job.schedule {
+                resolved.schedule = schedule.clone();
+            }
+            if let Some(time) = &job.time {
+                resolved.time = time.clone();
+            }
+            if let Some(day) = &job.day {
+                resolved.day = day.clone();
+            }
+            if let Some(hours) = job.interval_hours {
+                resolved.interval_hours = hours;
+            }
+            ScheduledJob {
+                name: Some(name.clone()),
+                config: resolved,
+                managers: job.managers.clone(),
+                prefetch: false,
+            }
+        })
+        .collect()
+}
+
+/// Job names end up in crontab lines and launchd labels, and their managers
+/// must be ones the config knows.
+fn check_jobs(config: &config::Config) -> Result<()> {
+    for (name, job) in &config.auto_update.jobs {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!(
+                "Invalid job name '{name}' in [auto_update.jobs]. Use letters, digits, - and _"
+            );
+        }
+        if let Some(unknown) = job
+            .managers
+            .iter()
+            .find(|manager| !config.managers.contains_key(*manager))
+        {
+            anyhow::bail!("Job '{name}' lists '{unknown}', which isn't in [managers]");
+        }
+    }
+    if !config.auto_update.prefetch_schedule.is_empty() {
+        parse_hour_minute(&config.auto_update.prefetch_schedule)
+            .map_err(|e| anyhow::anyhow!("prefetch_schedule: {e}"))?;
+    }
+    Ok(())
+}
+
+fn print_auto_update_status(config: &config::Config) {
+    println!("Auto-Update Status:");
+    println!(
+        "  Enabled:      {}",
+        if config.auto_update.enabled {
+            format!("{}Yes", a11y::mark("✓ "))
+        } else {
+            format!("{}No", a11y::mark("✗ "))
+        }
+    );
+
+    // What the scheduler will really do, which lags the config until the
+    // jobs are written again
+    let state = inspect(&config.auto_update);
+    for job in &state.jobs {
+        let timing = &job.job.config;
+        let indent = match (&job.job.name, job.job.prefetch) {
+            (_, true) => {
+                println!("  Prefetch:     downloads upgrades without installing them");
+                "    "
+            }
+            (Some(name), false) => {
+                println!("  Job {name}: {}", job.job.describe_managers());
+                "    "
+            }
+            (None, false) => "  ",
+        };
+        println!("{indent}Schedule:     {}", timing.schedule);
+        match timing.schedule.as_str() {
+            "daily" => println!("{indent}Time:         {}", timing.time),
+            "interval" => println!("{indent}Every:        {} hour(s)", timing.interval_hours),
+            _ => {
+                println!("{indent}Day:          {}", timing.day);
+                println!("{indent}Time:         18:00");
+            }
+        }
+
+        let mark = a11y::mark(if job.drift.is_empty() { "✓ " } else { "⚠ " });
+        match (&job.timing, &job.installed) {
+            (Some(installed), _) => println!(
+                "{indent}Installed:    {mark}{}",
+                describe_schedule(installed)
+            ),
+            (None, Some(_)) => println!(
+                "{indent}Installed:    {}timing not recognized",
+                a11y::mark("⚠ ")
+            ),
+            (None, None) => println!("{indent}Installed:    {mark}No"),
+        }
+        for difference in &job.drift {
+            println!("{indent}  - {difference}");
+        }
+        if let Some(installed) = &job.timing {
+            let now = Local::now();
+            match schedule::next_run(installed, now) {
+                Ok(Some(next)) => println!(
+                    "{indent}Next run:     {} (in {})",
+                    next.format("%a %Y-%m-%d %H:%M"),
+                    schedule::format_countdown(next - now)
+                ),
+                Ok(None) => {
+                    println!("{indent}Next run:     counted from when launchd loaded the job")
+                }
+                Err(e) => println!("{indent}Next run:     unknown ({e})"),
+            }
+        }
+    }
+    for stale in &state.stale {
+        println!("  {}{stale}", a11y::mark("⚠ "));
+    }
+    if state.drifted() {
+        println!("  Run 'spn auto --repair' to rewrite the schedule from the config.");
+    }
+
+    if config.auto_update.run_at_load {
+        println!("  Run at load:  {}Yes", a11y::mark("✓ "));
+    }
+
+    if config.auto_update.jitter_minutes > 0 {
+        println!(
+            "  Jitter:       up to {} minutes",
+            config.auto_update.jitter_minutes
+        );
+    }
+
+    if !config.auto_update.blackout.is_empty() {
+        println!("  Blackout:     {}", config.auto_update.blackout.join(", "));
+    }
+
+    if config.auto_update.defer_on_battery {
+        println!("  On battery:   deferred until plugged in");
+    }
+
+    println!("  Native updaters: {}", config.auto_update.native_updaters);
+
+    println!(
+        "  Notifications: {}",
+        if config.auto_update.notify {
+            format!("{}Enabled", a11y::mark("✓ "))
+        } else {
+            format!("{}Disabled", a11y::mark("✗ "))
+        }
+    );
+    println!(
+        "  Mode:         {}",
+        if config.auto_update.no_tui {
+            "Background"
+        } else {
+            "Interactive"
+        }
+    );
+}
+
+async fn enable_auto_update(config: &config::Config) -> Result<()> {
+    let binary_path = std::env::current_exe()?;
+    schedule::parse_blackouts(&config.auto_update)?;
+    let policy = NativePolicy::parse(&config.auto_update.native_updaters)?;
+
+    let jobs = install_jobs(config, &binary_path)?;
+
+    for job in &jobs {
+        let timing = &job.config;
+        if job.prefetch {
+            println!(
+                "{}Enabled prefetching {}",
+                a11y::mark("✓ "),
+                describe_schedule(timing)
+            );
+            continue;
+        }
+        match &job.name {
+            Some(name) => println!(
+                "{}Enabled job {name} ({}), {}",
+                a11y::mark("✓ "),
+                job.describe_managers(),
+                describe_schedule(timing)
+            ),
+            None => match timing.schedule.as_str() {
+                "daily" => println!(
+                    "{}Enabled automatic daily updates at {}",
+                    a11y::mark("✓ "),
+                    timing.time
+                ),
+                "interval" => println!(
+                    "{}Enabled automatic updates every {} hour(s)",
+                    a11y::mark("✓ "),
+                    timing.interval_hours
+                ),
+                _ => println!(
+                    "{}Enabled automatic weekly updates on {}",
+                    a11y::mark("✓ "),
+                    timing.day
+                ),
+            },
+        }
+    }
+
+    println!("\nUpdates will run in the background.");
+    if config.auto_update.notify {
+        println!("You'll receive a notification when complete.");
+    }
+
+    reconcile_native_updaters(policy);
+
+    Ok(())
+}
+
+/// Apply the `native_updaters` policy to any distro auto-update services, so
+/// packages aren't upgraded twice by competing schedules.
+fn reconcile_native_updaters(policy: NativePolicy) {
+    let updaters = native::detect_native_updaters();
+    if updaters.is_empty() {
+        return;
+    }
+
+    println!();
+    for updater in &updaters {
+        match policy {
+            NativePolicy::Warn => {
+                println!(
+                    "{}{} is also enabled and upgrades {}.",
+                    a11y::mark("⚠️  "),
+                    updater.name,
+                    updater.covers.join(", ")
+                );
+            }
+            NativePolicy::Defer => {
+                println!(
+                    "{}{} is enabled; scheduled runs will skip {}.",
+                    a11y::mark("ℹ️  "),
+                    updater.name,
+                    updater.covers.join(", ")
+                );
+            }
+            NativePolicy::Disable => match native::disable_native_updater(updater) {
+                Ok(()) => println!("{}Disabled {}", a11y::mark("✓ "), updater.name),
+                Err(e) => eprintln!("Warning: could not disable {}: {e}", updater.name),
+            },
+        }
+    }
+
+    if policy == NativePolicy::Warn {
+        println!("   Set native_updaters = \"defer\" or \"disable\" in [auto_update] to avoid double updates.");
+    }
+}
+
+/// `spn auto --repair`: write the jobs again from the config if the
+/// scheduler's copy has drifted from it.
+fn repair_auto_update(config: &config::Config) -> Result<()> {
+    let state = inspect(&config.auto_update);
+    if !state.drifted() {
+        println!(
+            "{}The installed schedule matches the config; nothing to repair",
+            a11y::mark("✓ ")
+        );
+        return Ok(());
+    }
+
+    println!("The installed schedule doesn't match the config:");
+    for job in &state.jobs {
+        for difference in &job.drift {
+            println!("  - {}{difference}", job.job.prefix());
+        }
+    }
+    for stale in &state.stale {
+        println!("  - {stale}");
+    }
+    schedule::parse_blackouts(&config.auto_update)?;
+    for job in install_jobs(config, &std::env::current_exe()?)? {
+        match job.title() {
+            Some(title) => println!(
+                "{}Rewrote {title} as {}",
+                a11y::mark("✓ "),
+                describe_schedule(&job.config)
+            ),
+            None => println!(
+                "{}Rewrote it as {}",
+                a11y::mark("✓ "),
+                describe_schedule(&job.config)
+            ),
+        }
+    }
+    Ok(())
+}
+
+async fn disable_auto_update() -> Result<()> {
+    remove_auto_update_schedule()?;
+    println!("{}Disabled automatic updates", a11y::mark("✓ "));
+    Ok(())
+}
+
+/// Delete the oldest per-run logs in `dir`, keeping the newest `keep`.
+pub fn rotate_run_logs(dir: &Path, keep: usize) -> Result<()> {
+    let mut logs: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("run-") && name.ends_with(".log"))
+        })
+        .collect();
+
+    // Names embed a sortable timestamp
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for path in logs.into_iter().take(excess) {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Directory holding one log file per scheduled run.
+pub fn run_log_dir() -> Option<std::path::PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        if config::system_mode() {
+            return config::data_dir().map(|dir| dir.join("runs"));
+        }
+        dirs::home_dir().map(|home| home.join("Library").join("Logs").join("spine"))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        None
+    }
+}
+
+fn parse_hour_minute(time: &str) -> Result<(u32, u32)> {
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() != 2 {
+        anyhow::bail!("Invalid time format. Use HH:MM (e.g., 18:00)");
+    }
+
+    match (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+        (Ok(hour), Ok(minute)) if hour < 24 && minute < 60 => Ok((hour, minute)),
+        _ => anyhow::bail!("Invalid time format. Use HH:MM (e.g., 18:00)"),
+    }
+}
+
+/// Weekday number, Monday = 1. Sunday is 7 for launchd and 0 for cron.
+fn parse_weekday(day: &str, sunday: u32) -> Result<u32> {
+    Ok(match day.to_lowercase().as_str() {
+        "monday" => 1,
+        "tuesday" => 2,
+        "wednesday" => 3,
+        "thursday" => 4,
+        "friday" => 5,
+        "saturday" => 6,
+        "sunday" => sunday,
+        _ => anyhow::bail!(
+            "Invalid day. Use: monday, tuesday, wednesday, thursday, friday, saturday, sunday"
+        ),
+    })
+}
+
+fn validate_interval(config: &AutoUpdateConfig) -> Result<u32> {
+    match config.interval_hours {
+        0 => anyhow::bail!("interval_hours must be at least 1"),
+        hours => Ok(hours),
+    }
+}
+
+/// Check the jobs, then hand them to the scheduler, replacing whatever spine
+/// installed before.
+fn install_jobs(config: &config::Config, binary_path: &Path) -> Result<Vec<ScheduledJob>> {
+    check_jobs(config)?;
+    let jobs = scheduled_jobs(&config.auto_update);
+    install_auto_update_schedule(&jobs, binary_path)?;
+    Ok(jobs)
+}
+
+#[cfg(target_os = "macos")]
+fn launchd_label(name: Option<&str>, prefetch: bool) -> String {
+    match name {
+        _ if prefetch => PREFETCH_LABEL.to_string(),
+        Some(name) => format!("{LAUNCHD_LABEL}.{name}"),
+        None => LAUNCHD_LABEL.to_string(),
+    }
+}
+
+/// Where the plist for `label` goes: a launch daemon run as root with
+/// `--system`, otherwise one of the user's launch agents.
+#[cfg(target_os = "macos")]
+fn launchd_plist_path(label: &str) -> Result<std::path::PathBuf> {
+    if config::system_mode() {
+        return Ok(Path::new("/Library/LaunchDaemons").join(format!("{label}.plist")));
+    }
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("No home directory"))?;
+    Ok(home
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{label}.plist")))
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The launchd job `spn auto --enable` installs for `job`.
+#[cfg(target_os = "macos")]
+fn launchd_plist(job: &ScheduledJob, binary_path: &Path) -> Result<String> {
+    let config = &job.config;
+    let schedule_xml = match config.schedule.as_str() {
+        "daily" => {
+            let (hour, minute) = parse_hour_minute(&config.time)?;
+            format!(
+                r#"    <key>StartCalendarInterval</key>
+    <dict>
+        <key>Hour</key>
+        <integer>{hour}</integer>
+        <key>Minute</key>
+        <integer>{minute}</integer>
+    </dict>"#
+            )
+        }
+        "interval" => {
+            let seconds = validate_interval(config)? * 3600;
+            format!(
+                r#"    <key>StartInterval</key>
+    <integer>{seconds}</integer>"#
+            )
+        }
+        _ => {
+            let weekday = parse_weekday(&config.day, 7)?;
+            format!(
+                r#"    <key>StartCalendarInterval</key>
+    <dict>
+        <key>Weekday</key>
+        <integer>{weekday}</integer>
+        <key>Hour</key>
+        <integer>18</integer>
+        <key>Minute</key>
+        <integer>0</integer>
+    </dict>"#
+            )
+        }
+    };
+
+    let log_dir = run_log_dir().ok_or_else(|| anyhow::anyhow!("No home directory"))?;
+
+    // Each run writes to its own timestamped file; spine prunes old ones
+    let run_command = format!(
+        "exec {} {} >> {}$(date +%Y%m%d-%H%M%S).log 2>&1",
+        execute::shell_quote(&binary_path.to_string_lossy()),
+        job.arguments(),
+        execute::shell_quote(&format!("{}/run-", log_dir.to_string_lossy()))
+    );
+    let run_at_load = if config.run_at_load {
+        "\n    <key>RunAtLoad</key>\n    <true/>"
+    } else {
+        ""
+    };
+    let launchd_log = log_dir.join("launchd.log");
+
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>/bin/sh</string>
+        <string>-c</string>
+        <string>{}</string>
+    </array>
+{schedule_xml}{run_at_load}
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>"#,
+        launchd_label(job.name.as_deref(), job.prefetch),
+        xml_escape(&run_command),
+        xml_escape(&launchd_log.to_string_lossy()),
+        xml_escape(&launchd_log.to_string_lossy())
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn unload_plist(plist_path: &Path) {
+    let _ = std::process::Command::new("launchctl")
+        .arg("unload")
+        .arg(plist_path)
+        .output();
+}
+
+#[cfg(target_os = "macos")]
+fn install_auto_update_schedule(jobs: &[ScheduledJob], binary_path: &Path) -> Result<()> {
+    let plists = jobs
+        .iter()
+        .map(|job| {
+            let label = launchd_label(job.name.as_deref(), job.prefetch);
+            Ok((label, launchd_plist(job, binary_path)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(log_dir) = run_log_dir() {
+        std::fs::create_dir_all(log_dir)?;
+    }
+
+    // Jobs taken out of the config stop running
+    for installed in installed_jobs() {
+        let label = installed.label();
+        if !plists.iter().any(|(wanted, _)| *wanted == label) {
+            let plist_path = launchd_plist_path(&label)?;
+            unload_plist(&plist_path);
+            let _ = std::fs::remove_file(&plist_path);
+        }
+    }
+
+    for (label, plist_content) in plists {
+        let plist_path = launchd_plist_path(&label)?;
+        if plist_path.exists() {
+            // Reload so schedule changes take effect
+            unload_plist(&plist_path);
+        }
+        std::fs::write(&plist_path, plist_content)?;
+
+        std::process::Command::new("launchctl")
+            .arg("load")
+            .arg("-w")
+            .arg(&plist_path)
+            .output()?;
+    }
+
+    Ok(())
+}
+
+/// The crontab line `spn auto --enable` installs for `job`.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn cron_entry(job: &ScheduledJob, binary_path: &Path) -> Result<String> {
+    let config = &job.config;
+    let timing = match config.schedule.as_str() {
+        "daily" => {
+            let (hour, minute) = parse_hour_minute(&config.time)?;
+            format!("{minute} {hour} * * *")
+        }
+        "interval" => {
+            let hours = validate_interval(config)?;
+            if hours >= 24 {
+                anyhow::bail!("interval_hours must be below 24 with cron; use the daily schedule");
+            }
+            format!("0 */{hours} * * *")
+        }
+        _ => format!("0 18 * * {}", parse_weekday(&config.day, 0)?),
+    };
+
+    let binary_path_str = binary_path.to_string_lossy();
+
+    // Lines in /etc/cron.d name the user they run as
+    if system_cron_file() {
+        return Ok(format!(
+            "{timing} root {binary_path_str} {} >> {SYSTEM_CRON_LOG} 2>&1\n",
+            job.arguments()
+        ));
+    }
+    if config::system_mode() {
+        return Ok(format!(
+            "{timing} {binary_path_str} {} >> {SYSTEM_CRON_LOG} 2>&1\n",
+            job.arguments()
+        ));
+    }
+    Ok(format!(
+        "{timing} {binary_path_str} {} >> /tmp/spine-auto-update.log 2>&1\n",
+        job.arguments()
+    ))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn install_auto_update_schedule(jobs: &[ScheduledJob], binary_path: &Path) -> Result<()> {
+    let mut entries = String::new();
+    for job in jobs {
+        entries.push_str(&cron_entry(job, binary_path)?);
+    }
+    let (mut current_crontab, _) = split_crontab(&read_crontab()?);
+
+    if !current_crontab.is_empty() {
+        current_crontab.push('\n');
+    }
+    current_crontab.push_str(&format!(
+        "{CRON_BEGIN} (managed by 'spn auto', changes here are overwritten)\n{entries}{CRON_END}\n"
+    ));
+
+    write_crontab(&current_crontab)
+}
+
+#[cfg(not(unix))]
+fn install_auto_update_schedule(_jobs: &[ScheduledJob], _binary_path: &Path) -> Result<()> {
+    anyhow::bail!("Auto-update is only supported on macOS, Linux and the BSDs")
+}
+
+/// Comments around the crontab lines spine manages. Everything outside them
+/// belongs to the user and is left alone.
+#[cfg(all(unix, not(target_os = "macos")))]
+const CRON_BEGIN: &str = "# BEGIN spine auto-update";
+#[cfg(all(unix, not(target_os = "macos")))]
+const CRON_END: &str = "# END spine auto-update";
+
+/// The system crontab `spn --system auto` writes on Linux, instead of the
+/// user's.
+#[cfg(all(unix, not(target_os = "macos")))]
+const SYSTEM_CRONTAB: &str = "/etc/cron.d/spine";
+
+/// Whether `--system` jobs go in SYSTEM_CRONTAB. The BSDs don't all read
+/// /etc/cron.d (OpenBSD's cron doesn't), so there they go in root's own
+/// crontab, which `sudo spn --system auto` edits.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn system_cron_file() -> bool {
+    config::system_mode() && cfg!(target_os = "linux")
+}
+
+/// Where the system crontab's jobs log.
+#[cfg(all(unix, not(target_os = "macos")))]
+const SYSTEM_CRON_LOG: &str = "/var/lib/spine/auto-update.log";
+
+/// The user's crontab, empty if they don't have one yet. With `--system` on
+/// Linux, spine's file in /etc/cron.d.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_crontab() -> Result<String> {
+    if system_cron_file() {
+        return match std::fs::read_to_string(SYSTEM_CRONTAB) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(anyhow::anyhow!("Could not read {SYSTEM_CRONTAB}: {e}")),
+        };
+    }
+    let output = std::process::Command::new("crontab")
+        .arg("-l")
+        .output()
+        .map_err(|e| anyhow::anyhow!("Could not run crontab: {e}"))?;
+    if !output.status.success() {
+        // Only a missing crontab is empty; after any other failure a rewrite
+        // would drop the user's own jobs
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("no crontab for") {
+            return Ok(String::new());
+        }
+        anyhow::bail!("Could not read the crontab: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Split a crontab into the user's lines and spine's jobs. Spine's lines are
+/// those between the markers, plus an unmarked job written before the
+/// markers existed.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn split_crontab(crontab: &str) -> (String, Vec<String>) {
+    let lines: Vec<&str> = crontab.lines().collect();
+    let begin = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with(CRON_BEGIN));
+    let block = begin.map(|begin| {
+        // Without an end marker only the begin marker is spine's
+        let end = lines[begin..]
+            .iter()
+            .position(|line| line.trim() == CRON_END)
+            .map_or(begin, |offset| begin + offset);
+        begin..=end
+    });
+    let legacy = |line: &str| {
+        !line.trim_start().starts_with('#')
+            && line.contains(" upgrade --no-tui --scheduled")
+            && line.contains(">> /tmp/spine-auto-update.log")
+    };
+
+    let mut kept = Vec::new();
+    let mut jobs = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let ours = block.as_ref().is_some_and(|block| block.contains(&index));
+        if ours || legacy(line) {
+            if !line.trim().is_empty() && !line.trim_start().starts_with('#') {
+                jobs.push(line.to_string());
+            }
+        } else {
+            kept.push(*line);
+        }
+    }
+    // Drop the blank line install puts before the block
+    let mut kept = kept.join("\n").trim_end().to_string();
+    if !kept.is_empty() {
+        kept.push('\n');
+    }
+    (kept, jobs)
+}
+
+/// Which job a crontab line runs, from its arguments.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn cron_job(line: String) -> InstalledJob {
+    let prefetch = line.contains(" prefetch --scheduled");
+    let mut words = line.split_whitespace();
+    let name = words
+        .find(|word| *word == "--job")
+        .and_then(|_| words.next())
+        .map(str::to_string);
+    InstalledJob {
+        name,
+        prefetch,
+        entry: line,
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn write_crontab(content: &str) -> Result<()> {
+    use std::io::Write;
+
+    if config::system_mode() && !content.trim().is_empty() {
+        // Without the log's directory the redirect, and so the job, would fail
+        std::fs::create_dir_all(
+            Path::new(SYSTEM_CRON_LOG)
+                .parent()
+                .unwrap_or(Path::new("/")),
+        )?;
+    }
+    if system_cron_file() {
+        if content.trim().is_empty() {
+            return match std::fs::remove_file(SYSTEM_CRONTAB) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+        std::fs::write(SYSTEM_CRONTAB, content)?;
+        return Ok(());
+    }
+
+    let mut child = std::process::Command::new("crontab")
+        .arg("-")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .context("Couldn't run crontab")?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .context("Couldn't write to crontab's input")?;
+    stdin.write_all(content.as_bytes())?;
+    // crontab reads until end of input
+    drop(stdin);
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("Could not write the crontab: crontab failed ({status})");
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn remove_auto_update_schedule() -> Result<()> {
+    for installed in installed_jobs() {
+        let plist_path = launchd_plist_path(&installed.label())?;
+        unload_plist(&plist_path);
+        let _ = std::fs::remove_file(&plist_path);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn remove_auto_update_schedule() -> Result<()> {
+    let crontab = read_crontab()?;
+    let (kept, _) = split_crontab(&crontab);
+    if kept.trim_end() != crontab.trim_end() {
+        write_crontab(&kept)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn remove_auto_update_schedule() -> Result<()> {
+    anyhow::bail!("Auto-update is only supported on macOS, Linux and the BSDs")
+}
+
+/// Check that the scheduler actually has every job, not just that we wrote
+/// them.
+fn verify_auto_update_schedule(config: &AutoUpdateConfig) -> Result<()> {
+    #[cfg(not(unix))]
+    anyhow::bail!("Auto-update is only supported on macOS, Linux and the BSDs");
+
+    let installed = installed_jobs();
+    if installed.is_empty() {
+        anyhow::bail!("No spine job is installed. Run 'spn auto --enable'.");
+    }
+    for job in &installed {
+        verify_job(job)?;
+    }
+    if let Some(dir) = run_log_dir() {
+        println!("  Run logs: {}", dir.display());
+    }
+
+    let missing: Vec<String> = scheduled_jobs(config)
+        .into_iter()
+        .filter(|job| !installed.iter().any(|installed| installed.is(job)))
+        .map(|job| job.title().unwrap_or("[auto_update]").to_string())
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Not installed: {}. Run 'spn auto --enable'.",
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn verify_job(job: &InstalledJob) -> Result<()> {
+    let label = job.label();
+    let output = std::process::Command::new("launchctl")
+        .args(["list", &label])
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} exists but launchctl has not loaded {label}. Try 'spn auto --enable' again.",
+            launchd_plist_path(&label)?.display()
+        );
+    }
+
+    println!("{}launchd job {label} is loaded", a11y::mark("✓ "));
+    let details = String::from_utf8_lossy(&output.stdout);
+    if let Some(line) = details.lines().find(|l| l.contains("LastExitStatus")) {
+        println!("  {}", line.trim().trim_end_matches(';'));
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn verify_job(job: &InstalledJob) -> Result<()> {
+    match (&job.name, job.prefetch) {
+        (_, true) => println!("{}cron entry for prefetch installed:", a11y::mark("✓ ")),
+        (Some(name), false) => println!("{}cron entry for {name} installed:", a11y::mark("✓ ")),
+        (None, false) => println!("{}cron entry installed:", a11y::mark("✓ ")),
+    }
+    println!("  {}", job.entry);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn verify_job(_job: &InstalledJob) -> Result<()> {
+    Ok(())
+}
+
+/// A spine job as the scheduler has it.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct InstalledJob {
+    name: Option<String>,
+    prefetch: bool,
+    /// The crontab line or plist.
+    entry: String,
+}
+
+impl InstalledJob {
+    fn is(&self, job: &ScheduledJob) -> bool {
+        self.prefetch == job.prefetch && self.name == job.name
+    }
+
+    #[cfg(target_os = "macos")]
+    fn label(&self) -> String {
+        launchd_label(self.name.as_deref(), self.prefetch)
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn installed_jobs() -> Vec<InstalledJob> {
+    let Ok(crontab) = read_crontab() else {
+        return Vec::new();
+    };
+    split_crontab(&crontab)
+        .1
+        .into_iter()
+        .map(cron_job)
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn installed_jobs() -> Vec<InstalledJob> {
+    let Some(dir) = launchd_plist_path(LAUNCHD_LABEL)
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut jobs = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_name = entry.file_name();
+        let Some(label) = file_name
+            .to_str()
+            .and_then(|file| file.strip_suffix(".plist"))
+        else {
+            continue;
+        };
+        let (name, prefetch) = match label.strip_prefix(LAUNCHD_LABEL) {
+            _ if label == PREFETCH_LABEL => (None, true),
+            Some("") => (None, false),
+            Some(rest) => match rest.strip_prefix('.') {
+                Some(name) => (Some(name.to_string()), false),
+                None => continue,
+            },
+            None => continue,
+        };
+        if let Ok(plist) = std::fs::read_to_string(entry.path()) {
+            jobs.push(InstalledJob {
+                name,
+                prefetch,
+                entry: plist,
+            });
+        }
+    }
+    jobs.sort();
+    jobs
+}
+
+#[cfg(not(unix))]
+fn installed_jobs() -> Vec<InstalledJob> {
+    Vec::new()
+}
+
+/// The job `spn auto --enable` would install now.
+fn expected_job(job: &ScheduledJob, binary_path: &Path) -> Result<String> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    return cron_entry(job, binary_path);
+    #[cfg(target_os = "macos")]
+    return launchd_plist(job, binary_path);
+    #[cfg(not(unix))]
+    {
+        let _ = (job, binary_path);
+        anyhow::bail!("Auto-update is only supported on macOS, Linux and the BSDs")
+    }
+}
+
+/// A job from the config next to what the scheduler has for it.
+pub struct JobState {
+    pub job: ScheduledJob,
+    /// The crontab line or plist, if the job is installed.
+    pub installed: Option<String>,
+    /// The timing read back from `installed`, None if it isn't one spine
+    /// writes.
+    pub timing: Option<AutoUpdateConfig>,
+    /// Each way the installed job differs from what `spn auto --enable`
+    /// would write now.
+    pub drift: Vec<String>,
+}
+
+/// Every job in the config and what the scheduler has for each.
+pub struct ScheduleState {
+    pub jobs: Vec<JobState>,
+    /// Installed jobs the config no longer has, in words.
+    pub stale: Vec<String>,
+}
+
+impl ScheduleState {
+    /// Whether `spn auto --repair` has anything to do.
+    pub fn drifted(&self) -> bool {
+        !self.stale.is_empty() || self.jobs.iter().any(|job| !job.drift.is_empty())
+    }
+
+    /// Whether the scheduler has any spine job at all.
+    pub fn installed(&self) -> bool {
+        !self.stale.is_empty() || self.jobs.iter().any(|job| job.installed.is_some())
+    }
+
+    /// The soonest any installed upgrade job runs.
+    pub fn next_run(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.jobs
+            .iter()
+            .filter(|job| !job.job.prefetch)
+            .filter_map(|job| job.timing.as_ref())
+            .filter_map(|timing| schedule::next_run(timing, now).ok().flatten())
+            .min()
+    }
+}
+
+/// Compare each job in `config` with what the scheduler has installed. A
+/// job that isn't installed only counts as drift while auto-update is
+/// enabled or other spine jobs are installed.
+pub fn inspect(config: &AutoUpdateConfig) -> ScheduleState {
+    let mut installed = installed_jobs();
+    let any_installed = !installed.is_empty();
+    let binary_path = std::env::current_exe();
+
+    let jobs = scheduled_jobs(config)
+        .into_iter()
+        .map(|job| {
+            let entry = installed
+                .iter()
+                .position(|installed| installed.is(&job))
+                .map(|index| installed.remove(index).entry);
+            let timing = entry
+                .as_deref()
+                .and_then(|entry| installed_timing(entry, &job.config));
+            let drift = match &entry {
+                Some(entry) => job_drift(&job, entry, timing.as_ref(), &binary_path),
+                None if config.enabled => {
+                    vec!["enabled in the config but not installed".to_string()]
+                }
+                None if any_installed => vec!["not installed".to_string()],
+                None => Vec::new(),
+            };
+            JobState {
+                job,
+                installed: entry,
+                timing,
+                drift,
+            }
+        })
+        .collect();
+
+    let stale = installed
+        .into_iter()
+        .map(|installed| match installed.name {
+            _ if installed.prefetch => {
+                "the prefetch job is installed, but prefetch_schedule is empty".to_string()
+            }
+            Some(name) => format!("job {name} is installed but no longer in [auto_update.jobs]"),
+            None => "the [auto_update] job is installed, but the config now has [auto_update.jobs]"
+                .to_string(),
+        })
+        .collect();
+
+    ScheduleState { jobs, stale }
+}
+
+/// Each way the installed `entry` differs from what `spn auto --enable`
+/// would write for `job` now.
+fn job_drift(
+    job: &ScheduledJob,
+    entry: &str,
+    timing: Option<&AutoUpdateConfig>,
+    binary_path: &std::io::Result<std::path::PathBuf>,
+) -> Vec<String> {
+    let config = &job.config;
+    let binary_path = match binary_path {
+        Ok(path) => path,
+        Err(e) => return vec![format!("can't tell which spn to run: {e}")],
+    };
+    let expected = match expected_job(job, binary_path) {
+        Ok(expected) => expected,
+        Err(e) => return vec![format!("the schedule is invalid: {e}")],
+    };
+
+    let mut drift = Vec::new();
+    #[cfg(target_os = "macos")]
+    if !launchd_loaded(&launchd_label(job.name.as_deref(), job.prefetch)) {
+        drift.push("isn't loaded by launchd".to_string());
+    }
+    if entry.trim() == expected.trim() {
+        return drift;
+    }
+
+    match timing {
+        Some(installed) if schedule_drifted(config, installed) => drift.push(format!(
+            "runs {}, but the config says {}",
+            describe_schedule(installed),
+            describe_schedule(config)
+        )),
+        Some(_) => {}
+        None => drift.push("has a timing spine doesn't write".to_string()),
+    }
+    let binary = binary_path.to_string_lossy();
+    if !entry.contains(binary.as_ref()) {
+        drift.push(format!("runs another spn binary instead of {binary}"));
+    }
+    if entry.contains("--notify") != config.notify {
+        drift.push(if config.notify {
+            "doesn't notify, but the config has notify = true".to_string()
+        } else {
+            "notifies, but the config has notify = false".to_string()
+        });
+    }
+    if cfg!(target_os = "macos") && entry.contains("<key>RunAtLoad</key>") != config.run_at_load {
+        drift.push(format!(
+            "doesn't match run_at_load = {}",
+            config.run_at_load
+        ));
+    }
+    if drift.is_empty() {
+        drift.push("was edited since spn auto --enable wrote it".to_string());
+    }
+    drift
+}
+
+/// The timing the scheduler actually has for a job, read back from its
+/// crontab line or launchd plist. The other fields are copied from `config`,
+/// so the result can go to `schedule::next_run`. None if the entry was edited
+/// into a timing spine doesn't write.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn installed_timing(entry: &str, config: &AutoUpdateConfig) -> Option<AutoUpdateConfig> {
+    let fields: Vec<&str> = entry.split_whitespace().take(5).collect();
+    let mut installed = config.clone();
+    match fields.as_slice() {
+        ["0", hours, "*", "*", "*"] if hours.starts_with("*/") => {
+            installed.schedule = "interval".to_string();
+            installed.interval_hours = hours[2..].parse().ok()?;
+        }
+        ["0", "18", "*", "*", day] if *day != "*" => {
+            installed.schedule = "weekly".to_string();
+            installed.day = weekday_name(day.parse().ok()?)?.to_string();
+        }
+        [minute, hour, "*", "*", "*"] => {
+            installed.schedule = "daily".to_string();
+            let (hour, minute): (u32, u32) = (hour.parse().ok()?, minute.parse().ok()?);
+            installed.time = format!("{hour:02}:{minute:02}");
+        }
+        _ => return None,
+    }
+    Some(installed)
+}
+
+#[cfg(target_os = "macos")]
+fn installed_timing(plist: &str, config: &AutoUpdateConfig) -> Option<AutoUpdateConfig> {
+    let mut installed = config.clone();
+    if let Some(seconds) = plist_integer(plist, "StartInterval") {
+        installed.schedule = "interval".to_string();
+        installed.interval_hours = seconds / 3600;
+        return Some(installed);
+    }
+    let hour = plist_integer(plist, "Hour")?;
+    let minute = plist_integer(plist, "Minute").unwrap_or(0);
+    match plist_integer(plist, "Weekday") {
+        Some(day) if (hour, minute) == (18, 0) => {
+            installed.schedule = "weekly".to_string();
+            installed.day = weekday_name(day)?.to_string();
+        }
+        Some(_) => return None,
+        None => {
+            installed.schedule = "daily".to_string();
+            installed.time = format!("{hour:02}:{minute:02}");
+        }
+    }
+    Some(installed)
+}
+
+#[cfg(not(unix))]
+fn installed_timing(_entry: &str, _config: &AutoUpdateConfig) -> Option<AutoUpdateConfig> {
+    None
+}
+
+/// The number in `<integer>` after `<key>key</key>` in a plist we wrote.
+#[cfg(target_os = "macos")]
+fn plist_integer(plist: &str, key: &str) -> Option<u32> {
+    let after_key = plist.split_once(&format!("<key>{key}</key>"))?.1;
+    let value = after_key
+        .split_once("<integer>")?
+        .1
+        .split_once("</integer>")?
+        .0;
+    value.trim().parse().ok()
+}
+
+/// Quietly check whether launchd has loaded a job.
+#[cfg(target_os = "macos")]
+fn launchd_loaded(label: &str) -> bool {
+    std::process::Command::new("launchctl")
+        .args(["list", label])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Lowercase day name for a cron or launchd weekday number, where Sunday may
+/// be 0 or 7.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn weekday_name(number: u32) -> Option<&'static str> {
+    const DAYS: [&str; 8] = [
+        "sunday",
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ];
+    DAYS.get(number as usize).copied()
+}
+
+/// Whether `installed` fires at other times than `config` asks for.
+fn schedule_drifted(config: &AutoUpdateConfig, installed: &AutoUpdateConfig) -> bool {
+    let kind = |schedule: &str| match schedule {
+        "daily" | "interval" => schedule.to_string(),
+        _ => "weekly".to_string(),
+    };
+    if kind(&config.schedule) != kind(&installed.schedule) {
+        return true;
+    }
+    match installed.schedule.as_str() {
+        "daily" => parse_hour_minute(&config.time).ok() != parse_hour_minute(&installed.time).ok(),
+        "interval" => config.interval_hours != installed.interval_hours,
+        _ => !config.day.eq_ignore_ascii_case(&installed.day),
+    }
+}
+
+/// A schedule in words, e.g. `daily at 06:00`.
+pub fn describe_schedule(config: &AutoUpdateConfig) -> String {
+    match config.schedule.as_str() {
+        "daily" => format!("daily at {}", config.time),
+        "interval" => format!("every {} hour(s)", config.interval_hours),
+        _ => format!("weekly on {} at 18:00", config.day),
+    }
+}
@@ -0,0 +1,37 @@
+use std::process::Command;
+
+/// Whether the machine runs on its battery, with no charger plugged in.
+/// False on desktops and wherever it can't be told.
+pub fn on_battery() -> bool {
+    if cfg!(target_os = "macos") {
+        // "Now drawing from 'Battery Power'" or "'AC Power'"
+        return Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .is_ok_and(|output| {
+                String::from_utf8_lossy(&output.stdout).contains("'Battery Power'")
+            });
+    }
+
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut discharging = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let read = |name: &str| {
+            std::fs::read_to_string(path.join(name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        match read("type").as_str() {
+            "Mains" | "USB" if read("online") == "1" => return false,
+            // scope = Device is a wireless mouse or headset, not the laptop
+            "Battery" if read("scope") != "Device" && read("status") == "Discharging" => {
+                discharging = true;
+            }
+            _ => {}
+        }
+    }
+    discharging
+}
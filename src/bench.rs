@@ -0,0 +1,406 @@
+use crate::config::{Config, ManagerConfig};
+use crate::detect;
+use crate::execute::{run_captured, RunAs};
+use crate::history;
+use crate::stats::format_duration_secs;
+use crate::trust::{self, Confirm};
+use anyhow::{bail, Result};
+use std::time::{Duration, Instant};
+
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(300);
+const OUTDATED_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A manager taking at least this share of the total is called out.
+const DOMINANT_SHARE: f64 = 0.4;
+/// Refreshes slower than this are worth running less often.
+const SLOW_REFRESH_SECS: f64 = 20.0;
+/// Update checks slower than this hold up `spn status` and `spn check`.
+const SLOW_CHECK_SECS: f64 = 10.0;
+/// Upgrades slower than this are worth prefetching.
+const SLOW_UPGRADE_SECS: f64 = 60.0;
+
+/// Timings of one step across runs.
+#[derive(Default)]
+struct Samples(Vec<f64>);
+
+impl Samples {
+    fn mean(&self) -> f64 {
+        if self.0.is_empty() {
+            0.0
+        } else {
+            self.0.iter().sum::<f64>() / self.0.len() as f64
+        }
+    }
+
+    fn min(&self) -> f64 {
+        self.0.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    fn max(&self) -> f64 {
+        self.0.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// "mean (min–max)", or just the mean when there's one sample.
+    fn describe(&self) -> String {
+        match self.0.len() {
+            0 => "-".to_string(),
+            1 => seconds(self.mean()),
+            _ => format!(
+                "{} ({}–{})",
+                seconds(self.mean()),
+                seconds(self.min()),
+                seconds(self.max())
+            ),
+        }
+    }
+}
+
+struct Timing {
+    name: String,
+    config: ManagerConfig,
+    refresh: Samples,
+    check: Samples,
+    error: Option<String>,
+}
+
+impl Timing {
+    fn total(&self) -> f64 {
+        self.refresh.mean() + self.check.mean()
+    }
+}
+
+/// `spn bench`: run each manager's refresh and outdated_command `runs` times,
+/// one manager at a time so they don't slow each other down, and report
+/// where the time goes. Returns the number of managers that failed.
+pub async fn run_bench(
+    config: &Config,
+    runs: usize,
+    only: &[String],
+    quiet: bool,
+    local: bool,
+) -> Result<usize> {
+    let mut managers: Vec<_> = detect::detect_package_managers(config)
+        .await?
+        .into_iter()
+        .filter(|m| m.config.standalone_refresh().is_some() || m.config.outdated_command.is_some())
+        .collect();
+    retain_named(&mut managers, only, |m| &m.name)?;
+    // Refreshing runs with sudo, as in `spn check`
+    trust::retain_trusted(config, &mut managers, Confirm::Skip, local, quiet).await?;
+    trust::retain_user_level(&mut managers, quiet);
+    if managers.is_empty() {
+        println!("No detected package manager has a refresh or outdated_command to time.");
+        return Ok(0);
+    }
+
+    let mut timings: Vec<Timing> = managers
+        .into_iter()
+        .map(|m| Timing {
+            name: m.name,
+            config: m.config,
+            refresh: Samples::default(),
+            check: Samples::default(),
+            error: None,
+        })
+        .collect();
+
+    for run in 1..=runs.max(1) {
+        for timing in timings.iter_mut().filter(|t| t.error.is_none()) {
+            let refresh = match timing.config.standalone_refresh() {
+                Some(command) => {
+                    time(command, RunAs::manager(&timing.config), REFRESH_TIMEOUT).await
+                }
+                None => Ok(None),
+            };
+            let check = match (&refresh, &timing.config.outdated_command) {
+                (Ok(_), Some(command)) => {
+                    time(
+                        command,
+                        RunAs::manager_query(&timing.config),
+                        OUTDATED_TIMEOUT,
+                    )
+                    .await
+                }
+                _ => Ok(None),
+            };
+            match (refresh, check) {
+                (Ok(refresh), Ok(check)) => {
+                    timing.refresh.0.extend(refresh);
+                    timing.check.0.extend(check);
+                    if !quiet {
+                        println!(
+                            "{} run {run}: refresh {}, check {}",
+                            timing.name,
+                            refresh.map_or("-".to_string(), seconds),
+                            check.map_or("-".to_string(), seconds)
+                        );
+                    }
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("{}: failed, not timed again: {e}", timing.name);
+                    timing.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    let failures = timings.iter().filter(|t| t.error.is_some()).count();
+    timings.retain(|t| !t.refresh.0.is_empty() || !t.check.0.is_empty());
+    timings.sort_by(|a, b| b.total().total_cmp(&a.total()));
+    if timings.is_empty() {
+        return Ok(failures);
+    }
+
+    let total: f64 = timings.iter().map(Timing::total).sum();
+    let width = timings
+        .iter()
+        .map(|t| t.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Manager".len());
+    println!();
+    println!(
+        "{:width$}  {:>22}  {:>22}  {:>8}  {:>5}",
+        "Manager", "Refresh", "Check", "Total", "Share"
+    );
+    for timing in &timings {
+        println!(
+            "{:width$}  {:>22}  {:>22}  {:>8}  {:>4.0}%",
+            timing.name,
+            timing.refresh.describe(),
+            timing.check.describe(),
+            seconds(timing.total()),
+            share(timing.total(), total) * 100.0
+        );
+    }
+
+    let mut suggestions = Vec::new();
+    for timing in &timings {
+        let name = &timing.name;
+        let part = share(timing.total(), total);
+        if timings.len() > 1 && part >= DOMINANT_SHARE {
+            suggestions.push(format!(
+                "{name} takes {:.0}% of the time on its own.",
+                part * 100.0
+            ));
+        }
+        let refresh = timing.refresh.mean();
+        if refresh >= SLOW_REFRESH_SECS {
+            suggestions.push(slow_refresh(config, timing, refresh));
+        }
+        if timing.refresh.0.len() > 1
+            && timing.refresh.max() >= 2.0 * timing.refresh.min()
+            && timing.refresh.max() - timing.refresh.min() >= 5.0
+        {
+            suggestions.push(format!(
+                "{name}'s refresh varies from {} to {}; a slow or distant mirror is the usual cause.",
+                seconds(timing.refresh.min()),
+                seconds(timing.refresh.max())
+            ));
+        }
+        let check = timing.check.mean();
+        if check >= SLOW_CHECK_SECS {
+            suggestions.push(format!(
+                "{name}'s outdated_command takes {}; spn check and spn status wait on it.",
+                seconds(check)
+            ));
+        }
+    }
+    print_suggestions(&suggestions);
+    Ok(failures)
+}
+
+/// `spn bench --history`: report where the time went in the last `last`
+/// recorded runs, phase by phase, without running anything.
+pub async fn run_history_bench(config: &Config, last: usize, only: &[String]) -> Result<()> {
+    let history = history::load_history().await;
+    let runs: Vec<_> = history.runs.iter().rev().take(last.max(1)).collect();
+    if runs.is_empty() {
+        println!("No runs recorded yet.");
+        return Ok(());
+    }
+
+    struct Recorded {
+        name: String,
+        total: Samples,
+        phases: Vec<(String, Samples)>,
+        failed: usize,
+        idle: usize,
+    }
+
+    let mut recorded: Vec<Recorded> = Vec::new();
+    for manager in runs.iter().flat_map(|run| &run.managers) {
+        let index = match recorded.iter().position(|r| r.name == manager.name) {
+            Some(index) => index,
+            None => {
+                recorded.push(Recorded {
+                    name: manager.name.clone(),
+                    total: Samples::default(),
+                    phases: Vec::new(),
+                    failed: 0,
+                    idle: 0,
+                });
+                recorded.len() - 1
+            }
+        };
+        let entry = &mut recorded[index];
+        entry.total.0.push(manager.duration_secs);
+        entry.failed += usize::from(manager.failed());
+        entry.idle += usize::from(manager.packages == Some(0));
+        for phase in manager.phases.iter().filter(|p| !p.skipped) {
+            let name = phase.phase.name();
+            match entry.phases.iter_mut().find(|(n, _)| n == name) {
+                Some((_, samples)) => samples.0.push(phase.duration_secs),
+                None => entry
+                    .phases
+                    .push((name.to_string(), Samples(vec![phase.duration_secs]))),
+            }
+        }
+    }
+    retain_named(&mut recorded, only, |r| &r.name)?;
+    recorded.sort_by(|a, b| b.total.mean().total_cmp(&a.total.mean()));
+
+    let total: f64 = recorded.iter().map(|r| r.total.mean()).sum();
+    println!(
+        "Average time per manager over the last {} run{}:",
+        runs.len(),
+        if runs.len() == 1 { "" } else { "s" }
+    );
+    for entry in &recorded {
+        println!(
+            "  {}  {}  {:.0}%  (in {} of {} runs{})",
+            entry.name,
+            entry.total.describe(),
+            share(entry.total.mean(), total) * 100.0,
+            entry.total.0.len(),
+            runs.len(),
+            if entry.failed > 0 {
+                format!(", {} failed", entry.failed)
+            } else {
+                String::new()
+            }
+        );
+        for (phase, samples) in &entry.phases {
+            println!("      {phase}: {}", samples.describe());
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for entry in &recorded {
+        let name = &entry.name;
+        let mean = entry.total.mean();
+        let part = share(mean, total);
+        if recorded.len() > 1 && part >= DOMINANT_SHARE {
+            suggestions.push(format!(
+                "{name} takes {:.0}% of the time on its own.",
+                part * 100.0
+            ));
+        }
+        let phase_mean = |phase: &str| {
+            entry
+                .phases
+                .iter()
+                .find(|(n, _)| n == phase)
+                .map_or(0.0, |(_, samples)| samples.mean())
+        };
+        let manager = config.managers.get(name);
+        let upgrade = phase_mean("Upgrade");
+        if upgrade >= SLOW_UPGRADE_SECS
+            && config.auto_update.prefetch_schedule.is_empty()
+            && manager.is_some_and(|m| m.prefetch.is_some())
+        {
+            suggestions.push(format!(
+                "{name}'s upgrade takes {}; set auto_update.prefetch_schedule to download it ahead of time.",
+                seconds(upgrade)
+            ));
+        }
+        let refresh = phase_mean("Refresh");
+        if refresh >= SLOW_REFRESH_SECS {
+            suggestions.push(format!(
+                "{name}'s refresh takes {}; an [auto_update.jobs] entry that runs it less often would save that on most runs.",
+                seconds(refresh)
+            ));
+        }
+        if entry.idle * 2 > entry.total.0.len() && mean >= SLOW_REFRESH_SECS {
+            suggestions.push(format!(
+                "{name} upgraded nothing in {} of {} runs but takes {}; run it less often with an [auto_update.jobs] entry.",
+                entry.idle,
+                entry.total.0.len(),
+                seconds(mean)
+            ));
+        }
+    }
+    print_suggestions(&suggestions);
+    Ok(())
+}
+
+/// Run a command and return how long it took, or `None` without a command.
+async fn time(command: &str, run_as: RunAs<'_>, timeout: Duration) -> Result<Option<f64>> {
+    let started = Instant::now();
+    run_captured(command, run_as, timeout).await?;
+    Ok(Some(started.elapsed().as_secs_f64()))
+}
+
+/// Keep only the managers named on the command line, if any were.
+fn retain_named<T>(
+    items: &mut Vec<T>,
+    only: &[String],
+    name: impl Fn(&T) -> &String,
+) -> Result<()> {
+    if only.is_empty() {
+        return Ok(());
+    }
+    if let Some(missing) = only
+        .iter()
+        .find(|wanted| !items.iter().any(|item| name(item) == *wanted))
+    {
+        bail!("No manager named {missing} to time");
+    }
+    items.retain(|item| only.contains(name(item)));
+    Ok(())
+}
+
+/// Tenths of a second for quick steps, which would otherwise all read "0s".
+fn seconds(secs: f64) -> String {
+    if secs < 60.0 {
+        format!("{secs:.1}s")
+    } else {
+        format_duration_secs(secs)
+    }
+}
+
+fn share(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total
+    } else {
+        0.0
+    }
+}
+
+/// What to suggest for a slow refresh: prefetching when the manager can,
+/// otherwise refreshing it less often.
+fn slow_refresh(config: &Config, timing: &Timing, refresh: f64) -> String {
+    let duration = seconds(refresh);
+    if timing.config.prefetch.is_some() && config.auto_update.prefetch_schedule.is_empty() {
+        format!(
+            "{}'s refresh takes {duration}; set auto_update.prefetch_schedule so it happens before you upgrade.",
+            timing.name
+        )
+    } else {
+        format!(
+            "{}'s refresh takes {duration}; raise check_ttl_minutes, or give it an [auto_update.jobs] entry that runs less often.",
+            timing.name
+        )
+    }
+}
+
+fn print_suggestions(suggestions: &[String]) {
+    if suggestions.is_empty() {
+        return;
+    }
+    println!();
+    println!("Suggestions:");
+    for suggestion in suggestions {
+        println!("  - {suggestion}");
+    }
+}
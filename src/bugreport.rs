@@ -0,0 +1,305 @@
+use crate::detect::DetectedManager;
+use crate::diagnose::diagnose;
+use crate::history::{self, ManagerRecord, RunRecord};
+use crate::redact;
+use crate::stats::{format_duration_secs, format_timestamp};
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Log lines included for each failure.
+const LOG_LINES: usize = 100;
+
+/// Proxy variables that change how managers reach their mirrors.
+const PROXY_VARS: &[&str] = &[
+    "http_proxy",
+    "https_proxy",
+    "ftp_proxy",
+    "all_proxy",
+    "no_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "FTP_PROXY",
+    "ALL_PROXY",
+    "NO_PROXY",
+];
+
+const ISSUES_URL: &str = "https://github.com/plyght/spine/issues";
+
+/// What the system looked like when a manager failed, redacted before it's
+/// stored so the report can be shared as is.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Environment {
+    pub system: String,
+    pub spine_version: String,
+    #[serde(default)]
+    pub manager_version: Option<String>,
+    pub path: String,
+    #[serde(default)]
+    pub proxies: BTreeMap<String, String>,
+    /// Free space on each filesystem upgrades write to.
+    #[serde(default)]
+    pub disk: Vec<String>,
+}
+
+/// Capture the environment diagnostics for a failed manager.
+pub fn capture(manager: &DetectedManager) -> Environment {
+    Environment {
+        manager_version: manager.version.as_deref().map(redact),
+        ..current()
+    }
+}
+
+/// The environment spn itself is running in.
+fn current() -> Environment {
+    Environment {
+        system: system(),
+        spine_version: env!("CARGO_PKG_VERSION").to_string(),
+        manager_version: None,
+        path: redact(&std::env::var("PATH").unwrap_or_default()),
+        proxies: PROXY_VARS
+            .iter()
+            .filter_map(|name| Some((name.to_string(), redact(&std::env::var(name).ok()?))))
+            .collect(),
+        disk: disk_space(),
+    }
+}
+
+/// OS, architecture and distribution, e.g. `linux x86_64 (Ubuntu 24.04 LTS)`.
+fn system() -> String {
+    let mut system = format!("{} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let pretty_name = std::fs::read_to_string("/etc/os-release")
+        .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+        .ok()
+        .and_then(|release| {
+            release.lines().find_map(|line| {
+                let value = line.strip_prefix("PRETTY_NAME=")?;
+                Some(value.trim_matches(['"', '\'']).to_string())
+            })
+        });
+    if let Some(name) = pretty_name {
+        system.push_str(&format!(" ({name})"));
+    }
+    system
+}
+
+/// Free space on the root filesystem, /var and /usr (where packages and
+/// their caches go), the home directory and the temp directory, one entry
+/// per filesystem.
+#[cfg(unix)]
+fn disk_space() -> Vec<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut paths = vec![
+        PathBuf::from("/"),
+        PathBuf::from("/var"),
+        PathBuf::from("/usr"),
+    ];
+    paths.extend(dirs::home_dir());
+    paths.push(std::env::temp_dir());
+
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| std::fs::metadata(path).is_ok_and(|metadata| seen.insert(metadata.dev())))
+        .filter_map(|path| {
+            let (free, total) = statvfs(&path)?;
+            Some(format!(
+                "{}: {} free of {}",
+                redact(&path.display().to_string()),
+                format_size(free),
+                format_size(total)
+            ))
+        })
+        .collect()
+}
+
+/// Bytes available to unprivileged users and the size of the filesystem
+/// holding `path`.
+#[cfg(unix)]
+fn statvfs(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: path is NUL-terminated and stat is a valid statvfs to fill in
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some((stat.f_bavail as u64 * block, stat.f_blocks as u64 * block))
+}
+
+#[cfg(not(unix))]
+fn disk_space() -> Vec<String> {
+    Vec::new()
+}
+
+fn format_size(bytes: u64) -> String {
+    let gib = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    if gib >= 1.0 {
+        format!("{gib:.1} GB")
+    } else {
+        format!("{:.0} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Mask what identifies the user or lets someone else in: secrets (see
+/// [`redact::redact`]), the home directory and the user name.
+fn redact(text: &str) -> String {
+    let mut text = redact::redact(text).into_owned();
+    if let Some(home) = dirs::home_dir().filter(|home| home.as_os_str().len() > 1) {
+        text = text.replace(&*home.to_string_lossy(), "~");
+    }
+    if let Some(user) = std::env::var("USER").ok().filter(|user| user != "root") {
+        // Only as a path component, e.g. /Users/NAME, so a short name doesn't
+        // mask unrelated words
+        let component = format!(r"/{}(/|[^\w.-]|$)", regex::escape(&user));
+        if let Ok(component) = Regex::new(&component) {
+            text = component.replace_all(&text, "/<user>$1").into_owned();
+        }
+    }
+    text
+}
+
+/// `spn bugreport`: write the environment and the latest failure of each
+/// manager (or just `manager`'s) with its last log lines, redacted, to a
+/// Markdown file for a GitHub issue. Returns the file's path.
+pub async fn write_report(output: Option<PathBuf>, manager: Option<&str>) -> Result<PathBuf> {
+    let history = history::load_history().await;
+    let failures: Vec<_> = history::latest_failures(&history)
+        .into_iter()
+        .filter(|(_, record)| manager.is_none_or(|name| record.name == name))
+        .collect();
+    if let (Some(name), true) = (manager, failures.is_empty()) {
+        anyhow::bail!("No recorded failure of {name}; its last run succeeded or it never ran");
+    }
+
+    let mut report = String::from("# Spine bug report\n\n");
+    let _ = writeln!(
+        report,
+        "Generated {}.\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M %Z")
+    );
+    report.push_str("## Environment\n\n");
+    write_environment(&mut report, &current());
+
+    if failures.is_empty() {
+        report.push_str("\nNo failures are recorded.\n");
+    }
+    for (run, record) in failures {
+        write_failure(&mut report, run, record);
+    }
+
+    let path = output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "spine-bugreport-{}.md",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+    tokio::fs::write(&path, report)
+        .await
+        .with_context(|| format!("Couldn't write {}", path.display()))?;
+    Ok(path)
+}
+
+/// The next steps after `spn bugreport` wrote `path`.
+pub fn print_written(path: &Path) {
+    println!("Wrote the bug report to {}", path.display());
+    println!(
+        "Home directories, user names, tokens and passwords are masked. Read it over, then attach it to an issue at {ISSUES_URL}"
+    );
+}
+
+fn write_environment(report: &mut String, environment: &Environment) {
+    let _ = writeln!(report, "- spn {}", environment.spine_version);
+    let _ = writeln!(report, "- System: {}", environment.system);
+    if let Some(version) = &environment.manager_version {
+        let _ = writeln!(report, "- Manager version: {version}");
+    }
+    let _ = writeln!(report, "- PATH: `{}`", environment.path);
+    for (name, value) in &environment.proxies {
+        let _ = writeln!(report, "- {name}: `{value}`");
+    }
+    if environment.proxies.is_empty() {
+        report.push_str("- No proxy set\n");
+    }
+    for disk in &environment.disk {
+        let _ = writeln!(report, "- Disk {disk}");
+    }
+}
+
+fn write_failure(report: &mut String, run: &RunRecord, record: &ManagerRecord) {
+    let _ = writeln!(
+        report,
+        "\n## {} {} ({})\n",
+        record.name,
+        record.outcome,
+        format_timestamp(run.started_at)
+    );
+    let log = record
+        .log_file
+        .as_deref()
+        .and_then(|path| std::fs::read_to_string(path).ok());
+    let error = record.error.as_deref().unwrap_or_default();
+    if !error.is_empty() {
+        let _ = writeln!(report, "- Error: {}", redact(error));
+    }
+    if let Some(cause) = diagnose(&record.name, error, log.as_deref().unwrap_or_default()) {
+        let _ = writeln!(report, "- Likely cause: {}", cause.english_title());
+    }
+    if !record.phases.is_empty() {
+        let steps: Vec<String> = record
+            .phases
+            .iter()
+            .map(|phase| {
+                let name = phase.phase.name().to_lowercase();
+                match (phase.exit_code, phase.signal) {
+                    _ if phase.skipped => format!("{name} skipped"),
+                    (Some(code), _) => format!(
+                        "{name} exited {code} after {}",
+                        format_duration_secs(phase.duration_secs)
+                    ),
+                    (_, Some(signal)) => format!(
+                        "{name} killed by signal {signal} after {}",
+                        format_duration_secs(phase.duration_secs)
+                    ),
+                    _ => format!(
+                        "{name} stopped after {}",
+                        format_duration_secs(phase.duration_secs)
+                    ),
+                }
+            })
+            .collect();
+        let _ = writeln!(report, "- Steps: {}", steps.join(", "));
+    }
+    for recovery in &record.recoveries {
+        let _ = writeln!(report, "- Recovery tried: {recovery}");
+    }
+
+    match &record.environment {
+        Some(environment) => {
+            report.push_str("\n### Environment at the time\n\n");
+            write_environment(report, environment);
+        }
+        None => report.push_str("\nThe environment wasn't captured for this run.\n"),
+    }
+
+    match log {
+        Some(log) => {
+            let lines: Vec<&str> = log.lines().collect();
+            let tail = &lines[lines.len().saturating_sub(LOG_LINES)..];
+            let _ = writeln!(report, "\n### Last {} log lines\n", tail.len());
+            report.push_str("```text\n");
+            for line in tail {
+                report.push_str(&redact(line));
+                report.push('\n');
+            }
+            report.push_str("```\n");
+        }
+        None => report.push_str("\nThe log of this run is no longer kept.\n"),
+    }
+}
@@ -0,0 +1,79 @@
+use crate::execute::{run_captured, shell_quote};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::time::Duration;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Outdated Homebrew casks split by whether upgrading them asks for a
+/// password, which nobody can type in a background run.
+pub struct CaskPlan {
+    pub upgrade: Vec<String>,
+    pub deferred: Vec<String>,
+}
+
+/// Look up the outdated casks (`--greedy` ones too if asked) and which of
+/// them run an installer that needs sudo.
+pub async fn plan(greedy: bool) -> Result<CaskPlan> {
+    let greedy = if greedy { " --greedy" } else { "" };
+    let outdated = run_captured(
+        &format!("brew outdated --cask --quiet{greedy}"),
+        false,
+        QUERY_TIMEOUT,
+    )
+    .await?;
+    let tokens: Vec<&str> = outdated.split_whitespace().collect();
+    if tokens.is_empty() {
+        return Ok(CaskPlan {
+            upgrade: Vec::new(),
+            deferred: Vec::new(),
+        });
+    }
+
+    let quoted: Vec<String> = tokens.iter().map(|token| shell_quote(token)).collect();
+    let info = run_captured(
+        &format!("brew info --json=v2 --cask {}", quoted.join(" ")),
+        false,
+        QUERY_TIMEOUT,
+    )
+    .await?;
+    let info: Value = serde_json::from_str(&info).context("Couldn't parse brew info output")?;
+    let asks_password: Vec<&str> = info["casks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|cask| needs_password(cask))
+        .flat_map(|cask| [cask["token"].as_str(), cask["full_token"].as_str()])
+        .flatten()
+        .collect();
+
+    let (deferred, upgrade) = tokens
+        .into_iter()
+        .map(str::to_string)
+        .partition(|token| asks_password.contains(&token.as_str()));
+    Ok(CaskPlan { upgrade, deferred })
+}
+
+/// Casks that install a .pkg, run an installer or remove system packages and
+/// kernel extensions go through sudo.
+fn needs_password(cask: &Value) -> bool {
+    cask["artifacts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|artifact| {
+            artifact.get("pkg").is_some()
+                || artifact.get("installer").is_some()
+                || artifact["uninstall"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .any(|step| step.get("pkgutil").is_some() || step.get("kext").is_some())
+        })
+}
+
+/// `command` limited to the given casks.
+pub fn limit(command: &str, casks: &[String]) -> String {
+    let quoted: Vec<String> = casks.iter().map(|cask| shell_quote(cask)).collect();
+    format!("{command} {}", quoted.join(" "))
+}
@@ -0,0 +1,240 @@
+use crate::config::{Config, ManagerConfig};
+use crate::detect;
+use crate::execute::{run_captured, RunAs};
+use crate::i18n::t;
+use crate::notify;
+use crate::outdated::{self, OutdatedCache};
+use crate::stats::format_duration_secs;
+use crate::trust::{self, Confirm};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinSet;
+
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(300);
+const OUTDATED_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Outdated packages listed per manager in a report.
+const REPORT_PACKAGES: usize = 20;
+
+/// `spn check`: refresh metadata and cache how many updates each manager has
+/// pending, optionally notifying with an "Upgrade now" action. Returns the
+/// number of managers that couldn't be checked.
+pub async fn run_check(
+    config: &Config,
+    force: bool,
+    refresh: bool,
+    notify_pending: bool,
+    quiet: bool,
+    local: bool,
+) -> Result<usize> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let cache = outdated::load_cache().await;
+    if !force && cache.is_fresh(config.check_ttl_minutes, now) {
+        if !quiet {
+            let age = now.saturating_sub(cache.checked_at.unwrap_or(now));
+            println!(
+                "Checked {} ago, within check_ttl_minutes ({}); use --force to check again.",
+                format_duration_secs(age as f64),
+                config.check_ttl_minutes
+            );
+        }
+        return Ok(0);
+    }
+
+    let Some((pending, failures)) = pending_updates(config, refresh, quiet, local).await? else {
+        return Ok(0);
+    };
+    let counts: BTreeMap<String, usize> = pending
+        .into_iter()
+        .map(|(name, packages)| (name, packages.len()))
+        .collect();
+
+    if !quiet {
+        for (name, count) in &counts {
+            println!("{name}: {count} outdated");
+        }
+    }
+
+    if notify_pending {
+        notify_pending_updates(&counts);
+    }
+
+    outdated::save_cache(&OutdatedCache {
+        checked_at: Some(now),
+        managers: counts,
+    })
+    .await?;
+    Ok(failures)
+}
+
+/// `--report-only` and `report_only`: refresh every manager and list what
+/// it would upgrade, print that and notify about it, without installing
+/// anything. Returns the number of managers that couldn't be checked.
+pub async fn run_report(config: &Config, quiet: bool, local: bool) -> Result<usize> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let Some((pending, failures)) = pending_updates(config, true, quiet, local).await? else {
+        return Ok(0);
+    };
+
+    let total: usize = pending.values().map(Vec::len).sum();
+    for (name, packages) in &pending {
+        if quiet {
+            println!("{name}: {} pending", packages.len());
+            continue;
+        }
+        if packages.is_empty() {
+            println!("{name}: up to date");
+            continue;
+        }
+        println!("{name}: {} pending", packages.len());
+        for package in packages.iter().take(REPORT_PACKAGES) {
+            println!("  {package}");
+        }
+        if packages.len() > REPORT_PACKAGES {
+            println!("  ... and {} more", packages.len() - REPORT_PACKAGES);
+        }
+    }
+    if !quiet {
+        let install = if config.report_only {
+            "set report_only = false and run spn upgrade"
+        } else {
+            "run spn upgrade without --report-only"
+        };
+        println!("\nReport only: {total} update(s) pending and nothing was installed; {install} to install them.");
+    }
+
+    if total > 0 {
+        let message = pending
+            .iter()
+            .filter(|(_, packages)| !packages.is_empty())
+            .map(|(name, packages)| format!("{name}: {}", packages.len()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Best effort: the digest was printed too
+        let _ = notify::send_notification(&t!("notify-pending-title", count = total), &message);
+    }
+
+    outdated::save_cache(&OutdatedCache {
+        checked_at: Some(now),
+        managers: pending
+            .into_iter()
+            .map(|(name, packages)| (name, packages.len()))
+            .collect(),
+    })
+    .await?;
+    Ok(failures)
+}
+
+/// The outdated packages of every manager with an outdated_command, after
+/// refreshing its metadata if `refresh`, and the number of managers that
+/// couldn't be checked. None when no manager can be checked.
+async fn pending_updates(
+    config: &Config,
+    refresh: bool,
+    quiet: bool,
+    local: bool,
+) -> Result<Option<(BTreeMap<String, Vec<String>>, usize)>> {
+    let mut managers: Vec<_> = detect::detect_package_managers(config)
+        .await?
+        .into_iter()
+        .filter(|m| m.config.outdated_command.is_some())
+        .collect();
+    // Refreshing runs with sudo, which waits until `spn upgrade` accepts
+    // managers that are new or changed in the config
+    if refresh {
+        trust::retain_trusted(config, &mut managers, Confirm::Skip, local, quiet).await?;
+    }
+    trust::retain_user_level(&mut managers, quiet);
+    if managers.is_empty() {
+        if !quiet {
+            println!("No detected package manager has an outdated_command configured.");
+        }
+        return Ok(None);
+    }
+
+    // Managers are independent, so check them all at once
+    let mut checks = JoinSet::new();
+    for manager in managers {
+        checks.spawn(async move {
+            let result = list_outdated(&manager.config, refresh).await;
+            (manager.name, result)
+        });
+    }
+
+    let mut pending = BTreeMap::new();
+    let mut failures = 0;
+    while let Some(joined) = checks.join_next().await {
+        let (name, result) = joined?;
+        match result {
+            Ok(packages) => {
+                pending.insert(name, packages);
+            }
+            Err(e) => {
+                eprintln!("{name}: check failed: {e}");
+                failures += 1;
+            }
+        }
+    }
+    Ok(Some((pending, failures)))
+}
+
+/// Tell the user about pending updates, with an action that upgrades them.
+fn notify_pending_updates(counts: &BTreeMap<String, usize>) {
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return;
+    }
+
+    let message = counts
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let upgrade = match std::env::current_exe() {
+        Ok(exe) => vec![
+            exe.to_string_lossy().into_owned(),
+            "upgrade".to_string(),
+            "--no-tui".to_string(),
+            "--notify".to_string(),
+        ],
+        Err(e) => {
+            eprintln!("Warning: can't locate spn for the notification action: {e}");
+            return;
+        }
+    };
+
+    let title = t!("notify-pending-title", count = total);
+    let action = t!("notify-upgrade-action");
+    if let Err(e) = notify::send_action_notification(&title, &message, &action, &upgrade) {
+        eprintln!("Warning: could not send notification: {e}");
+    }
+}
+
+/// Refresh the manager's metadata if asked to, then return the lines its
+/// outdated_command prints, one per outdated package.
+async fn list_outdated(config: &ManagerConfig, refresh: bool) -> Result<Vec<String>> {
+    if let (true, Some(refresh_cmd)) = (refresh, config.standalone_refresh()) {
+        run_captured(refresh_cmd, RunAs::manager(config), REFRESH_TIMEOUT).await?;
+    }
+
+    let Some(command) = &config.outdated_command else {
+        return Ok(Vec::new());
+    };
+    // Listing doesn't need root, and some managers (brew) refuse to run as root
+    let output = run_captured(command, RunAs::manager_query(config), OUTDATED_TIMEOUT).await?;
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
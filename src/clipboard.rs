@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Clipboard tools in order of preference, with the arguments that make
+/// them read the new contents from stdin.
+fn candidates() -> Vec<(&'static str, &'static [&'static str])> {
+    let mut tools: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if cfg!(target_os = "macos") {
+        tools.push(("pbcopy", &[]));
+    }
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-copy", &[]));
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        tools.push(("xclip", &["-selection", "clipboard"]));
+        tools.push(("xsel", &["--clipboard", "--input"]));
+    }
+    // WSL can reach the Windows clipboard
+    tools.push(("clip.exe", &[]));
+    tools
+}
+
+/// Put `text` on the system clipboard with the first clipboard tool that is
+/// installed, returning its name.
+pub async fn copy(text: &str) -> Result<&'static str> {
+    let (tool, args) = candidates()
+        .into_iter()
+        .find(|(tool, _)| which::which(tool).is_ok())
+        .context("No clipboard tool found; install wl-clipboard, xclip or xsel")?;
+
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Couldn't run {tool}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+    let status = child.wait().await?;
+    if !status.success() {
+        anyhow::bail!("{tool} failed ({status})");
+    }
+    Ok(tool)
+}
@@ -1,24 +1,672 @@
-use anyhow::Result;
+use crate::detect::{Phase, PhaseStep};
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::de::{self, DeserializeOwned, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
+    #[serde(default = "default_order")]
+    pub order: String,
+    #[serde(default = "default_check_ttl_minutes")]
+    pub check_ttl_minutes: u64,
+    /// How long detection results are reused before probing managers again.
+    #[serde(default = "default_detect_ttl_minutes")]
+    pub detect_ttl_minutes: u64,
+    /// Download rate cap such as `500k` or `2M` (KB/s) for every run; empty
+    /// for none.
+    #[serde(default)]
+    pub bandwidth_limit: String,
+    /// Make upgrades only refresh and list pending updates, as if every run
+    /// were passed `--report-only`.
+    #[serde(default)]
+    pub report_only: bool,
+    /// Keep the system awake while upgrades and prefetches run.
+    #[serde(default = "default_prevent_sleep")]
+    pub prevent_sleep: bool,
+    #[serde(deserialize_with = "deserialize_managers")]
     pub managers: HashMap<String, ManagerConfig>,
+    /// Groups of presets to add to the managers, such as `kubernetes`.
+    #[serde(default)]
+    pub preset_groups: Vec<String>,
+    /// Commands that aren't package managers, run by `spn upgrade` alongside
+    /// them.
+    #[serde(default)]
+    pub tasks: BTreeMap<String, TaskConfig>,
+    /// Git checkouts, such as dotfiles, that `spn upgrade` fast-forwards
+    /// alongside the managers.
+    #[serde(default)]
+    pub repos: BTreeMap<String, RepoConfig>,
     #[serde(default)]
     pub auto_update: AutoUpdateConfig,
+    #[serde(default)]
+    pub exclusions: ExclusionsConfig,
+    #[serde(default)]
+    pub restarts: RestartsConfig,
+    #[serde(default)]
+    pub logs: LogsConfig,
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub wsl: WslConfig,
+    #[serde(default)]
+    pub reporting: ReportingConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub tui: TuiConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ManagerConfig {
     pub name: String,
     pub check_command: String,
+    /// Only look for the manager on these operating systems (`linux`,
+    /// `macos`, `freebsd`, ...); empty means any.
+    #[serde(default)]
+    pub os: Vec<String>,
+    /// Only on these CPU architectures, e.g. `x86_64` or `aarch64`.
+    #[serde(default)]
+    pub arch: Vec<String>,
+    /// Only on these distributions, matched against ID and ID_LIKE in
+    /// /etc/os-release, so `debian` also covers Ubuntu.
+    #[serde(default)]
+    pub distro: Vec<String>,
+    /// Set to false to leave the manager out of detection, and so out of
+    /// every run, while keeping its table.
+    #[serde(default = "default_manager_enabled")]
+    pub enabled: bool,
     pub refresh: Option<String>,
+    /// The refresh is only safe right before the upgrade (`pacman -Sy`
+    /// would leave a partial upgrade), so checks, prefetching and `spn
+    /// bench` never run it on its own.
+    #[serde(default)]
+    pub refresh_needs_upgrade: bool,
     pub self_update: Option<String>,
     pub upgrade_all: String,
     pub cleanup: Option<String>,
     pub requires_sudo: bool,
+    #[serde(default)]
+    pub quiet_flags: Option<String>,
+    #[serde(default)]
+    pub verbose_flags: Vec<String>,
+    #[serde(default)]
+    pub noninteractive_env: HashMap<String, String>,
+    #[serde(default)]
+    pub noninteractive_args: Option<String>,
+    #[serde(default)]
+    pub stall_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub priority: i32,
+    /// Skip the manager while less than this (`12h`, `7d`, ...) has passed
+    /// since its last successful run, for slow ones like source builds.
+    #[serde(default)]
+    pub min_interval: Option<String>,
+    /// Prints why the manager needs someone to look at it before it runs,
+    /// such as uncommitted changes. When it prints anything, the run leaves
+    /// the manager alone and reports it as needing attention, not failed.
+    #[serde(default)]
+    pub attention_command: Option<String>,
+    #[serde(default)]
+    pub allow_stdin: bool,
+    #[serde(default)]
+    pub progress_patterns: Vec<String>,
+    #[serde(default)]
+    pub package_pattern: Option<String>,
+    #[serde(default)]
+    pub outdated_command: Option<String>,
+    /// Removes one package; `{package}` is replaced with its quoted name.
+    #[serde(default)]
+    pub remove: Option<String>,
+    /// Exits successfully if `{package}` was installed with this manager.
+    #[serde(default)]
+    pub owns_command: Option<String>,
+    /// Prints the package that installed `{path}`, a file or binary.
+    #[serde(default)]
+    pub owns_file_command: Option<String>,
+    /// Added to the upgrade command once per excluded pattern, e.g.
+    /// `--exclude={pattern}`.
+    #[serde(default)]
+    pub exclude_args: Option<String>,
+    /// Holds or pins the packages matching `{pattern}` before upgrading, for
+    /// managers without an exclude flag.
+    #[serde(default)]
+    pub exclude_command: Option<String>,
+    /// Undoes the holds an upgrade placed on kernel and OS packages. With
+    /// `{package}` it runs for each name exclude_command printed, releasing
+    /// only what that run held; with `{pattern}`, for each held pattern.
+    #[serde(default)]
+    pub release_command: Option<String>,
+    /// Kernel and OS packages that unattended runs hold back unless
+    /// `--include-os` or `auto_update.include_os` is set.
+    #[serde(default)]
+    pub os_packages: Vec<String>,
+    /// Prints one installed package per line as `name version`, compared
+    /// before and after each run to record what changed.
+    #[serde(default)]
+    pub installed_command: Option<String>,
+    /// Regexes for refresh output reporting a repository signature or key
+    /// problem; a match stops the manager before it upgrades anything.
+    #[serde(default)]
+    pub signature_patterns: Vec<String>,
+    /// Prints one security advisory per line as `package advisory status
+    /// [details]`, where status is `fixed` if a pending update fixes it and
+    /// `open` otherwise.
+    #[serde(default)]
+    pub advisory_command: Option<String>,
+    /// Homebrew only: upgrades casks as a step of its own after upgrade_all.
+    /// Background runs leave out casks whose installer asks for a password.
+    #[serde(default)]
+    pub cask_upgrade: Option<String>,
+    /// Also upgrade casks that update themselves or are versioned `latest`.
+    #[serde(default)]
+    pub greedy_casks: bool,
+    /// Cargo only: reinstalls one crate, with `{package}` and `{version}`
+    /// filled in. When set, the upgrade runs it for each installed crate
+    /// with a newer release on crates.io instead of running upgrade_all.
+    #[serde(default)]
+    pub crate_upgrade: Option<String>,
+    /// Prints the tools to upgrade one at a time, one per line as `name`,
+    /// `name installed` or `name installed latest`.
+    #[serde(default)]
+    pub tools_command: Option<String>,
+    /// Upgrades one tool from tools_command, with `{package}` and `{version}`
+    /// filled in; runs for each of them instead of upgrade_all.
+    #[serde(default)]
+    pub tool_upgrade: Option<String>,
+    /// Prints the packages, one per line, whose build files changed since they
+    /// were last reviewed, such as AUR PKGBUILDs. Background runs leave them
+    /// out of the upgrade.
+    #[serde(default)]
+    pub review_command: Option<String>,
+    /// Prints the changes to review for `{package}`, shown in the TUI's logs
+    /// before the upgrade builds it.
+    #[serde(default)]
+    pub review_diff_command: Option<String>,
+    /// Header regexes for the lists of packages an upgrade kept back, mapped
+    /// to the reason shown for them, e.g. `'kept back:$' = "dependencies"`.
+    #[serde(default)]
+    pub kept_back_patterns: HashMap<String, String>,
+    /// Upgrade variant that may install or remove packages to resolve
+    /// dependencies; `spn upgrade --full-upgrade` runs it when packages were
+    /// kept back.
+    #[serde(default)]
+    pub full_upgrade: Option<String>,
+    /// Shell conditions by phase, e.g. `{ refresh = "..." }`, checked before
+    /// the step runs; the step is skipped when its condition exits 0.
+    #[serde(default)]
+    pub skip_if: HashMap<Phase, String>,
+    /// Phases that need the network, skipped by `spn upgrade --offline`.
+    #[serde(default = "default_network_phases")]
+    pub network_phases: Vec<Phase>,
+    /// Downloads pending upgrades into the manager's cache without installing
+    /// them, for `spn prefetch`.
+    #[serde(default)]
+    pub prefetch: Option<String>,
+    /// Added to the manager's commands in network phases to cap the download
+    /// rate; `{kbps}` is the limit in KB/s. Without it spine falls back to
+    /// trickle.
+    #[serde(default)]
+    pub limit_rate_args: Option<String>,
+    /// Directory the manager's commands run in, instead of wherever spn was
+    /// started.
+    #[serde(default)]
+    pub workdir: Option<String>,
+    /// Run the manager's commands as this user (through `sudo -u`), for
+    /// user-level tools like cargo when spn itself runs as root from sudo or
+    /// a system cron job. Takes precedence over requires_sudo.
+    #[serde(default)]
+    pub run_as_user: Option<String>,
+    /// The manager installs into a user's home directory (brew, cargo, pip
+    /// --user), so spn never runs it as root: it runs as run_as_user or the
+    /// user who ran sudo, or is skipped.
+    #[serde(default)]
+    pub user_level: bool,
+    /// Tells apart the entries of a `[[managers.NAME]]` array, e.g. `arm`;
+    /// that entry is then the manager `NAME@arm`.
+    #[serde(default)]
+    pub instance: Option<String>,
+    /// Environment variables set for every command of the manager, such as
+    /// `HOMEBREW_PREFIX`.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Directories searched first for the manager's binary and put in front
+    /// of PATH for its commands, e.g. `["/opt/homebrew/bin"]`.
+    #[serde(default)]
+    pub path: Vec<String>,
+    /// Steps to run besides the built-in phases, such as removing old
+    /// kernels after an upgrade.
+    #[serde(default)]
+    pub phases: Vec<ExtraPhase>,
+}
+
+impl ManagerConfig {
+    /// Whether the upgrade goes one tool at a time (crate_upgrade, or
+    /// tools_command with tool_upgrade), skipping excluded ones itself.
+    pub fn upgrades_one_by_one(&self) -> bool {
+        self.crate_upgrade.is_some()
+            || (self.tools_command.is_some() && self.tool_upgrade.is_some())
+    }
+
+    /// The refresh command, unless it may only run as part of an upgrade.
+    pub fn standalone_refresh(&self) -> Option<&str> {
+        self.refresh
+            .as_deref()
+            .filter(|_| !self.refresh_needs_upgrade)
+    }
+
+    /// The command run for `phase`, if the manager has one.
+    pub fn command(&self, phase: &Phase) -> Option<&str> {
+        match phase {
+            Phase::Refresh => self.refresh.as_deref(),
+            Phase::SelfUpdate => self.self_update.as_deref(),
+            // Tasks leave it empty and run as a single extra phase
+            Phase::Upgrade => Some(self.upgrade_all.as_str()).filter(|command| !command.is_empty()),
+            Phase::Casks => self.cask_upgrade.as_deref(),
+            Phase::Cleanup => self.cleanup.as_deref(),
+            Phase::Custom(name) => self
+                .phases
+                .iter()
+                .find(|extra| extra.name == *name)
+                .map(|extra| extra.command.as_str()),
+        }
+    }
+
+    /// The steps a run goes through, in order: every built-in phase the
+    /// manager has a command for, each followed by the extra phases placed
+    /// `after` it, then the remaining extra phases.
+    pub fn steps(&self) -> Vec<PhaseStep> {
+        let extras_after = |after: Option<&Phase>| {
+            self.phases
+                .iter()
+                .filter(move |extra| extra.after.as_ref() == after)
+                .map(|extra| PhaseStep {
+                    phase: Phase::Custom(extra.name.clone()),
+                    command: extra.command.clone(),
+                    timeout: Duration::from_secs(extra.timeout_secs),
+                    optional: extra.optional,
+                    network: extra.network,
+                    skip_if: extra.skip_if.clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut steps = Vec::new();
+        for phase in Phase::ALL {
+            if let Some(command) = self.command(&phase) {
+                steps.push(PhaseStep {
+                    command: command.to_string(),
+                    timeout: phase.timeout(),
+                    optional: false,
+                    network: self.network_phases.contains(&phase),
+                    skip_if: self.skip_if.get(&phase).cloned(),
+                    phase: phase.clone(),
+                });
+            }
+            steps.extend(extras_after(Some(&phase)));
+        }
+        steps.extend(extras_after(None));
+        steps
+    }
+
+    /// Whether `--offline` leaves any of its phases to run.
+    pub fn works_offline(&self) -> bool {
+        self.steps().iter().any(|step| !step.network)
+    }
+}
+
+/// A step of a manager's run beyond the built-in phases, from a
+/// `[[managers.NAME.phases]]` table.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExtraPhase {
+    /// Shown in the logs, results and history.
+    pub name: String,
+    pub command: String,
+    /// Seconds the command may run before it's killed.
+    #[serde(default = "default_extra_phase_timeout")]
+    pub timeout_secs: u64,
+    /// The built-in phase it runs after, e.g. `upgrade`; unset runs it
+    /// after all of them.
+    #[serde(default)]
+    pub after: Option<Phase>,
+    /// Go on with the rest of the run when it fails.
+    #[serde(default)]
+    pub optional: bool,
+    /// Needs the network, so `spn upgrade --offline` skips it.
+    #[serde(default)]
+    pub network: bool,
+    /// Shell condition checked first; the phase is skipped when it exits 0.
+    #[serde(default)]
+    pub skip_if: Option<String>,
+}
+
+fn default_extra_phase_timeout() -> u64 {
+    600
+}
+
+/// A standalone command from a `[tasks.NAME]` table, such as `updatedb` or
+/// pulling dotfiles, run by `spn upgrade` as if it were a manager.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskConfig {
+    /// Shown in the TUI and summary instead of the table's name.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub command: String,
+    /// Whether it runs before the package managers or after them.
+    #[serde(default)]
+    pub when: TaskWhen,
+    #[serde(default)]
+    pub requires_sudo: bool,
+    /// Seconds the command may run before it's killed.
+    #[serde(default = "default_extra_phase_timeout")]
+    pub timeout_secs: u64,
+    /// Needs the network, so `spn upgrade --offline` skips it.
+    #[serde(default)]
+    pub network: bool,
+    /// Shell condition checked first; the task is skipped when it exits 0.
+    #[serde(default)]
+    pub skip_if: Option<String>,
+    #[serde(default = "default_manager_enabled")]
+    pub enabled: bool,
+}
+
+/// A local git checkout from a `[repos.NAME]` table, such as dotfiles or a
+/// suckless build, fast-forwarded by `spn upgrade` as if it were a manager.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RepoConfig {
+    /// Shown in the TUI and summary instead of the table's name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The checkout; a leading `~` stands for the home directory.
+    pub path: String,
+    /// Run in the checkout after a pull that brought new commits, e.g.
+    /// `sudo make install`.
+    #[serde(default)]
+    pub build: Option<String>,
+    /// Seconds the build may run before it's killed.
+    #[serde(default = "default_extra_phase_timeout")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_manager_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskWhen {
+    Before,
+    #[default]
+    After,
+}
+
+/// Packages no manager should upgrade, as shell-style patterns such as
+/// `linux-image-*`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExclusionsConfig {
+    #[serde(default)]
+    pub packages: Vec<String>,
+}
+
+/// Restarting services left running replaced libraries after an upgrade
+/// (Linux with systemd). Unit names are matched with shell-style patterns.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RestartsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Only restart services matching one of these; empty allows any.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Never restart these, even if allowed.
+    #[serde(default = "default_restart_deny")]
+    pub deny: Vec<String>,
+}
+
+impl Default for RestartsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow: Vec::new(),
+            deny: default_restart_deny(),
+        }
+    }
+}
+
+/// Running the remedy for a recognised failure, such as `dpkg --configure -a`
+/// after an interrupted dpkg run, and retrying the failed step once.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RecoveryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Updates across the WSL boundary, when spn runs inside Windows Subsystem
+/// for Linux.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WslConfig {
+    /// Also upgrade Windows through winget and WSL itself with `wsl --update`.
+    #[serde(default)]
+    pub windows: bool,
+    /// Other WSL distros whose APT packages to upgrade through `wsl.exe -d`.
+    #[serde(default)]
+    pub distros: Vec<String>,
+}
+
+/// Shell commands run when a run finishes, with its JSON summary on stdin.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct HooksConfig {
+    /// Run when every manager succeeded or was skipped.
+    #[serde(default)]
+    pub on_success: Option<String>,
+    /// Run when a manager failed or stopped at unverified signatures.
+    #[serde(default)]
+    pub on_failure: Option<String>,
+    /// Run after either of the above, whatever the outcome.
+    #[serde(default)]
+    pub on_complete: Option<String>,
+}
+
+/// How the TUI starts out.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TuiConfig {
+    /// Order of the manager list until `s` picks another.
+    #[serde(default)]
+    pub sort: ListSort,
+}
+
+/// Orders the TUI's manager list can be sorted in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSort {
+    /// The order managers run in, following `order`.
+    #[default]
+    Config,
+    Name,
+    /// Failures first, then stalled, running, pending and finished managers.
+    Status,
+    /// Longest first: this run's time, or the average of earlier runs.
+    Duration,
+    /// Most pending updates first, as counted by the last `spn check`.
+    Pending,
+}
+
+/// A central server every finished run is reported to, for keeping track of
+/// a fleet's patch status.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReportingConfig {
+    /// https:// URL the run's JSON summary is POSTed to; empty to not report.
+    #[serde(default)]
+    pub endpoint: String,
+    /// Sent as `Authorization: Bearer TOKEN` when set.
+    #[serde(default)]
+    pub token: String,
+}
+
+/// How much run history and saved failure output to keep. Pruned after
+/// every run and by `spn history prune`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoryConfig {
+    #[serde(default = "default_keep_runs")]
+    pub keep_runs: usize,
+    /// Drop runs older than this many days; 0 keeps them regardless of age.
+    #[serde(default)]
+    pub keep_days: u64,
+    /// Total size of the saved failure logs; the oldest go first.
+    #[serde(default = "default_history_max_mb")]
+    pub max_mb: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            keep_runs: default_keep_runs(),
+            keep_days: 0,
+            max_mb: default_history_max_mb(),
+        }
+    }
+}
+
+fn default_keep_runs() -> usize {
+    100
+}
+
+fn default_history_max_mb() -> u64 {
+    100
+}
+
+/// Where to report runs besides the desktop notification.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotifyConfig {
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Apprise-style service URLs (`tgram://`, `discord://`, `pover://`, ...)
+    /// sent a short message on each of `url_events`.
+    #[serde(default)]
+    pub urls: Vec<String>,
+    #[serde(default = "default_url_events")]
+    pub url_events: Vec<WebhookEvent>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            urls: Vec::new(),
+            url_events: default_url_events(),
+        }
+    }
+}
+
+/// An HTTP request sent as a run progresses, e.g. to a Home Assistant
+/// webhook trigger.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Events that fire it; all of them when left out.
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_webhook_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Request body with `{event}`, `{manager}`, `{state}` and the other
+    /// payload fields filled in; a JSON object of all of them by default.
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    RunStarted,
+    /// A manager finished, whatever its outcome.
+    ManagerFinished,
+    /// A manager failed or stopped on a signature problem.
+    ManagerFailed,
+    RunFinished,
+}
+
+fn default_webhook_events() -> Vec<WebhookEvent> {
+    vec![
+        WebhookEvent::RunStarted,
+        WebhookEvent::ManagerFinished,
+        WebhookEvent::ManagerFailed,
+        WebhookEvent::RunFinished,
+    ]
+}
+
+fn default_url_events() -> Vec<WebhookEvent> {
+    vec![WebhookEvent::RunFinished]
+}
+
+fn default_webhook_method() -> String {
+    "POST".to_string()
+}
+
+/// How much of each manager's output is kept in memory. The full output is
+/// always streamed to a file on disk.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogsConfig {
+    #[serde(default = "default_log_max_lines")]
+    pub max_lines: usize,
+    #[serde(default = "default_log_max_mb")]
+    pub max_mb: usize,
+    /// Lines from the start of the output that are always kept.
+    #[serde(default = "default_log_head_lines")]
+    pub head_lines: usize,
+    /// Show ANSI colors from command output in the TUI logs view.
+    #[serde(default = "default_log_colors")]
+    pub colors: bool,
+    /// Wrap long lines in the TUI logs view.
+    #[serde(default = "default_log_wrap")]
+    pub wrap: bool,
+    #[serde(default)]
+    pub line_numbers: bool,
+    /// Regexes for lines the logs view's next-error key jumps to.
+    #[serde(default = "default_log_error_patterns")]
+    pub error_patterns: Vec<String>,
+    /// Regexes for secrets to mask in output on top of the built-in ones;
+    /// a `secret` group limits the mask to that part of the match.
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+    /// What to mirror to journald, or syslog where there's no journal
+    /// (macOS's syslog goes to unified logging).
+    #[serde(default)]
+    pub system: SystemLog,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemLog {
+    #[default]
+    Off,
+    /// One entry per manager result and one for the run.
+    Summary,
+    /// The summary entries plus every line of output.
+    Full,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: default_log_max_lines(),
+            max_mb: default_log_max_mb(),
+            head_lines: default_log_head_lines(),
+            colors: default_log_colors(),
+            wrap: default_log_wrap(),
+            line_numbers: false,
+            error_patterns: default_log_error_patterns(),
+            redact_patterns: Vec::new(),
+            system: SystemLog::Off,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -35,6 +683,62 @@ pub struct AutoUpdateConfig {
     pub notify: bool,
     #[serde(default = "default_no_tui")]
     pub no_tui: bool,
+    #[serde(default)]
+    pub blackout: Vec<String>,
+    /// Hold scheduled runs while the machine runs on battery, until it's
+    /// plugged in.
+    #[serde(default)]
+    pub defer_on_battery: bool,
+    #[serde(default)]
+    pub jitter_minutes: u32,
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    #[serde(default)]
+    pub run_at_load: bool,
+    #[serde(default = "default_log_retention")]
+    pub log_retention: usize,
+    #[serde(default = "default_native_updaters")]
+    pub native_updaters: String,
+    /// Let unattended runs upgrade kernel and OS packages too.
+    #[serde(default)]
+    pub include_os: bool,
+    /// Time of day (HH:MM) for `spn prefetch` to download upgrades ahead of
+    /// the scheduled run; empty to not prefetch.
+    #[serde(default)]
+    pub prefetch_schedule: String,
+    /// Schedules of their own for groups of managers. When set, they are
+    /// installed instead of the schedule above.
+    #[serde(default)]
+    pub jobs: BTreeMap<String, AutoUpdateJob>,
+    /// HTTPS URL of a centrally managed config, pulled into place before
+    /// each scheduled run; empty to keep the local one.
+    #[serde(default)]
+    pub config_url: String,
+    /// SSH public key (`ssh-ed25519 AAAA...`) the pulled config must be
+    /// signed with, as `URL.sig`; empty to accept it unsigned.
+    #[serde(default)]
+    pub config_signing_key: String,
+    /// Scheduled runs go on with the built-in defaults when the config
+    /// doesn't parse, rather than stopping until it's fixed.
+    #[serde(default)]
+    pub fallback_to_defaults: bool,
+}
+
+/// One `[auto_update.jobs.NAME]` table. Timing keys left out are taken from
+/// `[auto_update]`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AutoUpdateJob {
+    #[serde(default)]
+    pub schedule: Option<String>,
+    #[serde(default)]
+    pub time: Option<String>,
+    #[serde(default)]
+    pub day: Option<String>,
+    #[serde(default)]
+    pub interval_hours: Option<u32>,
+    /// Managers the job upgrades; empty means every detected manager.
+    #[serde(default)]
+    pub managers: Vec<String>,
 }
 
 impl Default for AutoUpdateConfig {
@@ -46,10 +750,48 @@ impl Default for AutoUpdateConfig {
             day: default_day(),
             notify: default_notify(),
             no_tui: default_no_tui(),
+            blackout: Vec::new(),
+            defer_on_battery: false,
+            jitter_minutes: 0,
+            interval_hours: default_interval_hours(),
+            run_at_load: false,
+            log_retention: default_log_retention(),
+            native_updaters: default_native_updaters(),
+            include_os: false,
+            prefetch_schedule: String::new(),
+            jobs: BTreeMap::new(),
+            config_url: String::new(),
+            config_signing_key: String::new(),
+            fallback_to_defaults: false,
         }
     }
 }
 
+fn default_network_phases() -> Vec<Phase> {
+    vec![
+        Phase::Refresh,
+        Phase::SelfUpdate,
+        Phase::Upgrade,
+        Phase::Casks,
+    ]
+}
+
+fn default_order() -> String {
+    "alphabetical".to_string()
+}
+
+fn default_check_ttl_minutes() -> u64 {
+    60
+}
+
+fn default_detect_ttl_minutes() -> u64 {
+    60
+}
+
+fn default_prevent_sleep() -> bool {
+    true
+}
+
 fn default_schedule() -> String {
     "daily".to_string()
 }
@@ -62,6 +804,22 @@ fn default_day() -> String {
     "monday".to_string()
 }
 
+fn default_interval_hours() -> u32 {
+    6
+}
+
+fn default_log_retention() -> usize {
+    10
+}
+
+fn default_native_updaters() -> String {
+    "warn".to_string()
+}
+
+fn default_manager_enabled() -> bool {
+    true
+}
+
 fn default_notify() -> bool {
     true
 }
@@ -70,7 +828,231 @@ fn default_no_tui() -> bool {
     true
 }
 
+fn default_log_max_lines() -> usize {
+    20_000
+}
+
+fn default_log_max_mb() -> usize {
+    8
+}
+
+fn default_log_head_lines() -> usize {
+    200
+}
+
+fn default_log_colors() -> bool {
+    true
+}
+
+fn default_log_wrap() -> bool {
+    true
+}
+
+fn default_log_error_patterns() -> Vec<String> {
+    [r"(?i)\b(error|failed|fatal)\b", r"^E: "]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Services whose restart would end the desktop session or cut the network.
+fn default_restart_deny() -> Vec<String> {
+    [
+        "dbus*.service",
+        "systemd-logind.service",
+        "display-manager.service",
+        "gdm*.service",
+        "sddm.service",
+        "lightdm.service",
+        "NetworkManager.service",
+        "user@*.service",
+        "getty@*.service",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// Keys a config table accepts, read from its serde definition so
+/// generated docs can't drift from what the parser takes.
+pub fn table_keys<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut keys: &'static [&'static str] = &[];
+    // Derived structs hand their field list to deserialize_struct; stop there
+    let _ = T::deserialize(FieldProbe(&mut keys));
+    keys
+}
+
+struct FieldProbe<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldProbe<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> std::result::Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("fields recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map
+        enum identifier ignored_any
+    }
+}
+
+/// Read `[managers.NAME]` tables, and `[[managers.NAME]]` arrays defining
+/// several instances of one manager (two Homebrew prefixes, system and user
+/// pip), each entry keyed `NAME@instance`.
+fn deserialize_managers<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, ManagerConfig>, D::Error> {
+    struct ManagersVisitor;
+
+    impl<'de> Visitor<'de> for ManagersVisitor {
+        type Value = HashMap<String, ManagerConfig>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a table of managers")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            let mut managers = HashMap::new();
+            while let Some((name, definition)) = map.next_entry::<String, Definition>()? {
+                let entries = match definition {
+                    Definition::One(config) => vec![(name, *config)],
+                    Definition::Many(configs) => configs
+                        .into_iter()
+                        .map(|config| match &config.instance {
+                            Some(instance) => Ok((format!("{name}@{instance}"), config)),
+                            None => Err(de::Error::custom(format!(
+                                "every [[managers.{name}]] entry needs an instance name"
+                            ))),
+                        })
+                        .collect::<std::result::Result<_, _>>()?,
+                };
+                for (key, config) in entries {
+                    if managers.contains_key(&key) {
+                        return Err(de::Error::custom(format!("manager {key} is defined twice")));
+                    }
+                    managers.insert(key, config);
+                }
+            }
+            Ok(managers)
+        }
+    }
+
+    deserializer.deserialize_map(ManagersVisitor)
+}
+
+/// One `[managers.NAME]` table or a `[[managers.NAME]]` array of them.
+enum Definition {
+    One(Box<ManagerConfig>),
+    Many(Vec<ManagerConfig>),
+}
+
+impl<'de> Deserialize<'de> for Definition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct DefinitionVisitor;
+
+        impl<'de> Visitor<'de> for DefinitionVisitor {
+            type Value = Definition;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a manager table or an array of them")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> std::result::Result<Definition, A::Error> {
+                Box::<ManagerConfig>::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(Definition::One)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                seq: A,
+            ) -> std::result::Result<Definition, A::Error> {
+                Vec::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(Definition::Many)
+            }
+        }
+
+        deserializer.deserialize_any(DefinitionVisitor)
+    }
+}
+
+/// Project-scoped config read by `spn --local`.
+pub const LOCAL_CONFIG_FILE: &str = "spine.toml";
+
+/// Where `spn --system` keeps its config.
+const SYSTEM_CONFIG_DIR: &str = "/etc/spine";
+
+/// Where `spn --system` keeps history, logs and caches.
+const SYSTEM_DATA_DIR: &str = "/var/lib/spine";
+
+/// The config spn ships with, written out when there's none yet.
+const DEFAULT_CONFIG: &str = include_str!("../backbone.toml");
+
+/// Set by `--defaults`: a config that doesn't parse is replaced by the
+/// built-in defaults for the run instead of stopping it.
+static FALLBACK_TO_DEFAULTS: AtomicBool = AtomicBool::new(false);
+
+pub fn fall_back_to_defaults() {
+    FALLBACK_TO_DEFAULTS.store(true, Ordering::Relaxed);
+}
+
+/// Whether spn runs with `--defaults`.
+pub fn falls_back_to_defaults() -> bool {
+    FALLBACK_TO_DEFAULTS.load(Ordering::Relaxed)
+}
+
+/// Set once a config that didn't parse has been replaced by the defaults.
+static ON_DEFAULTS: AtomicBool = AtomicBool::new(false);
+
+/// Whether this run uses the built-in defaults in place of the config.
+pub fn runs_on_defaults() -> bool {
+    ON_DEFAULTS.load(Ordering::Relaxed)
+}
+
+/// Set by `--system` to use the machine-wide directories, for servers managed
+/// as root, instead of the user's.
+static SYSTEM: AtomicBool = AtomicBool::new(false);
+
+pub fn use_system_paths() {
+    SYSTEM.store(true, Ordering::Relaxed);
+}
+
+/// Whether spn runs with `--system`.
+pub fn system_mode() -> bool {
+    SYSTEM.load(Ordering::Relaxed)
+}
+
+/// Directory for spine's history, logs, caches and accepted managers:
+/// /var/lib/spine with `--system`, otherwise the user's data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    if system_mode() {
+        return Some(PathBuf::from(SYSTEM_DATA_DIR));
+    }
+    dirs::data_dir().map(|dir| dir.join("spine"))
+}
+
 fn get_config_paths() -> Vec<PathBuf> {
+    // --system reads only the machine-wide config, never a user's
+    if system_mode() {
+        return vec![PathBuf::from(SYSTEM_CONFIG_DIR).join("backbone.toml")];
+    }
+
     let mut paths = Vec::new();
 
     // XDG config directory (~/.config/spine/backbone.toml) - FIRST priority
@@ -103,7 +1085,16 @@ fn get_config_paths() -> Vec<PathBuf> {
 }
 
 async fn create_default_config() -> Result<PathBuf> {
-    let default_config = include_str!("../backbone.toml");
+    let default_config = DEFAULT_CONFIG;
+
+    if system_mode() {
+        tokio::fs::create_dir_all(SYSTEM_CONFIG_DIR)
+            .await
+            .map_err(|e| anyhow::anyhow!("Couldn't create {SYSTEM_CONFIG_DIR}: {e}"))?;
+        let config_path = PathBuf::from(SYSTEM_CONFIG_DIR).join("backbone.toml");
+        tokio::fs::write(&config_path, default_config).await?;
+        return Ok(config_path);
+    }
 
     // Always try XDG config directory first (default on all systems)
     if let Some(config_dir) = dirs::config_dir() {
@@ -133,25 +1124,293 @@ async fn create_default_config() -> Result<PathBuf> {
     anyhow::bail!("Unable to create config directory in any standard location");
 }
 
-pub async fn load_config() -> Result<Config> {
-    let possible_paths = get_config_paths();
-
-    for path in &possible_paths {
-        if path.exists() {
-            let content = tokio::fs::read_to_string(&path).await?;
-            let config: Config = toml::from_str(&content)?;
-            return Ok(config);
-        }
+/// Path of the config in use, creating the default one if there is none.
+pub async fn config_file() -> Result<PathBuf> {
+    if let Some(path) = get_config_paths().into_iter().find(|path| path.exists()) {
+        return Ok(path);
     }
 
     // No config found, create a default one
     let created_path = create_default_config().await?;
-    let content = tokio::fs::read_to_string(&created_path).await?;
-    let config: Config = toml::from_str(&content)?;
-
     eprintln!(
         "Created default configuration at: {}",
         created_path.display()
     );
+    Ok(created_path)
+}
+
+pub async fn load_config() -> Result<Config> {
+    let path = config_file().await?;
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("Couldn't read {}", path.display()))?;
+    let mut config = parse_config(&content, &path)?;
+    crate::preset_groups::add_managers(&mut config);
+    crate::wsl::add_managers(&mut config);
+    Ok(config)
+}
+
+/// Find the nearest `spine.toml` in the current directory or one of its parents.
+pub fn find_local_config() -> Option<PathBuf> {
+    let current_dir = std::env::current_dir().ok()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(LOCAL_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Load the project-local config, returning it with the path it was read from.
+pub async fn load_local_config() -> Result<(Config, PathBuf)> {
+    let Some(path) = find_local_config() else {
+        anyhow::bail!("No {LOCAL_CONFIG_FILE} found in the current directory or any parent");
+    };
+
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("Couldn't read {}", path.display()))?;
+    let mut config = parse_config(&content, &path)?;
+    crate::preset_groups::add_managers(&mut config);
+    Ok((config, path))
+}
+
+/// The built-in defaults, for a run whose own config doesn't parse.
+pub fn default_config() -> Result<Config> {
+    let mut config = parse_config(DEFAULT_CONFIG, Path::new("the built-in backbone.toml"))?;
+    crate::wsl::add_managers(&mut config);
     Ok(config)
 }
+
+/// The built-in defaults in place of the config `error` is about, with the
+/// `[auto_update]` and `[exclusions]` settings of that file that can still
+/// be read, so a mistake elsewhere doesn't lift exclusions, blackouts or
+/// battery deferral. Also returns which of those tables were kept.
+pub fn fallback_config(error: &ParseError) -> Result<(Config, Vec<&'static str>)> {
+    let mut config = default_config()?;
+    ON_DEFAULTS.store(true, Ordering::Relaxed);
+    let Ok(content) = std::fs::read_to_string(&error.path) else {
+        return Ok((config, Vec::new()));
+    };
+
+    let mut kept = Vec::new();
+    if let Some(auto_update) = recover_table(&content, "auto_update", &config.auto_update) {
+        config.auto_update = auto_update;
+        kept.push("[auto_update]");
+    }
+    if let Some(exclusions) = recover_table(&content, "exclusions", &config.exclusions) {
+        config.exclusions = exclusions;
+        kept.push("[exclusions]");
+    }
+    Ok((config, kept))
+}
+
+/// The table `name` of a config that doesn't parse as a whole, with each
+/// key that's valid on its own laid over `default`. When the file isn't
+/// even valid TOML, only the lines under `[name]` and `[name.*]` headers
+/// are read, so a mistake in another table doesn't matter.
+fn recover_table<T: Serialize + DeserializeOwned>(
+    content: &str,
+    name: &str,
+    default: &T,
+) -> Option<T> {
+    let table = match toml::from_str::<toml::Table>(content) {
+        Ok(mut document) => document.remove(name)?,
+        Err(_) => {
+            let mut section = String::new();
+            let mut inside = false;
+            let header = Regex::new(r#"^\s*\[\[?([A-Za-z0-9_.\-"'\s]+)\]\]?\s*(#.*)?$"#)
+                .expect("table header");
+            for line in content.lines() {
+                if let Some(captures) = header.captures(line) {
+                    let path: String = captures[1].chars().filter(|c| !c.is_whitespace()).collect();
+                    inside = path == name || path.starts_with(&format!("{name}."));
+                }
+                if inside {
+                    section.push_str(line);
+                    section.push('\n');
+                }
+            }
+            toml::from_str::<toml::Table>(&section).ok()?.remove(name)?
+        }
+    };
+    let toml::Value::Table(table) = table else {
+        return None;
+    };
+
+    let toml::Value::Table(mut merged) = toml::Value::try_from(default).ok()? else {
+        return None;
+    };
+    let mut kept_any = false;
+    for (key, value) in table {
+        let previous = merged.insert(key.clone(), value);
+        if T::deserialize(toml::Value::Table(merged.clone())).is_ok() {
+            kept_any = true;
+        } else {
+            match previous {
+                Some(previous) => merged.insert(key, previous),
+                None => merged.remove(&key),
+            };
+        }
+    }
+    if !kept_any {
+        return None;
+    }
+    T::deserialize(toml::Value::Table(merged)).ok()
+}
+
+/// Parse the contents of the config file at `path`. When they don't parse,
+/// the error is a [`ParseError`] pointing at the mistake.
+pub fn parse_config(content: &str, path: &Path) -> Result<Config> {
+    toml::from_str(content).map_err(|e| ParseError::new(&e, content, path).into())
+}
+
+/// A config file that doesn't parse: the file, the line at fault with the
+/// mistake marked, what's wrong and, for common slips, how to fix it.
+#[derive(Debug)]
+pub struct ParseError {
+    path: PathBuf,
+    /// Line and column (from 1), the line's text and how many characters
+    /// of it to mark, when the error points somewhere.
+    location: Option<(usize, usize, String, usize)>,
+    message: String,
+    hint: Option<String>,
+}
+
+impl ParseError {
+    /// The file, line and what's wrong, on one line for a notification.
+    pub fn summary(&self) -> String {
+        let message = self.message.lines().next().unwrap_or_default();
+        match &self.location {
+            Some((line, ..)) => format!("{}, line {line}: {message}", self.path.display()),
+            None => format!("{}: {message}", self.path.display()),
+        }
+    }
+
+    fn new(error: &toml::de::Error, content: &str, path: &Path) -> Self {
+        let message = error.message().trim().to_string();
+        let location = error.span().map(|span| {
+            let start = span.start.min(content.len());
+            let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+            let text = content[line_start..].lines().next().unwrap_or_default();
+            let column = content[line_start..start].chars().count() + 1;
+            let marked = content[start..span.end.min(content.len())]
+                .chars()
+                .take_while(|c| *c != '\n')
+                .count()
+                .max(1);
+            let line = content[..start].matches('\n').count() + 1;
+            (line, column, text.to_string(), marked)
+        });
+        let hint = hint(
+            &message,
+            location
+                .as_ref()
+                .map_or("", |(_, _, text, _)| text.as_str()),
+        );
+        ParseError {
+            path: path.to_path_buf(),
+            location,
+            message,
+            hint,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some((line, column, text, marked)) => {
+                let gutter = " ".repeat(line.to_string().len());
+                writeln!(
+                    f,
+                    "Couldn't parse {}, line {line}, column {column}:",
+                    self.path.display()
+                )?;
+                writeln!(f, "{gutter} |")?;
+                writeln!(f, "{line} | {text}")?;
+                writeln!(
+                    f,
+                    "{gutter} | {}{}",
+                    " ".repeat(column - 1),
+                    "^".repeat(*marked)
+                )?;
+                write!(f, "{}", self.message)?;
+            }
+            None => write!(
+                f,
+                "Couldn't parse {}: {}",
+                self.path.display(),
+                self.message
+            )?,
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\nHint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// How to fix the mistakes toml reports as `message` on the line `text`,
+/// with the line rewritten where that's clear.
+fn hint(message: &str, text: &str) -> Option<String> {
+    let (key, value) = text
+        .split_once('=')
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .unwrap_or_default();
+    // The unquoted value, without a trailing comment
+    let bare = value.split(" #").next().unwrap_or_default().trim();
+    // The value of `invalid type: string "60", expected u64`
+    let quoted = message
+        .strip_prefix("invalid type: string \"")
+        .and_then(|rest| rest.split_once("\", expected"))
+        .map(|(string, _)| string);
+
+    if let Some(field) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+    {
+        return Some(format!(
+            "{} needs a `{field}` key; 'spn man' lists the keys each table takes",
+            if text.starts_with('[') {
+                text.trim()
+            } else {
+                "This table"
+            }
+        ));
+    }
+    if message.starts_with("duplicate key") {
+        return Some("The key is set twice in this table; keep one of them".to_string());
+    }
+    if let Some(string) = quoted {
+        let expected = message.rsplit("expected ").next().unwrap_or_default();
+        if expected == "a boolean" {
+            return Some(format!(
+                "Use true or false, without quotes: {key} = {}",
+                !matches!(string, "false" | "no" | "off" | "0")
+            ));
+        }
+        if expected.starts_with('u') || expected.starts_with('i') || expected.contains("integer") {
+            return Some(format!(
+                "Numbers are written without quotes: {key} = {string}"
+            ));
+        }
+        if expected == "a sequence" {
+            return Some(format!("This is a list: {key} = [\"{string}\"]"));
+        }
+    }
+    if message.starts_with("invalid type:") && message.ends_with("expected a string") {
+        return Some(format!("Text needs quotes: {key} = \"{bare}\""));
+    }
+    if message.starts_with("invalid string") || message.starts_with("invalid time") {
+        return Some(format!("Text needs quotes: {key} = \"{bare}\""));
+    }
+    if message.contains("expected `]`") || message.starts_with("invalid table header") {
+        return Some("A `[` on this line or above isn't closed with `]`".to_string());
+    }
+    if message.starts_with("invalid basic string") || message.starts_with("invalid literal string")
+    {
+        return Some("A quote on this line isn't closed".to_string());
+    }
+    None
+}
@@ -0,0 +1,188 @@
+use crate::config::{
+    self, AutoUpdateConfig, AutoUpdateJob, Config, ExclusionsConfig, HistoryConfig, HooksConfig,
+    LogsConfig, ManagerConfig, NotifyConfig, RecoveryConfig, RepoConfig, ReportingConfig,
+    RestartsConfig, TaskConfig, TuiConfig, WslConfig,
+};
+use anyhow::{Context, Result};
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// Split a dotted key like `managers.brew.requires_sudo` and check it names a
+/// setting spine knows about, so typos don't get written silently.
+fn parse_key(key: &str) -> Result<Vec<&str>> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        anyhow::bail!("Invalid key '{key}'. Use dotted names, e.g. auto_update.time");
+    }
+
+    let known = |keys: &[&str], name: &str| keys.contains(&name);
+    let valid = match parts.as_slice() {
+        ["managers"] | ["managers", _] => true,
+        ["managers", _, "noninteractive_env", ..] => true,
+        ["managers", _, "env", ..] => true,
+        ["managers", _, "skip_if", ..] => true,
+        ["managers", _, name] => known(config::table_keys::<ManagerConfig>(), name),
+        ["tasks"] | ["tasks", _] => true,
+        ["tasks", _, name] => known(config::table_keys::<TaskConfig>(), name),
+        ["repos"] | ["repos", _] => true,
+        ["repos", _, name] => known(config::table_keys::<RepoConfig>(), name),
+        ["auto_update"] => true,
+        ["auto_update", name] => known(config::table_keys::<AutoUpdateConfig>(), name),
+        ["auto_update", "jobs", _] => true,
+        ["auto_update", "jobs", _, name] => known(config::table_keys::<AutoUpdateJob>(), name),
+        ["exclusions"] => true,
+        ["exclusions", name] => known(config::table_keys::<ExclusionsConfig>(), name),
+        ["restarts"] => true,
+        ["restarts", name] => known(config::table_keys::<RestartsConfig>(), name),
+        ["logs"] => true,
+        ["logs", name] => known(config::table_keys::<LogsConfig>(), name),
+        ["recovery"] => true,
+        ["recovery", name] => known(config::table_keys::<RecoveryConfig>(), name),
+        ["notify"] => true,
+        ["notify", name] => known(config::table_keys::<NotifyConfig>(), name),
+        ["history"] => true,
+        ["history", name] => known(config::table_keys::<HistoryConfig>(), name),
+        ["wsl"] => true,
+        ["wsl", name] => known(config::table_keys::<WslConfig>(), name),
+        ["hooks"] => true,
+        ["hooks", name] => known(config::table_keys::<HooksConfig>(), name),
+        ["reporting"] => true,
+        ["reporting", name] => known(config::table_keys::<ReportingConfig>(), name),
+        ["tui"] => true,
+        ["tui", name] => known(config::table_keys::<TuiConfig>(), name),
+        [name] => known(config::table_keys::<Config>(), name),
+        _ => false,
+    };
+    if !valid {
+        anyhow::bail!("Unknown setting '{key}'. See 'spn man' for the available keys");
+    }
+    Ok(parts)
+}
+
+async fn read(path: &Path) -> Result<String> {
+    tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Couldn't read {}", path.display()))
+}
+
+/// `spn config get`: print the value in effect for a key, including defaults
+/// the file leaves out. Strings are printed bare for use in scripts.
+pub async fn get(path: &Path, key: &str) -> Result<()> {
+    let parts = parse_key(key)?;
+    let config = config::parse_config(&read(path).await?, path)?;
+    let effective = toml::Value::try_from(config)?;
+
+    let value = parts
+        .iter()
+        .try_fold(&effective, |value, part| value.get(part))
+        .with_context(|| format!("'{key}' is not set in {}", path.display()))?;
+    match value {
+        toml::Value::String(text) => println!("{text}"),
+        toml::Value::Table(table) => print!("{}", toml::to_string(table)?),
+        other => println!("{other}"),
+    }
+    Ok(())
+}
+
+/// `spn config set`: change one value in place, keeping the rest of the file
+/// (comments, ordering, formatting) as it was.
+///
+/// The value is read as TOML (`false`, `6`, `["a"]`) and otherwise as a plain
+/// string, whichever the configuration accepts.
+pub async fn set(path: &Path, key: &str, raw: &str) -> Result<()> {
+    let value = set_value(path, key, raw).await?;
+    println!("{key} = {value}");
+    Ok(())
+}
+
+/// Write `raw` to `key` if the config still parses with it, keeping the
+/// file's comments and layout. Returns the value as written.
+pub async fn set_value(path: &Path, key: &str, raw: &str) -> Result<String> {
+    let parts = parse_key(key)?;
+    let document: DocumentMut = read(path)
+        .await?
+        .parse()
+        .with_context(|| format!("Couldn't parse {}", path.display()))?;
+
+    let candidates = raw
+        .parse::<Value>()
+        .ok()
+        .into_iter()
+        .chain([Value::from(raw)]);
+    let mut first_error = None;
+    for value in candidates {
+        let mut edited = document.clone();
+        assign(&mut edited, &parts, value.clone())?;
+        let content = edited.to_string();
+        match toml::from_str::<Config>(&content) {
+            Ok(_) => {
+                tokio::fs::write(path, content)
+                    .await
+                    .with_context(|| format!("Couldn't write {}", path.display()))?;
+                return Ok(value.to_string().trim().to_string());
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) => Err(e).with_context(|| format!("'{raw}' is not a valid value for {key}")),
+        None => anyhow::bail!("'{raw}' is not a valid value for {key}"),
+    }
+}
+
+fn assign(document: &mut DocumentMut, parts: &[&str], mut value: Value) -> Result<()> {
+    let Some((last, parents)) = parts.split_last() else {
+        return Ok(());
+    };
+
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    let mut inline = false;
+    for (depth, part) in parents.iter().enumerate() {
+        // managers.NAME@instance is the [[managers.NAME]] entry with that instance
+        if let (["managers"], Some((name, instance))) = (&parts[..depth], part.split_once('@')) {
+            table = table
+                .get_mut(name)
+                .and_then(Item::as_array_of_tables_mut)
+                .and_then(|entries| {
+                    entries.iter_mut().find(|entry| {
+                        entry.get("instance").and_then(Item::as_str) == Some(instance)
+                    })
+                })
+                .with_context(|| {
+                    format!("There's no [[managers.{name}]] entry with instance = \"{instance}\"")
+                })?;
+            inline = false;
+            continue;
+        }
+        let item = table.entry(part).or_insert(toml_edit::table());
+        inline = item.is_inline_table();
+        table = item
+            .as_table_like_mut()
+            .with_context(|| format!("'{}' is a value, not a table", parts[..=depth].join(".")))?;
+    }
+
+    match table.get_mut(last) {
+        // Keep the spacing and trailing comment of the line being replaced
+        Some(Item::Value(existing)) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        Some(item) if !item.is_none() => {
+            anyhow::bail!(
+                "'{}' is a table; set one of its keys instead",
+                parts.join(".")
+            )
+        }
+        _ => {
+            table.insert(last, Item::Value(value));
+            // Otherwise the new entry picks up the old last entry's spacing
+            if inline {
+                table.fmt();
+            }
+        }
+    }
+    Ok(())
+}
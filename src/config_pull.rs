@@ -0,0 +1,262 @@
+use crate::config::{self, Config};
+use crate::logs;
+use crate::secrets;
+use crate::webhook::curl_quote;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// How long fetching the config or its signature may take.
+const PULL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The namespace configs are signed in, as in
+/// `ssh-keygen -Y sign -n spine -f key backbone.toml`.
+const SIGNATURE_NAMESPACE: &str = "spine";
+
+/// The last config pulled, so an unchanged one is answered with 304 Not
+/// Modified instead of being downloaded again.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Cache {
+    url: String,
+    #[serde(default)]
+    etag: Option<String>,
+    body: String,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("pulled-config.toml"))
+}
+
+async fn load_cache() -> Option<Cache> {
+    let content = tokio::fs::read_to_string(cache_path()?).await.ok()?;
+    toml::from_str(&content).ok()
+}
+
+async fn save_cache(cache: &Cache) -> Result<()> {
+    let path = cache_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(cache)?).await?;
+    Ok(())
+}
+
+/// `spn config pull`: replace the config at `path` with the one at `url`, or
+/// at its auto_update.config_url when no URL is given.
+pub async fn run_pull(path: &Path, url: Option<&str>) -> Result<()> {
+    // The signing key comes from the current config, so without it any
+    // unsigned config would be taken
+    let current = read_config(path)
+        .await
+        .context("Not pulling, since the current config's config_signing_key can't be checked")?;
+    let url = match url {
+        Some(url) => url.to_string(),
+        None => Some(current.auto_update.config_url.clone())
+            .filter(|url| !url.is_empty())
+            .context("No URL given and auto_update.config_url isn't set")?,
+    };
+    let signing_key = current.auto_update.config_signing_key;
+    if pull(path, &url, &signing_key).await? {
+        println!("Updated {} from {url}", path.display());
+    } else {
+        println!("{} is already up to date with {url}", path.display());
+    }
+    Ok(())
+}
+
+async fn read_config(path: &Path) -> Result<Config> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Couldn't read {}", path.display()))?;
+    config::parse_config(&content, path)
+}
+
+/// Fetch the config at `url` and put it in place at `path`, returning
+/// whether that changed anything. With a `signing_key`, the config must
+/// come with a matching signature at `URL.sig`. Nothing is written unless
+/// it parses as a spine config.
+pub async fn pull(path: &Path, url: &str, signing_key: &str) -> Result<bool> {
+    if !url.starts_with("https://") {
+        anyhow::bail!("Only https:// config URLs are supported");
+    }
+    let cache = load_cache()
+        .await
+        .filter(|cache| cache.url == url)
+        // A cached copy without a signature can't be checked against a key
+        .filter(|cache| signing_key.is_empty() || cache.signature.is_some());
+
+    // A token in the URL stays out of the cache and messages
+    let source = secrets::expand(url).await?;
+    let cache = match fetch(&source, cache.as_ref().and_then(|c| c.etag.as_deref())).await? {
+        Fetched::NotModified => {
+            cache.context("The server answered 304 for a config never pulled")?
+        }
+        Fetched::Body { body, etag } => Cache {
+            url: url.to_string(),
+            etag,
+            signature: if signing_key.is_empty() {
+                None
+            } else {
+                match fetch(&format!("{source}.sig"), None).await? {
+                    Fetched::Body { body, .. } => Some(body),
+                    Fetched::NotModified => None,
+                }
+            },
+            body,
+        },
+    };
+
+    if !signing_key.is_empty() {
+        let signature = cache
+            .signature
+            .as_deref()
+            .context("The config has no signature")?;
+        verify(&cache.body, signature, signing_key).await?;
+    }
+    toml::from_str::<Config>(&cache.body)
+        .with_context(|| format!("{url} isn't a valid spine config"))?;
+
+    let changed = tokio::fs::read_to_string(path).await.ok().as_deref() != Some(&cache.body);
+    if changed {
+        // Written next to it and renamed, so a run never reads half a config
+        let partial = path.with_extension("toml.pulled");
+        tokio::fs::write(&partial, &cache.body)
+            .await
+            .with_context(|| format!("Couldn't write {}", partial.display()))?;
+        tokio::fs::rename(&partial, path)
+            .await
+            .with_context(|| format!("Couldn't replace {}", path.display()))?;
+    }
+    if let Err(e) = save_cache(&cache).await {
+        eprintln!("Warning: could not cache the pulled config: {e}");
+    }
+    Ok(changed)
+}
+
+enum Fetched {
+    NotModified,
+    Body { body: String, etag: Option<String> },
+}
+
+/// GET `url` through curl, HTTPS only, even across redirects. The URL goes
+/// in a curl config on stdin, since it may hold a token other users
+/// shouldn't see in `ps`.
+async fn fetch(url: &str, etag: Option<&str>) -> Result<Fetched> {
+    let mut config = format!("url = {}\n", curl_quote(url));
+    if let Some(etag) = etag {
+        config.push_str(&format!(
+            "header = {}\n",
+            curl_quote(&format!("If-None-Match: {etag}"))
+        ));
+    }
+
+    let mut cmd = Command::new("curl");
+    cmd.args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--location",
+        "--include",
+    ])
+    .args(["--proto", "=https", "--proto-redir", "=https", "--max-time"])
+    .arg(PULL_TIMEOUT.as_secs().to_string())
+    .args(["--config", "-"])
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true);
+    let mut child = cmd.spawn().context("Couldn't run curl")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).await?;
+    }
+    let output = tokio::time::timeout(PULL_TIMEOUT * 2, child.wait_with_output())
+        .await
+        .context("curl didn't finish")??;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    // --include puts each response's headers first, redirects included
+    let mut rest = String::from_utf8_lossy(&output.stdout).into_owned();
+    let mut headers = String::new();
+    while rest.starts_with("HTTP/") {
+        let Some((head, body)) = rest.split_once("\r\n\r\n") else {
+            break;
+        };
+        headers = head.to_string();
+        rest = body.to_string();
+    }
+    if headers
+        .lines()
+        .next()
+        .is_some_and(|status| status.split_whitespace().nth(1) == Some("304"))
+    {
+        return Ok(Fetched::NotModified);
+    }
+    let etag = headers.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("etag")
+            .then(|| value.trim().to_string())
+    });
+    Ok(Fetched::Body { body: rest, etag })
+}
+
+/// The private directory `verify` writes the key and signature to, removed
+/// however the check ends.
+struct VerifyDir(PathBuf);
+
+impl Drop for VerifyDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Check an SSH signature (`ssh-keygen -Y sign`) over `body` against
+/// `signing_key`.
+async fn verify(body: &str, signature: &str, signing_key: &str) -> Result<()> {
+    let dir = VerifyDir(
+        logs::private_dir("spine-verify").context("Couldn't create a directory to check in")?,
+    );
+    let signers = dir.0.join("allowed_signers");
+    let signature_path = dir.0.join("config.sig");
+    tokio::fs::write(&signers, format!("spine {}\n", signing_key.trim())).await?;
+    tokio::fs::write(&signature_path, signature).await?;
+
+    let mut child = Command::new("ssh-keygen")
+        .args([
+            "-Y",
+            "verify",
+            "-I",
+            "spine",
+            "-n",
+            SIGNATURE_NAMESPACE,
+            "-f",
+        ])
+        .arg(&signers)
+        .arg("-s")
+        .arg(&signature_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Couldn't run ssh-keygen to check the signature")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "The config's signature doesn't match config_signing_key: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use crate::config::ManagerConfig;
+use crate::detect::RunState;
+use crate::execute::{run_captured, shell_quote, RunAs};
+use crate::tools::ToolUpgrade;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// crates.io's sparse index, which cargo itself reads; unlike the web API it
+/// has no request rate limit.
+const INDEX_URL: &str = "https://index.crates.io";
+
+/// The installed crates to reinstall, and those that couldn't be looked up.
+pub struct CratePlan {
+    pub upgrades: Vec<ToolUpgrade>,
+    pub failed_lookups: Vec<(String, String)>,
+}
+
+/// The installed crates with a newer stable release, in the order `cargo
+/// install --list` prints them. Crates installed from git or a local path
+/// aren't on crates.io and are left alone.
+pub async fn plan(config: &ManagerConfig) -> Result<CratePlan> {
+    let list = run_captured(
+        "cargo install --list",
+        RunAs::manager_query(config),
+        QUERY_TIMEOUT,
+    )
+    .await?;
+    let installed = parse_install_list(&list);
+
+    let mut lookups = JoinSet::new();
+    for (index, (name, _)) in installed.iter().enumerate() {
+        let name = name.clone();
+        lookups.spawn(async move { (index, latest_version(&name).await) });
+    }
+    let mut latest = vec![None; installed.len()];
+    let mut failed_lookups = Vec::new();
+    while let Some(joined) = lookups.join_next().await {
+        let (index, result) = joined?;
+        match result {
+            Ok(version) => latest[index] = version,
+            Err(e) => failed_lookups.push((installed[index].0.clone(), format!("{e:#}"))),
+        }
+    }
+
+    let upgrades = installed
+        .into_iter()
+        .zip(latest)
+        .filter_map(|((name, installed), latest)| {
+            let latest = latest?;
+            (compare_versions(&latest, &installed) == Ordering::Greater).then_some(ToolUpgrade {
+                name,
+                installed: Some(installed),
+                latest: Some(latest),
+                state: RunState::Pending,
+            })
+        })
+        .collect();
+    Ok(CratePlan {
+        upgrades,
+        failed_lookups,
+    })
+}
+
+/// Crate names and versions from `cargo install --list`, whose entries look
+/// like `ripgrep v14.1.0:` followed by indented binary names.
+fn parse_install_list(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let (name, rest) = line.trim_end().strip_suffix(':')?.split_once(' ')?;
+            // `name v1.0.0 (https://...)` or `(/path)`: not from crates.io
+            let version = rest.strip_prefix('v').filter(|v| !v.contains(' '))?;
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// The newest version of `name` on crates.io that isn't yanked or a
+/// pre-release, or None if it has none.
+async fn latest_version(name: &str) -> Result<Option<String>> {
+    let url = format!("{INDEX_URL}/{}", index_path(name));
+    let body = run_captured(
+        &format!("curl -fsSL --max-time 30 {}", shell_quote(&url)),
+        false,
+        QUERY_TIMEOUT,
+    )
+    .await?;
+
+    let mut latest: Option<String> = None;
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: Value = serde_json::from_str(line).context("Unexpected index entry")?;
+        let Some(version) = entry["vers"].as_str() else {
+            continue;
+        };
+        if entry["yanked"].as_bool() == Some(true) || version.contains('-') {
+            continue;
+        }
+        if latest
+            .as_deref()
+            .is_none_or(|latest| compare_versions(version, latest) == Ordering::Greater)
+        {
+            latest = Some(version.to_string());
+        }
+    }
+    Ok(latest)
+}
+
+/// Where the index keeps a crate's file: `1/a`, `2/ab`, `3/a/abc`, then
+/// `ab/cd/abcd...` by the name's first four letters.
+fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Order two semver versions. Build metadata is ignored, and a pre-release
+/// comes before the release it leads up to.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| {
+        let version = version.split('+').next().unwrap_or_default();
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (version, None),
+        };
+        let numbers: Vec<u64> = core.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+        (numbers, pre)
+    };
+    let (a_numbers, a_pre) = parse(a);
+    let (b_numbers, b_pre) = parse(b);
+    a_numbers
+        .cmp(&b_numbers)
+        .then_with(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(&b),
+        })
+}
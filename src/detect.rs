@@ -1,47 +1,748 @@
-use crate::config::{Config, ManagerConfig};
+use crate::a11y;
+use crate::config::{self, Config, LogsConfig, ManagerConfig};
+use crate::diagnose::{Cause, Recovery};
+use crate::execute::{run_captured, ExitReason, RunAs};
+use crate::i18n::t;
+use crate::kept_back::KeptBack;
+use crate::logs::LogBuffer;
+use crate::stats::format_duration_secs;
+use crate::tools::ToolUpgrade;
+use crate::transaction::PackageChanges;
 use anyhow::Result;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinSet;
+
+/// Managers probed at once during detection.
+const DETECT_CONCURRENCY: usize = 4;
+
+/// How long a check_command gets to print the manager's version.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Detection that takes longer than this shows its progress.
+const DETECT_PROGRESS_DELAY: Duration = Duration::from_millis(300);
 
 #[derive(Debug, Clone)]
 pub struct DetectedManager {
     pub name: String,
     pub config: ManagerConfig,
     pub status: ManagerStatus,
-    pub logs: String,
+    pub logs: LogBuffer,
+    pub duration: Option<Duration>,
+    pub timeline: Vec<StatusChange>,
+    /// When the status was last updated, including phase results that
+    /// don't show up in the timeline.
+    pub updated_at: Instant,
+    /// Packages the run installed, upgraded and removed, when the manager
+    /// has an installed_command.
+    pub changes: Option<PackageChanges>,
+    /// First line printed by check_command, if it ran successfully.
+    pub version: Option<String>,
+    pub stage: Stage,
+}
+
+/// When a workflow runs: `[tasks]` go before or after the package managers,
+/// which run together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Before,
+    #[default]
+    Managers,
+    After,
 }
 
+/// A step of a manager's workflow, run in this order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Refresh,
+    SelfUpdate,
+    Upgrade,
+    /// Homebrew casks, upgraded separately from formulae.
+    Casks,
+    Cleanup,
+    /// One of the manager's own `[[managers.NAME.phases]]`, by name.
+    Custom(String),
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Phase::Refresh,
+        Phase::SelfUpdate,
+        Phase::Upgrade,
+        Phase::Casks,
+        Phase::Cleanup,
+    ];
+
+    /// English name used in logs and recorded errors.
+    pub fn name(&self) -> &str {
+        match self {
+            Phase::Refresh => "Refresh",
+            Phase::SelfUpdate => "Self-update",
+            Phase::Upgrade => "Upgrade",
+            Phase::Casks => "Casks",
+            Phase::Cleanup => "Cleanup",
+            Phase::Custom(name) => name,
+        }
+    }
+
+    fn message_id(&self) -> Option<&'static str> {
+        match self {
+            Phase::Refresh => Some("phase-refresh"),
+            Phase::SelfUpdate => Some("phase-self-update"),
+            Phase::Upgrade => Some("phase-upgrade"),
+            Phase::Casks => Some("phase-casks"),
+            Phase::Cleanup => Some("phase-cleanup"),
+            Phase::Custom(_) => None,
+        }
+    }
+
+    /// Translated name shown to the user; a custom phase's own name.
+    pub fn title(&self) -> String {
+        match self.message_id() {
+            Some(id) => t!(id),
+            None => self.name().to_string(),
+        }
+    }
+
+    /// Present participle shown while the phase runs, e.g. "Refreshing".
+    pub fn label(&self) -> String {
+        match self.message_id() {
+            Some(id) => t!(&format!("{id}-active")),
+            None => self.name().to_string(),
+        }
+    }
+
+    /// How long the phase's command may run before it's killed. Custom
+    /// phases set their own timeout_secs.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(match self {
+            Phase::Refresh | Phase::Cleanup => 300,
+            Phase::SelfUpdate | Phase::Custom(_) => 600,
+            Phase::Upgrade | Phase::Casks => 3600,
+        })
+    }
+}
+
+/// One step of a manager's run, planned from its config
+/// ([`ManagerConfig::steps`](crate::config::ManagerConfig::steps)).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseStep {
+    pub phase: Phase,
+    pub command: String,
+    pub timeout: Duration,
+    /// The steps after it still run when it fails; its failure is recorded
+    /// but doesn't fail the manager.
+    pub optional: bool,
+    /// Needs the network, so `--offline` skips it.
+    pub network: bool,
+    /// Shell condition checked first; the step is skipped when it exits 0.
+    pub skip_if: Option<String>,
+}
+
+/// Outcome of one finished phase.
 #[derive(Debug, Clone, PartialEq)]
-pub enum ManagerStatus {
+pub struct PhaseResult {
+    pub phase: Phase,
+    pub duration: Duration,
+    /// None when the command never finished on its own (timeout, stall, kill).
+    pub exit: Option<ExitReason>,
+    pub error: Option<String>,
+    /// Not run because its skip_if condition held.
+    pub skipped: bool,
+}
+
+impl PhaseResult {
+    pub fn success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// One-line rendering, e.g. `✓ Refresh 2s (exit 0)`.
+    pub fn summary(&self) -> String {
+        if self.skipped {
+            return format!(
+                "{}{}: {}",
+                a11y::mark("↷ "),
+                self.phase.title(),
+                t!("phase-skipped-condition")
+            );
+        }
+        let outcome = match (&self.exit, &self.error) {
+            (Some(exit), _) => exit.describe(),
+            (None, Some(error)) => error.clone(),
+            (None, None) => String::new(),
+        };
+        format!(
+            "{}{} {} ({outcome})",
+            a11y::mark(if self.success() { "✓ " } else { "✗ " }),
+            self.phase.title(),
+            format_duration_secs(self.duration.as_secs_f64())
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
     Pending,
-    Running(String),
+    Running,
+    /// Still running the phase, but without output for a while.
+    Stalled,
     Success,
-    Failed(String),
+    Failed,
+    /// Stopped after the refresh reported a signature or key problem, so
+    /// nothing was upgraded from an unverified repository.
+    Unverified,
+}
+
+impl RunState {
+    /// Lowercase English name for machine-readable output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            RunState::Pending => "pending",
+            RunState::Running => "running",
+            RunState::Stalled => "stalled",
+            RunState::Success => "success",
+            RunState::Failed => "failed",
+            RunState::Unverified => "unverified",
+        }
+    }
 }
 
+/// Where a manager's workflow is and how each phase went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagerStatus {
+    pub state: RunState,
+    /// The running phase, or the one that failed.
+    pub phase: Option<Phase>,
+    pub phase_started: Option<Instant>,
+    pub phases: Vec<PhaseResult>,
+    /// Why the workflow failed, without the logs.
+    pub error: Option<String>,
+    /// How far along the running phase is, between 0 and 1, when its output
+    /// matches one of the manager's progress patterns.
+    pub progress: Option<f64>,
+    /// Packages upgraded so far, when the manager has a `package_pattern`.
+    pub packages: Option<usize>,
+    /// Known failure the error and logs match, once the workflow failed.
+    pub cause: Option<&'static Cause>,
+    /// Remedies run for failed steps before retrying them.
+    pub recoveries: Vec<Recovery>,
+    /// Casks a background run left alone because they ask for a password.
+    pub deferred: Vec<String>,
+    /// Packages a background run left out because their build files changed
+    /// since they were last reviewed.
+    pub needs_review: Vec<String>,
+    /// Packages the upgrade left at their installed version, with the reason.
+    pub kept_back: Vec<KeptBack>,
+    /// Tools a manager with a crate_upgrade or tool_upgrade upgrades one at
+    /// a time.
+    pub tools: Vec<ToolUpgrade>,
+    /// Why the run left the manager alone for someone to look at, from its
+    /// attention_command.
+    pub attention: Option<String>,
+}
+
+impl Default for ManagerStatus {
+    fn default() -> Self {
+        Self {
+            state: RunState::Pending,
+            phase: None,
+            phase_started: None,
+            phases: Vec::new(),
+            error: None,
+            progress: None,
+            packages: None,
+            cause: None,
+            recoveries: Vec::new(),
+            deferred: Vec::new(),
+            needs_review: Vec::new(),
+            kept_back: Vec::new(),
+            tools: Vec::new(),
+            attention: None,
+        }
+    }
+}
+
+impl ManagerStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.state,
+            RunState::Success | RunState::Failed | RunState::Unverified
+        )
+    }
+
+    /// Label of the running phase, e.g. "Upgrading".
+    pub fn operation(&self) -> String {
+        if let Some(index) = self
+            .tools
+            .iter()
+            .position(|tool| tool.state == RunState::Running)
+        {
+            return t!(
+                "phase-tool-active",
+                name = self.tools[index].name.as_str(),
+                current = index + 1,
+                total = self.tools.len()
+            );
+        }
+        self.phase_label()
+    }
+
+    fn phase_label(&self) -> String {
+        self.phase
+            .as_ref()
+            .map(|phase| phase.label())
+            .unwrap_or_else(|| t!("phase-starting"))
+    }
+
+    /// Short lowercase name used in timelines.
+    pub fn label(&self) -> String {
+        match self.state {
+            RunState::Pending => t!("timeline-queued"),
+            // Tool by tool progress would flood the timeline
+            RunState::Running => self.phase_label().to_lowercase(),
+            RunState::Stalled => t!("timeline-stalled"),
+            RunState::Success => t!("timeline-done"),
+            RunState::Failed => t!("timeline-failed"),
+            RunState::Unverified => t!("timeline-unverified"),
+        }
+    }
+}
+
+/// A status transition and when it happened.
+#[derive(Debug, Clone)]
+pub struct StatusChange {
+    pub label: String,
+    pub at: Instant,
+}
+
+impl DetectedManager {
+    /// A manager or task that hasn't started yet.
+    pub fn new(name: String, config: ManagerConfig, logs: &LogsConfig, stage: Stage) -> Self {
+        let now = Instant::now();
+        DetectedManager {
+            name,
+            config,
+            status: ManagerStatus::default(),
+            logs: LogBuffer::with_limits(
+                logs.max_lines,
+                logs.max_mb.saturating_mul(1024 * 1024),
+                logs.head_lines,
+            ),
+            duration: None,
+            timeline: vec![StatusChange {
+                label: ManagerStatus::default().label(),
+                at: now,
+            }],
+            updated_at: now,
+            changes: None,
+            version: None,
+            stage,
+        }
+    }
+
+    pub fn set_status(&mut self, status: ManagerStatus, at: Instant) {
+        // Recording a phase result doesn't change what the manager is doing
+        let label = status.label();
+        if self
+            .timeline
+            .last()
+            .is_none_or(|change| change.label != label)
+        {
+            self.timeline.push(StatusChange { label, at });
+        }
+        self.status = status;
+        self.updated_at = at;
+    }
+
+    /// The failure reason followed by the captured logs, as shown in summaries.
+    pub fn failure_details(&self) -> String {
+        format!(
+            "{}\n\nLogs:\n{}",
+            self.status.error.as_deref().unwrap_or("Unknown failure"),
+            self.logs.contents()
+        )
+    }
+
+    /// Total time and the time each phase that ran took, in English, e.g.
+    /// `4m 12s (refresh 40s, upgrade 3m 20s, cleanup 12s)`.
+    pub fn duration_summary(&self) -> Option<String> {
+        let total = format_duration_secs(self.duration?.as_secs_f64());
+        let phases: Vec<String> = self
+            .status
+            .phases
+            .iter()
+            .filter(|result| !result.skipped)
+            .map(|result| {
+                format!(
+                    "{} {}",
+                    result.phase.name().to_lowercase(),
+                    format_duration_secs(result.duration.as_secs_f64())
+                )
+            })
+            .collect();
+        Some(match phases.as_slice() {
+            [] => total,
+            phases => format!("{total} ({})", phases.join(", ")),
+        })
+    }
+
+    /// Compact rendering of the status transitions with the time elapsed since
+    /// the manager was queued, e.g. `queued 0s → refreshing 2s → done 40s`.
+    pub fn timeline_summary(&self) -> String {
+        let Some(first) = self.timeline.first() else {
+            return String::new();
+        };
+        self.timeline
+            .iter()
+            .map(|change| {
+                let offset = change.at.saturating_duration_since(first.at);
+                format!(
+                    "{} {}",
+                    change.label,
+                    format_duration_secs(offset.as_secs_f64())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+}
+
+/// What the last detection found, saved so later commands can skip probing.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DetectionCache {
+    /// Unix time of the detection.
+    #[serde(default)]
+    pub detected_at: Option<u64>,
+    /// Every configured manager that was probed, installed or not.
+    #[serde(default)]
+    pub managers: BTreeMap<String, CachedManager>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CachedManager {
+    /// The check_command it was probed with; changing it means probing again.
+    pub check_command: String,
+    /// The manager's `path` when it was probed, searched before PATH.
+    #[serde(default)]
+    pub search_path: Vec<String>,
+    /// Where its binary was found, or None if it isn't installed.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+impl DetectionCache {
+    /// Whether the cache is younger than `ttl_minutes` and still describes
+    /// `candidates`: each probed with its current check_command and path, and every
+    /// binary found still in place.
+    fn is_valid(
+        &self,
+        candidates: &[(&String, &ManagerConfig)],
+        ttl_minutes: u64,
+        now: u64,
+    ) -> bool {
+        let fresh = self
+            .detected_at
+            .is_some_and(|detected_at| now.saturating_sub(detected_at) < ttl_minutes * 60);
+        fresh
+            && candidates.iter().all(|(name, manager)| {
+                self.managers.get(*name).is_some_and(|cached| {
+                    cached.check_command == manager.check_command
+                        && cached.search_path == manager.path
+                        && cached.path.as_ref().is_none_or(|path| path.exists())
+                })
+            })
+    }
+
+    /// Names of the managers found installed, alphabetically.
+    pub fn installed(&self) -> impl Iterator<Item = &str> {
+        self.managers
+            .iter()
+            .filter(|(_, cached)| cached.path.is_some())
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// Set by `--refresh-detect` to probe every manager again whatever the cache
+/// says.
+static REFRESH: AtomicBool = AtomicBool::new(false);
+
+pub fn force_refresh() {
+    REFRESH.store(true, Ordering::Relaxed);
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("detected.toml"))
+}
+
+pub async fn load_cache() -> DetectionCache {
+    let Some(path) = cache_path() else {
+        return DetectionCache::default();
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => toml::from_str(&content).unwrap_or_default(),
+        Err(_) => DetectionCache::default(),
+    }
+}
+
+async fn save_cache(cache: &DetectionCache) -> Result<()> {
+    let path = cache_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(cache)?).await?;
+    Ok(())
+}
+
+/// Find the configured managers installed on this system, reusing the last
+/// detection while it is younger than detect_ttl_minutes.
 pub async fn detect_package_managers(config: &Config) -> Result<Vec<DetectedManager>> {
-    let mut detected = Vec::new();
-
-    for (name, manager_config) in &config.managers {
-        if is_manager_available(&manager_config.check_command).await? {
-            detected.push(DetectedManager {
-                name: name.clone(),
-                config: manager_config.clone(),
-                status: ManagerStatus::Pending,
-                logs: String::new(),
-            });
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    // Disabled managers and those meant for other systems aren't probed at all
+    let platform = Platform::current();
+    let candidates: Vec<_> = config
+        .managers
+        .iter()
+        .filter(|(_, manager)| manager.enabled && platform.allows(manager))
+        .collect();
+
+    let mut cache = load_cache().await;
+    if REFRESH.swap(false, Ordering::Relaxed)
+        || !cache.is_valid(&candidates, config.detect_ttl_minutes, now)
+    {
+        cache = DetectionCache {
+            detected_at: Some(now),
+            managers: probe_managers(&candidates).await?,
+        };
+        if let Err(e) = save_cache(&cache).await {
+            eprintln!("Warning: could not save detected managers: {e}");
         }
     }
 
-    detected.sort_by(|a, b| a.name.cmp(&b.name));
+    let detected = cache
+        .managers
+        .into_iter()
+        .filter(|(name, cached)| {
+            cached.path.is_some() && candidates.iter().any(|(candidate, _)| *candidate == name)
+        })
+        .map(|(name, cached)| DetectedManager {
+            version: cached.version,
+            ..DetectedManager::new(
+                name.clone(),
+                config.managers[&name].clone(),
+                &config.logs,
+                Stage::Managers,
+            )
+        })
+        .collect();
 
     Ok(detected)
 }
 
-async fn is_manager_available(check_command: &str) -> Result<bool> {
-    let parts: Vec<&str> = check_command.split_whitespace().collect();
-    if parts.is_empty() {
-        return Ok(false);
+/// Probe every configured manager, a few at a time, with a progress line on
+/// stderr if that takes a while.
+async fn probe_managers(
+    candidates: &[(&String, &ManagerConfig)],
+) -> Result<BTreeMap<String, CachedManager>> {
+    let mut pending = candidates.iter();
+    let mut probes = JoinSet::new();
+    let mut probed = BTreeMap::new();
+
+    let progress =
+        ProgressBar::with_draw_target(Some(candidates.len() as u64), ProgressDrawTarget::hidden());
+    progress.set_style(
+        ProgressStyle::with_template("{spinner} Detecting package managers {pos}/{len}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+    );
+    let reveal = tokio::time::sleep(DETECT_PROGRESS_DELAY);
+    tokio::pin!(reveal);
+    let mut revealed = false;
+
+    loop {
+        while probes.len() < DETECT_CONCURRENCY {
+            let Some((name, manager_config)) = pending.next() else {
+                break;
+            };
+            let name = name.to_string();
+            let manager_config = ManagerConfig::clone(manager_config);
+            probes.spawn(async move {
+                let path = find_manager(&manager_config);
+                let version = match path {
+                    Some(_) => probe_version(&manager_config).await,
+                    None => None,
+                };
+                let cached = CachedManager {
+                    check_command: manager_config.check_command,
+                    search_path: manager_config.path,
+                    path,
+                    version,
+                };
+                (name, cached)
+            });
+        }
+
+        tokio::select! {
+            joined = probes.join_next() => {
+                let Some(joined) = joined else {
+                    break;
+                };
+                let (name, cached) = joined?;
+                progress.inc(1);
+                probed.insert(name, cached);
+            }
+            _ = &mut reveal, if !revealed && !a11y::enabled() => {
+                revealed = true;
+                progress.set_draw_target(ProgressDrawTarget::stderr());
+                progress.enable_steady_tick(Duration::from_millis(100));
+            }
+        }
+    }
+    progress.finish_and_clear();
+
+    Ok(probed)
+}
+
+/// Order in which managers are run, set with the top-level `order` key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunOrder {
+    Alphabetical,
+    /// Highest `priority` first, ties broken alphabetically.
+    Priority,
+    /// Shuffled each run, so a slow manager doesn't always delay the same ones.
+    Random,
+}
+
+impl RunOrder {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "alphabetical" => Ok(Self::Alphabetical),
+            "priority" => Ok(Self::Priority),
+            "random" => Ok(Self::Random),
+            _ => anyhow::bail!(
+                "Invalid order '{value}'. Use \"alphabetical\", \"priority\" or \"random\""
+            ),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Alphabetical => "alphabetical",
+            Self::Priority => "priority",
+            Self::Random => "random",
+        }
+    }
+}
+
+/// Sort detected managers for this run. Returns the shuffle seed for random
+/// order so the run can be reproduced from history.
+pub fn order_managers(managers: &mut [DetectedManager], order: RunOrder) -> Option<u64> {
+    // Detection already sorted alphabetically
+    let seed = match order {
+        RunOrder::Alphabetical => None,
+        RunOrder::Priority => {
+            managers.sort_by_key(|m| std::cmp::Reverse(m.config.priority));
+            None
+        }
+        RunOrder::Random => {
+            // Kept within TOML's signed integer range for the history file
+            let seed = fastrand::u64(..i64::MAX as u64);
+            fastrand::Rng::with_seed(seed).shuffle(managers);
+            Some(seed)
+        }
+    };
+    // Tasks keep their place before or after the managers
+    managers.sort_by_key(|m| m.stage);
+    seed
+}
+
+/// The system a manager can be limited to with its `os`, `arch` and
+/// `distro` keys.
+struct Platform {
+    os: &'static str,
+    arch: &'static str,
+    /// ID and ID_LIKE from /etc/os-release, e.g. `ubuntu` and `debian`.
+    distros: Vec<String>,
+}
+
+impl Platform {
+    fn current() -> Self {
+        let os_release = std::fs::read_to_string("/etc/os-release")
+            .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+            .unwrap_or_default();
+        let distros = os_release
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .filter(|(key, _)| matches!(*key, "ID" | "ID_LIKE"))
+            .flat_map(|(_, value)| value.trim_matches(['"', '\'']).split_whitespace())
+            .map(str::to_lowercase)
+            .collect();
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            distros,
+        }
+    }
+
+    /// Whether `config` may run here. An empty list allows anything.
+    fn allows(&self, config: &ManagerConfig) -> bool {
+        let listed = |allowed: &[String], value: &str| {
+            allowed
+                .iter()
+                .any(|entry| entry.eq_ignore_ascii_case(value))
+        };
+        (config.os.is_empty() || listed(&config.os, self.os))
+            && (config.arch.is_empty() || listed(&config.arch, self.arch))
+            && (config.distro.is_empty()
+                || self
+                    .distros
+                    .iter()
+                    .any(|distro| listed(&config.distro, distro)))
+    }
+}
+
+/// Whether `config`'s os, arch and distro keys allow it on this system.
+pub fn runs_here(config: &ManagerConfig) -> bool {
+    Platform::current().allows(config)
+}
+
+/// Path of the binary check_command runs, if it is in the manager's `path`
+/// directories or on PATH.
+fn find_manager(config: &ManagerConfig) -> Option<PathBuf> {
+    let command = config.check_command.split_whitespace().next()?;
+    if config.path.is_empty() {
+        return which::which(command).ok();
     }
+    let mut dirs = config.path.join(":");
+    if let Ok(path) = std::env::var("PATH") {
+        dirs = format!("{dirs}:{path}");
+    }
+    which::which_in(command, Some(dirs), std::env::current_dir().ok()?).ok()
+}
 
-    let command = parts[0];
-    Ok(which::which(command).is_ok())
+/// Run check_command and keep the first line it prints, which for the usual
+/// `--version` checks is the version.
+async fn probe_version(config: &ManagerConfig) -> Option<String> {
+    let output = run_captured(
+        &config.check_command,
+        RunAs::environment(config),
+        VERSION_TIMEOUT,
+    )
+    .await
+    .ok()?;
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
 }
@@ -0,0 +1,297 @@
+use crate::i18n::{self, t};
+
+/// How many lines from the end of a log are searched for a known failure,
+/// besides the error itself. Earlier output is mostly progress that can
+/// mention "conflict" or "signature" without anything having gone wrong.
+const TAIL_LINES: usize = 30;
+
+/// Likely reason a manager failed, with a suggestion for fixing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cause {
+    /// Names the `diagnosis-{id}` title and `diagnosis-{id}-hint` messages.
+    pub id: &'static str,
+    /// Command that usually fixes it, to run by hand.
+    pub fix: Option<&'static str>,
+    /// Safe to run unattended before retrying, with `[recovery] enabled`.
+    pub remedy: Option<Remedy>,
+}
+
+/// A command that repairs a known failure without asking anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Remedy {
+    pub command: &'static str,
+    pub sudo: bool,
+}
+
+/// A remedy spine ran during a workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    pub cause: Cause,
+    pub command: &'static str,
+    /// Whether the remedy itself succeeded, so the failed step was retried.
+    pub succeeded: bool,
+}
+
+impl Recovery {
+    /// Translated one-line account for summaries.
+    pub fn describe(&self) -> String {
+        let id = if self.succeeded {
+            "recovery-retried"
+        } else {
+            "recovery-failed"
+        };
+        t!(id, cause = self.cause.title(), command = self.command)
+    }
+
+    /// English one-line account kept in the run history.
+    pub fn record(&self) -> String {
+        let cause = self.cause.english_title();
+        if self.succeeded {
+            format!("{cause}: ran {}, then retried", self.command)
+        } else {
+            format!("{cause}: {} failed", self.command)
+        }
+    }
+}
+
+impl Cause {
+    /// Translated short name, e.g. "Disk full".
+    pub fn title(&self) -> String {
+        t!(&format!("diagnosis-{}", self.id))
+    }
+
+    /// Translated advice on what to do about it.
+    pub fn hint(&self) -> String {
+        t!(&format!("diagnosis-{}-hint", self.id))
+    }
+
+    /// The short name in English, for history and reports meant to be shared.
+    pub fn english_title(&self) -> String {
+        i18n::english(&format!("diagnosis-{}", self.id))
+    }
+
+    /// Translated lines explaining the cause and, if there is one, the fix.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = vec![t!("cause-likely", title = self.title(), hint = self.hint())];
+        lines.extend(self.fix.map(|command| t!("cause-fix", command = command)));
+        lines
+    }
+}
+
+/// A known failure: any of `patterns` (lowercase) in the error or log tail of
+/// one of `managers`, or of any manager when that is empty.
+struct Rule {
+    managers: &'static [&'static str],
+    patterns: &'static [&'static str],
+    cause: Cause,
+}
+
+/// Known failure signatures, checked in order: specific ones before the
+/// general ones they would also match.
+const RULES: &[Rule] = &[
+    Rule {
+        managers: &[],
+        patterns: &["dpkg was interrupted"],
+        cause: Cause {
+            id: "dpkg-interrupted",
+            fix: Some("sudo dpkg --configure -a"),
+            remedy: Some(Remedy {
+                command: "dpkg --configure -a",
+                sudo: true,
+            }),
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &[
+            "could not get lock /var/lib/dpkg/lock",
+            "could not get lock /var/lib/apt/lists/lock",
+            "unable to acquire the dpkg frontend lock",
+        ],
+        cause: Cause {
+            id: "apt-lock",
+            fix: Some("sudo fuser -v /var/lib/dpkg/lock-frontend /var/lib/apt/lists/lock"),
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &["brew"],
+        patterns: &["is a shallow clone"],
+        cause: Cause {
+            id: "brew-shallow-tap",
+            fix: Some("git -C \"$(brew --repo homebrew/core)\" fetch --unshallow"),
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &["brew"],
+        patterns: &[
+            "couldn't find remote ref",
+            "not a git repository",
+            "fatal: bad object",
+            "some taps failed to update",
+        ],
+        cause: Cause {
+            id: "brew-broken-checkout",
+            fix: Some("brew update-reset"),
+            remedy: Some(Remedy {
+                command: "brew update-reset",
+                sudo: false,
+            }),
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &[
+            "no token found for",
+            "please run `cargo login`",
+            "token rejected for",
+        ],
+        cause: Cause {
+            id: "cargo-login",
+            fix: Some("cargo login"),
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &["pacman"],
+        patterns: &[
+            "invalid or corrupted package (pgp signature)",
+            "unknown trust",
+        ],
+        cause: Cause {
+            id: "arch-keyring",
+            fix: Some("sudo pacman -Sy --needed archlinux-keyring"),
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &["no space left on device", "not enough free space"],
+        cause: Cause {
+            id: "disk-full",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &[
+            "could not get lock",
+            "database is locked",
+            "unable to lock",
+            "waiting for cache lock",
+            "another instance",
+        ],
+        cause: Cause {
+            id: "database-locked",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &[
+            "a password is required",
+            "authorization required",
+            "permission denied",
+            "eacces",
+            "are you root",
+            "operation not permitted",
+        ],
+        cause: Cause {
+            id: "privileges",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &[
+            "could not resolve",
+            "temporary failure in name resolution",
+            "failed to fetch",
+            "connection timed out",
+            "connection refused",
+            "network is unreachable",
+            "could not connect",
+        ],
+        cause: Cause {
+            id: "network",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &["no_pubkey", "signature", "gpg", "not signed", "keyring"],
+        cause: Cause {
+            id: "signature",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &[
+            "unmet dependencies",
+            "broken packages",
+            "conflict",
+            "nothing provides",
+            "could not satisfy",
+        ],
+        cause: Cause {
+            id: "dependency-conflict",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &["sigkill", "out of memory", "out-of-memory"],
+        cause: Cause {
+            id: "out-of-memory",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &["stalled", "waiting for input"],
+        cause: Cause {
+            id: "waiting-for-input",
+            fix: None,
+            remedy: None,
+        },
+    },
+    Rule {
+        managers: &[],
+        patterns: &["timed out"],
+        cause: Cause {
+            id: "timed-out",
+            fix: None,
+            remedy: None,
+        },
+    },
+];
+
+/// Match a failure of `manager` against the known failures, looking at the
+/// error and the last lines of its log. None when nothing matches.
+pub fn diagnose(manager: &str, error: &str, log: &str) -> Option<&'static Cause> {
+    // Instances such as brew@arm share their manager's rules
+    let manager = manager.split_once('@').map_or(manager, |(name, _)| name);
+    let error = error.to_lowercase();
+    let lines: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+    let tail = lines[lines.len().saturating_sub(TAIL_LINES)..]
+        .join("\n")
+        .to_lowercase();
+    RULES
+        .iter()
+        .filter(|rule| rule.managers.is_empty() || rule.managers.contains(&manager))
+        .find(|rule| {
+            rule.patterns
+                .iter()
+                .any(|p| error.contains(p) || tail.contains(p))
+        })
+        .map(|rule| &rule.cause)
+}
@@ -0,0 +1,279 @@
+use crate::a11y;
+use crate::apprise;
+use crate::config::Config;
+use crate::detect;
+use crate::execute;
+use crate::kept_back::KeptBackParser;
+use crate::native::{self, NativePolicy};
+use crate::progress::ProgressParser;
+use crate::schedule;
+use crate::secrets;
+use anyhow::Result;
+
+/// Report problems with the configuration and the environment spine runs in.
+pub async fn run_doctor(config: &Config, local: bool) -> Result<()> {
+    let mut issues = 0;
+
+    println!("Spine Doctor");
+    if !a11y::enabled() {
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    }
+    println!(
+        "{}Configuration loaded ({} managers defined)",
+        a11y::mark("✓ "),
+        config.managers.len()
+    );
+
+    let managers = detect::detect_package_managers(config).await?;
+    if managers.is_empty() {
+        println!("{}No package managers detected", a11y::mark("⚠️  "));
+        issues += 1;
+    } else {
+        println!(
+            "{}Detected: {}",
+            a11y::mark("✓ "),
+            managers
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if managers.iter().any(|m| m.config.requires_sudo) {
+        if execute::check_sudo_availability().await {
+            println!(
+                "{}sudo is available without a password prompt",
+                a11y::mark("✓ ")
+            );
+        } else {
+            println!(
+                "{}Some managers need sudo, but it would prompt for a password",
+                a11y::mark("⚠️  ")
+            );
+            issues += 1;
+        }
+    }
+
+    for (name, manager) in &config.managers {
+        if let Err(e) = ProgressParser::new(manager) {
+            println!("{}{name}: {e:#}", a11y::mark("⚠️  "));
+            issues += 1;
+        }
+        if let Err(e) = regex::RegexSet::new(&manager.signature_patterns) {
+            println!("{}{name}.signature_patterns: {e}", a11y::mark("⚠️  "));
+            issues += 1;
+        }
+        if let Some(Err(e)) = manager
+            .min_interval
+            .as_deref()
+            .map(schedule::parse_interval)
+        {
+            println!("{}{name}.min_interval: {e}", a11y::mark("⚠️  "));
+            issues += 1;
+        }
+        if let Err(e) = KeptBackParser::new(manager) {
+            println!("{}{name}: {e:#}", a11y::mark("⚠️  "));
+            issues += 1;
+        }
+        if let Some(workdir) = &manager.workdir {
+            if !std::path::Path::new(workdir).is_dir() {
+                println!(
+                    "{}{name}.workdir: {workdir} is not a directory",
+                    a11y::mark("⚠️  ")
+                );
+                issues += 1;
+            }
+        }
+        if let Some(user) = &manager.run_as_user {
+            if !execute::user_exists(user) {
+                println!(
+                    "{}{name}.run_as_user: there's no user named {user}",
+                    a11y::mark("⚠️  ")
+                );
+                issues += 1;
+            }
+        }
+        for dir in &manager.path {
+            if !std::path::Path::new(dir).is_dir() {
+                println!(
+                    "{}{name}.path: {dir} is not a directory",
+                    a11y::mark("⚠️  ")
+                );
+                issues += 1;
+            }
+        }
+    }
+
+    if !config.exclusions.packages.is_empty() {
+        let unsupported: Vec<&str> = managers
+            .iter()
+            .filter(|m| {
+                m.config.exclude_args.is_none()
+                    && m.config.exclude_command.is_none()
+                    && !m.config.upgrades_one_by_one()
+            })
+            .map(|m| m.name.as_str())
+            .collect();
+        if unsupported.is_empty() {
+            println!(
+                "{}Exclusions apply to every detected manager ({})",
+                a11y::mark("✓ "),
+                config.exclusions.packages.join(", ")
+            );
+        } else {
+            println!(
+                "{}Exclusions can't be applied by {}; they upgrade everything",
+                a11y::mark("⚠️  "),
+                unsupported.join(", ")
+            );
+            issues += 1;
+        }
+    }
+
+    if !config.bandwidth_limit.trim().is_empty() {
+        match execute::parse_rate(&config.bandwidth_limit) {
+            Err(e) => {
+                println!("{}bandwidth_limit: {e}", a11y::mark("⚠️  "));
+                issues += 1;
+            }
+            Ok(_) if which::which("trickle").is_ok() => {
+                println!(
+                    "{}bandwidth_limit applies to every detected manager",
+                    a11y::mark("✓ ")
+                );
+            }
+            Ok(_) => {
+                let unsupported: Vec<&str> = managers
+                    .iter()
+                    .filter(|m| m.config.limit_rate_args.is_none())
+                    .map(|m| m.name.as_str())
+                    .collect();
+                if unsupported.is_empty() {
+                    println!(
+                        "{}bandwidth_limit applies to every detected manager",
+                        a11y::mark("✓ ")
+                    );
+                } else {
+                    println!(
+                        "{}bandwidth_limit can't be applied to {} without trickle installed",
+                        a11y::mark("⚠️  "),
+                        unsupported.join(", ")
+                    );
+                    issues += 1;
+                }
+            }
+        }
+    }
+
+    if let Err(e) = regex::RegexSet::new(&config.logs.error_patterns) {
+        println!("{}logs.error_patterns: {e}", a11y::mark("⚠️  "));
+        issues += 1;
+    }
+    if let Err(e) = regex::RegexSet::new(&config.logs.redact_patterns) {
+        println!("{}logs.redact_patterns: {e}", a11y::mark("⚠️  "));
+        issues += 1;
+    }
+
+    if which::which("apprise").is_err() {
+        let unsupported: Vec<&str> = config
+            .notify
+            .urls
+            .iter()
+            .filter_map(|url| url.split_once("://").map(|(scheme, _)| scheme))
+            .filter(|scheme| !apprise::NATIVE_SCHEMES.contains(scheme))
+            .collect();
+        if !unsupported.is_empty() {
+            println!(
+                "{}notify.urls: {}:// URLs can only be sent with apprise installed",
+                a11y::mark("⚠️  "),
+                unsupported.join("://, ")
+            );
+            issues += 1;
+        }
+    }
+
+    let names = toml::to_string(config)
+        .map(|text| secrets::references(&text))
+        .unwrap_or_default();
+    for name in names {
+        if let Err(e) = secrets::lookup(&name).await {
+            println!("{}${{secret:{name}}}: {e:#}", a11y::mark("⚠️  "));
+            issues += 1;
+        }
+    }
+
+    if config.restarts.enabled && which::which("systemctl").is_err() {
+        println!(
+            "{}restarts.enabled is set, but services can only be restarted with systemd",
+            a11y::mark("⚠️  ")
+        );
+        issues += 1;
+    }
+
+    if !local {
+        issues += check_native_updaters(config, &managers);
+    }
+
+    if !a11y::enabled() {
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    }
+    match issues {
+        0 => println!("No problems found."),
+        n => println!("{n} potential problem(s) found."),
+    }
+
+    Ok(())
+}
+
+/// Warn when a distro auto-updater and spine's schedule would both upgrade
+/// the same managers.
+fn check_native_updaters(config: &Config, managers: &[detect::DetectedManager]) -> usize {
+    let policy = match NativePolicy::parse(&config.auto_update.native_updaters) {
+        Ok(policy) => policy,
+        Err(e) => {
+            println!("{}{e}", a11y::mark("⚠️  "));
+            return 1;
+        }
+    };
+
+    let mut issues = 0;
+    for updater in native::detect_native_updaters() {
+        let overlap: Vec<&str> = updater
+            .covers
+            .iter()
+            .copied()
+            .filter(|key| managers.iter().any(|m| m.name == *key))
+            .collect();
+        if overlap.is_empty() {
+            continue;
+        }
+
+        if !config.auto_update.enabled || policy == NativePolicy::Defer {
+            println!(
+                "{}{} handles {} (native_updaters = \"{}\")",
+                a11y::mark("✓ "),
+                updater.name,
+                overlap.join(", "),
+                config.auto_update.native_updaters
+            );
+            continue;
+        }
+
+        println!(
+            "{}{} and spine's schedule both upgrade {}",
+            a11y::mark("⚠️  "),
+            updater.name,
+            overlap.join(", ")
+        );
+        println!(
+            "   Set native_updaters = \"defer\" to skip them in scheduled runs, or \"disable\""
+        );
+        println!(
+            "   and re-run 'spn auto --enable' to turn {} off.",
+            updater.name
+        );
+        issues += 1;
+    }
+    issues
+}
@@ -1,186 +1,1218 @@
-use crate::detect::{DetectedManager, ManagerStatus};
+use crate::casks;
+use crate::config::{ManagerConfig, NotifyConfig, WebhookEvent};
+use crate::crates;
+use crate::detect::{DetectedManager, ManagerStatus, Phase, PhaseResult, PhaseStep, RunState};
+use crate::diagnose::{diagnose, Recovery};
+use crate::holds;
+use crate::kept_back::KeptBackParser;
+use crate::logs::LogBuffer;
+use crate::power::PowerAction;
+use crate::progress::ProgressParser;
+use crate::redact::redact;
+use crate::review;
+use crate::sudo;
+use crate::tools::{self, ToolUpgrade};
+use crate::transaction;
+use crate::webhook;
 use anyhow::Result;
+use regex::RegexSet;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
-use tokio::sync::Mutex;
-
-pub async fn execute_manager_workflow(manager_ref: Arc<Mutex<DetectedManager>>) -> Result<()> {
-    let config = {
-        let manager = manager_ref.lock().await;
-        manager.config.clone()
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+/// Change notification published while a workflow runs, so the TUI can redraw
+/// only when something actually changed.
+#[derive(Debug, Clone)]
+pub enum ManagerEvent {
+    Status {
+        manager: String,
+        status: Box<ManagerStatus>,
+        at: Instant,
+    },
+    Log {
+        manager: String,
+    },
+}
+
+pub type EventSender = broadcast::Sender<ManagerEvent>;
+
+/// What the user decided to do about a stalled command.
+#[derive(Debug, Clone)]
+pub enum ManagerControl {
+    /// Keep waiting; the stall timer starts over.
+    Wait,
+    Kill,
+    /// Write a line to the command's stdin.
+    Input(String),
+}
+
+pub type ControlSender = mpsc::UnboundedSender<ManagerControl>;
+pub type ControlReceiver = mpsc::UnboundedReceiver<ManagerControl>;
+
+/// Run-wide settings that apply to every manager's workflow.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// -1 for `--quiet`, 0 by default, and one more for each `-v`.
+    pub verbosity: i8,
+    /// Set for background runs, where nobody is around to answer a prompt.
+    pub noninteractive: bool,
+    /// Package patterns kept out of the upgrade phase (`[exclusions]`).
+    pub exclusions: Vec<String>,
+    /// Also keep each manager's os_packages out, releasing them afterwards.
+    pub hold_os: bool,
+    /// Run the remedy for a recognised failure and retry the step once
+    /// (`[recovery]`).
+    pub recover: bool,
+    /// Upgrade even when the refresh reported a signature problem.
+    pub allow_unverified: bool,
+    /// Only run phases that work without the network (`--offline`).
+    pub offline: bool,
+    /// Follow an upgrade that kept packages back with the manager's
+    /// full_upgrade (`--full-upgrade`).
+    pub full_upgrade: bool,
+    /// Told about each manager as it finishes (`[notify]` webhooks and URLs).
+    pub notify: NotifyConfig,
+    /// The `[auto_update.jobs]` entry being run (`--job`), which limits the
+    /// managers and is noted in the history.
+    pub job: Option<String>,
+    /// Download rate cap in KB/s for network phases (`--limit-rate` or
+    /// `bandwidth_limit`).
+    pub limit_rate: Option<u64>,
+    /// Run sudo managers that are new or changed in the config without
+    /// asking (`--accept-new-managers`).
+    pub accept_new_managers: bool,
+    /// Only refresh and list pending updates (`--report-only` or
+    /// `report_only`).
+    pub report_only: bool,
+    /// Shut down or reboot once every manager has finished
+    /// (`--shutdown-after`, `--reboot-after`).
+    pub power: Option<PowerAction>,
+    /// Do that even when a manager failed (`--force`).
+    pub power_force: bool,
+    /// Run managers whose min_interval hasn't passed yet
+    /// (`--ignore-intervals`).
+    pub ignore_intervals: bool,
+}
+
+/// How long a background command may go without output before it is
+/// assumed to be stuck on a prompt.
+const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(900);
+
+/// How long a step's skip_if condition may run before the step runs anyway.
+const SKIP_IF_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long an attention_command may run before the manager runs anyway.
+const ATTENTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long one exclude_command or release_command may take.
+const HOLD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long a remedy for a known failure may run.
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How long an interactive command may go without output before it is
+/// shown as stalled.
+const DEFAULT_STALL_WARNING: Duration = Duration::from_secs(180);
+
+/// How long to keep reading output still in the pipes once a command exits,
+/// in case something it left running in the background holds them open.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a command's processes get to exit after SIGTERM before the
+/// rest are sent SIGKILL.
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// Per-manager settings for running its commands in this run.
+struct CommandSettings {
+    env: Vec<(String, String)>,
+    stall_timeout: Duration,
+    /// Kill a stalled command instead of asking the user what to do.
+    kill_on_stall: bool,
+    /// Give the command a stdin the TUI can write to.
+    stdin: bool,
+    progress: ProgressParser,
+}
+
+impl RunOptions {
+    /// Flags to add to a manager's commands for the current verbosity.
+    fn verbosity_flags<'a>(&self, config: &'a ManagerConfig) -> Option<&'a str> {
+        match self.verbosity {
+            0 => None,
+            v if v < 0 => config.quiet_flags.as_deref(),
+            v => {
+                let index = (v as usize - 1).min(config.verbose_flags.len().checked_sub(1)?);
+                config.verbose_flags.get(index).map(String::as_str)
+            }
+        }
+    }
+
+    /// Adjust a configured command for this run. Verbosity flags, in
+    /// background runs non-interactive flags, and for the upgrade phase
+    /// exclude_args are appended to every `&&`-joined step that invokes the
+    /// manager's own program. Network phases are rate-limited when asked to.
+    /// exclude_command holds are placed by `Workflow::hold` instead.
+    fn prepare_command(
+        &self,
+        step: &PhaseStep,
+        command: &str,
+        config: &ManagerConfig,
+        unreviewed: &[String],
+    ) -> String {
+        let os_packages: &[String] = if self.hold_os {
+            &config.os_packages
+        } else {
+            &[]
+        };
+        let run_holds: Vec<&String> = os_packages.iter().chain(unreviewed).collect();
+        let noninteractive_args = config
+            .noninteractive_args
+            .as_deref()
+            .filter(|_| self.noninteractive);
+        let mut flags: Vec<String> = [self.verbosity_flags(config), noninteractive_args]
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect();
+        if step.phase == Phase::Upgrade {
+            if let Some(template) = &config.exclude_args {
+                for pattern in self.exclusions.iter().chain(run_holds) {
+                    flags.push(pattern_command(template, pattern));
+                }
+            }
+        }
+
+        let mut command = append_to_steps(command, config, &flags.join(" "));
+        if let Some(kbps) = self.limit_rate.filter(|_| step.network) {
+            command = limit_rate(&command, config, kbps);
+        }
+        command
+    }
+
+    fn command_settings(&self, config: &ManagerConfig) -> CommandSettings {
+        CommandSettings {
+            env: self.command_env(config),
+            stall_timeout: config
+                .stall_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(if self.noninteractive {
+                    DEFAULT_STALL_TIMEOUT
+                } else {
+                    DEFAULT_STALL_WARNING
+                }),
+            kill_on_stall: self.noninteractive,
+            stdin: config.allow_stdin && !self.noninteractive,
+            progress: ProgressParser::default(),
+        }
+    }
+
+    /// Extra environment for a manager's commands in this run.
+    fn command_env(&self, config: &ManagerConfig) -> Vec<(String, String)> {
+        if !self.noninteractive {
+            return Vec::new();
+        }
+        let mut env: Vec<_> = config
+            .noninteractive_env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        env.sort();
+        env
+    }
+}
+
+/// Add the running phase's outcome to `status`.
+fn push_phase_result(status: &mut ManagerStatus, exit: Option<ExitReason>, error: Option<String>) {
+    if let Some(phase) = &status.phase {
+        status.phases.push(PhaseResult {
+            phase: phase.clone(),
+            duration: status
+                .phase_started
+                .map(|started| started.elapsed())
+                .unwrap_or_default(),
+            exit,
+            error,
+            skipped: false,
+        });
+    }
+    status.progress = None;
+}
+
+/// Records a workflow's output and status changes and publishes them as events.
+struct Reporter {
+    manager: String,
+    manager_ref: Arc<Mutex<DetectedManager>>,
+    logs: LogBuffer,
+    events: Option<EventSender>,
+    control: Mutex<Option<ControlReceiver>>,
+}
+
+impl Reporter {
+    fn log_line(&self, line: String) {
+        self.logs.push_line(line);
+        self.publish(ManagerEvent::Log {
+            manager: self.manager.clone(),
+        });
+    }
+
+    fn log(&self, text: &str) {
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        for line in text.split('\n') {
+            self.log_line(line.to_string());
+        }
+    }
+
+    /// Apply `change` to the manager's status and publish the result.
+    async fn update_status(&self, change: impl FnOnce(&mut ManagerStatus)) {
+        let at = Instant::now();
+        let status = {
+            let mut manager = self.manager_ref.lock().await;
+            let mut status = manager.status.clone();
+            change(&mut status);
+            manager.set_status(status.clone(), at);
+            status
+        };
+        self.publish(ManagerEvent::Status {
+            manager: self.manager.clone(),
+            status: Box::new(status),
+            at,
+        });
+    }
+
+    async fn start_phase(&self, phase: Phase) {
+        self.update_status(|status| {
+            status.state = RunState::Running;
+            status.phase = Some(phase);
+            status.phase_started = Some(Instant::now());
+            status.progress = None;
+        })
+        .await;
+    }
+
+    /// Record how the current phase ended. A phase with an error fails the
+    /// whole workflow.
+    async fn finish_phase(&self, exit: Option<ExitReason>, error: Option<String>) {
+        let error = error.map(|error| redact(&error).into_owned());
+        let cause = error
+            .as_deref()
+            .and_then(|error| diagnose(&self.manager, error, &self.logs.full_text()));
+        self.update_status(|status| {
+            push_phase_result(status, exit, error.clone());
+            if error.is_some() {
+                status.state = RunState::Failed;
+                status.error = error;
+                status.cause = cause;
+            }
+        })
+        .await;
+    }
+
+    /// Record that an optional phase failed, without failing the workflow.
+    async fn finish_optional_phase(&self, exit: Option<ExitReason>, error: String) {
+        let error = redact(&error).into_owned();
+        self.update_status(|status| push_phase_result(status, exit, Some(error)))
+            .await;
+    }
+
+    /// Record `phase` as skipped because its skip_if condition held.
+    async fn skip_phase(&self, phase: Phase) {
+        self.update_status(|status| {
+            status.phases.push(PhaseResult {
+                phase,
+                duration: Duration::ZERO,
+                exit: None,
+                error: None,
+                skipped: true,
+            })
+        })
+        .await;
+    }
+
+    /// End the workflow after the current phase reported a signature
+    /// problem, recording `warning` as the reason.
+    async fn stop_unverified(&self, exit: Option<ExitReason>, warning: String) {
+        let warning = redact(&warning).into_owned();
+        self.update_status(|status| {
+            push_phase_result(status, exit, Some(warning.clone()));
+            status.state = RunState::Unverified;
+            status.error = Some(warning);
+        })
+        .await;
+    }
+
+    /// Track the running phase's progress, redrawing at most once per percent.
+    async fn set_progress(&self, ratio: f64) {
+        let percent = |ratio: f64| (ratio * 100.0).round();
+        let current = self.manager_ref.lock().await.status.progress;
+        if current.is_some_and(|current| percent(current) == percent(ratio)) {
+            return;
+        }
+        self.update_status(|status| status.progress = Some(ratio))
+            .await;
+    }
+
+    /// Count a package reported as upgraded. Only the upgrade phases count,
+    /// so e.g. cleanup output can't inflate the number.
+    async fn count_package(&self) {
+        self.update_status(|status| {
+            if matches!(status.phase, Some(Phase::Upgrade | Phase::Casks)) {
+                *status.packages.get_or_insert(0) += 1;
+            }
+        })
+        .await;
+    }
+
+    async fn set_state(&self, state: RunState) {
+        self.update_status(|status| status.state = state).await;
+    }
+
+    fn publish(&self, event: ManagerEvent) {
+        if let Some(events) = &self.events {
+            // An error only means nobody is listening right now
+            let _ = events.send(event);
+        }
+    }
+}
+
+pub async fn execute_manager_workflow(
+    manager_ref: Arc<Mutex<DetectedManager>>,
+    events: Option<EventSender>,
+    control: Option<ControlReceiver>,
+    options: RunOptions,
+) -> Result<()> {
+    let config = manager_ref.lock().await.config.clone();
+    let before = transaction::snapshot(&config).await;
+
+    let started = Instant::now();
+    let result = run_workflow_phases(&manager_ref, events, control, &options).await;
+    let duration = started.elapsed();
+
+    let changes = match before {
+        Some(before) => transaction::snapshot(&config)
+            .await
+            .map(|after| transaction::diff(&before, &after)),
+        None => None,
+    };
+    let (name, status) = {
+        let mut manager = manager_ref.lock().await;
+        manager.duration = Some(duration);
+        manager.changes = changes;
+        (manager.name.clone(), manager.status.clone())
     };
 
-    let mut accumulated_logs = String::new();
+    if !options.notify.webhooks.is_empty() || !options.notify.urls.is_empty() {
+        let failed = matches!(status.state, RunState::Failed | RunState::Unverified);
+        let fields = || {
+            vec![
+                ("manager", name.as_str().into()),
+                ("state", status.state.label().into()),
+                ("error", status.error.clone().into()),
+                ("packages", status.packages.into()),
+                ("duration_secs", duration.as_secs().into()),
+            ]
+        };
+        let mut errors =
+            webhook::fire(&options.notify, WebhookEvent::ManagerFinished, fields()).await;
+        if failed {
+            errors.extend(
+                webhook::fire(&options.notify, WebhookEvent::ManagerFailed, fields()).await,
+            );
+        }
+        let logs = manager_ref.lock().await.logs.clone();
+        for error in errors {
+            logs.push_line(format!("Warning: {error}"));
+        }
+    }
+    result
+}
 
-    // Refresh repositories
-    if let Some(refresh_cmd) = &config.refresh {
-        accumulated_logs.push_str("=== REFRESHING REPOSITORIES ===\n");
-        {
-            let mut manager = manager_ref.lock().await;
-            manager.status = ManagerStatus::Running("Refreshing".to_string());
-            manager.logs = accumulated_logs.clone();
-        }
-
-        match execute_command_with_logs(
-            refresh_cmd,
-            config.requires_sudo,
-            Duration::from_secs(300),
-            manager_ref.clone(),
-            "Refreshing".to_string(),
-            &mut accumulated_logs,
-        )
-        .await
+/// What a manager's run does after a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Next {
+    /// Go on with the next step.
+    Continue,
+    /// End the run here; the status says why.
+    Stop,
+}
+
+/// A step's command once the checks before running it have passed.
+struct Prepared {
+    command: String,
+    /// Packages left out of the upgrade until someone reviews their build
+    /// files.
+    unreviewed: Vec<String>,
+}
+
+/// One manager's run as it goes through its steps.
+struct Workflow<'a> {
+    config: ManagerConfig,
+    options: &'a RunOptions,
+    reporter: Reporter,
+    settings: CommandSettings,
+    signature_patterns: RegexSet,
+    kept_back_parser: KeptBackParser,
+}
+
+async fn run_workflow_phases(
+    manager_ref: &Arc<Mutex<DetectedManager>>,
+    events: Option<EventSender>,
+    control: Option<ControlReceiver>,
+    options: &RunOptions,
+) -> Result<()> {
+    let workflow = Workflow::new(manager_ref, events, control, options).await;
+    // Holds an earlier run placed for itself but was stopped before releasing
+    workflow.release_run_holds().await;
+    if let Some(reason) = workflow.needs_attention().await {
+        workflow
+            .reporter
+            .update_status(|status| {
+                status.state = RunState::Success;
+                status.phase = None;
+                status.phase_started = None;
+                status.attention = Some(reason);
+            })
+            .await;
+        return Ok(());
+    }
+    for step in workflow.config.steps() {
+        if workflow.run_step(&step).await == Next::Stop {
+            return Ok(());
+        }
+    }
+
+    workflow
+        .reporter
+        .update_status(|status| {
+            status.state = RunState::Success;
+            status.phase = None;
+            status.phase_started = None;
+        })
+        .await;
+    Ok(())
+}
+
+impl<'a> Workflow<'a> {
+    async fn new(
+        manager_ref: &Arc<Mutex<DetectedManager>>,
+        events: Option<EventSender>,
+        control: Option<ControlReceiver>,
+        options: &'a RunOptions,
+    ) -> Workflow<'a> {
+        let (config, reporter) = {
+            let manager = manager_ref.lock().await;
+            let reporter = Reporter {
+                manager: manager.name.clone(),
+                manager_ref: manager_ref.clone(),
+                logs: manager.logs.clone(),
+                events,
+                control: Mutex::new(control),
+            };
+            (manager.config.clone(), reporter)
+        };
+        let mut settings = options.command_settings(&config);
+        match ProgressParser::new(&config) {
+            Ok(parser) => settings.progress = parser,
+            Err(e) => reporter.log(&format!("Warning: {e:#}; progress won't be shown\n")),
+        }
+        let signature_patterns = RegexSet::new(&config.signature_patterns).unwrap_or_else(|e| {
+            reporter.log(&format!(
+                "Warning: invalid signature_patterns ({e}); signature problems won't be caught\n"
+            ));
+            RegexSet::empty()
+        });
+        let kept_back_parser = KeptBackParser::new(&config).unwrap_or_else(|e| {
+            reporter.log(&format!(
+                "Warning: {e:#}; kept-back packages won't be reported\n"
+            ));
+            KeptBackParser::default()
+        });
+        if settings.progress.counts_packages() {
+            reporter
+                .update_status(|status| status.packages = Some(0))
+                .await;
+        }
+
+        Workflow {
+            config,
+            options,
+            reporter,
+            settings,
+            signature_patterns,
+            kept_back_parser,
+        }
+    }
+
+    /// Run one step: check that it should run, run its command (again after
+    /// a recovery, if one applies) and record how it went.
+    async fn run_step(&self, step: &PhaseStep) -> Next {
+        let phase = &step.phase;
+        let prepared = match self.prepare(step).await {
+            Ok(prepared) => prepared,
+            Err(next) => return next,
+        };
+
+        self.reporter.log(&format!("{}\n", phase_header(phase)));
+        self.reporter.start_phase(phase.clone()).await;
+        if *phase == Phase::Upgrade
+            && !self.options.exclusions.is_empty()
+            && self.config.exclude_args.is_none()
+            && self.config.exclude_command.is_none()
+            && !self.config.upgrades_one_by_one()
         {
-            Ok(true) => {
-                accumulated_logs.push_str("\n✓ Refresh completed\n\n");
+            self.reporter.log(&format!(
+                "Warning: {} can't exclude packages, so [exclusions] doesn't apply to it\n",
+                self.config.name
+            ));
+        }
+
+        if *phase == Phase::Upgrade {
+            if let Err(e) = self.hold(&prepared.unreviewed).await {
+                self.release_run_holds().await;
+                return self.finish(step, Err(e)).await;
             }
-            Ok(false) => {
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!(
-                    "Refresh command failed\n\nLogs:\n{accumulated_logs}"
+        }
+
+        let first_line = self.reporter.logs.len();
+        let command = self.options.prepare_command(
+            step,
+            &prepared.command,
+            &self.config,
+            &prepared.unreviewed,
+        );
+        let mut result = self.run_command(step, &command).await;
+        if let Some(error) = phase_error(phase, &result) {
+            if self.options.recover
+                && recover(&error, &self.config, &self.settings, &self.reporter).await
+            {
+                self.reporter.log(&format!(
+                    "\n=== RETRYING {} ===\n",
+                    phase.name().to_uppercase()
                 ));
-                return Ok(());
+                result = self.run_command(step, &command).await;
             }
-            Err(e) => {
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!(
-                    "Refresh error: {e}\n\nLogs:\n{accumulated_logs}"
-                ));
-                return Ok(());
+        }
+
+        if *phase == Phase::Refresh && self.unverified(first_line, &result).await {
+            return Next::Stop;
+        }
+        if *phase == Phase::Upgrade && result.as_ref().is_ok_and(|exit| exit.success()) {
+            if let Some(full_upgrade) = self
+                .record_kept_back(step, first_line, &prepared.unreviewed)
+                .await
+            {
+                result = full_upgrade;
             }
         }
+        if *phase == Phase::Upgrade {
+            // A step of its own, so it happens however the upgrade ended,
+            // killed and timed out included
+            self.release_run_holds().await;
+        }
+
+        self.finish(step, result).await
     }
 
-    // Self-update
-    if let Some(self_update_cmd) = &config.self_update {
-        accumulated_logs.push_str("=== SELF-UPDATE ===\n");
-        {
-            let mut manager = manager_ref.lock().await;
-            manager.status = ManagerStatus::Running("Self-updating".to_string());
-            manager.logs = accumulated_logs.clone();
-        }
-
-        match execute_command_with_logs(
-            self_update_cmd,
-            config.requires_sudo,
-            Duration::from_secs(600),
-            manager_ref.clone(),
-            "Self-updating".to_string(),
-            &mut accumulated_logs,
+    /// Hold what the upgrade has to leave alone, for managers with an
+    /// exclude_command: the [exclusions], which stay held, and for this run
+    /// only its OS packages and `unreviewed` ones. Those are recorded in the
+    /// data directory as they're held, for `release_run_holds`.
+    async fn hold(&self, unreviewed: &[String]) -> Result<()> {
+        let Some(template) = &self.config.exclude_command else {
+            return Ok(());
+        };
+        let options = self.options;
+        let hold = |pattern: &str| {
+            let command = pattern_command(template, pattern);
+            async move { run_captured(&command, RunAs::manager(&self.config), HOLD_TIMEOUT).await }
+        };
+        for pattern in &options.exclusions {
+            hold(pattern).await?;
+        }
+
+        let os_packages: &[String] = if options.hold_os {
+            &self.config.os_packages
+        } else {
+            &[]
+        };
+        let run_only = os_packages
+            .iter()
+            .chain(unreviewed)
+            .filter(|pattern| !options.exclusions.contains(pattern));
+        let Some(release) = &self.config.release_command else {
+            // Nothing can undo them, so they stay held like [exclusions]
+            for pattern in run_only {
+                hold(pattern).await?;
+            }
+            return Ok(());
+        };
+        // A release_command taking {package} undoes only what exclude_command
+        // reports holding, not holds that were already there; one taking
+        // {pattern} releases everything matching
+        let per_package = release.contains("{package}");
+        let name = &self.reporter.manager;
+        let mut held = holds::run_holds(name).await;
+        for pattern in run_only {
+            if !per_package && !held.contains(pattern) {
+                held.push(pattern.clone());
+                holds::save_run_holds(name, &held).await?;
+            }
+            let output = hold(pattern).await?;
+            if per_package {
+                held.extend(
+                    output
+                        .lines()
+                        .map(str::trim)
+                        .filter(|package| !package.is_empty())
+                        .map(String::from),
+                );
+                holds::save_run_holds(name, &held).await?;
+            }
+        }
+        if !held.is_empty() {
+            self.reporter
+                .log(&format!("Held for this run: {}\n", held.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// Release what `hold` recorded for this manager, including what a run
+    /// that was killed or timed out left held. Any that can't be released
+    /// stay recorded for the next run.
+    async fn release_run_holds(&self) {
+        let name = &self.reporter.manager;
+        let held = holds::run_holds(name).await;
+        if held.is_empty() {
+            return;
+        }
+        let Some(template) = &self.config.release_command else {
+            self.reporter.log(&format!(
+                "Warning: {} still held for an earlier run, and {name} has no release_command to release them\n",
+                held.join(", ")
+            ));
+            return;
+        };
+        let mut released = Vec::new();
+        let mut remaining = Vec::new();
+        for package in held {
+            let command = if template.contains("{package}") {
+                template.replace("{package}", &shell_quote(&package))
+            } else {
+                pattern_command(template, &package)
+            };
+            match run_captured(&command, RunAs::manager(&self.config), HOLD_TIMEOUT).await {
+                Ok(_) => released.push(package),
+                Err(e) => {
+                    self.reporter.log(&format!(
+                        "Warning: couldn't release {package} ({e:#}); trying again next run\n"
+                    ));
+                    remaining.push(package);
+                }
+            }
+        }
+        if !released.is_empty() {
+            self.reporter
+                .log(&format!("Released: {}\n", released.join(", ")));
+        }
+        if let Err(e) = holds::save_run_holds(name, &remaining).await {
+            self.reporter.log(&format!(
+                "Warning: couldn't update the record of held packages ({e:#})\n"
+            ));
+        }
+    }
+
+    /// What the attention_command printed, in which case none of the
+    /// manager's steps run. A command that fails doesn't hold the run up.
+    async fn needs_attention(&self) -> Option<String> {
+        let command = self.config.attention_command.as_ref()?;
+        let output = match run_captured(
+            command,
+            RunAs::manager_query(&self.config),
+            ATTENTION_TIMEOUT,
         )
         .await
         {
-            Ok(true) => {
-                accumulated_logs.push_str("\n✓ Self-update completed\n\n");
+            Ok(output) => output,
+            Err(e) => {
+                self.reporter.log(&format!(
+                    "Warning: attention_command failed ({e:#}); running anyway\n"
+                ));
+                return None;
+            }
+        };
+        let reason = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("; ");
+        if reason.is_empty() {
+            return None;
+        }
+        self.reporter.log(&format!(
+            "=== NEEDS ATTENTION ===\n{reason}\nLeaving it alone until that's dealt with\n"
+        ));
+        Some(reason)
+    }
+
+    /// The checks before a step runs, which may skip it or end the run
+    /// (the `Err`). Otherwise the command to run, adjusted for this run.
+    async fn prepare(&self, step: &PhaseStep) -> std::result::Result<Prepared, Next> {
+        let phase = &step.phase;
+        if self.options.offline && step.network {
+            self.reporter.log(&format!(
+                "Skipping {}: it needs the network (--offline)\n",
+                phase.name()
+            ));
+            return Err(Next::Continue);
+        }
+        if let Some(condition) = &step.skip_if {
+            if run_captured(
+                condition,
+                RunAs::manager_query(&self.config),
+                SKIP_IF_TIMEOUT,
+            )
+            .await
+            .is_ok()
+            {
+                self.reporter.log(&format!(
+                    "Skipping {}: its skip_if condition holds ({})\n",
+                    phase.name(),
+                    condition.trim()
+                ));
+                self.reporter.skip_phase(phase.clone()).await;
+                return Err(Next::Continue);
+            }
+        }
+
+        let mut command = step.command.clone();
+        if *phase == Phase::Casks {
+            command = self.cask_command(command).await.ok_or(Next::Continue)?;
+        }
+        let unreviewed = match phase {
+            Phase::Upgrade => self.unreviewed().await?,
+            _ => Vec::new(),
+        };
+        Ok(Prepared {
+            command,
+            unreviewed,
+        })
+    }
+
+    /// The casks phase's command, or None when every outdated cask is left
+    /// for an interactive run.
+    async fn cask_command(&self, mut command: String) -> Option<String> {
+        if self.config.greedy_casks {
+            command.push_str(" --greedy");
+        }
+        // Nobody is around to type a password for a .pkg installer
+        if self.options.noninteractive {
+            match casks::plan(self.config.greedy_casks).await {
+                Ok(plan) if !plan.deferred.is_empty() => {
+                    self.reporter.log(&format!(
+                        "Leaving casks that ask for a password for an interactive run: {}\n",
+                        plan.deferred.join(", ")
+                    ));
+                    self.reporter
+                        .update_status(|status| status.deferred = plan.deferred)
+                        .await;
+                    if plan.upgrade.is_empty() {
+                        return None;
+                    }
+                    command = casks::limit(&command, &plan.upgrade);
+                }
+                Ok(_) => {}
+                Err(e) => self.reporter.log(&format!(
+                    "Warning: couldn't check which casks ask for a password ({e:#}); upgrading all of them\n"
+                )),
             }
-            Ok(false) => {
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!(
-                    "Self-update command failed\n\nLogs:\n{accumulated_logs}"
+        }
+        Some(command)
+    }
+
+    /// Packages whose build files changed since they were last reviewed,
+    /// which an unattended upgrade leaves out. An interactive run shows the
+    /// changes in the logs instead and upgrades them.
+    async fn unreviewed(&self) -> std::result::Result<Vec<String>, Next> {
+        let Some(review_command) = &self.config.review_command else {
+            return Ok(Vec::new());
+        };
+        match review::pending(review_command).await {
+            Ok(packages) if packages.is_empty() => Ok(packages),
+            // Nothing unattended builds from build files nobody has read
+            Ok(packages) if self.options.noninteractive => {
+                self.reporter.log(&format!(
+                    "Leaving packages whose build files changed for a manual review: {}\n",
+                    packages.join(", ")
                 ));
-                return Ok(());
+                self.reporter
+                    .update_status(|status| status.needs_review = packages.clone())
+                    .await;
+                if self.config.exclude_args.is_none() && self.config.exclude_command.is_none() {
+                    self.reporter.log(&format!(
+                        "{} can't exclude packages, so its upgrade is skipped\n",
+                        self.config.name
+                    ));
+                    return Err(Next::Continue);
+                }
+                Ok(packages)
+            }
+            Ok(packages) => {
+                for package in &packages {
+                    self.reporter
+                        .log(&format!("=== CHANGES TO REVIEW: {package} ===\n"));
+                    let changes = match &self.config.review_diff_command {
+                        Some(template) => review::diff(template, package)
+                            .await
+                            .unwrap_or_else(|e| format!("Couldn't show the changes: {e:#}")),
+                        None => "Changed since the last review".to_string(),
+                    };
+                    self.reporter.log(&format!("{}\n\n", changes.trim_end()));
+                }
+                Ok(Vec::new())
+            }
+            Err(e) if self.options.noninteractive => {
+                self.reporter.start_phase(Phase::Upgrade).await;
+                self.reporter
+                    .finish_phase(
+                        None,
+                        Some(format!("Couldn't check which packages need review: {e:#}")),
+                    )
+                    .await;
+                Err(Next::Stop)
             }
             Err(e) => {
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!(
-                    "Self-update error: {e}\n\nLogs:\n{accumulated_logs}"
+                self.reporter.log(&format!(
+                    "Warning: couldn't check which packages need review ({e:#})\n"
                 ));
-                return Ok(());
+                Ok(Vec::new())
             }
         }
     }
 
-    // Upgrade all packages
-    accumulated_logs.push_str("=== UPGRADING PACKAGES ===\n");
-    {
-        let mut manager = manager_ref.lock().await;
-        manager.status = ManagerStatus::Running("Upgrading".to_string());
-        manager.logs = accumulated_logs.clone();
+    /// Run a step's command. Managers with a crate_upgrade or tool_upgrade
+    /// upgrade one tool at a time instead of running upgrade_all.
+    async fn run_command(&self, step: &PhaseStep, command: &str) -> Result<ExitReason> {
+        if step.phase == Phase::Upgrade {
+            if let Some(template) = &self.config.crate_upgrade {
+                let plan = crates::plan(&self.config).await?;
+                for (name, error) in &plan.failed_lookups {
+                    self.reporter.log(&format!(
+                        "Warning: couldn't look up {name} on crates.io ({error}); leaving it as it is\n"
+                    ));
+                }
+                return self.upgrade_tools(step, template, plan.upgrades).await;
+            }
+            if let (Some(list), Some(template)) =
+                (&self.config.tools_command, &self.config.tool_upgrade)
+            {
+                let tools = tools::list(list, &self.config).await?;
+                return self.upgrade_tools(step, template, tools).await;
+            }
+        }
+        self.run(command, step.timeout).await
     }
 
-    match execute_command_with_logs(
-        &config.upgrade_all,
-        config.requires_sudo,
-        Duration::from_secs(3600),
-        manager_ref.clone(),
-        "Upgrading".to_string(),
-        &mut accumulated_logs,
-    )
-    .await
-    {
-        Ok(true) => {
-            accumulated_logs.push_str("\n✓ Upgrade completed\n\n");
+    /// Upgrade the tools one by one, each listed under the manager as it
+    /// goes. A tool that fails, or hangs until it's killed, doesn't stop the
+    /// others; the step fails afterwards, naming it.
+    async fn upgrade_tools(
+        &self,
+        step: &PhaseStep,
+        template: &str,
+        tools: Vec<ToolUpgrade>,
+    ) -> Result<ExitReason> {
+        let (excluded, tools): (Vec<_>, Vec<_>) = tools.into_iter().partition(|tool| {
+            self.options
+                .exclusions
+                .iter()
+                .any(|pattern| holds::glob_match(pattern, &tool.name))
+        });
+        if !excluded.is_empty() {
+            let names: Vec<&str> = excluded.iter().map(|tool| tool.name.as_str()).collect();
+            self.reporter.log(&format!(
+                "Leaving out excluded packages: {}\n",
+                names.join(", ")
+            ));
+        }
+        if tools.is_empty() {
+            self.reporter.log("Nothing to upgrade\n");
+            return Ok(ExitReason::Code(0));
         }
-        Ok(false) => {
-            let mut manager = manager_ref.lock().await;
-            manager.status = ManagerStatus::Failed(format!(
-                "Upgrade command failed\n\nLogs:\n{accumulated_logs}"
+        let total = tools.len();
+        self.reporter
+            .update_status(|status| {
+                status.tools = tools.clone();
+                status.packages = Some(0);
+            })
+            .await;
+
+        let mut failed = Vec::new();
+        let mut unconfirmed = Vec::new();
+        for (index, tool) in tools.iter().enumerate() {
+            self.reporter.log(&format!(
+                "\n=== {} ({}/{total}) ===\n",
+                tool.describe(),
+                index + 1
             ));
-            return Ok(());
+            self.reporter
+                .update_status(|status| status.tools[index].state = RunState::Running)
+                .await;
+            let command = self.options.prepare_command(
+                step,
+                &tools::command(template, tool),
+                &self.config,
+                &[],
+            );
+            let result = self.run(&command, step.timeout).await;
+            let succeeded = matches!(&result, Ok(exit) if exit.success());
+            self.reporter
+                .update_status(|status| {
+                    status.tools[index].state = if succeeded {
+                        RunState::Success
+                    } else {
+                        RunState::Failed
+                    };
+                    status.progress = Some((index + 1) as f64 / total as f64);
+                    if succeeded && tool.latest.is_some() {
+                        status.packages = Some(status.packages.unwrap_or(0) + 1);
+                    }
+                })
+                .await;
+            if let Err(e) = &result {
+                self.reporter.log(&format!("\n{}: {e}\n", tool.name));
+            }
+            if succeeded && tool.latest.is_none() {
+                unconfirmed.push(tool);
+            } else if !succeeded {
+                failed.push(tool.name.as_str());
+            }
         }
-        Err(e) => {
-            let mut manager = manager_ref.lock().await;
-            manager.status =
-                ManagerStatus::Failed(format!("Upgrade error: {e}\n\nLogs:\n{accumulated_logs}"));
-            return Ok(());
+        if !unconfirmed.is_empty() {
+            let changed = self.changed_tools(&unconfirmed).await;
+            self.reporter
+                .update_status(|status| {
+                    status.packages = Some(status.packages.unwrap_or(0) + changed)
+                })
+                .await;
+        }
+
+        if failed.is_empty() {
+            Ok(ExitReason::Code(0))
+        } else {
+            anyhow::bail!("Couldn't upgrade {}", failed.join(", "))
         }
     }
 
-    // Cleanup
-    if let Some(cleanup_cmd) = &config.cleanup {
-        accumulated_logs.push_str("=== CLEANUP ===\n");
-        {
-            let mut manager = manager_ref.lock().await;
-            manager.status = ManagerStatus::Running("Cleaning".to_string());
-            manager.logs = accumulated_logs.clone();
-        }
-
-        match execute_command_with_logs(
-            cleanup_cmd,
-            config.requires_sudo,
-            Duration::from_secs(300),
-            manager_ref.clone(),
-            "Cleaning".to_string(),
-            &mut accumulated_logs,
+    /// How many of `tools`, upgraded without a known latest version, are
+    /// now at a different version. Listing them again is the only way to
+    /// tell, since upgrading one that's already current succeeds too.
+    async fn changed_tools(&self, tools: &[&ToolUpgrade]) -> usize {
+        let Some(list) = &self.config.tools_command else {
+            return 0;
+        };
+        let now = match tools::list(list, &self.config).await {
+            Ok(now) => now,
+            Err(e) => {
+                self.reporter.log(&format!(
+                    "Warning: couldn't check which tools changed ({e:#})\n"
+                ));
+                return 0;
+            }
+        };
+        tools
+            .iter()
+            .filter(|tool| {
+                now.iter()
+                    .find(|current| current.name == tool.name)
+                    .is_some_and(|current| current.installed != tool.installed)
+            })
+            .count()
+    }
+
+    async fn run(&self, command: &str, timeout: Duration) -> Result<ExitReason> {
+        execute_command_with_logs(
+            command,
+            RunAs::manager(&self.config),
+            timeout,
+            &self.settings,
+            &self.reporter,
         )
         .await
-        {
-            Ok(true) => {
-                accumulated_logs.push_str("\n✓ Cleanup completed\n\n");
+    }
+
+    /// Whether the refresh printed a signature problem (from `first_line`
+    /// on) that stops the run, in which case the status records it. Nothing
+    /// gets upgraded from a repository that failed verification.
+    async fn unverified(&self, first_line: usize, result: &Result<ExitReason>) -> bool {
+        let Some(line) = self
+            .reporter
+            .logs
+            .find_line(first_line, |line| self.signature_patterns.is_match(line))
+            .and_then(|index| self.reporter.logs.lines(index, 1).pop())
+        else {
+            return false;
+        };
+        let line = line.trim();
+        if self.options.allow_unverified {
+            self.reporter.log(&format!(
+                "\nWarning: signature verification failed ({line}); continuing because of --allow-unverified\n"
+            ));
+            return false;
+        }
+        self.reporter.log(&format!(
+            "\n=== SECURITY WARNING ===\n{line}\nNot upgrading {}: fix the repository keys, or run with --allow-unverified to upgrade anyway\n",
+            self.config.name
+        ));
+        let exit = result.as_ref().ok().copied();
+        self.reporter
+            .stop_unverified(exit, format!("Signature verification failed: {line}"))
+            .await;
+        true
+    }
+
+    /// Record the packages the upgrade kept back, after running full_upgrade
+    /// for them with `--full-upgrade`. Returns the full upgrade's result when
+    /// it ran.
+    async fn record_kept_back(
+        &self,
+        step: &PhaseStep,
+        first_line: usize,
+        unreviewed: &[String],
+    ) -> Option<Result<ExitReason>> {
+        let read_kept_back = |from: usize| {
+            self.kept_back_parser
+                .parse(&self.reporter.logs.lines(from, usize::MAX))
+        };
+        let mut kept_back = read_kept_back(first_line);
+        let mut result = None;
+        if let (true, false, Some(full_upgrade)) = (
+            self.options.full_upgrade,
+            kept_back.is_empty(),
+            &self.config.full_upgrade,
+        ) {
+            self.reporter
+                .log("\n=== FULL UPGRADE FOR KEPT-BACK PACKAGES ===\n");
+            let first_line = self.reporter.logs.len();
+            let command =
+                self.options
+                    .prepare_command(step, full_upgrade, &self.config, unreviewed);
+            result = Some(self.run(&command, step.timeout).await);
+            kept_back = read_kept_back(first_line);
+        }
+        self.reporter
+            .update_status(|status| status.kept_back = kept_back)
+            .await;
+        result
+    }
+
+    /// Record how the step ended. A failure ends the run unless the step is
+    /// optional.
+    async fn finish(&self, step: &PhaseStep, result: Result<ExitReason>) -> Next {
+        let error = phase_error(&step.phase, &result);
+        let exit = result.ok();
+        match error {
+            None => {
+                self.reporter
+                    .log(&format!("\n✓ {} completed\n\n", step.phase.name()));
+                self.reporter.finish_phase(exit, None).await;
+                Next::Continue
             }
-            Ok(false) => {
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!(
-                    "Cleanup command failed\n\nLogs:\n{accumulated_logs}"
+            Some(error) if step.optional => {
+                self.reporter.log(&format!(
+                    "\n✗ {} failed; continuing, since it's optional\n\n",
+                    step.phase.name()
                 ));
-                return Ok(());
+                self.reporter.finish_optional_phase(exit, error).await;
+                Next::Continue
             }
-            Err(e) => {
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!(
-                    "Cleanup error: {e}\n\nLogs:\n{accumulated_logs}"
-                ));
-                return Ok(());
+            Some(error) => {
+                self.reporter.finish_phase(exit, Some(error)).await;
+                Next::Stop
             }
         }
     }
+}
 
-    // Set final success status with complete logs
-    {
-        let mut manager = manager_ref.lock().await;
-        manager.status = ManagerStatus::Success;
-        manager.logs = accumulated_logs;
+/// Why a phase failed, or None if it succeeded.
+fn phase_error(phase: &Phase, result: &Result<ExitReason>) -> Option<String> {
+    match result {
+        Ok(exit) if exit.success() => None,
+        Ok(exit) => Some(format!(
+            "{} command failed: {}",
+            phase.name(),
+            exit.describe()
+        )),
+        Err(e) => Some(format!("{} error: {e}", phase.name())),
+    }
+}
+
+/// Run the remedy for a recognised failure, if it has one, recording it in
+/// the logs and status. Returns whether the failed step should be retried.
+async fn recover(
+    error: &str,
+    config: &ManagerConfig,
+    settings: &CommandSettings,
+    reporter: &Reporter,
+) -> bool {
+    let Some(cause) = diagnose(&reporter.manager, error, &reporter.logs.full_text()) else {
+        return false;
+    };
+    let Some(remedy) = cause.remedy else {
+        return false;
+    };
+
+    reporter.log(&format!(
+        "\n=== RECOVERY: {} ===\nRunning {}{}\n",
+        cause.english_title(),
+        remedy.command,
+        if remedy.sudo { " with sudo" } else { "" }
+    ));
+    let result = execute_command_with_logs(
+        remedy.command,
+        RunAs {
+            sudo: remedy.sudo,
+            ..RunAs::environment(config)
+        },
+        RECOVERY_TIMEOUT,
+        settings,
+        reporter,
+    )
+    .await;
+    let succeeded = matches!(result, Ok(exit) if exit.success());
+    let recovery = Recovery {
+        cause: *cause,
+        command: remedy.command,
+        succeeded,
+    };
+    reporter
+        .update_status(|status| status.recoveries.push(recovery))
+        .await;
+    succeeded
+}
+
+/// Log banner printed before a phase's output.
+fn phase_header(phase: &Phase) -> String {
+    match phase {
+        Phase::Refresh => "=== REFRESHING REPOSITORIES ===".to_string(),
+        Phase::SelfUpdate => "=== SELF-UPDATE ===".to_string(),
+        Phase::Upgrade => "=== UPGRADING PACKAGES ===".to_string(),
+        Phase::Casks => "=== UPGRADING CASKS ===".to_string(),
+        Phase::Cleanup => "=== CLEANUP ===".to_string(),
+        Phase::Custom(name) => format!("=== {} ===", name.to_uppercase()),
     }
-    Ok(())
 }
 
 // Wrapper function for backwards compatibility with non-TUI usage
-pub async fn execute_manager_workflow_simple(manager: &mut DetectedManager) -> Result<()> {
+pub async fn execute_manager_workflow_simple(
+    manager: &mut DetectedManager,
+    options: &RunOptions,
+) -> Result<()> {
     let manager_ref = Arc::new(Mutex::new(manager.clone()));
-    execute_manager_workflow(manager_ref.clone()).await?;
+    execute_manager_workflow(manager_ref.clone(), None, None, options.clone()).await?;
 
     // Copy the updated state back
     let updated_manager = manager_ref.lock().await;
@@ -189,26 +1221,149 @@ pub async fn execute_manager_workflow_simple(manager: &mut DetectedManager) -> R
     Ok(())
 }
 
+/// How a finished command terminated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Code(i32),
+    Signal(i32),
+}
+
+impl ExitReason {
+    fn from_status(status: std::process::ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return ExitReason::Signal(signal);
+            }
+        }
+        ExitReason::Code(status.code().unwrap_or(-1))
+    }
+
+    pub fn success(&self) -> bool {
+        matches!(self, ExitReason::Code(0))
+    }
+
+    /// Human readable description, distinguishing e.g. an OOM kill from a
+    /// regular non-zero exit.
+    pub fn describe(&self) -> String {
+        match self {
+            ExitReason::Code(code) => format!("exited with code {code}"),
+            ExitReason::Signal(signal) => {
+                let (name, hint) = signal_name(*signal);
+                match hint {
+                    Some(hint) => format!("terminated by {name} ({hint})"),
+                    None => format!("terminated by {name}"),
+                }
+            }
+        }
+    }
+}
+
+/// Name of a signal, with what it usually means. The numbers differ between
+/// platforms (SIGBUS is 7 on Linux but 10 on macOS), hence libc's constants.
+#[cfg(unix)]
+fn signal_name(signal: i32) -> (String, Option<&'static str>) {
+    match signal {
+        libc::SIGHUP => ("SIGHUP".to_string(), None),
+        libc::SIGINT => ("SIGINT".to_string(), Some("interrupted")),
+        libc::SIGILL => ("SIGILL".to_string(), Some("crashed")),
+        libc::SIGABRT => ("SIGABRT".to_string(), Some("aborted")),
+        libc::SIGBUS => ("SIGBUS".to_string(), Some("crashed")),
+        libc::SIGKILL => (
+            "SIGKILL".to_string(),
+            Some("killed, possibly by the out-of-memory killer"),
+        ),
+        libc::SIGSEGV => ("SIGSEGV".to_string(), Some("crashed")),
+        libc::SIGPIPE => ("SIGPIPE".to_string(), None),
+        libc::SIGTERM => ("SIGTERM".to_string(), Some("asked to terminate")),
+        other => (format!("signal {other}"), None),
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_name(signal: i32) -> (String, Option<&'static str>) {
+    (format!("signal {signal}"), None)
+}
+
+/// Stop every process in the group led by the command (see build_command):
+/// SIGTERM first, which sudo passes on to the command it runs, then SIGKILL
+/// for whatever is left after KILL_GRACE. `exited` waits for the command
+/// itself, which has to be reaped before the group can be seen to be empty.
+pub async fn kill_process_group(
+    pgid: Option<u32>,
+    exited: impl std::future::Future,
+) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if let Some(pgid) = pgid {
+        let deadline = tokio::time::Instant::now() + KILL_GRACE;
+        if signal_group(pgid, libc::SIGTERM)? {
+            let _ = tokio::time::timeout_at(deadline, exited).await;
+            while signal_group(pgid, 0)? && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            signal_group(pgid, libc::SIGKILL)?;
+        }
+        return Ok(());
+    }
+    let _ = (pgid, exited);
+    Ok(())
+}
+
+/// Send `signal` to a process group; false when nothing is left in it.
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) -> std::io::Result<bool> {
+    if unsafe { libc::kill(-(pgid as libc::pid_t), signal) } == 0 {
+        return Ok(true);
+    }
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ESRCH) => Ok(false),
+        _ => Err(error),
+    }
+}
+
+/// Kill the command and everything it spawned, then wait for it so no
+/// zombie is left behind.
+async fn kill_and_reap(
+    child: &mut Child,
+    pgid: Option<u32>,
+    reporter: &Reporter,
+) -> Option<ExitReason> {
+    if let Err(e) = kill_process_group(pgid, child.wait()).await {
+        reporter.log(&format!(
+            "Warning: couldn't kill the command's processes: {e}\n"
+        ));
+    }
+    let _ = child.start_kill();
+    child.wait().await.ok().map(ExitReason::from_status)
+}
+
 async fn execute_command_with_logs(
     command: &str,
-    requires_sudo: bool,
+    run_as: RunAs<'_>,
     timeout: Duration,
-    manager_ref: Arc<Mutex<DetectedManager>>,
-    operation: String,
-    accumulated_logs: &mut String,
-) -> Result<bool> {
-    let mut cmd = build_command(command, requires_sudo)?;
+    settings: &CommandSettings,
+    reporter: &Reporter,
+) -> Result<ExitReason> {
+    let mut cmd = build_command(command, run_as, &settings.env, settings.stdin)?;
+    let first_use = if run_as.uses_sudo() {
+        sudo::first_use().await
+    } else {
+        None
+    };
 
     let mut child = cmd.spawn()?;
+    // Remember the group id; the pid is no longer available once reaped
+    let pgid = child.id();
+
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        kill_and_reap(&mut child, pgid, reporter).await;
+        anyhow::bail!("Failed to capture command output");
+    };
 
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| anyhow::anyhow!("Failed to get stdout"))?;
-    let stderr = child
-        .stderr
-        .take()
-        .ok_or_else(|| anyhow::anyhow!("Failed to get stderr"))?;
+    let mut stdin = child.stdin.take();
+    let mut control = reporter.control.lock().await;
 
     let mut stdout_reader = BufReader::new(stdout).lines();
     let mut stderr_reader = BufReader::new(stderr).lines();
@@ -216,34 +1371,87 @@ async fn execute_command_with_logs(
     let timeout_future = tokio::time::sleep(timeout);
     tokio::pin!(timeout_future);
 
+    // Pushed back whenever the command prints something
+    let stall_limit = settings.stall_timeout;
+    let stall_future = tokio::time::sleep(stall_limit);
+    tokio::pin!(stall_future);
+    let mut stalled = false;
+
     let mut stdout_closed = false;
     let mut stderr_closed = false;
 
     loop {
         tokio::select! {
             () = &mut timeout_future => {
-                let _ = child.kill().await;
-                accumulated_logs.push_str("\nERROR: Command timed out\n");
-                let mut manager = manager_ref.lock().await;
-                manager.status = ManagerStatus::Failed(format!("Command timed out\n\nLogs:\n{accumulated_logs}"));
-                return Err(anyhow::anyhow!("Command timed out"));
+                kill_and_reap(&mut child, pgid, reporter).await;
+                reporter.log(&format!("\nERROR: Command timed out after {}s\n", timeout.as_secs()));
+                return Err(anyhow::anyhow!("Command timed out after {}s", timeout.as_secs()));
+            }
+
+            () = &mut stall_future, if !stalled => {
+                if settings.kill_on_stall {
+                    kill_and_reap(&mut child, pgid, reporter).await;
+                    reporter.log(&format!(
+                        "\nERROR: No output for {}s, the command is probably waiting for input\n",
+                        stall_limit.as_secs()
+                    ));
+                    return Err(anyhow::anyhow!(
+                        "Command stalled: no output for {}s (likely an interactive prompt)",
+                        stall_limit.as_secs()
+                    ));
+                }
+
+                reporter.log(&format!(
+                    "\nNo output for {}s - the command may be waiting for input\n",
+                    stall_limit.as_secs()
+                ));
+                reporter.set_state(RunState::Stalled).await;
+                stalled = true;
+            }
+
+            request = next_control(&mut control) => {
+                match request {
+                    Some(ManagerControl::Wait) => {
+                        reporter.log("Still waiting for the command...");
+                    }
+                    Some(ManagerControl::Kill) => {
+                        kill_and_reap(&mut child, pgid, reporter).await;
+                        reporter.log("\nERROR: Killed from the TUI\n");
+                        return Err(anyhow::anyhow!("Command killed by user"));
+                    }
+                    Some(ManagerControl::Input(text)) => {
+                        if let Some(stdin) = stdin.as_mut() {
+                            let line = format!("{text}\n");
+                            if stdin.write_all(line.as_bytes()).await.is_ok() {
+                                let _ = stdin.flush().await;
+                            }
+                        }
+                        reporter.log_line(format!("> {text}"));
+                    }
+                    None => {
+                        // Nobody can send controls any more
+                        *control = None;
+                        continue;
+                    }
+                }
+                resume_after_stall(&mut stalled, reporter).await;
+                stall_future.as_mut().reset(tokio::time::Instant::now() + stall_limit);
             }
 
             stdout_line = stdout_reader.next_line(), if !stdout_closed => {
                 match stdout_line {
                     Ok(Some(line)) => {
-                        accumulated_logs.push_str(&line);
-                        accumulated_logs.push('\n');
-
-                        let mut manager = manager_ref.lock().await;
-                        manager.status = ManagerStatus::Running(operation.clone());
-                        manager.logs = accumulated_logs.clone();
+                        stall_future.as_mut().reset(tokio::time::Instant::now() + stall_limit);
+                        resume_after_stall(&mut stalled, reporter).await;
+                        track_progress(&line, settings, reporter).await;
+                        reporter.log_line(line);
                     }
                     Ok(None) => {
                         stdout_closed = true;
                     }
                     Err(e) => {
-                        accumulated_logs.push_str(&format!("ERROR reading stdout: {e}\n"));
+                        kill_and_reap(&mut child, pgid, reporter).await;
+                        reporter.log(&format!("ERROR reading stdout: {e}\n"));
                         return Err(anyhow::anyhow!("Error reading stdout: {e}"));
                     }
                 }
@@ -252,19 +1460,17 @@ async fn execute_command_with_logs(
             stderr_line = stderr_reader.next_line(), if !stderr_closed => {
                 match stderr_line {
                     Ok(Some(line)) => {
-                        accumulated_logs.push_str("STDERR: ");
-                        accumulated_logs.push_str(&line);
-                        accumulated_logs.push('\n');
-
-                        let mut manager = manager_ref.lock().await;
-                        manager.status = ManagerStatus::Running(operation.clone());
-                        manager.logs = accumulated_logs.clone();
+                        stall_future.as_mut().reset(tokio::time::Instant::now() + stall_limit);
+                        resume_after_stall(&mut stalled, reporter).await;
+                        track_progress(&line, settings, reporter).await;
+                        reporter.log_line(format!("STDERR: {line}"));
                     }
                     Ok(None) => {
                         stderr_closed = true;
                     }
                     Err(e) => {
-                        accumulated_logs.push_str(&format!("ERROR reading stderr: {e}\n"));
+                        kill_and_reap(&mut child, pgid, reporter).await;
+                        reporter.log(&format!("ERROR reading stderr: {e}\n"));
                         return Err(anyhow::anyhow!("Error reading stderr: {e}"));
                     }
                 }
@@ -273,14 +1479,38 @@ async fn execute_command_with_logs(
             status = child.wait() => {
                 match status {
                     Ok(exit_status) => {
-                        let success = exit_status.success();
-                        if !success {
-                            accumulated_logs.push_str(&format!("\nCommand exited with code: {}\n", exit_status.code().unwrap_or(-1)));
+                        let exit = ExitReason::from_status(exit_status);
+                        // Its last lines may not have been read yet
+                        let _ = tokio::time::timeout(DRAIN_TIMEOUT, async {
+                            while let Ok(Some(line)) = stdout_reader.next_line().await {
+                                track_progress(&line, settings, reporter).await;
+                                reporter.log_line(line);
+                            }
+                            while let Ok(Some(line)) = stderr_reader.next_line().await {
+                                track_progress(&line, settings, reporter).await;
+                                reporter.log_line(format!("STDERR: {line}"));
+                            }
+                        })
+                        .await;
+                        if matches!(exit, ExitReason::Signal(_)) {
+                            // Don't leave the rest of a killed command's tree running
+                            if let Err(e) = kill_process_group(pgid, std::future::ready(())).await {
+                                reporter.log(&format!(
+                                    "Warning: couldn't kill the command's processes: {e}\n"
+                                ));
+                            }
+                        }
+                        if !exit.success() {
+                            reporter.log(&format!("\nCommand {}\n", exit.describe()));
                         }
-                        return Ok(success);
+                        if let Some(first_use) = first_use {
+                            first_use.finish(exit.success());
+                        }
+                        return Ok(exit);
                     }
                     Err(e) => {
-                        accumulated_logs.push_str(&format!("ERROR waiting for command: {e}\n"));
+                        kill_and_reap(&mut child, pgid, reporter).await;
+                        reporter.log(&format!("ERROR waiting for command: {e}\n"));
                         return Err(anyhow::anyhow!("Error waiting for command: {e}"));
                     }
                 }
@@ -289,42 +1519,484 @@ async fn execute_command_with_logs(
     }
 }
 
-fn build_command(command: &str, requires_sudo: bool) -> Result<Command> {
+/// Update the progress and package count from a line of output.
+async fn track_progress(line: &str, settings: &CommandSettings, reporter: &Reporter) {
+    if let Some(ratio) = settings.progress.parse(line) {
+        reporter.set_progress(ratio).await;
+    }
+    if settings.progress.is_package(line) {
+        reporter.count_package().await;
+    }
+}
+
+/// Wait for the next control request, or forever if there is no TUI.
+async fn next_control(control: &mut Option<ControlReceiver>) -> Option<ManagerControl> {
+    match control {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Put a stalled manager back into its running state.
+async fn resume_after_stall(stalled: &mut bool, reporter: &Reporter) {
+    if std::mem::take(stalled) {
+        reporter.set_state(RunState::Running).await;
+    }
+}
+
+/// Who runs a command, and where.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunAs<'a> {
+    /// Run it as root through sudo.
+    pub sudo: bool,
+    /// Run it as this user instead (`run_as_user`), which takes precedence
+    /// over `sudo`.
+    pub user: Option<&'a str>,
+    /// Run it in this directory (`workdir`).
+    pub workdir: Option<&'a str>,
+    /// Set these variables for it (the manager's `env`).
+    pub env: Option<&'a HashMap<String, String>>,
+    /// Put these directories in front of its PATH (the manager's `path`).
+    pub path: &'a [String],
+    /// The manager's own program, the first word of its check_command,
+    /// which `{binary}` resolves to.
+    pub program: Option<&'a str>,
+}
+
+impl<'a> RunAs<'a> {
+    /// How a manager's upgrade steps run.
+    pub fn manager(config: &'a ManagerConfig) -> Self {
+        RunAs {
+            sudo: config.requires_sudo,
+            user: config.run_as_user.as_deref(),
+            workdir: config.workdir.as_deref(),
+            ..RunAs::environment(config)
+        }
+    }
+
+    /// Just a manager's `env` and `path`, for probing its binary during
+    /// detection.
+    pub fn environment(config: &'a ManagerConfig) -> Self {
+        RunAs {
+            env: Some(&config.env),
+            path: &config.path,
+            program: config.check_command.split_whitespace().next(),
+            ..RunAs::default()
+        }
+    }
+
+    /// How a manager's read-only queries, such as outdated_command, run:
+    /// as its user and in its directory, but never with sudo.
+    pub fn manager_query(config: &'a ManagerConfig) -> Self {
+        RunAs {
+            sudo: false,
+            ..RunAs::manager(config)
+        }
+    }
+
+    /// The user to switch to, unless spn already runs as them.
+    fn other_user(&self) -> Option<&'a str> {
+        self.user.filter(|user| !is_current_user(user))
+    }
+
+    /// Whether the command goes through sudo.
+    pub fn uses_sudo(&self) -> bool {
+        self.sudo || self.other_user().is_some()
+    }
+}
+
+impl From<bool> for RunAs<'_> {
+    fn from(sudo: bool) -> Self {
+        RunAs {
+            sudo,
+            ..RunAs::default()
+        }
+    }
+}
+
+/// Whether spn runs as root.
+#[cfg(unix)]
+pub fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn is_root() -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn is_current_user(user: &str) -> bool {
+    // SAFETY: getpwuid returns null or a pointer to a static passwd entry,
+    // whose pw_name is NUL-terminated
+    unsafe {
+        let entry = libc::getpwuid(libc::geteuid());
+        !entry.is_null() && std::ffi::CStr::from_ptr((*entry).pw_name).to_bytes() == user.as_bytes()
+    }
+}
+
+#[cfg(not(unix))]
+fn is_current_user(_user: &str) -> bool {
+    false
+}
+
+/// `user`'s home directory from the password database.
+#[cfg(unix)]
+pub fn user_home(user: &str) -> Option<PathBuf> {
+    let name = std::ffi::CString::new(user).ok()?;
+    // SAFETY: name is NUL-terminated; getpwnam returns null or a pointer to a
+    // static passwd entry, whose pw_dir is NUL-terminated
+    unsafe {
+        let entry = libc::getpwnam(name.as_ptr());
+        if entry.is_null() {
+            return None;
+        }
+        let dir = std::ffi::CStr::from_ptr((*entry).pw_dir);
+        Some(PathBuf::from(dir.to_string_lossy().into_owned()))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn user_home(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// Fill in the placeholders any command may use: `{binary}` (the manager's
+/// program as found on its PATH), `{home}` (the home directory of the user
+/// it runs as), `{jobs}` (the number of CPUs) and `{tmpdir}`. Paths are
+/// quoted when they need it.
+fn expand_placeholders(command: &str, run_as: &RunAs) -> String {
+    if !command.contains('{') {
+        return command.to_string();
+    }
+    let quoted = |path: PathBuf| {
+        let path = path.to_string_lossy().into_owned();
+        if path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+:@%,=".contains(c))
+        {
+            path
+        } else {
+            shell_quote(&path)
+        }
+    };
+
+    let mut command = command.to_string();
+    if command.contains("{binary}") {
+        if let Some(program) = run_as.program {
+            let binary = std::env::current_dir()
+                .ok()
+                .and_then(|cwd| which::which_in(program, Some(search_path(run_as.path)), cwd).ok())
+                .map_or_else(|| program.to_string(), quoted);
+            command = command.replace("{binary}", &binary);
+        }
+    }
+    if command.contains("{home}") {
+        let home = match run_as.other_user() {
+            Some(user) => user_home(user),
+            None => dirs::home_dir(),
+        };
+        if let Some(home) = home {
+            command = command.replace("{home}", &quoted(home));
+        }
+    }
+    if command.contains("{jobs}") {
+        let jobs = std::thread::available_parallelism().map_or(1, |jobs| jobs.get());
+        command = command.replace("{jobs}", &jobs.to_string());
+    }
+    if command.contains("{tmpdir}") {
+        command = command.replace("{tmpdir}", &quoted(std::env::temp_dir()));
+    }
+    command
+}
+
+/// `dirs` ahead of spn's own PATH, as a manager with a `path` sees it.
+fn search_path(dirs: &[String]) -> String {
+    let mut search = dirs.join(":");
+    if let Ok(path) = std::env::var("PATH") {
+        if search.is_empty() {
+            return path;
+        }
+        search = format!("{search}:{path}");
+    }
+    search
+}
+
+/// Whether `user` has an account here, for `spn doctor`.
+#[cfg(unix)]
+pub fn user_exists(user: &str) -> bool {
+    let Ok(name) = std::ffi::CString::new(user) else {
+        return false;
+    };
+    // SAFETY: name is NUL-terminated; only the returned pointer is checked
+    !unsafe { libc::getpwnam(name.as_ptr()) }.is_null()
+}
+
+#[cfg(not(unix))]
+pub fn user_exists(_user: &str) -> bool {
+    false
+}
+
+fn build_command(
+    command: &str,
+    run_as: RunAs,
+    env: &[(String, String)],
+    stdin: bool,
+) -> Result<Command> {
+    let mut cmd = shell_command(command, run_as, env, false)?;
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdin(if stdin { Stdio::piped() } else { Stdio::null() })
+        .kill_on_drop(true);
+
+    // Run in a fresh process group so a timeout can take down the whole tree
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    Ok(cmd)
+}
+
+/// `sh -c command` as `run_as` says, through sudo when it needs to. Only an
+/// `interactive` command may have sudo ask for a password.
+fn shell_command(
+    command: &str,
+    run_as: RunAs,
+    env: &[(String, String)],
+    interactive: bool,
+) -> Result<Command> {
     if command.is_empty() {
         anyhow::bail!("Empty command");
     }
+    let command = &expand_placeholders(command, &run_as);
+
+    let mut env = env.to_vec();
+    if let Some(vars) = run_as.env {
+        let mut vars: Vec<_> = vars
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        vars.sort();
+        env.splice(0..0, vars);
+    }
+    if !run_as.path.is_empty() {
+        env.push(("PATH".to_string(), search_path(run_as.path)));
+    }
 
-    let mut cmd = if requires_sudo {
-        if which::which("sudo").is_err() {
-            anyhow::bail!("sudo is required but not available");
+    let mut cmd = if run_as.uses_sudo() {
+        let Some(program) = sudo::program() else {
+            anyhow::bail!("sudo (or doas) is required but not available");
+        };
+        let mut c = Command::new(program);
+        if !interactive {
+            c.arg("-n");
+        }
+        let user = run_as.other_user();
+        if let Some(user) = user {
+            c.args(["-u", user]);
+            // doas always sets the target user's HOME
+            if program == "sudo" {
+                c.arg("-H");
+            }
+        }
+        // sudo and doas reset the environment, so pass variables through env(1)
+        if !env.is_empty() {
+            c.arg("env");
+            c.args(env.iter().map(|(key, value)| format!("{key}={value}")));
         }
-        let mut c = Command::new("sudo");
-        c.arg("-n");
         c.arg("sh");
-        c.arg("-c");
+        // Another user gets a login shell for their PATH (~/.cargo/bin and
+        // the like) rather than sudo's secure_path
+        c.arg(if user.is_some() { "-lc" } else { "-c" });
         c.arg(command);
         c
     } else {
         let mut c = Command::new("sh");
         c.arg("-c");
         c.arg(command);
+        c.envs(env.iter().map(|(key, value)| (key, value)));
         c
     };
+    if let Some(workdir) = run_as.workdir {
+        if !std::path::Path::new(workdir).is_dir() {
+            anyhow::bail!("workdir {workdir} is not a directory");
+        }
+        cmd.current_dir(workdir);
+    }
+    Ok(cmd)
+}
 
-    cmd.stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .stdin(Stdio::null());
+/// Run a command to completion without streaming its output and return its
+/// stdout, for quick queries such as outdated checks.
+pub async fn run_captured<'a>(
+    command: &str,
+    run_as: impl Into<RunAs<'a>>,
+    timeout: Duration,
+) -> Result<String> {
+    let run_as = run_as.into();
+    let mut cmd = build_command(command, run_as, &[], false)?;
+    let first_use = if run_as.uses_sudo() {
+        sudo::first_use().await
+    } else {
+        None
+    };
+    let child = cmd.spawn()?;
+    let pgid = child.id();
+    let output = child.wait_with_output();
+    tokio::pin!(output);
+    let output = match tokio::time::timeout(timeout, &mut output).await {
+        Ok(output) => output?,
+        Err(_) => {
+            // Dropping the child would only kill sh, not what it started
+            if let Err(e) = kill_process_group(pgid, &mut output).await {
+                anyhow::bail!(
+                    "Command timed out after {}s and couldn't be killed: {e}",
+                    timeout.as_secs()
+                );
+            }
+            anyhow::bail!("Command timed out after {}s", timeout.as_secs())
+        }
+    };
+    if let Some(first_use) = first_use {
+        first_use.finish(output.status.success());
+    }
 
-    Ok(cmd)
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = ExitReason::from_status(output.status).describe();
+        let command = redact(command);
+        match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(line) => anyhow::bail!("'{command}' {reason}: {}", redact(line.trim())),
+            None => anyhow::bail!("'{command}' {reason}"),
+        }
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run a command attached to the terminal, so the user sees its output and
+/// can answer sudo's password prompt or the manager's own questions.
+pub async fn run_interactive<'a>(
+    command: &str,
+    run_as: impl Into<RunAs<'a>>,
+) -> Result<ExitReason> {
+    let status = shell_command(command, run_as.into(), &[], true)?
+        .status()
+        .await?;
+    Ok(ExitReason::from_status(status))
+}
+
+/// Append `flags` to every `&&`- or `||`-joined step of `command` that
+/// invokes the manager's own program, so `dnf check-update || [ $? -eq 100 ]`
+/// gets them on dnf rather than on the test.
+fn append_to_steps(command: &str, config: &ManagerConfig, flags: &str) -> String {
+    if flags.is_empty() {
+        return command.to_string();
+    }
+    let program = config.check_command.split_whitespace().next();
+    let append = |step: &str| {
+        let step = step.trim();
+        if step.split_whitespace().next() == program {
+            format!("{step} {flags}")
+        } else {
+            step.to_string()
+        }
+    };
+
+    let mut joined = String::new();
+    let mut rest = command;
+    while let Some(at) = [rest.find("&&"), rest.find("||")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        joined.push_str(&append(&rest[..at]));
+        joined.push_str(&format!(" {} ", &rest[at..at + 2]));
+        rest = &rest[at + 2..];
+    }
+    joined.push_str(&append(rest));
+    joined
+}
+
+/// Cap the download rate of `command` at `kbps` KB/s, with the manager's
+/// limit_rate_args if it has them and under trickle otherwise. Left alone
+/// when neither is possible; see `warn_rate_unsupported`.
+pub fn limit_rate(command: &str, config: &ManagerConfig, kbps: u64) -> String {
+    match &config.limit_rate_args {
+        Some(template) => append_to_steps(
+            command,
+            config,
+            &template.replace("{kbps}", &kbps.to_string()),
+        ),
+        None if which::which("trickle").is_ok() => {
+            format!("trickle -s -d {kbps} sh -c {}", shell_quote(command))
+        }
+        None => command.to_string(),
+    }
+}
+
+/// Warn about the managers a rate limit can't be applied to, those without
+/// limit_rate_args when trickle isn't installed.
+pub fn warn_rate_unsupported(managers: &[DetectedManager]) {
+    if which::which("trickle").is_ok() {
+        return;
+    }
+    let unlimited: Vec<&str> = managers
+        .iter()
+        .filter(|m| m.config.limit_rate_args.is_none())
+        .map(|m| m.name.as_str())
+        .collect();
+    if !unlimited.is_empty() {
+        eprintln!(
+            "Warning: can't limit the download rate of {}: no limit_rate_args, and trickle isn't installed",
+            unlimited.join(", ")
+        );
+    }
+}
+
+/// Parse a download rate such as `500k` or `2M` into KB/s. A bare number is
+/// in KB/s too.
+pub fn parse_rate(rate: &str) -> Result<u64> {
+    let rate = rate.trim();
+    let (number, scale) = match rate.char_indices().last() {
+        Some((end, 'k' | 'K')) => (&rate[..end], 1.0),
+        Some((end, 'm' | 'M')) => (&rate[..end], 1024.0),
+        _ => (rate, 1.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(number) if number > 0.0 && number.is_finite() => {
+            Ok(((number * scale).round() as u64).max(1))
+        }
+        _ => anyhow::bail!("Invalid rate '{rate}'. Use KB/s, e.g. 500, 500k or 2M"),
+    }
+}
+
+/// The rate cap for a run: `--limit-rate` if given, else `bandwidth_limit`.
+pub fn resolve_limit_rate(flag: Option<u64>, bandwidth_limit: &str) -> Result<Option<u64>> {
+    if flag.is_some() || bandwidth_limit.trim().is_empty() {
+        return Ok(flag);
+    }
+    parse_rate(bandwidth_limit)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("bandwidth_limit: {e}"))
+}
+
+/// Fill in an exclusion template; `{pattern}` is replaced with the quoted
+/// pattern, which the manager matches itself.
+fn pattern_command(template: &str, pattern: &str) -> String {
+    template.replace("{pattern}", &shell_quote(pattern))
+}
+
+/// Quote `arg` for use as a single word in an `sh -c` script.
+pub fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
 }
 
 pub async fn check_sudo_availability() -> bool {
-    if which::which("sudo").is_err() {
+    let Some(program) = sudo::program() else {
         return false;
-    }
+    };
 
     // Test if we can run sudo without password prompt
-    match Command::new("sudo")
+    match Command::new(program)
         .args(["-n", "true"])
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -0,0 +1,731 @@
+use crate::a11y;
+use crate::bugreport::{self, Environment};
+use crate::config::{self, HistoryConfig};
+use crate::detect::{DetectedManager, Phase, RunOrder, RunState};
+use crate::execute::ExitReason;
+use crate::kept_back::{self, KeptBack};
+use crate::stats::{format_duration_secs, format_timestamp, removed_marker};
+use crate::transaction::PackageChanges;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Log of recent upgrade runs, oldest first.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct History {
+    #[serde(default)]
+    pub runs: Vec<RunRecord>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RunRecord {
+    pub started_at: u64,
+    /// Wall-clock time of the whole run; unset in runs recorded before it was.
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+    pub order: String,
+    /// Shuffle seed when the run used random order.
+    pub seed: Option<u64>,
+    /// Managers in the order they were run.
+    pub managers: Vec<ManagerRecord>,
+    /// The `[auto_update.jobs]` entry that started the run.
+    #[serde(default)]
+    pub job: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ManagerRecord {
+    pub name: String,
+    pub outcome: String,
+    pub duration_secs: f64,
+    /// First line of the failure message.
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Full output of a failed run, kept for `spn triage`.
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+    /// Phases that ran, in order; the last one is where a failure happened.
+    #[serde(default)]
+    pub phases: Vec<PhaseRecord>,
+    /// Packages upgraded, for managers with a `package_pattern`.
+    #[serde(default)]
+    pub packages: Option<usize>,
+    /// Remedies run for known failures before retrying.
+    #[serde(default)]
+    pub recoveries: Vec<String>,
+    /// Packages installed, upgraded and removed, for managers with an
+    /// installed_command.
+    #[serde(default)]
+    pub changes: Option<PackageChanges>,
+    /// Casks left for an interactive run because they ask for a password.
+    #[serde(default)]
+    pub deferred: Vec<String>,
+    /// Packages left out because their build files need a manual review.
+    #[serde(default)]
+    pub needs_review: Vec<String>,
+    /// Packages the upgrade kept back, with the reason.
+    #[serde(default)]
+    pub kept_back: Vec<KeptBack>,
+    /// Why the run left the manager alone for someone to look at.
+    #[serde(default)]
+    pub attention: Option<String>,
+    /// PATH, proxies, disk space and the manager's version when it failed,
+    /// for `spn bugreport`.
+    #[serde(default)]
+    pub environment: Option<Environment>,
+}
+
+impl ManagerRecord {
+    /// Failed, or stopped because its repositories failed signature checks.
+    pub fn failed(&self) -> bool {
+        matches!(self.outcome.as_str(), "failed" | "unverified")
+    }
+
+    fn symbol(&self) -> &'static str {
+        if a11y::enabled() {
+            return match self.outcome.as_str() {
+                "success" => "SUCCESS",
+                "failed" => "FAILED",
+                "unverified" => "SECURITY WARNING",
+                _ => "SKIPPED",
+            };
+        }
+        match self.outcome.as_str() {
+            "success" => "✓",
+            "failed" => "✗",
+            "unverified" => "⚠",
+            _ => "-",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PhaseRecord {
+    pub phase: Phase,
+    pub duration_secs: f64,
+    /// Unset when the command was killed for a timeout or stall.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub signal: Option<i32>,
+    /// Not run because its skip_if condition held.
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+fn history_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("history.toml"))
+}
+
+pub fn failure_log_dir() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("logs"))
+}
+
+/// Save a failed manager's complete output next to the history file, or
+/// what was kept in memory if it couldn't be spooled to disk.
+async fn save_failure_log(started_at: u64, manager: &DetectedManager) -> Option<PathBuf> {
+    let dir = failure_log_dir()?;
+    tokio::fs::create_dir_all(&dir).await.ok()?;
+    let path = dir.join(format!("{started_at}-{}.log", manager.name));
+    match manager.logs.spool_path() {
+        Some(spool) => {
+            tokio::fs::copy(&spool, &path).await.ok()?;
+        }
+        None => tokio::fs::write(&path, manager.logs.contents())
+            .await
+            .ok()?,
+    }
+    Some(path)
+}
+
+pub async fn load_history() -> History {
+    let Some(path) = history_path() else {
+        return History::default();
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => toml::from_str(&content).unwrap_or_default(),
+        Err(_) => History::default(),
+    }
+}
+
+async fn save_history(history: &History) -> Result<()> {
+    let path = history_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(history)?).await?;
+    Ok(())
+}
+
+/// Append a finished run, then prune what `retention` no longer keeps.
+pub async fn record_run(
+    started_at: SystemTime,
+    order: RunOrder,
+    seed: Option<u64>,
+    managers: &[DetectedManager],
+    retention: &HistoryConfig,
+    job: Option<&str>,
+) -> Result<()> {
+    let mut history = load_history().await;
+    let duration_secs = started_at.elapsed().ok().map(|d| d.as_secs_f64());
+    let started_at = started_at
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let mut records = Vec::with_capacity(managers.len());
+    for manager in managers {
+        let (outcome, log_file) = match manager.status.state {
+            RunState::Success => ("success", None),
+            RunState::Failed => ("failed", save_failure_log(started_at, manager).await),
+            RunState::Unverified => ("unverified", save_failure_log(started_at, manager).await),
+            _ => ("skipped", None),
+        };
+        let phases = manager
+            .status
+            .phases
+            .iter()
+            .map(|result| PhaseRecord {
+                phase: result.phase.clone(),
+                duration_secs: result.duration.as_secs_f64(),
+                exit_code: match result.exit {
+                    Some(ExitReason::Code(code)) => Some(code),
+                    _ => None,
+                },
+                signal: match result.exit {
+                    Some(ExitReason::Signal(signal)) => Some(signal),
+                    _ => None,
+                },
+                skipped: result.skipped,
+            })
+            .collect();
+        records.push(ManagerRecord {
+            name: manager.name.clone(),
+            outcome: outcome.to_string(),
+            duration_secs: manager.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0),
+            error: manager.status.error.clone(),
+            log_file,
+            phases,
+            packages: manager.status.packages,
+            recoveries: manager
+                .status
+                .recoveries
+                .iter()
+                .map(|recovery| recovery.record())
+                .collect(),
+            changes: manager.changes.clone(),
+            deferred: manager.status.deferred.clone(),
+            needs_review: manager.status.needs_review.clone(),
+            kept_back: manager.status.kept_back.clone(),
+            attention: manager.status.attention.clone(),
+            environment: matches!(
+                manager.status.state,
+                RunState::Failed | RunState::Unverified
+            )
+            .then(|| bugreport::capture(manager)),
+        });
+    }
+
+    history.runs.push(RunRecord {
+        started_at,
+        duration_secs,
+        order: order.label().to_string(),
+        seed,
+        managers: records,
+        job: job.map(str::to_string),
+    });
+
+    prune(&mut history, retention).await;
+    save_history(&history).await
+}
+
+/// What pruning removed.
+#[derive(Debug, Default)]
+pub struct Pruned {
+    pub runs: usize,
+    pub logs: usize,
+    pub bytes: u64,
+}
+
+/// `spn history prune`: apply the retention settings now.
+pub async fn prune_history(retention: &HistoryConfig) -> Result<Pruned> {
+    let mut history = load_history().await;
+    let pruned = prune(&mut history, retention).await;
+    if pruned.runs > 0 || pruned.logs > 0 {
+        save_history(&history).await?;
+    }
+    Ok(pruned)
+}
+
+/// Drop runs beyond `keep_runs` or older than `keep_days` along with their
+/// failure logs, delete logs no remaining run refers to, then the oldest
+/// logs until the rest fit in `max_mb`.
+async fn prune(history: &mut History, retention: &HistoryConfig) -> Pruned {
+    let mut pruned = Pruned::default();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let cutoff = match retention.keep_days {
+        0 => 0,
+        days => now.saturating_sub(days * 24 * 60 * 60),
+    };
+    let before = history.runs.len();
+    history.runs.retain(|run| run.started_at >= cutoff);
+    let excess = history.runs.len().saturating_sub(retention.keep_runs);
+    history.runs.drain(..excess);
+    pruned.runs = before - history.runs.len();
+
+    let Some(dir) = failure_log_dir() else {
+        return pruned;
+    };
+    let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+        return pruned;
+    };
+    let referenced: HashSet<PathBuf> = history
+        .runs
+        .iter()
+        .flat_map(|run| &run.managers)
+        .filter_map(|manager| manager.log_file.clone())
+        .collect();
+    let mut kept = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if referenced.contains(&path) {
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            kept.push((modified, metadata.len(), path));
+        } else if tokio::fs::remove_file(&path).await.is_ok() {
+            pruned.logs += 1;
+            pruned.bytes += metadata.len();
+        }
+    }
+
+    // Oldest first, until what's left fits
+    kept.sort();
+    let mut total: u64 = kept.iter().map(|(_, size, _)| size).sum();
+    let limit = retention.max_mb * 1024 * 1024;
+    for (_, size, path) in kept {
+        if total <= limit {
+            break;
+        }
+        if tokio::fs::remove_file(&path).await.is_err() {
+            continue;
+        }
+        total -= size;
+        pruned.logs += 1;
+        pruned.bytes += size;
+        for manager in history.runs.iter_mut().flat_map(|run| &mut run.managers) {
+            if manager.log_file.as_ref() == Some(&path) {
+                manager.log_file = None;
+            }
+        }
+    }
+    pruned
+}
+
+/// The latest recorded result of every manager whose most recent run failed,
+/// newest first.
+pub fn latest_failures(history: &History) -> Vec<(&RunRecord, &ManagerRecord)> {
+    let mut seen = HashSet::new();
+    let mut failures = Vec::new();
+    for run in history.runs.iter().rev() {
+        for manager in &run.managers {
+            if manager.outcome == "skipped" || !seen.insert(manager.name.as_str()) {
+                continue;
+            }
+            if manager.failed() {
+                failures.push((run, manager));
+            }
+        }
+    }
+    failures
+}
+
+/// When the latest run that upgraded `name` successfully started, in seconds
+/// since the epoch. Runs that left it alone because it needed attention
+/// don't count, since nothing was upgraded.
+pub fn last_success(history: &History, name: &str) -> Option<u64> {
+    history
+        .runs
+        .iter()
+        .rev()
+        .find(|run| {
+            run.managers
+                .iter()
+                .any(|m| m.name == name && m.outcome == "success" && m.attention.is_none())
+        })
+        .map(|run| run.started_at)
+}
+
+/// Print the most recent `limit` runs, newest first. Managers missing from
+/// `configured` are still shown, marked as no longer configured.
+pub async fn print_history(limit: usize, configured: Option<&HashSet<String>>) {
+    let history = load_history().await;
+    if history.runs.is_empty() {
+        println!("No runs recorded yet.");
+        return;
+    }
+
+    for run in history.runs.iter().rev().take(limit) {
+        let started = format_timestamp(run.started_at);
+        let order = match run.seed {
+            Some(seed) => format!("{} (seed {seed})", run.order),
+            None => run.order.clone(),
+        };
+        let failed = run.managers.iter().filter(|m| m.failed()).count();
+
+        let mut heading = format!("{started}  {order}");
+        if let Some(secs) = run.duration_secs {
+            heading.push_str(&format!("  {}", format_duration_secs(secs)));
+        }
+        if let Some(job) = &run.job {
+            heading.push_str(&format!("  (job {job})"));
+        }
+        println!("{heading}");
+        println!(
+            "  {}",
+            run.managers
+                .iter()
+                .map(|m| format!(
+                    "{} {}{}",
+                    m.symbol(),
+                    m.name,
+                    removed_marker(&m.name, configured)
+                ))
+                .collect::<Vec<_>>()
+                .join(" → ")
+        );
+        for manager in run.managers.iter().filter(|m| m.failed()) {
+            let Some(error) = &manager.error else {
+                continue;
+            };
+            let symbol = manager.symbol();
+            match manager.phases.last() {
+                Some(phase) => println!(
+                    "  {symbol} {}: {error} after {}",
+                    manager.name,
+                    format_duration_secs(phase.duration_secs)
+                ),
+                None => println!("  {symbol} {}: {error}", manager.name),
+            }
+        }
+        for manager in &run.managers {
+            for recovery in &manager.recoveries {
+                println!("  ↻ {}: {recovery}", manager.name);
+            }
+        }
+        if failed > 0 {
+            println!("  {failed} failed");
+        }
+    }
+}
+
+/// `spn history show`: print everything recorded about one run, `back` runs
+/// ago counting the most recent as 1, including the packages it changed.
+pub async fn show_run(back: usize, configured: Option<&HashSet<String>>) -> Result<()> {
+    let history = load_history().await;
+    let run = run_back(&history, back)?;
+
+    match run.duration_secs {
+        Some(secs) => println!(
+            "Run started {}, took {}",
+            format_timestamp(run.started_at),
+            format_duration_secs(secs)
+        ),
+        None => println!("Run started {}", format_timestamp(run.started_at)),
+    }
+    match run.seed {
+        Some(seed) => println!("Order: {} (seed {seed})", run.order),
+        None => println!("Order: {}", run.order),
+    }
+    if let Some(job) = &run.job {
+        println!("Job: {job}");
+    }
+
+    for manager in &run.managers {
+        let symbol = manager.symbol();
+        let mut details = vec![format_duration_secs(manager.duration_secs)];
+        if let Some(packages) = manager.packages {
+            details.push(format!("{packages} packages reported"));
+        }
+        println!(
+            "\n{symbol} {}{} ({})",
+            manager.name,
+            removed_marker(&manager.name, configured),
+            details.join(", ")
+        );
+        if !manager.phases.is_empty() {
+            let phases: Vec<String> = manager
+                .phases
+                .iter()
+                .map(|phase| {
+                    let name = phase.phase.name().to_lowercase();
+                    if phase.skipped {
+                        format!("{name} skipped")
+                    } else if phase.exit_code.is_some_and(|code| code != 0)
+                        || phase.signal.is_some()
+                    {
+                        format!(
+                            "{name} failed {}",
+                            format_duration_secs(phase.duration_secs)
+                        )
+                    } else {
+                        format!("{name} {}", format_duration_secs(phase.duration_secs))
+                    }
+                })
+                .collect();
+            println!("  Steps: {}", phases.join(", "));
+        }
+        if let Some(error) = &manager.error {
+            println!("  Error: {error}");
+        }
+        for recovery in &manager.recoveries {
+            println!("  ↻ {recovery}");
+        }
+        if !manager.deferred.is_empty() {
+            println!(
+                "  Deferred (ask for a password): {}",
+                manager.deferred.join(", ")
+            );
+        }
+        if !manager.needs_review.is_empty() {
+            println!(
+                "  Needs manual attention (review build files): {}",
+                manager.needs_review.join(", ")
+            );
+        }
+        if let Some(reason) = &manager.attention {
+            println!("  Needs attention, left alone: {reason}");
+        }
+        if !manager.kept_back.is_empty() {
+            println!("  Kept back: {}", kept_back::describe(&manager.kept_back));
+        }
+        match &manager.changes {
+            Some(changes) if changes.is_empty() => println!("  No packages changed"),
+            Some(changes) => print_changes(changes),
+            None => {}
+        }
+        if let Some(log_file) = &manager.log_file {
+            println!("  Log: {}", log_file.display());
+        }
+    }
+    Ok(())
+}
+
+/// The run `back` runs ago, counting the most recent as 1.
+fn run_back(history: &History, back: usize) -> Result<&RunRecord> {
+    back.checked_sub(1)
+        .and_then(|index| history.runs.iter().rev().nth(index))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No run {back} in the history; {} runs are recorded",
+                history.runs.len()
+            )
+        })
+}
+
+/// A manager counts as slower when it took this many seconds longer...
+const SLOWER_SECS: f64 = 10.0;
+/// ...and this much longer relative to the earlier run.
+const SLOWER_RATIO: f64 = 1.25;
+
+/// `spn history diff`: compare run `older` with run `newer` (counting back
+/// from the most recent as 1): managers whose outcome changed, ones that got
+/// noticeably slower, and packages only one of the runs changed.
+pub async fn diff_runs(
+    older: usize,
+    newer: usize,
+    configured: Option<&HashSet<String>>,
+) -> Result<()> {
+    let history = load_history().await;
+    let (a, b) = (run_back(&history, older)?, run_back(&history, newer)?);
+    println!(
+        "Run {older} ({}) → run {newer} ({})",
+        format_timestamp(a.started_at),
+        format_timestamp(b.started_at)
+    );
+    if let (Some(before), Some(after)) = (a.duration_secs, b.duration_secs) {
+        println!(
+            "Took {} → {} ({})",
+            format_duration_secs(before),
+            format_duration_secs(after),
+            signed_duration(after - before)
+        );
+    }
+
+    let mut names: Vec<&str> = a.managers.iter().map(|m| m.name.as_str()).collect();
+    for manager in &b.managers {
+        if !names.contains(&manager.name.as_str()) {
+            names.push(&manager.name);
+        }
+    }
+
+    let mut outcomes = Vec::new();
+    let mut slower = Vec::new();
+    let mut packages = Vec::new();
+    for name in names {
+        let label = format!("{name}{}", removed_marker(name, configured));
+        let before = a.managers.iter().find(|m| m.name == name);
+        let after = b.managers.iter().find(|m| m.name == name);
+        let (before, after) = match (before, after) {
+            (Some(before), Some(after)) => (before, after),
+            (Some(before), None) => {
+                outcomes.push(format!("  - {label}: {} → not run", before.outcome));
+                continue;
+            }
+            (None, Some(after)) => {
+                outcomes.push(format!("  + {label}: not run → {}", after.outcome));
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        if before.outcome != after.outcome {
+            let mut line = format!(
+                "  {} {label}: {} → {}",
+                after.symbol(),
+                before.outcome,
+                after.outcome
+            );
+            if let Some(error) = after.error.as_ref().filter(|_| after.failed()) {
+                line.push_str(&format!(" ({error})"));
+            }
+            outcomes.push(line);
+        }
+
+        let grew = after.duration_secs - before.duration_secs;
+        if grew >= SLOWER_SECS && after.duration_secs >= before.duration_secs * SLOWER_RATIO {
+            let mut line = format!(
+                "  {label}: {} → {} ({})",
+                format_duration_secs(before.duration_secs),
+                format_duration_secs(after.duration_secs),
+                signed_duration(grew)
+            );
+            // Which step the time went to
+            let step = after
+                .phases
+                .iter()
+                .map(|phase| {
+                    let earlier = before
+                        .phases
+                        .iter()
+                        .find(|p| p.phase == phase.phase)
+                        .map_or(0.0, |p| p.duration_secs);
+                    (phase.duration_secs - earlier, phase)
+                })
+                .max_by(|(x, _), (y, _)| x.total_cmp(y));
+            if let Some((extra, phase)) = step.filter(|(extra, _)| *extra >= SLOWER_SECS) {
+                line.push_str(&format!(
+                    ", mostly {} ({})",
+                    phase.phase.name().to_lowercase(),
+                    signed_duration(extra)
+                ));
+            }
+            slower.push(line);
+        }
+
+        let (only_before, only_after) = package_differences(before, after);
+        if !only_before.is_empty() || !only_after.is_empty() {
+            packages.push(format!("  {label}:"));
+            packages.extend(only_before.iter().map(|change| format!("    - {change}")));
+            packages.extend(only_after.iter().map(|change| format!("    + {change}")));
+        }
+    }
+
+    if outcomes.is_empty() && slower.is_empty() && packages.is_empty() {
+        println!("\nNo differences in outcomes, durations or packages.");
+        return Ok(());
+    }
+    for (title, lines) in [
+        ("Outcomes changed", outcomes),
+        ("Slower", slower),
+        ("Packages changed in only one run", packages),
+    ] {
+        if !lines.is_empty() {
+            println!("\n{title}:");
+            for line in lines {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The package changes only the earlier run made, and only the later one,
+/// each as "name from → to" or "name version (installed)".
+fn package_differences(
+    before: &ManagerRecord,
+    after: &ManagerRecord,
+) -> (Vec<String>, Vec<String>) {
+    fn describe(changes: Option<&PackageChanges>) -> Vec<String> {
+        let Some(changes) = changes else {
+            return Vec::new();
+        };
+        let upgraded = changes
+            .upgraded
+            .iter()
+            .map(|p| format!("{} {} → {}", p.name, p.from, p.to));
+        let installed = changes
+            .installed
+            .iter()
+            .map(|p| format!("{} {} (installed)", p.name, p.version));
+        let removed = changes
+            .removed
+            .iter()
+            .map(|p| format!("{} {} (removed)", p.name, p.version));
+        upgraded.chain(installed).chain(removed).collect()
+    }
+    let (earlier, later) = (
+        describe(before.changes.as_ref()),
+        describe(after.changes.as_ref()),
+    );
+    let only_earlier = earlier
+        .iter()
+        .filter(|c| !later.contains(c))
+        .cloned()
+        .collect();
+    let only_later = later
+        .iter()
+        .filter(|c| !earlier.contains(c))
+        .cloned()
+        .collect();
+    (only_earlier, only_later)
+}
+
+fn signed_duration(secs: f64) -> String {
+    if secs < 0.0 {
+        format!("-{}", format_duration_secs(-secs))
+    } else {
+        format!("+{}", format_duration_secs(secs))
+    }
+}
+
+fn print_changes(changes: &PackageChanges) {
+    if !changes.upgraded.is_empty() {
+        println!("  Upgraded:");
+        for package in &changes.upgraded {
+            println!("    {} {} → {}", package.name, package.from, package.to);
+        }
+    }
+    if !changes.installed.is_empty() {
+        println!("  Installed:");
+        for package in &changes.installed {
+            println!("    {} {}", package.name, package.version);
+        }
+    }
+    if !changes.removed.is_empty() {
+        println!("  Removed:");
+        for package in &changes.removed {
+            println!("    {} {}", package.name, package.version);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::a11y;
+use crate::config::{self, ManagerConfig};
+use crate::execute::{run_captured, RunAs};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const OUTDATED_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Managers the user has put on hold; upgrades skip them until released.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Holds {
+    #[serde(default)]
+    pub managers: BTreeSet<String>,
+}
+
+fn holds_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("holds.toml"))
+}
+
+pub async fn load_holds() -> Holds {
+    let Some(path) = holds_path() else {
+        return Holds::default();
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => toml::from_str(&content).unwrap_or_default(),
+        Err(_) => Holds::default(),
+    }
+}
+
+async fn save_holds(holds: &Holds) -> Result<()> {
+    let path = holds_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(holds)?).await?;
+    Ok(())
+}
+
+/// Put a manager on hold or release it.
+pub async fn set_hold(manager: &str, held: bool) -> Result<()> {
+    let mut holds = load_holds().await;
+    if held {
+        holds.managers.insert(manager.to_string());
+    } else {
+        holds.managers.remove(manager);
+    }
+    save_holds(&holds).await
+}
+
+/// `spn hold`: list holds, or hold/release one manager.
+pub async fn manage_holds(manager: Option<String>, release: bool) -> Result<()> {
+    let Some(manager) = manager else {
+        let holds = load_holds().await;
+        if holds.managers.is_empty() {
+            println!("No managers are on hold.");
+        } else {
+            println!("On hold:");
+            for name in &holds.managers {
+                println!("  {name}");
+            }
+        }
+        return Ok(());
+    };
+
+    set_hold(&manager, !release).await?;
+    if release {
+        println!("{}Released {manager}", a11y::mark("✓ "));
+    } else {
+        println!(
+            "{}{manager} is on hold and will be skipped by upgrades",
+            a11y::mark("✓ ")
+        );
+        println!("  Release it with 'spn hold --release {manager}'");
+    }
+    Ok(())
+}
+
+/// Shell-style match supporting `*` and `?`, as used for package and unit
+/// name patterns.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+/// Pending updates to kernel and OS packages (`os_packages`), read from the
+/// manager's outdated_command. None when that can't tell, in which case
+/// callers should assume there are some.
+pub async fn pending_os_updates(config: &ManagerConfig) -> Option<BTreeSet<String>> {
+    let command = config.outdated_command.as_ref()?;
+    let output = run_captured(command, RunAs::manager_query(config), OUTDATED_TIMEOUT)
+        .await
+        .ok()?;
+    // Outdated listings differ per manager, so look at every word: "name/suite"
+    // for apt, "name.arch" for dnf, "| name |" columns for zypper
+    Some(
+        output
+            .lines()
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == '|' || c == '/'))
+            .filter(|word| {
+                config
+                    .os_packages
+                    .iter()
+                    .any(|pattern| glob_match(pattern, word))
+            })
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Where the packages an upgrade held only for itself are recorded until
+/// they're released, so a run that was killed first leaves them for the next.
+fn run_holds_path(manager: &str) -> Option<PathBuf> {
+    let name: String = manager
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    config::data_dir().map(|dir| dir.join("run-holds").join(name))
+}
+
+/// Packages (or, for a `{pattern}` release_command, patterns) still held
+/// for a manager's run, one per line.
+pub async fn run_holds(manager: &str) -> Vec<String> {
+    let Some(path) = run_holds_path(manager) else {
+        return Vec::new();
+    };
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Record what a manager's run holds, replacing the previous record; an
+/// empty list removes it.
+pub async fn save_run_holds(manager: &str, held: &[String]) -> Result<()> {
+    let path =
+        run_holds_path(manager).ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if held.is_empty() {
+        return match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut content = held.join("\n");
+    content.push('\n');
+    tokio::fs::write(&path, content).await?;
+    Ok(())
+}
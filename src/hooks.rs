@@ -0,0 +1,79 @@
+use crate::config::HooksConfig;
+use crate::execute;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// How long a hook may run before it's killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Run the `[hooks]` that apply to a finished run: on_success or on_failure,
+/// then on_complete. Each gets `summary` on stdin. Returns a message for
+/// each hook that failed.
+pub async fn run_hooks(config: &HooksConfig, summary: &Value, failed: bool) -> Vec<String> {
+    let outcome = if failed {
+        ("on_failure", &config.on_failure)
+    } else {
+        ("on_success", &config.on_success)
+    };
+    let input = format!("{summary}\n");
+    let mut errors = Vec::new();
+    for (name, command) in [outcome, ("on_complete", &config.on_complete)] {
+        let Some(command) = command.as_deref().filter(|c| !c.trim().is_empty()) else {
+            continue;
+        };
+        if let Err(e) = run_hook(name, command, &input).await {
+            errors.push(format!("hooks.{name} failed: {e:#}"));
+        }
+    }
+    errors
+}
+
+async fn run_hook(name: &str, command: &str, input: &str) -> Result<()> {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .env("SPINE_HOOK", name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Its own process group, so a timeout also kills what it started
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd.spawn().context("Couldn't start it")?;
+    let pgid = child.id();
+    let run = async {
+        if let Some(mut stdin) = child.stdin.take() {
+            // A hook that doesn't read its input closes the pipe early
+            let _ = stdin.write_all(input.as_bytes()).await;
+        }
+        child.wait_with_output().await
+    };
+    tokio::pin!(run);
+    let output = match tokio::time::timeout(HOOK_TIMEOUT, &mut run).await {
+        Ok(output) => output?,
+        Err(_) => {
+            if let Err(e) = execute::kill_process_group(pgid, &mut run).await {
+                anyhow::bail!(
+                    "still running after {}s and couldn't be killed: {e}",
+                    HOOK_TIMEOUT.as_secs()
+                );
+            }
+            anyhow::bail!("still running after {}s", HOOK_TIMEOUT.as_secs());
+        }
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last = stderr.lines().rev().find(|line| !line.trim().is_empty());
+        match (output.status.code(), last) {
+            (Some(code), Some(line)) => anyhow::bail!("exited with code {code}: {line}"),
+            (Some(code), None) => anyhow::bail!("exited with code {code}"),
+            (None, _) => anyhow::bail!("killed by a signal"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Translations built into the binary. English is complete and is the
+/// fallback for anything another language doesn't translate.
+const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+];
+
+/// The user's language first, then English.
+static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+
+/// Language from the locale environment, in POSIX precedence, e.g. `de` for
+/// `LANG=de_DE.UTF-8`. None for the C/POSIX locale or when nothing is set.
+fn requested_language() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())?;
+    let language = locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match language.as_str() {
+        "" | "c" | "posix" => None,
+        _ => Some(language),
+    }
+}
+
+fn bundle(language: &str, source: &str) -> Option<FluentBundle<FluentResource>> {
+    let langid: LanguageIdentifier = language.parse().ok()?;
+    let resource = FluentResource::try_new(source.to_string()).ok()?;
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Unicode isolation marks show up as stray characters in most terminals
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).ok()?;
+    Some(bundle)
+}
+
+fn bundles() -> &'static [FluentBundle<FluentResource>] {
+    BUNDLES.get_or_init(|| {
+        let requested = requested_language();
+        LOCALES
+            .iter()
+            .filter(|(language, _)| *language == "en" || requested.as_deref() == Some(*language))
+            // The requested language goes before the English fallback
+            .rev()
+            .filter_map(|(language, source)| bundle(language, source))
+            .collect()
+    })
+}
+
+/// Look up a message in the user's language, falling back to English and
+/// then to the message id itself. Use the `t!` macro rather than calling
+/// this directly.
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    for bundle in bundles() {
+        let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+            continue;
+        };
+        let mut errors = Vec::new();
+        return bundle
+            .format_pattern(pattern, args, &mut errors)
+            .into_owned();
+    }
+    id.to_string()
+}
+
+/// A message in English whatever the user's language, for text such as
+/// history records that is kept or shared rather than read right away.
+pub fn english(id: &str) -> String {
+    bundles()
+        .last()
+        .and_then(|bundle| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = Vec::new();
+            Some(
+                bundle
+                    .format_pattern(pattern, None, &mut errors)
+                    .into_owned(),
+            )
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translate a message: `t!("upgrade-starting")`, or with arguments,
+/// `t!("upgrade-skip-held", name = manager.name.as_str())`.
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+
+pub(crate) use t;
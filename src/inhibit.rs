@@ -0,0 +1,74 @@
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+/// How long the inhibitor gets to fail (for instance when polkit refuses a
+/// session-less cron job) before it's assumed to be holding.
+const STARTUP_GRACE: Duration = Duration::from_millis(200);
+
+/// Keeps the system from going to sleep while it's alive: `caffeinate` on
+/// macOS, a `systemd-inhibit` lock on Linux. Both watch spn's PID, so the
+/// lock goes away with spn even if it's killed before this is dropped.
+pub struct SleepInhibitor {
+    child: Child,
+}
+
+impl SleepInhibitor {
+    /// Start preventing sleep, warning on stderr and returning None when
+    /// that isn't possible here.
+    pub async fn start(why: &str) -> Option<Self> {
+        let pid = std::process::id().to_string();
+        let mut command = if cfg!(target_os = "macos") {
+            let mut command = Command::new("caffeinate");
+            // Idle and system sleep; the display may still turn off
+            command.args(["-i", "-s", "-w", &pid]);
+            command
+        } else if which::which("systemd-inhibit").is_ok() {
+            let mut command = Command::new("systemd-inhibit");
+            command
+                .args(["--what=sleep:idle", "--who=spine", "--mode=block"])
+                .arg(format!("--why={why}"))
+                .args([
+                    "sh",
+                    "-c",
+                    "while kill -0 \"$1\" 2>/dev/null; do sleep 5; done",
+                ])
+                .args(["sh", &pid]);
+            command
+        } else {
+            return None;
+        };
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| eprintln!("Warning: could not prevent sleep: {e}"))
+            .ok()?;
+
+        tokio::time::sleep(STARTUP_GRACE).await;
+        if let Ok(Some(status)) = child.try_wait() {
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = std::io::Read::read_to_string(&mut pipe, &mut stderr);
+            }
+            match stderr.lines().find(|line| !line.trim().is_empty()) {
+                Some(reason) => eprintln!(
+                    "Warning: could not prevent sleep: {} (prevent_sleep = false stops trying)",
+                    reason.trim()
+                ),
+                None => eprintln!(
+                    "Warning: could not prevent sleep ({status}; prevent_sleep = false stops trying)"
+                ),
+            }
+            return None;
+        }
+        Some(Self { child })
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
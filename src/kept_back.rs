@@ -0,0 +1,83 @@
+use crate::config::ManagerConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A package the upgrade left at its installed version, and why.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct KeptBack {
+    pub package: String,
+    pub reason: String,
+}
+
+/// Finds the packages an upgrade kept back from the manager's
+/// `kept_back_patterns`: each one matches a header line, and the indented
+/// lines right after it list the packages, separated by whitespace.
+#[derive(Debug, Clone, Default)]
+pub struct KeptBackParser {
+    headers: Vec<(Regex, String)>,
+}
+
+impl KeptBackParser {
+    pub fn new(config: &ManagerConfig) -> Result<Self> {
+        let headers = config
+            .kept_back_patterns
+            .iter()
+            .map(|(pattern, reason)| {
+                let regex = Regex::new(pattern)
+                    .with_context(|| format!("Invalid kept-back pattern '{pattern}'"))?;
+                Ok((regex, reason.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { headers })
+    }
+
+    pub fn parse(&self, lines: &[String]) -> Vec<KeptBack> {
+        let mut kept_back: Vec<KeptBack> = Vec::new();
+        let mut reason = None;
+        for line in lines {
+            if let Some((_, header_reason)) =
+                self.headers.iter().find(|(regex, _)| regex.is_match(line))
+            {
+                reason = Some(header_reason);
+                continue;
+            }
+            let Some(reason) = reason.filter(|_| line.starts_with(char::is_whitespace)) else {
+                reason = None;
+                continue;
+            };
+            for package in line.split_whitespace() {
+                if !kept_back.iter().any(|kept| kept.package == package) {
+                    kept_back.push(KeptBack {
+                        package: package.to_string(),
+                        reason: reason.clone(),
+                    });
+                }
+            }
+        }
+        kept_back
+    }
+}
+
+/// e.g. `a, b (phasing); c (dependencies or holds)`, grouped by reason in the
+/// order they came up.
+pub fn describe(kept_back: &[KeptBack]) -> String {
+    let mut reasons: Vec<&str> = Vec::new();
+    for kept in kept_back {
+        if !reasons.contains(&kept.reason.as_str()) {
+            reasons.push(&kept.reason);
+        }
+    }
+    reasons
+        .iter()
+        .map(|reason| {
+            let packages: Vec<&str> = kept_back
+                .iter()
+                .filter(|kept| kept.reason == *reason)
+                .map(|kept| kept.package.as_str())
+                .collect();
+            format!("{} ({reason})", packages.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
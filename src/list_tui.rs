@@ -0,0 +1,267 @@
+use crate::config::{Config, ManagerConfig};
+use crate::config_edit;
+use crate::detect::{self, Phase};
+use crate::i18n::t;
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Margin},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// Whether a configured manager was found on this system.
+enum Presence {
+    Detected(Option<String>),
+    NotInstalled,
+    OtherSystem,
+}
+
+struct Entry {
+    key: String,
+    config: ManagerConfig,
+    presence: Presence,
+}
+
+/// Feedback for the last toggle, shown at the bottom.
+struct Message {
+    text: String,
+    error: bool,
+}
+
+/// `spn list --interactive`: every configured manager, detected or not, with
+/// Space toggling its `enabled` key in the config file at `path`.
+pub async fn run(config: &Config, path: &Path) -> Result<()> {
+    // Probe disabled managers too, so they show whether they're installed
+    let mut probe = config.clone();
+    for manager in probe.managers.values_mut() {
+        manager.enabled = true;
+    }
+    let versions: HashMap<String, Option<String>> = detect::detect_package_managers(&probe)
+        .await?
+        .into_iter()
+        .map(|manager| (manager.name, manager.version))
+        .collect();
+
+    let mut entries: Vec<Entry> = config
+        .managers
+        .iter()
+        .map(|(key, manager)| Entry {
+            key: key.clone(),
+            config: manager.clone(),
+            presence: match versions.get(key) {
+                Some(version) => Presence::Detected(version.clone()),
+                None if detect::runs_here(manager) => Presence::NotInstalled,
+                None => Presence::OtherSystem,
+            },
+        })
+        .collect();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    if entries.is_empty() {
+        println!("No package managers are configured in {}", path.display());
+        return Ok(());
+    }
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let result = event_loop(&mut terminal, &mut entries, path).await;
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+async fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    entries: &mut [Entry],
+    path: &Path,
+) -> Result<()> {
+    let mut list_state = ListState::default();
+    list_state.select(Some(0));
+    let mut message: Option<Message> = None;
+
+    loop {
+        terminal.draw(|f| render(f, entries, &mut list_state, message.as_ref()))?;
+
+        // Nothing changes on screen except in response to a key
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let selected = list_state.selected().unwrap_or_default();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => {
+                list_state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                list_state.select(Some((selected + 1).min(entries.len() - 1)));
+            }
+            KeyCode::Home => list_state.select(Some(0)),
+            KeyCode::End => list_state.select(Some(entries.len() - 1)),
+            KeyCode::Char(' ') => {
+                let entry = &mut entries[selected];
+                let enabled = !entry.config.enabled;
+                let key = format!("managers.{}.enabled", entry.key);
+                message = Some(
+                    match config_edit::set_value(path, &key, &enabled.to_string()).await {
+                        Ok(_) => {
+                            entry.config.enabled = enabled;
+                            let id = if enabled {
+                                "list-enabled"
+                            } else {
+                                "list-disabled"
+                            };
+                            Message {
+                                text: t!(id, name = entry.key.as_str()),
+                                error: false,
+                            }
+                        }
+                        Err(e) => Message {
+                            text: t!(
+                                "list-save-failed",
+                                name = entry.key.as_str(),
+                                error = format!("{e:#}")
+                            ),
+                            error: true,
+                        },
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render(f: &mut Frame, entries: &[Entry], list_state: &mut ListState, message: Option<&Message>) {
+    let area = f.area().inner(Margin {
+        horizontal: 2,
+        vertical: 1,
+    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+        .split(area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
+        .split(rows[0]);
+
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            let checkbox = if entry.config.enabled { "[x]" } else { "[ ]" };
+            let (presence, color) = match &entry.presence {
+                Presence::Detected(Some(version)) => (
+                    t!("list-detected-version", version = version.as_str()),
+                    Color::Green,
+                ),
+                Presence::Detected(None) => (t!("list-detected"), Color::Green),
+                Presence::NotInstalled => (t!("list-not-installed"), Color::DarkGray),
+                Presence::OtherSystem => (t!("list-other-system"), Color::DarkGray),
+            };
+            let name_style = if entry.config.enabled {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{checkbox} {:<12} ", entry.key), name_style),
+                Span::styled(presence, Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t!("list-title")),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(list, columns[0], list_state);
+
+    if let Some(entry) = list_state.selected().and_then(|index| entries.get(index)) {
+        let details = Paragraph::new(details(&entry.config))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t!("tui-config")),
+            )
+            .wrap(Wrap { trim: true });
+        f.render_widget(details, columns[1]);
+    }
+
+    let footer = match message {
+        Some(message) => Paragraph::new(message.text.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t!("tui-status")),
+            )
+            .style(Style::default().fg(if message.error {
+                Color::Red
+            } else {
+                Color::Green
+            })),
+        None => Paragraph::new(
+            [
+                t!("tui-help-navigate"),
+                t!("list-help-toggle"),
+                t!("tui-help-quit"),
+            ]
+            .join(" | "),
+        )
+        .block(Block::default().borders(Borders::ALL).title(t!("tui-help")))
+        .style(Style::default().fg(Color::Cyan)),
+    };
+    f.render_widget(footer, rows[1]);
+}
+
+fn details(config: &ManagerConfig) -> String {
+    let unset = t!("tui-config-unset");
+    let mut lines = vec![
+        t!("tui-config-name", value = config.name.as_str()),
+        t!("tui-config-check", value = config.check_command.as_str()),
+        t!(
+            "list-requires-sudo",
+            value = config.requires_sudo.to_string()
+        ),
+    ];
+    let limits: Vec<&str> = [&config.os, &config.arch, &config.distro]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    if !limits.is_empty() {
+        lines.push(t!("list-limited-to", value = limits.join(", ")));
+    }
+    lines.push(String::new());
+    for phase in Phase::ALL {
+        lines.push(format!(
+            "{}: {}",
+            phase.title(),
+            config.command(&phase).unwrap_or(&unset)
+        ));
+    }
+    for extra in &config.phases {
+        lines.push(format!("{}: {}", extra.name, extra.command));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,342 @@
+use crate::redact::redact;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+const DEFAULT_MAX_LINES: usize = 20_000;
+const DEFAULT_MAX_BYTES: usize = 8 * 1024 * 1024;
+const DEFAULT_HEAD_LINES: usize = 200;
+
+/// Shared, capped buffer of log lines: the first lines of the output are kept
+/// for good, later ones in a ring buffer, with a truncation marker between
+/// them once lines had to be dropped.
+///
+/// Cloning the buffer is cheap and yields a handle to the same storage, so the
+/// executor can append lines while the TUI reads them without copying the
+/// whole log on every update.
+///
+/// Every line is also appended to a spool file with an index of line offsets,
+/// so the full log can be paged through without keeping it in memory.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    inner: Arc<RwLock<LogLines>>,
+}
+
+#[derive(Debug)]
+struct LogLines {
+    head: Vec<String>,
+    lines: VecDeque<String>,
+    bytes: usize,
+    dropped: usize,
+    max_lines: usize,
+    max_bytes: usize,
+    max_head: usize,
+    spool: Option<LogSpool>,
+    spool_failed: bool,
+}
+
+/// Append-only on-disk copy of the log, indexed by line start offsets.
+#[derive(Debug)]
+struct LogSpool {
+    path: PathBuf,
+    file: File,
+    offsets: Vec<u64>,
+    end: u64,
+}
+
+static SPOOL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl LogSpool {
+    /// Create the spool in a fresh directory only the current user can
+    /// enter, so other users can't read the output or plant a file or
+    /// symlink at a path they guessed in advance.
+    fn create() -> std::io::Result<Self> {
+        let id = SPOOL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = private_dir(&format!("spine-log-{}-{id}", std::process::id()))?;
+        let path = dir.join("output.log");
+        let mut options = std::fs::OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = std::fs::remove_dir(&dir);
+                return Err(e);
+            }
+        };
+        Ok(Self {
+            path,
+            file,
+            offsets: Vec::new(),
+            end: 0,
+        })
+    }
+
+    fn append(&mut self, line: &str) -> std::io::Result<()> {
+        let mut record = Vec::with_capacity(line.len() + 1);
+        record.extend_from_slice(line.as_bytes());
+        record.push(b'\n');
+        self.file.write_all(&record)?;
+        self.offsets.push(self.end);
+        self.end += record.len() as u64;
+        Ok(())
+    }
+
+    fn read_lines(&self, start: usize, count: usize) -> std::io::Result<Vec<String>> {
+        let end_index = start.saturating_add(count).min(self.offsets.len());
+        if start >= end_index {
+            return Ok(Vec::new());
+        }
+
+        let from = self.offsets[start];
+        let to = self.offsets.get(end_index).copied().unwrap_or(self.end);
+        let mut buf = vec![0; (to - from) as usize];
+        read_exact_at(&self.file, &mut buf, from)?;
+
+        Ok(String::from_utf8_lossy(&buf)
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+impl Drop for LogSpool {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        if let Some(dir) = self.path.parent() {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Create a new directory under the temp dir, named `prefix` plus a random
+/// suffix, that only the current user can access. Never reuses an existing
+/// directory.
+pub(crate) fn private_dir(prefix: &str) -> std::io::Result<PathBuf> {
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    let mut attempts = 0;
+    loop {
+        let dir = std::env::temp_dir().join(format!("{prefix}-{:08x}", fastrand::u32(..)));
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempts < 16 => {
+                attempts += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset)
+}
+
+#[cfg(not(unix))]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = file.try_clone()?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::with_limits(DEFAULT_MAX_LINES, DEFAULT_MAX_BYTES, DEFAULT_HEAD_LINES)
+    }
+}
+
+impl LogBuffer {
+    /// Keep at most `max_lines` lines and `max_bytes` bytes in memory, of
+    /// which the first `head_lines` are never evicted.
+    pub fn with_limits(max_lines: usize, max_bytes: usize, head_lines: usize) -> Self {
+        let max_lines = max_lines.max(2);
+        Self {
+            inner: Arc::new(RwLock::new(LogLines {
+                head: Vec::new(),
+                lines: VecDeque::new(),
+                bytes: 0,
+                dropped: 0,
+                max_lines,
+                max_bytes: max_bytes.max(1),
+                // Leave room for at least one line of tail
+                max_head: head_lines.min(max_lines - 1),
+                spool: None,
+                spool_failed: false,
+            })),
+        }
+    }
+
+    /// Append a single line with its secrets masked, evicting the oldest
+    /// lines after the head once a limit is hit.
+    pub fn push_line(&self, line: impl Into<String>) {
+        let line = line.into();
+        let masked = match redact(&line) {
+            Cow::Owned(masked) => Some(masked),
+            Cow::Borrowed(_) => None,
+        };
+        let line = masked.unwrap_or(line);
+        let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+        inner.spool_line(&line);
+        inner.bytes += line.len();
+        if inner.head.len() < inner.max_head && inner.lines.is_empty() {
+            inner.head.push(line);
+            return;
+        }
+        inner.lines.push_back(line);
+
+        while inner.head.len() + inner.lines.len() > inner.max_lines
+            || (inner.bytes > inner.max_bytes && inner.lines.len() > 1)
+        {
+            if let Some(evicted) = inner.lines.pop_front() {
+                inner.bytes -= evicted.len();
+                inner.dropped += 1;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let inner = self.read();
+        inner.head.is_empty() && inner.lines.is_empty()
+    }
+
+    /// Number of lines that can be paged through with [`LogBuffer::lines`].
+    pub fn len(&self) -> usize {
+        let inner = self.read();
+        match &inner.spool {
+            Some(spool) => spool.offsets.len(),
+            None => inner.retained().count(),
+        }
+    }
+
+    /// Number of lines no longer viewable because the buffer was full and
+    /// no spool file was available.
+    pub fn dropped(&self) -> usize {
+        let inner = self.read();
+        if inner.spool.is_some() {
+            0
+        } else {
+            inner.dropped
+        }
+    }
+
+    /// Copy out `count` lines starting at `start`, reading from the spool
+    /// file when there is one.
+    pub fn lines(&self, start: usize, count: usize) -> Vec<String> {
+        let inner = self.read();
+        if let Some(spool) = &inner.spool {
+            if let Ok(lines) = spool.read_lines(start, count) {
+                return lines;
+            }
+        }
+        inner
+            .retained()
+            .skip(start)
+            .take(count)
+            .map(|line| line.into_owned())
+            .collect()
+    }
+
+    /// Index of the first line at or after `start` that `matches` accepts,
+    /// reading the log in chunks rather than all at once.
+    pub fn find_line(&self, start: usize, matches: impl Fn(&str) -> bool) -> Option<usize> {
+        const CHUNK: usize = 1000;
+        let len = self.len();
+        let mut index = start;
+        while index < len {
+            let lines = self.lines(index, CHUNK);
+            if lines.is_empty() {
+                break;
+            }
+            if let Some(offset) = lines.iter().position(|line| matches(line)) {
+                return Some(index + offset);
+            }
+            index += lines.len();
+        }
+        None
+    }
+
+    /// Join the lines kept in memory into a single string: the head, a
+    /// truncation marker if lines were dropped, then the most recent lines.
+    pub fn contents(&self) -> String {
+        let inner = self.read();
+        let mut out = String::with_capacity(inner.bytes + inner.head.len() + inner.lines.len());
+        for line in inner.retained() {
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The complete output: the spool file when there is one, otherwise the
+    /// lines kept in memory.
+    pub fn full_text(&self) -> String {
+        self.spool_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_else(|| self.contents())
+    }
+
+    /// The on-disk copy of the complete output, if it could be written.
+    pub fn spool_path(&self) -> Option<PathBuf> {
+        self.read().spool.as_ref().map(|spool| spool.path.clone())
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, LogLines> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl LogLines {
+    /// The lines kept in memory, with a marker where lines were dropped.
+    fn retained(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        let marker = (self.dropped > 0)
+            .then(|| Cow::Owned(format!("... {} lines truncated ...", self.dropped)));
+        self.head
+            .iter()
+            .map(|line| Cow::Borrowed(line.as_str()))
+            .chain(marker)
+            .chain(self.lines.iter().map(|line| Cow::Borrowed(line.as_str())))
+    }
+
+    /// Mirror a line to the spool file, created lazily on the first line. If
+    /// the file can't be written the buffer falls back to memory only.
+    fn spool_line(&mut self, line: &str) {
+        if self.spool_failed {
+            return;
+        }
+        if self.spool.is_none() {
+            // Lines already in memory would be missing from the file
+            if !self.head.is_empty() || !self.lines.is_empty() {
+                self.spool_failed = true;
+                return;
+            }
+            match LogSpool::create() {
+                Ok(spool) => self.spool = Some(spool),
+                Err(_) => {
+                    self.spool_failed = true;
+                    return;
+                }
+            }
+        }
+        if let Some(spool) = &mut self.spool {
+            if spool.append(line).is_err() {
+                self.spool = None;
+                self.spool_failed = true;
+            }
+        }
+    }
+}
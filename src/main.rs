@@ -1,21 +1,133 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::io;
-
-use crate::detect::{DetectedManager, ManagerStatus};
-use crate::execute::execute_manager_workflow_simple;
-
+use std::collections::HashSet;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use crate::config::WebhookEvent;
+use crate::detect::{DetectedManager, RunState};
+use crate::execute::{execute_manager_workflow_simple, RunOptions};
+use crate::i18n::t;
+use crate::power::PowerAction;
+
+mod a11y;
+mod apprise;
+mod audit;
+mod auto_update;
+mod battery;
+mod bench;
+mod bugreport;
+mod casks;
+mod check;
+mod clipboard;
 mod config;
+mod config_edit;
+mod config_pull;
+mod crates;
 mod detect;
+mod diagnose;
+mod doctor;
 mod execute;
+mod history;
+mod holds;
+mod hooks;
+mod i18n;
+mod inhibit;
+mod kept_back;
+mod list_tui;
+mod logs;
+mod man;
+mod native;
 mod notify;
+mod outdated;
+mod packages;
+mod power;
+mod prefetch;
+mod preset_groups;
+mod profiles;
+mod progress;
+mod redact;
+mod reporting;
+mod repos;
+mod restart;
+mod review;
+mod schedule;
+mod secrets;
+mod serve;
+mod stats;
+mod status;
+mod sudo;
+mod summary;
+mod system_log;
+mod tasks;
+mod tools;
+mod transaction;
+mod triage;
+mod trust;
 mod tui;
+mod tui_prefs;
+mod webhook;
+mod wsl;
 
 #[derive(Parser)]
 #[command(name = "spn")]
 #[command(about = "A meta package manager for Unix-like systems")]
 struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Use the project-local spine.toml instead of the system configuration"
+    )]
+    local: bool,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "local",
+        help = "Use /etc/spine and /var/lib/spine instead of your own config and data directories"
+    )]
+    system: bool,
+    #[arg(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Ask package managers to produce less output; upgrade prints one line per manager"
+    )]
+    quiet: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Probe package managers again instead of reusing the cached detection"
+    )]
+    refresh_detect: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Only refresh and list pending updates; never install, download or remove packages"
+    )]
+    report_only: bool,
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        help = "Ask package managers for more output (repeat for more)"
+    )]
+    verbose: u8,
+    #[arg(
+        long,
+        global = true,
+        help = "Screen-reader friendly output: no TUI, spinners, box drawing or color, and each step on a line of its own"
+    )]
+    a11y: bool,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "local",
+        help = "If the config doesn't parse, run with the built-in defaults instead of stopping"
+    )]
+    defaults: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -38,9 +150,252 @@ enum Commands {
         no_tui: bool,
         #[arg(long, help = "Send notification when upgrade completes")]
         notify: bool,
+        #[arg(
+            long,
+            help = "Upgrade kernel and OS packages even in unattended runs (--no-tui, -q, scheduled)"
+        )]
+        include_os: bool,
+        #[arg(
+            long,
+            help = "Upgrade even when a refresh reports a repository signature or key problem"
+        )]
+        allow_unverified: bool,
+        #[arg(
+            long,
+            help = "Only run steps that don't need the network, such as cleanup"
+        )]
+        offline: bool,
+        #[arg(
+            long,
+            help = "When packages are kept back, run the manager's full_upgrade (may install or remove packages)"
+        )]
+        full_upgrade: bool,
+        #[arg(long, hide = true)]
+        scheduled: bool,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Only upgrade the managers of this [auto_update.jobs] entry"
+        )]
+        job: Option<String>,
+        #[arg(
+            long,
+            value_name = "RATE",
+            value_parser = execute::parse_rate,
+            help = "Cap download speed, e.g. 500k or 2M (KB/s), overriding bandwidth_limit"
+        )]
+        limit_rate: Option<u64>,
+        #[arg(
+            long,
+            help = "Run managers that are new or changed in the config with sudo without asking"
+        )]
+        accept_new_managers: bool,
+        #[arg(
+            long,
+            help = "Run managers even if their min_interval hasn't passed since they last succeeded"
+        )]
+        ignore_intervals: bool,
+        #[arg(
+            long,
+            group = "power_after",
+            help = "Shut down once every manager has finished, after a 60 second countdown"
+        )]
+        shutdown_after: bool,
+        #[arg(
+            long,
+            group = "power_after",
+            help = "Reboot once every manager has finished, after a 60 second countdown"
+        )]
+        reboot_after: bool,
+        #[arg(
+            long,
+            requires = "power_after",
+            help = "Shut down or reboot even when a manager failed"
+        )]
+        force: bool,
     },
     #[command(about = "List detected package managers")]
-    List,
+    List {
+        #[arg(long, help = "Show cumulative run statistics for each manager")]
+        stats: bool,
+        #[arg(
+            short,
+            long,
+            conflicts_with = "stats",
+            help = "Browse all configured managers and enable or disable them with Space"
+        )]
+        interactive: bool,
+    },
+    #[command(about = "Show the last run, the auto-update schedule and pending updates")]
+    Status {
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format, including status bar modules"
+        )]
+        format: status::StatusFormat,
+    },
+    #[command(about = "Refresh metadata and cache the number of pending updates per manager")]
+    Check {
+        #[arg(long, help = "Check even if the cached result is still fresh")]
+        force: bool,
+        #[arg(long, help = "Count pending updates without refreshing metadata first")]
+        no_refresh: bool,
+        #[arg(
+            long,
+            help = "Notify about pending updates, with an \"Upgrade now\" action"
+        )]
+        notify: bool,
+    },
+    #[command(
+        about = "Download pending upgrades without installing them, so the next upgrade is quick"
+    )]
+    Prefetch {
+        #[arg(
+            long,
+            value_name = "RATE",
+            value_parser = execute::parse_rate,
+            help = "Cap download speed, e.g. 500k or 2M (KB/s), overriding bandwidth_limit"
+        )]
+        limit_rate: Option<u64>,
+        #[arg(long, hide = true)]
+        scheduled: bool,
+    },
+    #[command(
+        about = "List installed packages with known security advisories and which pending updates fix them"
+    )]
+    Audit {
+        #[arg(long, help = "Only list advisories that a pending update fixes")]
+        fixable: bool,
+    },
+    #[command(about = "Serve status, run history and failure logs as JSON over HTTP (read-only)")]
+    Serve {
+        #[arg(
+            long,
+            default_value = "127.0.0.1:7878",
+            help = "Address to listen on; only this machine can connect to the default"
+        )]
+        addr: std::net::SocketAddr,
+    },
+    #[command(about = "Show run statistics for every manager that has been upgraded")]
+    Stats,
+    #[command(about = "Show recent upgrade runs and the order managers ran in")]
+    History {
+        #[arg(
+            short = 'n',
+            long,
+            default_value_t = 10,
+            help = "Number of runs to show"
+        )]
+        limit: usize,
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+    },
+    #[command(
+        about = "Time each manager's refresh and update check, or past runs, to see which ones slow spn down"
+    )]
+    Bench {
+        #[arg(
+            short = 'n',
+            long,
+            default_value_t = 3,
+            help = "Number of times to run each refresh and check"
+        )]
+        runs: usize,
+        #[arg(long, help = "Report on recorded runs instead of running anything")]
+        history: bool,
+        #[arg(
+            long,
+            default_value_t = 20,
+            help = "Number of recent runs to use with --history"
+        )]
+        last: usize,
+        #[arg(value_name = "MANAGER", help = "Only time these managers")]
+        managers: Vec<String>,
+    },
+    #[command(about = "Walk through recent failures and fix them one by one")]
+    Triage,
+    #[command(
+        about = "Write the latest failures with their environment and logs to a file for a bug report"
+    )]
+    Bugreport {
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Where to write it (default: spine-bugreport-<time>.md here)"
+        )]
+        output: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "MANAGER",
+            help = "Only include this manager's failure"
+        )]
+        manager: Option<String>,
+    },
+    #[command(about = "Hold a manager so upgrades skip it, or list held managers")]
+    Hold {
+        #[arg(help = "Manager to hold (e.g. apt)")]
+        manager: Option<String>,
+        #[arg(long, requires = "manager", help = "Release the manager instead")]
+        release: bool,
+    },
+    #[command(about = "Check the configuration and environment for problems")]
+    Doctor,
+    #[command(about = "Remove a package with the manager that installed it")]
+    Remove {
+        #[arg(help = "Package to remove")]
+        package: String,
+        #[arg(
+            long,
+            value_name = "MANAGER",
+            help = "Remove it with this manager (e.g. brew) instead of asking each one"
+        )]
+        with: Option<String>,
+        #[arg(
+            short,
+            long,
+            help = "Don't ask for confirmation; the manager may still ask"
+        )]
+        yes: bool,
+    },
+    #[command(about = "Show which package manager installed a binary, file or package")]
+    Which {
+        #[arg(help = "Binary on PATH, path to a file, or package name")]
+        target: String,
+    },
+    #[command(about = "Create the configuration, optionally set up for a kind of machine")]
+    Init {
+        #[arg(
+            long,
+            value_parser = clap::builder::PossibleValuesParser::new(profiles::names()),
+            help = "Apply a profile: laptop defers runs on battery and in working hours, caps bandwidth and leaves firmware alone"
+        )]
+        profile: Option<String>,
+    },
+    #[command(about = "Read or change configuration settings from scripts")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    #[command(about = "Store tokens in the OS keyring for ${secret:NAME} in the config")]
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+    #[command(about = "Print the manual page, or write all of them to a directory")]
+    Man {
+        #[arg(help = "Subcommand to show the page for (e.g. upgrade)")]
+        subcommand: Option<String>,
+        #[arg(
+            long,
+            value_name = "DIR",
+            conflicts_with = "subcommand",
+            help = "Write spn.1 and a page per subcommand into DIR"
+        )]
+        out_dir: Option<PathBuf>,
+    },
     #[command(about = "Enable or disable automatic background updates")]
     Auto {
         #[arg(long, help = "Enable automatic updates")]
@@ -49,45 +404,478 @@ enum Commands {
         disable: bool,
         #[arg(long, help = "Show current auto-update status")]
         status: bool,
+        #[arg(long, help = "Check that the scheduler has actually loaded the job")]
+        verify: bool,
+        #[arg(
+            long,
+            help = "Rewrite the installed job from the config if it no longer matches"
+        )]
+        repair: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryAction {
+    #[command(about = "Show one run in full, including the packages it changed")]
+    Show {
+        #[arg(
+            default_value_t = 1,
+            help = "Run to show, counting back from the most recent (1)"
+        )]
+        run: usize,
+    },
+    #[command(about = "Compare two runs: changed outcomes, slower managers and packages")]
+    Diff {
+        #[arg(
+            default_value_t = 2,
+            help = "Earlier run, counting back from the most recent (1)"
+        )]
+        older: usize,
+        #[arg(default_value_t = 1, help = "Later run to compare it with")]
+        newer: usize,
+    },
+    #[command(about = "Delete runs and failure logs beyond the [history] retention settings")]
+    Prune,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    #[command(about = "Print the value in effect for a setting")]
+    Get {
+        #[arg(help = "Dotted key, e.g. auto_update.time")]
+        key: String,
+    },
+    #[command(about = "Change a setting, keeping the file's comments and layout")]
+    Set {
+        #[arg(help = "Dotted key, e.g. managers.brew.requires_sudo")]
+        key: String,
+        #[arg(help = "New value, e.g. false, 6 or 07:30")]
+        value: String,
+    },
+    #[command(about = "Print the path of the configuration file in use")]
+    Path,
+    #[command(about = "Replace the configuration with a centrally managed one")]
+    Pull {
+        #[arg(help = "HTTPS URL of the config; auto_update.config_url when left out")]
+        url: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretAction {
+    #[command(about = "Store a secret, typed at a hidden prompt or piped in on stdin")]
+    Set {
+        #[arg(help = "Name the config refers to it by, e.g. reporting_token")]
+        name: String,
+    },
+    #[command(about = "Remove a secret from the keyring")]
+    Delete {
+        #[arg(help = "Name of the secret")]
+        name: String,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    if cli.system {
+        config::use_system_paths();
+    }
+    if cli.refresh_detect {
+        detect::force_refresh();
+    }
+    if cli.a11y {
+        a11y::enable();
+    }
+    if cli.defaults {
+        config::fall_back_to_defaults();
+    }
 
     match cli.command {
         Commands::Upgrade {
             selective,
             no_tui,
             notify,
+            include_os,
+            allow_unverified,
+            offline,
+            full_upgrade,
+            scheduled,
+            job,
+            limit_rate,
+            accept_new_managers,
+            ignore_intervals,
+            shutdown_after,
+            reboot_after,
+            force,
+        } => {
+            // Cron jobs and pipes get the plain output --no-tui would give
+            // rather than failing to set up the terminal
+            let no_tui = no_tui
+                || cli.a11y
+                || (!cli.quiet
+                    && tui::unavailable()
+                        .inspect(|reason| {
+                            eprintln!("Not starting the TUI: {reason}. Running as with --no-tui.")
+                        })
+                        .is_some());
+            let options = RunOptions {
+                verbosity: if cli.quiet {
+                    -1
+                } else {
+                    cli.verbose.min(i8::MAX as u8) as i8
+                },
+                noninteractive: no_tui || scheduled || cli.quiet,
+                hold_os: (no_tui || scheduled || cli.quiet) && !include_os,
+                allow_unverified,
+                offline,
+                full_upgrade,
+                job,
+                limit_rate,
+                accept_new_managers,
+                ignore_intervals,
+                report_only: cli.report_only,
+                power: if shutdown_after {
+                    Some(PowerAction::Shutdown)
+                } else if reboot_after {
+                    Some(PowerAction::Reboot)
+                } else {
+                    None
+                },
+                power_force: force,
+                ..RunOptions::default()
+            };
+            upgrade(
+                selective, no_tui, notify, scheduled, cli.quiet, cli.local, options,
+            )
+            .await?;
+        }
+        Commands::List { stats, interactive } => {
+            let unavailable = if interactive && !cli.a11y {
+                tui::unavailable()
+            } else {
+                None
+            };
+            if let Some(reason) = &unavailable {
+                eprintln!("Not starting the interactive list: {reason}.");
+            }
+            if interactive && !cli.a11y && unavailable.is_none() {
+                let config = load_config_or_exit(cli.local).await;
+                list_tui::run(&config, &config_path(cli.local).await?).await?;
+            } else {
+                list_managers(cli.local, stats).await?;
+            }
+        }
+        Commands::Status { format } => {
+            let config = load_config_or_exit(cli.local).await;
+            status::print_status(&config, cli.local, format).await;
+        }
+        Commands::Check {
+            force,
+            no_refresh,
+            notify,
         } => {
-            upgrade(selective, no_tui, notify).await?;
+            let config = load_config_or_exit(cli.local).await;
+            let failures =
+                check::run_check(&config, force, !no_refresh, notify, cli.quiet, cli.local).await?;
+            if failures > 0 {
+                std::process::exit(1);
+            }
         }
-        Commands::List => {
-            list_managers().await?;
+        Commands::Prefetch {
+            limit_rate,
+            scheduled,
+        } => {
+            let _run_lock = if scheduled {
+                schedule::lock_scheduled_run().await
+            } else {
+                None
+            };
+            let config = load_config_or_exit(cli.local).await;
+            if cli.report_only || config.report_only {
+                if check::run_report(&config, cli.quiet, cli.local).await? > 0 {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            // Like scheduled upgrades, stay out of blackout windows
+            if scheduled {
+                schedule::apply_jitter(&config.auto_update).await;
+                if !schedule::wait_until_clear(&config.auto_update, false).await {
+                    return Ok(());
+                }
+            }
+            let limit_rate = execute::resolve_limit_rate(limit_rate, &config.bandwidth_limit)?;
+            let _awake = if config.prevent_sleep {
+                inhibit::SleepInhibitor::start("Downloading package updates").await
+            } else {
+                None
+            };
+            if prefetch::run_prefetch(&config, cli.quiet, limit_rate, cli.local).await? > 0 {
+                std::process::exit(1);
+            }
+        }
+        Commands::Audit { fixable } => {
+            let config = load_config_or_exit(cli.local).await;
+            if audit::run_audit(&config, fixable).await? > 0 {
+                std::process::exit(1);
+            }
+        }
+        Commands::Serve { addr } => {
+            let config = load_config_or_exit(cli.local).await;
+            serve::run_server(config, addr).await?;
+        }
+        Commands::Stats => {
+            let configured = configured_manager_names(cli.local).await;
+            stats::print_stats(configured.as_ref()).await;
+        }
+        Commands::History { limit, action } => {
+            let configured = configured_manager_names(cli.local).await;
+            match action {
+                Some(HistoryAction::Show { run }) => {
+                    history::show_run(run, configured.as_ref()).await?
+                }
+                Some(HistoryAction::Diff { older, newer }) => {
+                    history::diff_runs(older, newer, configured.as_ref()).await?
+                }
+                Some(HistoryAction::Prune) => {
+                    let config = load_config_or_exit(cli.local).await;
+                    let pruned = history::prune_history(&config.history).await?;
+                    println!(
+                        "Removed {} run(s) and {} failure log(s), freeing {:.1} MB",
+                        pruned.runs,
+                        pruned.logs,
+                        pruned.bytes as f64 / (1024.0 * 1024.0)
+                    );
+                }
+                None => history::print_history(limit, configured.as_ref()).await,
+            }
+        }
+        Commands::Bench {
+            runs,
+            history,
+            last,
+            managers,
+        } => {
+            let config = load_config_or_exit(cli.local).await;
+            if history {
+                bench::run_history_bench(&config, last, &managers).await?;
+            } else if bench::run_bench(&config, runs, &managers, cli.quiet, cli.local).await? > 0 {
+                std::process::exit(1);
+            }
+        }
+        Commands::Triage => {
+            let mut config = load_config_or_exit(cli.local).await;
+            config.report_only |= cli.report_only;
+            triage::run_triage(&config).await?;
+        }
+        Commands::Bugreport { output, manager } => {
+            // The report should work even when the config doesn't load
+            if let Ok(config) = config::load_config().await {
+                redact::configure(&config.logs.redact_patterns);
+            }
+            let path = bugreport::write_report(output, manager.as_deref()).await?;
+            bugreport::print_written(&path);
+        }
+        Commands::Hold { manager, release } => {
+            holds::manage_holds(manager, release).await?;
+        }
+        Commands::Doctor => {
+            let config = load_config_or_exit(cli.local).await;
+            doctor::run_doctor(&config, cli.local).await?;
+        }
+        Commands::Remove { package, with, yes } => {
+            let config = load_config_or_exit(cli.local).await;
+            if cli.report_only || config.report_only {
+                anyhow::bail!(
+                    "Not removing {package}: packages aren't changed in report-only mode"
+                );
+            }
+            packages::run_remove(&config, &package, with.as_deref(), yes).await?;
+        }
+        Commands::Which { target } => {
+            let config = load_config_or_exit(cli.local).await;
+            packages::run_which(&config, &target).await?;
+        }
+        Commands::Init { profile } => {
+            let path = config_path(cli.local).await?;
+            profiles::run_init(&path, profile.as_deref()).await?;
+        }
+        Commands::Config { action } => {
+            let path = config_path(cli.local).await?;
+            match action {
+                ConfigAction::Get { key } => config_edit::get(&path, &key).await?,
+                ConfigAction::Set { key, value } => config_edit::set(&path, &key, &value).await?,
+                ConfigAction::Path => println!("{}", path.display()),
+                ConfigAction::Pull { url } => config_pull::run_pull(&path, url.as_deref()).await?,
+            }
+        }
+        Commands::Secret { action } => match action {
+            SecretAction::Set { name } => secrets::run_set(&name).await?,
+            SecretAction::Delete { name } => secrets::run_delete(&name).await?,
+        },
+        Commands::Man {
+            subcommand,
+            out_dir,
+        } => {
+            man::run_man(Cli::command(), subcommand.as_deref(), out_dir.as_deref())?;
         }
         Commands::Auto {
             enable,
             disable,
             status,
+            verify,
+            repair,
         } => {
-            manage_auto_update(enable, disable, status).await?;
+            if cli.local {
+                anyhow::bail!("Automatic updates are not available in --local mode");
+            }
+            auto_update::manage_auto_update(enable, disable, status, verify, repair).await?;
         }
     }
 
     Ok(())
 }
 
-async fn list_managers() -> Result<()> {
-    let config = match config::load_config().await {
-        Ok(config) => config,
+/// Load either the system or the project-local configuration and apply its
+/// redaction patterns, exiting with a hint if it cannot be read. In local mode
+/// the working directory is moved to the project root so commands run against
+/// the project.
+async fn load_config_or_exit(local: bool) -> config::Config {
+    let config = if local {
+        match config::load_local_config().await {
+            Ok((config, path)) => {
+                if let Some(project_dir) = path.parent() {
+                    if let Err(e) = std::env::set_current_dir(project_dir) {
+                        eprintln!("Error entering {}: {e}", project_dir.display());
+                        std::process::exit(1);
+                    }
+                }
+                config
+            }
+            Err(e) if e.is::<config::ParseError>() => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error loading project configuration: {e}");
+                eprintln!(
+                    "Create a {} in your project root to define project-scoped managers.",
+                    config::LOCAL_CONFIG_FILE
+                );
+                std::process::exit(1);
+            }
+        }
+    } else {
+        match config::load_config().await {
+            Ok(config) => config,
+            Err(e) => match e.downcast::<config::ParseError>() {
+                Ok(error) => defaults_or_exit(&error),
+                Err(e) => {
+                    eprintln!("Error loading configuration: {e}");
+                    eprintln!("Please ensure backbone.toml is available in the current directory or installed with the binary.");
+                    std::process::exit(1);
+                }
+            },
+        }
+    };
+    redact::configure(&config.logs.redact_patterns);
+    config
+}
+
+/// The built-in defaults in place of a config that doesn't parse, with
+/// `--defaults` or once the user agrees to it; otherwise exit. Unattended
+/// runs notify either way, so a typo doesn't quietly stop updates for good.
+fn defaults_or_exit(error: &config::ParseError) -> config::Config {
+    eprintln!("{error}\n");
+    let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+    let fallback = config::falls_back_to_defaults()
+        || (interactive && {
+            eprint!("Run with the built-in defaults this time instead? [y/N] ");
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).is_ok()
+                && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+        });
+    if !interactive {
+        let summary = error.summary();
+        let outcome = if fallback {
+            "Running with the built-in defaults until it's fixed."
+        } else {
+            "Updates are stopped until it's fixed."
+        };
+        let _ = notify::send_notification(
+            "Spine Configuration Error",
+            &format!("{summary}. {outcome}"),
+        );
+    }
+    if !fallback {
+        eprintln!("Fix it, or pass --defaults to run with the built-in defaults instead.");
+        std::process::exit(1);
+    }
+    match config::fallback_config(error) {
+        Ok((config, kept)) if kept.is_empty() => {
+            eprintln!(
+                "Running with the built-in defaults; none of your settings apply to this run."
+            );
+            config
+        }
+        Ok((config, kept)) => {
+            eprintln!(
+                "Running with the built-in defaults and your {}; none of your other settings apply to this run.",
+                kept.join(" and ")
+            );
+            config
+        }
         Err(e) => {
-            eprintln!("Error loading configuration: {e}");
-            eprintln!("Please ensure backbone.toml is available in the current directory or installed with the binary.");
+            eprintln!("{e}");
             std::process::exit(1);
         }
+    }
+}
+
+/// Names of the managers and tasks in the active configuration, or None if
+/// it can't be loaded. Recorded runs are still shown either way.
+async fn configured_manager_names(local: bool) -> Option<HashSet<String>> {
+    let config = if local {
+        config::load_local_config().await.ok()?.0
+    } else {
+        config::load_config().await.ok()?
     };
+    Some(
+        config
+            .managers
+            .into_keys()
+            .chain(config.tasks.into_keys())
+            .chain(config.repos.into_keys())
+            .collect(),
+    )
+}
+
+/// The file `spn config` and `spn list --interactive` edit: the nearest
+/// spine.toml with --local, the user's config otherwise.
+async fn config_path(local: bool) -> Result<PathBuf> {
+    if local {
+        match config::find_local_config() {
+            Some(path) => Ok(path),
+            None => anyhow::bail!(
+                "No {} found in the current directory or any parent",
+                config::LOCAL_CONFIG_FILE
+            ),
+        }
+    } else {
+        config::config_file().await
+    }
+}
+
+async fn list_managers(local: bool, show_stats: bool) -> Result<()> {
+    let config = load_config_or_exit(local).await;
+    let mut disabled: Vec<&str> = config
+        .managers
+        .iter()
+        .filter(|(_, manager)| !manager.enabled)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    disabled.sort_unstable();
 
     let managers = match detect::detect_package_managers(&config).await {
         Ok(managers) => managers,
@@ -103,107 +891,499 @@ async fn list_managers() -> Result<()> {
             "Spine checked for: {}",
             config
                 .managers
-                .keys()
-                .cloned()
+                .iter()
+                .filter(|(_, manager)| manager.enabled)
+                .map(|(name, _)| name.as_str())
                 .collect::<Vec<_>>()
                 .join(", ")
         );
+        if !disabled.is_empty() {
+            println!("Disabled: {}", disabled.join(", "));
+        }
         return Ok(());
     }
 
+    let stats = if show_stats {
+        Some(stats::load_stats().await)
+    } else {
+        None
+    };
+
     println!("Detected {} package manager(s):", managers.len());
     for manager in &managers {
-        println!("  ✓ {} ({})", manager.name, manager.config.name);
+        println!(
+            "  {}{} ({})",
+            a11y::mark("✓ "),
+            manager.name,
+            manager.config.name
+        );
         println!("    Check command: {}", manager.config.check_command);
+        if let Some(version) = &manager.version {
+            println!("    Version:       {version}");
+        }
         println!("    Requires sudo: {}", manager.config.requires_sudo);
+        if let Some(stats) = &stats {
+            match stats.managers.get(&manager.name) {
+                Some(manager_stats) => {
+                    println!("    Stats:         {}", manager_stats.summary());
+                    if let Some(reason) = &manager_stats.last_failure {
+                        println!("    Last failure:  {reason}");
+                    }
+                }
+                None => println!("    Stats:         No runs recorded yet"),
+            }
+        }
         println!();
     }
+    if !disabled.is_empty() {
+        println!("Disabled ({}): {}", disabled.len(), disabled.join(", "));
+        println!("Turn them back on with 'spn list --interactive'.");
+    }
 
     Ok(())
 }
 
-async fn upgrade(selective: bool, no_tui: bool, notify_on_complete: bool) -> Result<()> {
+async fn upgrade(
+    selective: bool,
+    no_tui: bool,
+    notify_on_complete: bool,
+    scheduled: bool,
+    quiet: bool,
+    local: bool,
+    options: RunOptions,
+) -> Result<()> {
+    if quiet && selective {
+        anyhow::bail!("--selective can't be combined with --quiet");
+    }
+
+    // One scheduled run at a time, from before it waits until it's done
+    let _run_lock = if scheduled {
+        schedule::lock_scheduled_run().await
+    } else {
+        None
+    };
+
     // Load configuration with error handling
-    let config = match config::load_config().await {
-        Ok(config) => config,
-        Err(e) => {
-            eprintln!("Error loading configuration: {e}");
-            eprintln!("Please ensure backbone.toml is available in the current directory or installed with the binary.");
-            std::process::exit(1);
+    let mut config = load_config_or_exit(local).await;
+
+    // Scheduled runs first pick up the centrally managed config, if any
+    let config_url = config.auto_update.config_url.clone();
+    if scheduled && !local && !config_url.is_empty() && config::runs_on_defaults() {
+        eprintln!("Warning: not pulling the configuration from {config_url}: its config_signing_key can't be checked in a config that doesn't parse");
+    } else if scheduled && !local && !config_url.is_empty() {
+        let pulled = match config::config_file().await {
+            Ok(path) => {
+                config_pull::pull(&path, &config_url, &config.auto_update.config_signing_key).await
+            }
+            Err(e) => Err(e),
+        };
+        match pulled {
+            Ok(true) => {
+                println!("Pulled the configuration from {config_url}");
+                config = load_config_or_exit(local).await;
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Warning: could not pull the configuration from {config_url}: {e}"),
         }
+    }
+
+    let options = RunOptions {
+        exclusions: config.exclusions.packages.clone(),
+        recover: config.recovery.enabled,
+        notify: config.notify.clone(),
+        // Nothing is upgraded offline, so there's nothing to hold back
+        hold_os: options.hold_os && !config.auto_update.include_os && !options.offline,
+        limit_rate: execute::resolve_limit_rate(options.limit_rate, &config.bandwidth_limit)?,
+        ..options
     };
 
-    // Check for sudo availability if any managers require it
-    let requires_sudo = config.managers.values().any(|m| m.requires_sudo);
-    if requires_sudo {
-        match execute::check_sudo_availability().await {
-            true => {}
-            false => {
-                eprintln!("Warning: Some package managers require sudo access.");
-                eprintln!("Please ensure you have the necessary privileges or run with sudo.");
-                eprintln!("Continuing anyway - some operations may fail...\n");
+    // A job only upgrades the managers it lists
+    let job_managers = match &options.job {
+        Some(name) => match config.auto_update.jobs.get(name) {
+            Some(job) => job.managers.clone(),
+            // The job may only be missing because the config didn't parse
+            None if config::runs_on_defaults() => {
+                eprintln!(
+                    "Warning: no job '{name}' in the configuration in use; upgrading every manager"
+                );
+                Vec::new()
             }
+            None => anyhow::bail!("No job '{name}' in [auto_update.jobs]"),
+        },
+        None => Vec::new(),
+    };
+
+    // Runs started by the scheduler are spread out by the configured jitter
+    // and wait out any blackout window, and the battery, first
+    if scheduled {
+        if let Some(dir) = auto_update::run_log_dir() {
+            if let Err(e) = auto_update::rotate_run_logs(&dir, config.auto_update.log_retention) {
+                eprintln!("Warning: could not rotate logs in {}: {e}", dir.display());
+            }
+        }
+        schedule::apply_jitter(&config.auto_update).await;
+        if !schedule::wait_until_clear(&config.auto_update, notify_on_complete).await {
+            return Ok(());
+        }
+    }
+
+    if options.report_only || config.report_only {
+        if check::run_report(&config, quiet, local).await? > 0 {
+            std::process::exit(1);
         }
+        return Ok(());
     }
 
     // Detect available package managers
-    let managers = match detect::detect_package_managers(&config).await {
+    let mut managers = match detect::detect_package_managers(&config).await {
         Ok(managers) => managers,
         Err(e) => {
             eprintln!("Error detecting package managers: {e}");
             std::process::exit(1);
         }
     };
+    match tasks::task_managers(&config) {
+        Ok(tasks) => managers.extend(tasks),
+        Err(e) => {
+            eprintln!("Error in configuration: {e}");
+            std::process::exit(1);
+        }
+    }
+    match repos::repo_managers(&config) {
+        Ok(repos) => managers.extend(repos),
+        Err(e) => {
+            eprintln!("Error in configuration: {e}");
+            std::process::exit(1);
+        }
+    }
+
+    if !job_managers.is_empty() {
+        managers.retain(|m| job_managers.contains(&m.name));
+    }
+
+    // Leave managers a distro auto-updater already handles to that service
+    if scheduled
+        && native::NativePolicy::parse(&config.auto_update.native_updaters).ok()
+            == Some(native::NativePolicy::Defer)
+    {
+        let covered = native::covered_managers(&native::detect_native_updaters());
+        managers.retain(|m| {
+            let skip = covered.contains(&m.name.as_str());
+            if skip && quiet {
+                println!("{}: SKIPPED (handled by system updates)", m.name);
+            } else if skip {
+                println!("{}", t!("upgrade-skip-native", name = m.name.as_str()));
+            }
+            !skip
+        });
+    }
+
+    // Held managers (see `spn hold`) sit out until released
+    let holds = holds::load_holds().await;
+    managers.retain(|m| {
+        let held = holds.managers.contains(&m.name);
+        if held && quiet {
+            println!("{}: SKIPPED (on hold)", m.name);
+        } else if held {
+            println!("{}", t!("upgrade-skip-held", name = m.name.as_str()));
+        }
+        !held
+    });
+
+    // Managers with a min_interval sit out until it has passed since they
+    // last succeeded
+    if !options.ignore_intervals {
+        let history = history::load_history().await;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        managers.retain(|m| {
+            let Some(spec) = &m.config.min_interval else {
+                return true;
+            };
+            let interval = match schedule::parse_interval(spec) {
+                Ok(interval) => interval,
+                Err(e) => {
+                    eprintln!("Warning: {}: min_interval: {e}", m.name);
+                    return true;
+                }
+            };
+            let Some(last) = history::last_success(&history, &m.name) else {
+                return true;
+            };
+            let elapsed = now.saturating_sub(last);
+            if elapsed >= interval.as_secs() {
+                return true;
+            }
+            let ago = stats::format_duration_secs(elapsed as f64);
+            if quiet {
+                println!("{}: SKIPPED (fresh, upgraded {ago} ago)", m.name);
+            } else {
+                println!(
+                    "{}",
+                    t!(
+                        "upgrade-skip-fresh",
+                        name = m.name.as_str(),
+                        ago = ago,
+                        interval = spec.as_str()
+                    )
+                );
+            }
+            false
+        });
+    }
+
+    // Managers that would run something new with sudo wait for the user to
+    // accept it
+    let confirm = if options.accept_new_managers {
+        trust::Confirm::Accept
+    } else if scheduled {
+        trust::Confirm::Skip
+    } else {
+        trust::Confirm::Ask
+    };
+    trust::retain_trusted(&config, &mut managers, confirm, local, quiet).await?;
+    trust::retain_user_level(&mut managers, quiet);
+
+    // Offline runs only have work for managers with a local step left
+    if options.offline {
+        managers.retain(|m| {
+            let skip = !m.config.works_offline();
+            if skip && quiet {
+                println!("{}: SKIPPED (offline)", m.name);
+            } else if skip {
+                println!("{}", t!("upgrade-skip-offline", name = m.name.as_str()));
+            }
+            !skip
+        });
+    }
+
+    // Unattended runs leave kernel and OS updates for someone to approve.
+    // Managers that can't exclude packages sit the run out while any are
+    // pending.
+    if options.hold_os {
+        let mut held = HashSet::new();
+        for manager in managers.iter().filter(|m| !m.config.os_packages.is_empty()) {
+            let pending = holds::pending_os_updates(&manager.config).await;
+            let packages = match &pending {
+                Some(pending) if pending.is_empty() => continue,
+                Some(pending) => pending.iter().cloned().collect::<Vec<_>>().join(", "),
+                None => manager.config.os_packages.join(", "),
+            };
+            let excludable =
+                manager.config.exclude_args.is_some() || manager.config.exclude_command.is_some();
+            match (excludable, quiet) {
+                (true, true) => println!("{}: HELD OS updates ({packages})", manager.name),
+                (false, true) => println!("{}: SKIPPED (OS updates held)", manager.name),
+                (true, false) => println!(
+                    "{}",
+                    t!(
+                        "upgrade-hold-os",
+                        name = manager.name.as_str(),
+                        packages = packages
+                    )
+                ),
+                (false, false) => println!(
+                    "{}",
+                    t!(
+                        "upgrade-skip-os",
+                        name = manager.name.as_str(),
+                        packages = packages
+                    )
+                ),
+            }
+            if !excludable {
+                held.insert(manager.name.clone());
+            }
+        }
+        managers.retain(|m| !held.contains(&m.name));
+    }
 
     if managers.is_empty() {
-        println!("No package managers detected on this system.");
+        println!("{}", t!("upgrade-none-detected"));
         println!(
-            "Spine checked for: {}",
-            config
-                .managers
-                .keys()
-                .cloned()
-                .collect::<Vec<_>>()
-                .join(", ")
+            "{}",
+            t!(
+                "upgrade-checked-for",
+                managers = config
+                    .managers
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
         );
         return Ok(());
     }
 
-    println!(
-        "Detected {} package manager(s): {}",
-        managers.len(),
-        managers
-            .iter()
-            .map(|m| &m.name)
-            .cloned()
-            .collect::<Vec<_>>()
-            .join(", ")
-    );
-    println!("Starting upgrade process...\n");
+    if options.limit_rate.is_some() {
+        execute::warn_rate_unsupported(&managers);
+    }
 
-    // Choose between TUI and non-TUI workflow
-    let result = if no_tui {
-        run_spinner_upgrade(managers, selective).await
+    let order = match detect::RunOrder::parse(&config.order) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("Error in configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+    let seed = detect::order_managers(&mut managers, order);
+    let started_at = std::time::SystemTime::now();
+
+    if !quiet {
+        println!(
+            "{}",
+            t!(
+                "upgrade-detected",
+                count = managers.len(),
+                managers = managers
+                    .iter()
+                    .map(|m| &m.name)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        );
+        println!("{}\n", t!("upgrade-starting"));
+    }
+
+    // Ask for the sudo password once, up front, instead of each manager
+    // failing on `sudo -n`, and keep it cached for the rest of the run
+    let _sudo = if managers.iter().any(|m| m.config.requires_sudo) {
+        let sudo = sudo::prewarm(!scheduled && io::stdin().is_terminal()).await;
+        if sudo.is_none() {
+            eprintln!("Warning: Some package managers require sudo access.");
+            eprintln!("Please ensure you have the necessary privileges or run with sudo.");
+            eprintln!("Continuing anyway - some operations may fail...\n");
+        }
+        sudo
     } else {
-        tui::run_tui(managers, config, selective).await
+        None
+    };
+
+    // A suspend in the middle of dpkg can leave packages half-configured
+    let _awake = if config.prevent_sleep {
+        inhibit::SleepInhibitor::start("Upgrading packages").await
+    } else {
+        None
+    };
+    let restarts = config.restarts.clone();
+    let offline = options.offline;
+    let system_log = config.logs.system;
+    let retention = config.history.clone();
+    let job = options.job.clone();
+    let (power_action, power_force) = (options.power, options.power_force);
+    let notify_config = config.notify.clone();
+    let reporting_config = config.reporting.clone();
+    let hooks_config = config.hooks.clone();
+    let names: Vec<String> = managers.iter().map(|m| m.name.clone()).collect();
+    for error in webhook::fire(
+        &notify_config,
+        WebhookEvent::RunStarted,
+        vec![("managers", names.clone().into())],
+    )
+    .await
+    {
+        eprintln!("Warning: {error}");
+    }
+
+    // Choose between TUI and non-TUI workflow; the TUI counts down to a
+    // shutdown or reboot itself
+    let mut counted = None;
+    let result = if quiet {
+        run_quiet_upgrade(managers, &options).await
+    } else if no_tui {
+        run_spinner_upgrade(managers, selective, &options).await
+    } else {
+        tui::run_tui(managers, config, selective, options)
+            .await
+            .map(|(finished, countdown)| {
+                counted = countdown;
+                finished
+            })
     };
 
     match result {
-        Ok(()) => {
-            println!("Upgrade process completed.");
+        Ok(finished) => {
+            if let Err(e) = stats::record_run(&finished).await {
+                eprintln!("Warning: could not save run statistics: {e}");
+            }
+            if let Err(e) = history::record_run(
+                started_at,
+                order,
+                seed,
+                &finished,
+                &retention,
+                job.as_deref(),
+            )
+            .await
+            {
+                eprintln!("Warning: could not save run history: {e}");
+            }
+            if let Err(e) = system_log::record_run(system_log, started_at, &finished).await {
+                eprintln!("Warning: could not write to the system log: {e}");
+            }
+            // Offline runs skip the upgrades, so the updates are still pending
+            if !offline {
+                if let Err(e) = outdated::clear_upgraded(&finished).await {
+                    eprintln!("Warning: could not update the pending updates cache: {e}");
+                }
+            }
+            if restarts.enabled && finished.iter().any(|m| m.status.state == RunState::Success) {
+                report_restarts(&restarts, quiet).await;
+            }
+            if !quiet {
+                println!("{}", t!("upgrade-completed"));
+            }
             if notify_on_complete {
                 let _ = notify::send_notification(
-                    "Spine Update Complete",
-                    "All package managers have been updated successfully.",
+                    &t!("notify-complete-title"),
+                    &t!("notify-complete-body"),
                 );
             }
+            let failed: Vec<&str> = finished
+                .iter()
+                .filter(|m| matches!(m.status.state, RunState::Failed | RunState::Unverified))
+                .map(|m| m.name.as_str())
+                .collect();
+            let any_failed = !failed.is_empty();
+            let fields = vec![
+                ("managers", names.into()),
+                ("failed", failed.into()),
+                (
+                    "duration_secs",
+                    started_at.elapsed().unwrap_or_default().as_secs().into(),
+                ),
+            ];
+            for error in webhook::fire(&notify_config, WebhookEvent::RunFinished, fields).await {
+                eprintln!("Warning: {error}");
+            }
+            let summary = reporting::run_summary(started_at, &finished, job.as_deref());
+            if let Err(e) = reporting::report_run(&reporting_config, &summary).await {
+                eprintln!("Warning: could not report the run: {e:#}");
+            }
+            for error in hooks::run_hooks(&hooks_config, &summary, any_failed).await {
+                eprintln!("Warning: {error}");
+            }
+            if let Some(action) = power_action {
+                power::after_run(action, &finished, power_force, counted, quiet).await;
+            }
+            // Scripts read the outcome from the exit code in quiet mode
+            if quiet
+                && finished
+                    .iter()
+                    .any(|m| matches!(m.status.state, RunState::Failed | RunState::Unverified))
+            {
+                std::process::exit(1);
+            }
         }
         Err(e) => {
-            eprintln!("Error during upgrade process: {e}");
+            eprintln!("{}", t!("upgrade-error", error = e.to_string()));
             if notify_on_complete {
                 let _ = notify::send_notification(
-                    "Spine Update Failed",
-                    "Package manager updates encountered errors.",
+                    &t!("notify-failed-title"),
+                    &t!("notify-failed-body"),
                 );
             }
             std::process::exit(1);
@@ -213,38 +1393,145 @@ async fn upgrade(selective: bool, no_tui: bool, notify_on_complete: bool) -> Res
     Ok(())
 }
 
-async fn run_spinner_upgrade(mut managers: Vec<DetectedManager>, selective: bool) -> Result<()> {
-    println!("Running package manager upgrades...\n");
+/// Restart services still running replaced libraries and report what
+/// happened below the upgrade summary.
+async fn report_restarts(config: &config::RestartsConfig, quiet: bool) {
+    let report = match restart::restart_services(config).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", t!("restart-check-failed", error = format!("{e:#}")));
+            return;
+        }
+    };
+
+    if quiet {
+        if !report.restarted.is_empty() {
+            println!("services: restarted {}", report.restarted.join(", "));
+        }
+        for (service, error) in &report.failed {
+            println!("services: FAILED restarting {service} ({error})");
+        }
+        return;
+    }
+
+    println!("\n{}", t!("restart-title"));
+    if report.restarted.is_empty() && report.failed.is_empty() && report.skipped.is_empty() {
+        println!("  {}", t!("restart-none"));
+    }
+    if !report.restarted.is_empty() {
+        println!(
+            "  {}",
+            t!("restart-restarted", services = report.restarted.join(", "))
+        );
+    }
+    for (service, error) in &report.failed {
+        println!(
+            "  {}",
+            t!(
+                "restart-failed",
+                service = service.as_str(),
+                error = error.as_str()
+            )
+        );
+    }
+    if !report.skipped.is_empty() {
+        println!(
+            "  {}",
+            t!("restart-skipped", services = report.skipped.join(", "))
+        );
+    }
+    println!();
+}
+
+/// `spn -q upgrade`: run the managers one after another and print a single
+/// line for each as it finishes.
+async fn run_quiet_upgrade(
+    mut managers: Vec<DetectedManager>,
+    options: &RunOptions,
+) -> Result<Vec<DetectedManager>> {
+    for manager in managers.iter_mut() {
+        execute_manager_workflow_simple(manager, options).await?;
+        println!("{}", quiet_summary(manager));
+    }
+    Ok(managers)
+}
+
+/// e.g. `brew: OK, 12 packages, 1m 43s`
+fn quiet_summary(manager: &DetectedManager) -> String {
+    let mut parts = vec![match manager.status.state {
+        RunState::Success => match &manager.status.attention {
+            Some(reason) => format!("NEEDS ATTENTION ({reason})"),
+            None => "OK".to_string(),
+        },
+        RunState::Failed => format!(
+            "FAILED ({})",
+            manager.status.error.as_deref().unwrap_or("unknown error")
+        ),
+        RunState::Unverified => format!(
+            "UNVERIFIED ({})",
+            manager.status.error.as_deref().unwrap_or_default()
+        ),
+        _ => "INCOMPLETE".to_string(),
+    }];
+    if let Some(packages) = manager.status.packages {
+        parts.push(match packages {
+            1 => "1 package".to_string(),
+            n => format!("{n} packages"),
+        });
+    }
+    if !manager.status.kept_back.is_empty() {
+        parts.push(format!("{} kept back", manager.status.kept_back.len()));
+    }
+    parts.extend(manager.duration_summary());
+    format!("{}: {}", manager.name, parts.join(", "))
+}
+
+async fn run_spinner_upgrade(
+    mut managers: Vec<DetectedManager>,
+    selective: bool,
+    options: &RunOptions,
+) -> Result<Vec<DetectedManager>> {
+    println!("{}\n", t!("upgrade-running"));
 
     if selective {
         // In selective mode, prompt for each manager
         let mut i = 0;
         while i < managers.len() {
-            println!("Run upgrade for {} (y/N)?", managers[i].name);
+            println!("{}", t!("upgrade-prompt", name = managers[i].name.as_str()));
             let mut input = String::new();
             io::stdin().read_line(&mut input)?;
 
             if input.trim().to_lowercase() == "y" || input.trim().to_lowercase() == "yes" {
-                run_manager_with_spinner(&mut managers[i]).await?;
+                run_manager_with_spinner(&mut managers[i], options).await?;
             } else {
-                println!("Skipping {}\n", managers[i].name);
+                println!(
+                    "{}\n",
+                    t!("upgrade-skipping", name = managers[i].name.as_str())
+                );
             }
             i += 1;
         }
     } else {
         // Run all managers sequentially
         for manager in managers.iter_mut() {
-            run_manager_with_spinner(manager).await?;
+            run_manager_with_spinner(manager, options).await?;
         }
     }
 
     // Print summary using the same function as TUI
-    print_spinner_summary(&managers);
+    summary::print_summary(&managers);
 
-    Ok(())
+    Ok(managers)
 }
 
-async fn run_manager_with_spinner(manager: &mut DetectedManager) -> Result<()> {
+async fn run_manager_with_spinner(
+    manager: &mut DetectedManager,
+    options: &RunOptions,
+) -> Result<()> {
+    if a11y::enabled() {
+        return a11y::run_manager(manager, options).await;
+    }
+
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -252,469 +1539,42 @@ async fn run_manager_with_spinner(manager: &mut DetectedManager) -> Result<()> {
             .template("{spinner:.green} {msg}")?,
     );
 
-    pb.set_message(format!("Starting {}", manager.name));
+    pb.set_message(t!("spinner-starting", name = manager.name.as_str()));
     pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
     // Execute the manager workflow
-    let result = execute_manager_workflow_simple(manager).await;
-
-    pb.finish_with_message(match &manager.status {
-        ManagerStatus::Success => format!("✓ {} completed successfully", manager.name),
-        ManagerStatus::Failed(err) => format!("✗ {} failed: {}", manager.name, err),
-        _ => format!("? {} finished with unknown status", manager.name),
-    });
-
-    println!();
-
-    result
-}
-
-fn print_spinner_summary(managers: &[DetectedManager]) {
-    let total = managers.len();
-    let successful = managers
-        .iter()
-        .filter(|m| matches!(m.status, ManagerStatus::Success))
-        .count();
-    let failed = managers
-        .iter()
-        .filter(|m| matches!(m.status, ManagerStatus::Failed(_)))
-        .count();
-
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("                           SPINE UPGRADE SUMMARY");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-
-    println!("\nOverall Results:");
-    println!("  Total Managers:    {total}");
-    println!(
-        "  ✓ Successful:      {} ({:.1}%)",
-        successful,
-        (successful as f32 / total as f32) * 100.0
-    );
-    println!(
-        "  ✗ Failed:          {} ({:.1}%)",
-        failed,
-        (failed as f32 / total as f32) * 100.0
-    );
-
-    println!("\nDetailed Results:");
-    for manager in managers {
-        match &manager.status {
-            ManagerStatus::Success => {
-                println!("  ✓ {:<20} Success", manager.name);
-            }
-            ManagerStatus::Failed(err) => {
-                println!("  ✗ {:<20} Failed", manager.name);
-                println!("    └─ Error: {err}");
-            }
-            _ => {
-                println!("  ? {:<20} Incomplete", manager.name);
-            }
-        }
-    }
-
-    if failed > 0 {
-        println!("\n⚠️  Some package managers failed to upgrade completely.");
-        println!("   Check the error details above and consider running 'spn upgrade' again.");
-        println!("   You may also need to run the failed managers manually with sudo privileges.");
-    } else if successful > 0 {
-        println!("\n🎉 All package managers upgraded successfully!");
-        println!("   Your system is now up to date.");
-    }
-
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-}
-
-async fn manage_auto_update(enable: bool, disable: bool, status_only: bool) -> Result<()> {
-    let config = config::load_config().await?;
-
-    if status_only {
-        print_auto_update_status(&config);
-        return Ok(());
-    }
-
-    if !enable && !disable {
-        print_auto_update_status(&config);
-        eprintln!("\nUse --enable or --disable to change settings");
-        eprintln!("Edit ~/.config/spine/backbone.toml to configure schedule");
-        return Ok(());
-    }
-
-    if enable {
-        enable_auto_update(&config).await?;
-    } else if disable {
-        disable_auto_update().await?;
-    }
-
-    Ok(())
-}
-
-fn print_auto_update_status(config: &config::Config) {
-    println!("Auto-Update Status:");
-    println!(
-        "  Enabled:      {}",
-        if config.auto_update.enabled {
-            "✓ Yes"
-        } else {
-            "✗ No"
-        }
-    );
-    println!("  Schedule:     {}", config.auto_update.schedule);
-
-    if config.auto_update.schedule == "daily" {
-        println!("  Time:         {}", config.auto_update.time);
-    } else {
-        println!("  Day:          {}", config.auto_update.day);
-        println!("  Time:         18:00");
-    }
-
-    println!(
-        "  Notifications: {}",
-        if config.auto_update.notify {
-            "✓ Enabled"
-        } else {
-            "✗ Disabled"
-        }
-    );
-    println!(
-        "  Mode:         {}",
-        if config.auto_update.no_tui {
-            "Background"
-        } else {
-            "Interactive"
-        }
-    );
-}
-
-async fn enable_auto_update(config: &config::Config) -> Result<()> {
-    let binary_path = std::env::current_exe()?;
-
-    if config.auto_update.schedule == "daily" {
-        setup_daily_auto_update(
-            &config.auto_update.time,
-            &binary_path,
-            config.auto_update.notify,
-        )?;
-        println!(
-            "✓ Enabled automatic daily updates at {}",
-            config.auto_update.time
-        );
-    } else {
-        setup_weekly_auto_update(
-            &config.auto_update.day,
-            &binary_path,
-            config.auto_update.notify,
-        )?;
-        println!(
-            "✓ Enabled automatic weekly updates on {}",
-            config.auto_update.day
-        );
-    }
-
-    println!("\nUpdates will run in the background.");
-    if config.auto_update.notify {
-        println!("You'll receive a notification when complete.");
-    }
-
-    Ok(())
-}
-
-async fn disable_auto_update() -> Result<()> {
-    remove_auto_update_schedule()?;
-    println!("✓ Disabled automatic updates");
-    Ok(())
-}
-
-#[cfg(target_os = "macos")]
-fn setup_daily_auto_update(time: &str, binary_path: &std::path::Path, notify: bool) -> Result<()> {
-    use std::env;
-    use std::fs;
-
-    let parts: Vec<&str> = time.split(':').collect();
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid time format. Use HH:MM (e.g., 18:00)");
-    }
-
-    let hour = parts[0];
-    let minute = parts[1];
-
-    let notify_flag = if notify { " --notify" } else { "" };
-    let binary_path_str = binary_path.to_string_lossy();
-
-    let plist_content = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-    <key>Label</key>
-    <string>com.spine.auto-update</string>
-    <key>ProgramArguments</key>
-    <array>
-        <string>{binary_path_str}</string>
-        <string>upgrade</string>
-        <string>--no-tui</string>{notify_flag}
-    </array>
-    <key>StartCalendarInterval</key>
-    <dict>
-        <key>Hour</key>
-        <integer>{hour}</integer>
-        <key>Minute</key>
-        <integer>{minute}</integer>
-    </dict>
-    <key>StandardOutPath</key>
-    <string>/tmp/spine-auto-update.log</string>
-    <key>StandardErrorPath</key>
-    <string>/tmp/spine-auto-update-error.log</string>
-</dict>
-</plist>"#
-    );
-
-    let home = env::var("HOME")?;
-    let plist_path = format!("{home}/Library/LaunchAgents/com.spine.auto-update.plist");
-    fs::write(&plist_path, plist_content)?;
-
-    std::process::Command::new("launchctl")
-        .args(["load", "-w", &plist_path])
-        .output()?;
-
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-fn setup_daily_auto_update(time: &str, binary_path: &std::path::Path, notify: bool) -> Result<()> {
-    let parts: Vec<&str> = time.split(':').collect();
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid time format. Use HH:MM (e.g., 18:00)");
-    }
-
-    let hour = parts[0];
-    let minute = parts[1];
-
-    let notify_flag = if notify { " --notify" } else { "" };
-    let binary_path_str = binary_path.to_string_lossy();
-
-    let cron_entry = format!(
-        "{minute} {hour} * * * {binary_path_str} upgrade --no-tui{notify_flag} >> /tmp/spine-auto-update.log 2>&1\n"
-    );
-
-    let output = std::process::Command::new("crontab").arg("-l").output();
-
-    let mut current_crontab = if output.is_ok() {
-        String::from_utf8_lossy(&output.unwrap().stdout).to_string()
-    } else {
-        String::new()
-    };
-
-    current_crontab = current_crontab
-        .lines()
-        .filter(|line| !line.contains("spine") && !line.contains("spn"))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    if !current_crontab.is_empty() && !current_crontab.ends_with('\n') {
-        current_crontab.push('\n');
-    }
-    current_crontab.push_str(&cron_entry);
-
-    let mut child = std::process::Command::new("crontab")
-        .arg("-")
-        .stdin(std::process::Stdio::piped())
-        .spawn()?;
-
-    use std::io::Write;
-    child
-        .stdin
-        .as_mut()
-        .unwrap()
-        .write_all(current_crontab.as_bytes())?;
-    child.wait()?;
-
-    Ok(())
-}
-
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn setup_daily_auto_update(
-    _time: &str,
-    _binary_path: &std::path::Path,
-    _notify: bool,
-) -> Result<()> {
-    anyhow::bail!("Auto-update is only supported on macOS and Linux")
-}
-
-#[cfg(target_os = "macos")]
-fn setup_weekly_auto_update(day: &str, binary_path: &std::path::Path, notify: bool) -> Result<()> {
-    let weekday = match day.to_lowercase().as_str() {
-        "monday" => 1,
-        "tuesday" => 2,
-        "wednesday" => 3,
-        "thursday" => 4,
-        "friday" => 5,
-        "saturday" => 6,
-        "sunday" => 7,
-        _ => anyhow::bail!(
-            "Invalid day. Use: monday, tuesday, wednesday, thursday, friday, saturday, sunday"
+    let result = execute_manager_workflow_simple(manager, options).await;
+
+    let message = match manager.status.state {
+        RunState::Success => match &manager.status.attention {
+            Some(reason) => t!(
+                "spinner-attention",
+                name = manager.name.as_str(),
+                reason = reason.as_str()
+            ),
+            None => t!("spinner-success", name = manager.name.as_str()),
+        },
+        RunState::Failed => t!(
+            "spinner-failed",
+            name = manager.name.as_str(),
+            details = manager.failure_details()
         ),
-    };
-
-    let notify_flag = if notify { " --notify" } else { "" };
-    let binary_path_str = binary_path.to_string_lossy();
-
-    let plist_content = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-    <key>Label</key>
-    <string>com.spine.auto-update</string>
-    <key>ProgramArguments</key>
-    <array>
-        <string>{binary_path_str}</string>
-        <string>upgrade</string>
-        <string>--no-tui</string>{notify_flag}
-    </array>
-    <key>StartCalendarInterval</key>
-    <dict>
-        <key>Weekday</key>
-        <integer>{weekday}</integer>
-        <key>Hour</key>
-        <integer>18</integer>
-        <key>Minute</key>
-        <integer>0</integer>
-    </dict>
-    <key>StandardOutPath</key>
-    <string>/tmp/spine-auto-update.log</string>
-    <key>StandardErrorPath</key>
-    <string>/tmp/spine-auto-update-error.log</string>
-</dict>
-</plist>"#
-    );
-
-    use std::env;
-    use std::fs;
-    let home = env::var("HOME")?;
-    let plist_path = format!("{home}/Library/LaunchAgents/com.spine.auto-update.plist");
-    fs::write(&plist_path, plist_content)?;
-
-    std::process::Command::new("launchctl")
-        .args(["load", "-w", &plist_path])
-        .output()?;
-
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-fn setup_weekly_auto_update(day: &str, binary_path: &std::path::Path, notify: bool) -> Result<()> {
-    let weekday = match day.to_lowercase().as_str() {
-        "monday" => "1",
-        "tuesday" => "2",
-        "wednesday" => "3",
-        "thursday" => "4",
-        "friday" => "5",
-        "saturday" => "6",
-        "sunday" => "0",
-        _ => anyhow::bail!(
-            "Invalid day. Use: monday, tuesday, wednesday, thursday, friday, saturday, sunday"
+        RunState::Unverified => t!(
+            "spinner-unverified",
+            name = manager.name.as_str(),
+            details = manager.failure_details()
         ),
+        _ => t!("spinner-unknown", name = manager.name.as_str()),
     };
-
-    let notify_flag = if notify { " --notify" } else { "" };
-    let binary_path_str = binary_path.to_string_lossy();
-
-    let cron_entry = format!(
-        "0 18 * * {weekday} {binary_path_str} upgrade --no-tui{notify_flag} >> /tmp/spine-auto-update.log 2>&1\n"
-    );
-
-    let output = std::process::Command::new("crontab").arg("-l").output();
-
-    let mut current_crontab = if output.is_ok() {
-        String::from_utf8_lossy(&output.unwrap().stdout).to_string()
+    // indicatif draws nothing when stderr isn't a terminal, as in a
+    // scheduled run's log
+    if pb.is_hidden() {
+        println!("{message}");
     } else {
-        String::new()
-    };
-
-    current_crontab = current_crontab
-        .lines()
-        .filter(|line| !line.contains("spine") && !line.contains("spn"))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    if !current_crontab.is_empty() && !current_crontab.ends_with('\n') {
-        current_crontab.push('\n');
+        pb.finish_with_message(message);
     }
-    current_crontab.push_str(&cron_entry);
-
-    let mut child = std::process::Command::new("crontab")
-        .arg("-")
-        .stdin(std::process::Stdio::piped())
-        .spawn()?;
-
-    use std::io::Write;
-    child
-        .stdin
-        .as_mut()
-        .unwrap()
-        .write_all(current_crontab.as_bytes())?;
-    child.wait()?;
-
-    Ok(())
-}
-
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn setup_weekly_auto_update(
-    _day: &str,
-    _binary_path: &std::path::Path,
-    _notify: bool,
-) -> Result<()> {
-    anyhow::bail!("Auto-update is only supported on macOS and Linux")
-}
-
-#[cfg(target_os = "macos")]
-fn remove_auto_update_schedule() -> Result<()> {
-    use std::env;
-    let home = env::var("HOME")?;
-
-    let plist_path = format!("{home}/Library/LaunchAgents/com.spine.auto-update.plist");
 
-    if std::path::Path::new(&plist_path).exists() {
-        let _ = std::process::Command::new("launchctl")
-            .args(["unload", &plist_path])
-            .output();
-        let _ = std::fs::remove_file(&plist_path);
-    }
-
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-fn remove_auto_update_schedule() -> Result<()> {
-    let output = std::process::Command::new("crontab").arg("-l").output();
-
-    if output.is_ok() {
-        let current_crontab = String::from_utf8_lossy(&output.unwrap().stdout);
-        let filtered: String = current_crontab
-            .lines()
-            .filter(|line| !line.contains("spine") && !line.contains("spn"))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let mut child = std::process::Command::new("crontab")
-            .arg("-")
-            .stdin(std::process::Stdio::piped())
-            .spawn()?;
-
-        use std::io::Write;
-        child
-            .stdin
-            .as_mut()
-            .unwrap()
-            .write_all(filtered.as_bytes())?;
-        child.wait()?;
-    }
-
-    Ok(())
-}
+    println!();
 
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn remove_auto_update_schedule() -> Result<()> {
-    anyhow::bail!("Auto-update is only supported on macOS and Linux")
+    result
 }
@@ -0,0 +1,856 @@
+use crate::config::{
+    self, AutoUpdateConfig, AutoUpdateJob, Config, ExclusionsConfig, HistoryConfig, HooksConfig,
+    LogsConfig, ManagerConfig, NotifyConfig, RecoveryConfig, RepoConfig, ReportingConfig,
+    RestartsConfig, TaskConfig, TuiConfig, WebhookConfig, WslConfig,
+};
+use anyhow::{Context, Result};
+use clap_mangen::Man;
+use roff::{bold, italic, roman, Roff};
+use std::io::Write;
+use std::path::Path;
+
+/// The keys every manager needs; the rest come out as optional with their
+/// serde defaults.
+const SAMPLE_MANAGER: &str = r#"
+name = "Example"
+check_command = "example --version"
+upgrade_all = "example upgrade"
+requires_sudo = false
+"#;
+
+/// What each configuration key does, looked up as `table.key` first for keys
+/// that mean different things in different tables. Keys missing here are
+/// still listed, just without a description.
+const KEY_DOCS: &[(&str, &str)] = &[
+    (
+        "order",
+        "Order managers run in: alphabetical, priority or random.",
+    ),
+    (
+        "check_ttl_minutes",
+        "How long spn check trusts its cached pending update counts.",
+    ),
+    (
+        "detect_ttl_minutes",
+        "How long detected managers and versions are reused before probing again.",
+    ),
+    (
+        "bandwidth_limit",
+        "Download speed cap such as 500k or 2M (KB/s); empty for none.",
+    ),
+    (
+        "report_only",
+        "Only refresh and list pending updates in upgrade and prefetch runs, like --report-only.",
+    ),
+    (
+        "prevent_sleep",
+        "Keep the system awake during upgrades and prefetches with caffeinate or systemd-inhibit.",
+    ),
+    ("managers", "One [managers.NAME] table per package manager."),
+    (
+        "preset_groups",
+        "Groups of extra presets to add, e.g. [\"kubernetes\"] for Helm, krew and kubectl.",
+    ),
+    (
+        "tasks",
+        "Commands that aren't package managers, one [tasks.NAME] table each, run by spn upgrade.",
+    ),
+    ("tasks.NAME.name", "Display name; the table's name when unset."),
+    ("tasks.NAME.command", "Shell command the task runs."),
+    (
+        "tasks.NAME.when",
+        "before or after, relative to the package managers.",
+    ),
+    (
+        "tasks.NAME.requires_sudo",
+        "Run the command with sudo.",
+    ),
+    (
+        "tasks.NAME.timeout_secs",
+        "Seconds the command may run before it's killed.",
+    ),
+    (
+        "tasks.NAME.network",
+        "Needs the network, so spn upgrade --offline skips it.",
+    ),
+    (
+        "tasks.NAME.skip_if",
+        "Shell condition; the task is skipped when it exits 0.",
+    ),
+    (
+        "tasks.NAME.enabled",
+        "Set to false to skip the task while keeping its table.",
+    ),
+    (
+        "repos",
+        "Git checkouts spn upgrade fast-forwards, one [repos.NAME] table each.",
+    ),
+    ("repos.NAME.name", "Display name; the table's name when unset."),
+    (
+        "repos.NAME.path",
+        "The checkout's directory; ~ stands for the home directory.",
+    ),
+    (
+        "repos.NAME.build",
+        "Shell command run in the checkout after a pull that brought new commits.",
+    ),
+    (
+        "repos.NAME.timeout_secs",
+        "Seconds the build may run before it's killed.",
+    ),
+    (
+        "repos.NAME.enabled",
+        "Set to false to skip the repo while keeping its table.",
+    ),
+    (
+        "os",
+        "Only look for the manager on these systems, e.g. [\"linux\", \"macos\"].",
+    ),
+    (
+        "arch",
+        "Only look for the manager on these CPU architectures, e.g. [\"aarch64\"].",
+    ),
+    (
+        "distro",
+        "Only look for the manager on these distributions, matched against ID and ID_LIKE in /etc/os-release.",
+    ),
+    (
+        "[auto_update]",
+        "Background update settings used by spn auto.",
+    ),
+    (
+        "[exclusions]",
+        "Packages kept out of upgrades for every manager.",
+    ),
+    (
+        "[logs]",
+        "How much of each manager's output is kept in memory.",
+    ),
+    (
+        "[recovery]",
+        "Repairing known failures automatically and retrying the failed step once.",
+    ),
+    (
+        "[restarts]",
+        "Restarting services still on replaced libraries after upgrades (Linux).",
+    ),
+    ("[notify]", "Where runs are reported besides desktop notifications."),
+    (
+        "[history]",
+        "How many runs and how much failure output spn history keeps.",
+    ),
+    (
+        "[wsl]",
+        "Updates across the Windows boundary when spn runs inside WSL.",
+    ),
+    (
+        "[reporting]",
+        "A central server each run's results are posted to.",
+    ),
+    (
+        "[hooks]",
+        "Shell commands run when a run finishes, with its JSON summary on stdin.",
+    ),
+    ("[tui]", "How the TUI starts out."),
+    ("name", "Display name of the manager."),
+    (
+        "check_command",
+        "Command whose success means the manager is installed.",
+    ),
+    ("refresh", "Refreshes package metadata before upgrading."),
+    (
+        "refresh_needs_upgrade",
+        "The refresh only runs right before the upgrade, never for checks or prefetching.",
+    ),
+    ("self_update", "Updates the manager itself."),
+    ("upgrade_all", "Upgrades every installed package."),
+    ("cleanup", "Removes caches and leftovers after upgrading."),
+    (
+        "requires_sudo",
+        "Run the manager's commands with sudo, or doas where sudo isn't installed.",
+    ),
+    ("quiet_flags", "Flags appended with spn -q."),
+    (
+        "verbose_flags",
+        "Flags appended with spn -v, one entry per -v.",
+    ),
+    (
+        "noninteractive_env",
+        "Environment set for background runs, e.g. DEBIAN_FRONTEND.",
+    ),
+    (
+        "noninteractive_args",
+        "Arguments appended for background runs.",
+    ),
+    (
+        "stall_timeout_secs",
+        "Seconds without output before the manager counts as stalled.",
+    ),
+    ("priority", "Higher runs first with order = \"priority\"."),
+    (
+        "min_interval",
+        "Skip the manager until this long (e.g. 24h or 7d) after its last successful run.",
+    ),
+    (
+        "attention_command",
+        "Prints why the manager needs looking at first; if it prints anything, the run leaves it alone.",
+    ),
+    (
+        "allow_stdin",
+        "Let the TUI answer prompts and send typed lines to the command.",
+    ),
+    (
+        "progress_patterns",
+        "Regexes with a percent group, or current and total groups, for the progress bar.",
+    ),
+    (
+        "package_pattern",
+        "Regex matching one line per upgraded package.",
+    ),
+    (
+        "outdated_command",
+        "Prints one line per outdated package, for spn check.",
+    ),
+    (
+        "limit_rate_args",
+        "Appended in network phases to cap downloads; {kbps} is the cap in KB/s.",
+    ),
+    (
+        "prefetch",
+        "Downloads pending upgrades without installing them, for spn prefetch.",
+    ),
+    (
+        "crate_upgrade",
+        "Reinstalls {package} at {version}; the upgrade runs it for each outdated crate from cargo install --list.",
+    ),
+    (
+        "tools_command",
+        "Prints the tools to upgrade one at a time, one per line as name [installed [latest]].",
+    ),
+    (
+        "tool_upgrade",
+        "Upgrades one tool from tools_command ({package}, {version}), instead of upgrade_all.",
+    ),
+    ("workdir", "Directory the manager's commands run in."),
+    (
+        "run_as_user",
+        "Run the manager's commands as this user when spn runs as someone else, e.g. root.",
+    ),
+    (
+        "instance",
+        "Names one entry of a [[managers.NAME]] array; that manager is then NAME@instance.",
+    ),
+    (
+        "env",
+        "Environment variables set for all of the manager's commands.",
+    ),
+    (
+        "path",
+        "Directories searched first for the manager's binary and put in front of PATH.",
+    ),
+    (
+        "user_level",
+        "Installs into a home directory, so never run as root: run as run_as_user or SUDO_USER (DOAS_USER), or skip.",
+    ),
+    (
+        "remove",
+        "Removes {package} for spn remove; the name is inserted shell-quoted.",
+    ),
+    (
+        "owns_command",
+        "Exits successfully if {package} was installed with this manager.",
+    ),
+    (
+        "owns_file_command",
+        "Prints the package that installed {path}, for spn which.",
+    ),
+    (
+        "exclude_args",
+        "Added to the upgrade command for each excluded {pattern}.",
+    ),
+    (
+        "exclude_command",
+        "Holds or pins packages matching {pattern} before upgrading.",
+    ),
+    (
+        "managers.NAME.enabled",
+        "Set to false to skip the manager everywhere while keeping its table.",
+    ),
+    (
+        "enabled",
+        "Whether spn auto --enable schedules background updates.",
+    ),
+    ("schedule", "daily, weekly or interval."),
+    ("time", "Time of day for daily runs (HH:MM)."),
+    ("day", "Day of the week for weekly runs."),
+    (
+        "notify",
+        "Send a notification when a scheduled run finishes.",
+    ),
+    ("no_tui", "Run scheduled upgrades without the TUI."),
+    (
+        "blackout",
+        "Windows such as \"Mon-Fri 09:00-18:00\" that scheduled runs wait out.",
+    ),
+    (
+        "defer_on_battery",
+        "Hold scheduled runs on battery until the machine is plugged in, for up to 6 hours.",
+    ),
+    (
+        "jitter_minutes",
+        "Start scheduled runs up to this many minutes late, at random.",
+    ),
+    (
+        "interval_hours",
+        "Hours between runs for the interval schedule.",
+    ),
+    (
+        "jobs",
+        "Separate schedules for groups of managers, one [auto_update.jobs.NAME] table each.",
+    ),
+    (
+        "auto_update.jobs.NAME.managers",
+        "Managers the job upgrades; empty means all of them.",
+    ),
+    (
+        "auto_update.jobs.NAME.schedule",
+        "daily, weekly or interval; unset keys come from [auto_update].",
+    ),
+    (
+        "config_url",
+        "https:// URL scheduled runs pull this config from before upgrading.",
+    ),
+    (
+        "config_signing_key",
+        "SSH public key the pulled config's URL.sig signature must match.",
+    ),
+    (
+        "fallback_to_defaults",
+        "Scheduled runs use the built-in defaults when the config doesn't parse (adds --defaults to the job).",
+    ),
+    (
+        "run_at_load",
+        "Also run when the job loads, catching up after sleep (macOS).",
+    ),
+    (
+        "log_retention",
+        "Number of scheduled run logs kept (macOS).",
+    ),
+    (
+        "native_updaters",
+        "What to do about distro auto-updaters: warn, defer or disable.",
+    ),
+    (
+        "release_command",
+        "Undoes the holds a run placed: for each {package} exclude_command printed, or each {pattern}.",
+    ),
+    (
+        "os_packages",
+        "Kernel and OS package patterns unattended runs hold back.",
+    ),
+    ("include_os", "Let unattended runs upgrade os_packages too."),
+    (
+        "prefetch_schedule",
+        "Time of day (HH:MM) to run spn prefetch; empty for never.",
+    ),
+    (
+        "installed_command",
+        "Prints installed packages as name and version, compared before and after each run.",
+    ),
+    (
+        "signature_patterns",
+        "Regexes for signature or key failures in refresh output; a match stops the manager unless --allow-unverified.",
+    ),
+    (
+        "cask_upgrade",
+        "Homebrew: upgrades casks as a separate step; background runs skip casks that ask for a password.",
+    ),
+    (
+        "greedy_casks",
+        "Homebrew: also upgrade casks that update themselves or are versioned latest.",
+    ),
+    (
+        "kept_back_patterns",
+        "Header regexes mapped to a reason; the indented lines after them list packages the upgrade kept back.",
+    ),
+    (
+        "full_upgrade",
+        "Upgrade that may install or remove packages, run by spn upgrade --full-upgrade when packages were kept back.",
+    ),
+    (
+        "review_command",
+        "Prints packages whose build files changed since the last review; background runs leave them out.",
+    ),
+    (
+        "review_diff_command",
+        "Prints the changes to review for {package}, shown in the TUI before the upgrade builds it.",
+    ),
+    (
+        "skip_if",
+        "Shell condition per phase, e.g. { refresh = \"...\" }; the step is skipped when it exits 0.",
+    ),
+    (
+        "phases",
+        "Extra steps: [[managers.NAME.phases]] tables with name, command, timeout_secs, after, optional, network and skip_if.",
+    ),
+    (
+        "network_phases",
+        "Phases spn upgrade --offline skips; refresh, self_update, upgrade and casks by default.",
+    ),
+    (
+        "advisory_command",
+        "Prints security advisories for spn audit as package, advisory, fixed or open, then details.",
+    ),
+    (
+        "wsl.windows",
+        "Inside WSL, also upgrade Windows with winget and WSL with wsl --update.",
+    ),
+    (
+        "wsl.distros",
+        "Other WSL distros whose APT packages are upgraded through wsl.exe, as apt@DISTRO.",
+    ),
+    (
+        "on_success",
+        "Run after a run in which no manager failed.",
+    ),
+    (
+        "on_failure",
+        "Run after a run in which a manager failed.",
+    ),
+    (
+        "on_complete",
+        "Run after every run, following on_success or on_failure.",
+    ),
+    (
+        "tui.sort",
+        "Order of the TUI's manager list: config (the order managers run in), name, status (failures first), duration (longest first) or pending (most pending updates first). s cycles through them, and the TUI remembers the last one picked over this setting.",
+    ),
+    (
+        "reporting.endpoint",
+        "https:// URL each run's JSON summary is POSTed to; empty to not report.",
+    ),
+    (
+        "reporting.token",
+        "Sent as Authorization: Bearer TOKEN with each report.",
+    ),
+    (
+        "restarts.enabled",
+        "Restart outdated services after each upgrade.",
+    ),
+    (
+        "recovery.enabled",
+        "Run remedies such as dpkg --configure -a for recognised failures, then retry.",
+    ),
+    (
+        "history.keep_runs",
+        "Runs kept in the history; older ones and their failure logs are deleted after each run.",
+    ),
+    (
+        "history.keep_days",
+        "Also delete runs older than this many days; 0 keeps them regardless of age.",
+    ),
+    (
+        "history.max_mb",
+        "Total size of saved failure logs in megabytes; the oldest are deleted first.",
+    ),
+    (
+        "notify.webhooks",
+        "HTTP requests sent on run_started, manager_finished, manager_failed and run_finished.",
+    ),
+    (
+        "notify.urls",
+        "Apprise-style service URLs such as tgram://, discord:// or pover://; any apprise supports when it's installed.",
+    ),
+    (
+        "notify.url_events",
+        "Events that send a message to the URLs.",
+    ),
+    ("notify.webhooks.url", "Where the request goes."),
+    (
+        "notify.webhooks.events",
+        "Events that send it; all of them when left out.",
+    ),
+    ("notify.webhooks.method", "HTTP method."),
+    ("notify.webhooks.headers", "Extra request headers as a table."),
+    (
+        "notify.webhooks.body",
+        "Body template; {event}, {manager}, {state}, {error} and the other fields are filled in. A JSON object of every field by default.",
+    ),
+    (
+        "allow",
+        "Only restart services matching these patterns; empty allows any.",
+    ),
+    ("deny", "Never restart services matching these patterns."),
+    (
+        "max_lines",
+        "Lines of output kept in memory per manager; the rest is read back from disk.",
+    ),
+    ("max_mb", "Megabytes of output kept in memory per manager."),
+    (
+        "head_lines",
+        "Lines from the start of the output that are always kept.",
+    ),
+    (
+        "colors",
+        "Show ANSI colors from command output in the TUI logs view; c toggles them.",
+    ),
+    (
+        "wrap",
+        "Wrap long lines in the TUI logs view; W toggles it.",
+    ),
+    (
+        "line_numbers",
+        "Number lines in the TUI logs view; N toggles them.",
+    ),
+    (
+        "error_patterns",
+        "Regexes for the error lines e jumps between in the TUI logs view.",
+    ),
+    (
+        "redact_patterns",
+        "Extra regexes for secrets masked in output before it's stored, shown or sent; only a secret group is masked if there is one.",
+    ),
+    (
+        "system",
+        "Mirror runs to journald, or syslog where there's none (unified logging on macOS): off, summary, or full to include every line of output.",
+    ),
+    (
+        "packages",
+        "Shell-style patterns, e.g. \"linux-image-*\", that no manager upgrades.",
+    ),
+];
+
+/// `spn man`: print the page for spn or one of its subcommands, or write
+/// every page into `out_dir`.
+pub fn run_man(cmd: clap::Command, subcommand: Option<&str>, out_dir: Option<&Path>) -> Result<()> {
+    let mut cmd = cmd.disable_help_subcommand(true);
+    cmd.build();
+
+    if let Some(dir) = out_dir {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Couldn't create {}", dir.display()))?;
+        for sub in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+            let path = Man::new(sub.clone()).generate_to(dir)?;
+            println!("Wrote {}", path.display());
+        }
+        let path = dir.join(Man::new(cmd.clone()).get_filename());
+        let mut file = std::fs::File::create(&path)
+            .with_context(|| format!("Couldn't create {}", path.display()))?;
+        render_main_page(&cmd, &mut file)?;
+        println!("Wrote {}", path.display());
+        return Ok(());
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    match subcommand {
+        Some(name) => {
+            let sub = cmd
+                .find_subcommand(name)
+                .with_context(|| format!("Unknown subcommand '{name}'"))?;
+            Man::new(sub.clone()).render(&mut stdout)?;
+        }
+        None => render_main_page(&cmd, &mut stdout)?,
+    }
+    Ok(())
+}
+
+/// spn(1): the generated command reference plus configuration, files and
+/// environment.
+fn render_main_page(cmd: &clap::Command, out: &mut dyn Write) -> Result<()> {
+    Man::new(cmd.clone()).render(out)?;
+
+    let mut page = Roff::new();
+    render_configuration(&mut page)?;
+    render_files(&mut page);
+    render_environment(&mut page);
+
+    page.control("SH", ["SEE ALSO"]);
+    let see_also: Vec<String> = cmd
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(|sub| format!("{}-{}(1)", cmd.get_name(), sub.get_name()))
+        .collect();
+    page.text([roman(see_also.join(", "))]);
+
+    page.to_writer(out)?;
+    Ok(())
+}
+
+fn render_configuration(page: &mut Roff) -> Result<()> {
+    let sample: toml::Table = toml::from_str(SAMPLE_MANAGER)?;
+    let config_source = |manager: &toml::Table| -> Result<Config> {
+        let mut managers = toml::Table::new();
+        managers.insert("example".to_string(), manager.clone().into());
+        let mut root = toml::Table::new();
+        root.insert("managers".to_string(), managers.into());
+        Ok(toml::from_str(&root.to_string())?)
+    };
+
+    // Parsing a minimal config fills in every serde default
+    let defaults = toml::Value::try_from(
+        config_source(&sample).context("SAMPLE_MANAGER no longer parses as a manager")?,
+    )?;
+    let manager_defaults = defaults
+        .get("managers")
+        .and_then(|managers| managers.get("example"))
+        .and_then(|manager| manager.as_table())
+        .cloned()
+        .unwrap_or_default();
+    let auto_update_defaults = toml::Value::try_from(AutoUpdateConfig::default())?;
+
+    // A sample key is required if the config stops parsing without it
+    let required: Vec<&str> = sample
+        .keys()
+        .map(String::as_str)
+        .filter(|key| {
+            let mut manager = sample.clone();
+            manager.remove(*key);
+            config_source(&manager).is_err()
+        })
+        .collect();
+
+    page.control("SH", ["CONFIGURATION"]);
+    page.text([
+        roman("Managers and settings are read from "),
+        bold("backbone.toml"),
+        roman(", or "),
+        bold(config::LOCAL_CONFIG_FILE),
+        roman(" with "),
+        bold("--local"),
+        roman(". Unset keys take the defaults below."),
+    ]);
+    page.control("PP", []);
+    page.text([
+        roman("Commands may use "),
+        bold("{binary}"),
+        roman(" (the manager's program found on its PATH), "),
+        bold("{home}"),
+        roman(" (the home directory of the user the command runs as), "),
+        bold("{jobs}"),
+        roman(" (the number of CPUs) and "),
+        bold("{tmpdir}"),
+        roman(", filled in when the command runs."),
+    ]);
+    page.control("PP", []);
+    page.text([
+        roman("Notification URLs, webhooks, "),
+        bold("[reporting]"),
+        roman(" and "),
+        bold("auto_update.config_url"),
+        roman(" may use "),
+        bold("${secret:NAME}"),
+        roman(" for a token stored in the OS keyring with "),
+        bold("spn secret set NAME"),
+        roman("."),
+    ]);
+
+    let exclusions_defaults = toml::Value::try_from(ExclusionsConfig::default())?;
+    let restarts_defaults = toml::Value::try_from(RestartsConfig::default())?;
+    let logs_defaults = toml::Value::try_from(LogsConfig::default())?;
+    let recovery_defaults = toml::Value::try_from(RecoveryConfig::default())?;
+    let history_defaults = toml::Value::try_from(HistoryConfig::default())?;
+    let job_defaults = toml::Value::try_from(AutoUpdateJob::default())?;
+    let notify_defaults = toml::Value::try_from(NotifyConfig::default())?;
+    // url is required, so only the other keys have defaults
+    let mut webhook_defaults = toml::Value::try_from(toml::from_str::<WebhookConfig>("url = ''")?)?;
+    if let Some(table) = webhook_defaults.as_table_mut() {
+        table.remove("url");
+    }
+    let wsl_defaults = toml::Value::try_from(WslConfig::default())?;
+    let reporting_defaults = toml::Value::try_from(ReportingConfig::default())?;
+    let hooks_defaults = toml::Value::try_from(HooksConfig::default())?;
+    let tui_defaults = toml::Value::try_from(TuiConfig::default())?;
+    let mut task_defaults = toml::Value::try_from(toml::from_str::<TaskConfig>("command = ''")?)?;
+    if let Some(table) = task_defaults.as_table_mut() {
+        table.remove("command");
+    }
+    let mut repo_defaults = toml::Value::try_from(toml::from_str::<RepoConfig>("path = ''")?)?;
+    if let Some(table) = repo_defaults.as_table_mut() {
+        table.remove("path");
+    }
+
+    let sections: [(&str, &[&str], Option<&toml::Value>); 17] = [
+        ("Top level", config::table_keys::<Config>(), Some(&defaults)),
+        (
+            "[managers.NAME]",
+            config::table_keys::<ManagerConfig>(),
+            None,
+        ),
+        (
+            "[tasks.NAME]",
+            config::table_keys::<TaskConfig>(),
+            Some(&task_defaults),
+        ),
+        (
+            "[repos.NAME]",
+            config::table_keys::<RepoConfig>(),
+            Some(&repo_defaults),
+        ),
+        (
+            "[auto_update]",
+            config::table_keys::<AutoUpdateConfig>(),
+            Some(&auto_update_defaults),
+        ),
+        (
+            "[auto_update.jobs.NAME]",
+            config::table_keys::<AutoUpdateJob>(),
+            Some(&job_defaults),
+        ),
+        (
+            "[exclusions]",
+            config::table_keys::<ExclusionsConfig>(),
+            Some(&exclusions_defaults),
+        ),
+        (
+            "[restarts]",
+            config::table_keys::<RestartsConfig>(),
+            Some(&restarts_defaults),
+        ),
+        (
+            "[logs]",
+            config::table_keys::<LogsConfig>(),
+            Some(&logs_defaults),
+        ),
+        (
+            "[recovery]",
+            config::table_keys::<RecoveryConfig>(),
+            Some(&recovery_defaults),
+        ),
+        (
+            "[history]",
+            config::table_keys::<HistoryConfig>(),
+            Some(&history_defaults),
+        ),
+        (
+            "[notify]",
+            config::table_keys::<NotifyConfig>(),
+            Some(&notify_defaults),
+        ),
+        (
+            "[[notify.webhooks]]",
+            config::table_keys::<WebhookConfig>(),
+            Some(&webhook_defaults),
+        ),
+        (
+            "[wsl]",
+            config::table_keys::<WslConfig>(),
+            Some(&wsl_defaults),
+        ),
+        (
+            "[reporting]",
+            config::table_keys::<ReportingConfig>(),
+            Some(&reporting_defaults),
+        ),
+        (
+            "[hooks]",
+            config::table_keys::<HooksConfig>(),
+            Some(&hooks_defaults),
+        ),
+        (
+            "[tui]",
+            config::table_keys::<TuiConfig>(),
+            Some(&tui_defaults),
+        ),
+    ];
+    for (title, keys, section_defaults) in sections {
+        page.control("SS", [title]);
+        for key in keys {
+            let default = match section_defaults {
+                Some(values) => values.get(*key),
+                None => manager_defaults.get(*key),
+            };
+            // Tables at the top level are documented as [name], so their
+            // docs don't stand in for keys of the same name inside a table
+            let qualified = match title {
+                "Top level" => format!("[{key}]"),
+                _ => format!("{}.{key}", title.trim_matches(['[', ']'])),
+            };
+            let doc = |wanted: &str| {
+                KEY_DOCS
+                    .iter()
+                    .find(|(name, _)| *name == wanted)
+                    .map(|(_, doc)| *doc)
+            };
+            let mut text = vec![roman(doc(&qualified).or(doc(key)).unwrap_or_default())];
+            if section_defaults.is_none() && required.contains(key) {
+                text.push(roman(" Required."));
+            } else if let Some(default) = default.filter(|value| shows_default(value)) {
+                text.push(roman(" Default: "));
+                text.push(italic(default.to_string()));
+            }
+            page.control("TP", []);
+            page.text([bold(*key)]);
+            page.text(text);
+        }
+    }
+    Ok(())
+}
+
+/// Empty lists and tables say nothing useful as a default.
+fn shows_default(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Array(items) => !items.is_empty(),
+        toml::Value::Table(_) => false,
+        _ => true,
+    }
+}
+
+fn render_files(page: &mut Roff) {
+    page.control("SH", ["FILES"]);
+    page.text([roman(
+        "The first backbone.toml found is used, searching in this order:",
+    )]);
+    for path in [
+        "~/.config/spine/backbone.toml (~/Library/Application Support/spine on macOS)",
+        "./backbone.toml",
+        "~/.spine/backbone.toml",
+        "backbone.toml next to the spn binary",
+        "/etc/spine/backbone.toml",
+        "/usr/local/etc/spine/backbone.toml",
+    ] {
+        page.control("IP", []);
+        page.text([italic(path)]);
+    }
+    page.control("PP", []);
+    page.text([
+        roman("Run statistics, history, holds, logs and cached update counts live in "),
+        italic("~/.local/share/spine"),
+        roman(" ("),
+        italic("~/Library/Application Support/spine"),
+        roman(" on macOS)."),
+    ]);
+    page.control("PP", []);
+    page.text([
+        roman("With "),
+        bold("--system"),
+        roman(", only "),
+        italic("/etc/spine/backbone.toml"),
+        roman(" is read and the data lives in "),
+        italic("/var/lib/spine"),
+        roman("; scheduled jobs go in "),
+        italic("/etc/cron.d/spine"),
+        roman(" (root's crontab on the BSDs, "),
+        italic("/Library/LaunchDaemons"),
+        roman(" on macOS)."),
+    ]);
+}
+
+fn render_environment(page: &mut Roff) {
+    page.control("SH", ["ENVIRONMENT"]);
+    page.control("TP", []);
+    page.text([
+        bold("LC_ALL"),
+        roman(", "),
+        bold("LC_MESSAGES"),
+        roman(", "),
+        bold("LANG"),
+    ]);
+    page.text([roman(
+        "Language of messages, checked in that order. Untranslated messages are shown in English.",
+    )]);
+    page.control("TP", []);
+    page.text([bold("PAGER")]);
+    page.text([roman(
+        "Program used by spn triage to open logs. Defaults to less.",
+    )]);
+}
@@ -0,0 +1,163 @@
+use crate::execute;
+use anyhow::Result;
+
+/// A distro-native automatic update mechanism that may compete with spine's
+/// own schedule.
+#[derive(Debug, Clone, Copy)]
+pub struct NativeUpdater {
+    pub name: &'static str,
+    /// Spine managers whose packages this service already upgrades.
+    pub covers: &'static [&'static str],
+    units: &'static [&'static str],
+    detail: fn() -> bool,
+}
+
+const NATIVE_UPDATERS: &[NativeUpdater] = &[
+    NativeUpdater {
+        name: "unattended-upgrades",
+        covers: &["apt"],
+        units: &["apt-daily-upgrade.timer"],
+        detail: unattended_upgrades_configured,
+    },
+    NativeUpdater {
+        name: "dnf-automatic",
+        covers: &["dnf", "yum"],
+        units: &[
+            "dnf-automatic.timer",
+            "dnf-automatic-install.timer",
+            "dnf5-automatic.timer",
+        ],
+        detail: always,
+    },
+    NativeUpdater {
+        name: "PackageKit offline updates",
+        covers: &["apt", "dnf", "yum", "zypper"],
+        units: &["packagekit-offline-update.service"],
+        detail: packagekit_downloads_updates,
+    },
+];
+
+/// What to do about native updaters when spine schedules its own runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NativePolicy {
+    /// Leave them alone, but warn about double-scheduling.
+    Warn,
+    /// Skip managers they already cover during scheduled runs.
+    Defer,
+    /// Disable their timers when enabling spine's schedule.
+    Disable,
+}
+
+impl NativePolicy {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "warn" => Ok(Self::Warn),
+            "defer" => Ok(Self::Defer),
+            "disable" => Ok(Self::Disable),
+            _ => anyhow::bail!(
+                "Invalid native_updaters '{value}'. Use \"warn\", \"defer\" or \"disable\""
+            ),
+        }
+    }
+}
+
+/// Native updaters that are currently enabled on this system.
+pub fn detect_native_updaters() -> Vec<NativeUpdater> {
+    NATIVE_UPDATERS
+        .iter()
+        .filter(|updater| enabled_units(updater).next().is_some() && (updater.detail)())
+        .copied()
+        .collect()
+}
+
+/// Managers covered by any of `updaters`.
+pub fn covered_managers(updaters: &[NativeUpdater]) -> Vec<&'static str> {
+    let mut covered: Vec<&str> = updaters
+        .iter()
+        .flat_map(|u| u.covers.iter().copied())
+        .collect();
+    covered.sort_unstable();
+    covered.dedup();
+    covered
+}
+
+/// Stop and disable the systemd units behind `updater`.
+pub fn disable_native_updater(updater: &NativeUpdater) -> Result<()> {
+    let units: Vec<&str> = enabled_units(updater).collect();
+    let mut cmd = if execute::is_root() {
+        std::process::Command::new("systemctl")
+    } else {
+        let mut cmd = std::process::Command::new("sudo");
+        cmd.arg("systemctl");
+        cmd
+    };
+
+    let status = cmd.arg("disable").arg("--now").args(&units).status()?;
+    if !status.success() {
+        anyhow::bail!("systemctl disable {} failed", units.join(" "));
+    }
+    Ok(())
+}
+
+fn enabled_units(updater: &NativeUpdater) -> impl Iterator<Item = &'static str> {
+    updater
+        .units
+        .iter()
+        .copied()
+        .filter(|unit| unit_enabled(unit))
+}
+
+#[cfg(target_os = "linux")]
+fn unit_enabled(unit: &str) -> bool {
+    std::process::Command::new("systemctl")
+        .args(["is-enabled", "--quiet", unit])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unit_enabled(_unit: &str) -> bool {
+    false
+}
+
+fn always() -> bool {
+    true
+}
+
+/// The apt timer runs even when unattended upgrades are switched off in
+/// apt's periodic settings, so check those too.
+fn unattended_upgrades_configured() -> bool {
+    let Ok(entries) = std::fs::read_dir("/etc/apt/apt.conf.d") else {
+        return false;
+    };
+
+    let mut enabled = false;
+    let mut files: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    // apt applies these in lexical order, later files win
+    files.sort();
+    for path in files {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with("APT::Periodic::Unattended-Upgrade") {
+                enabled = !line.contains("\"0\"");
+            }
+        }
+    }
+    enabled
+}
+
+/// The offline update service is enabled by default on most desktops but only
+/// does anything when the software center downloads updates in the background.
+fn packagekit_downloads_updates() -> bool {
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.software", "download-updates"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+        .unwrap_or(false)
+}
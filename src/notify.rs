@@ -1,3 +1,4 @@
+use crate::execute::shell_quote;
 use anyhow::Result;
 use std::process::Command;
 
@@ -41,3 +42,81 @@ fn send_linux_notification(title: &str, message: &str) -> Result<()> {
 
     Ok(())
 }
+
+/// Show a notification with a button that runs `command` when clicked.
+///
+/// The notification is handed to a detached shell that waits for the click,
+/// so this returns immediately and the action still works after spine exits.
+pub fn send_action_notification(
+    title: &str,
+    message: &str,
+    action: &str,
+    command: &[String],
+) -> Result<()> {
+    let command = command
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    #[cfg(target_os = "macos")]
+    {
+        spawn_detached(MACOS_ACTION_SCRIPT, title, message, action, &command)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        spawn_detached(LINUX_ACTION_SCRIPT, title, message, action, &command)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (title, message, action, command);
+        Ok(())
+    }
+}
+
+/// $0 title, $1 message, $2 action label, $3 command. `--action` needs
+/// libnotify 0.7.10; older notify-send falls back to a plain notification.
+#[cfg(target_os = "linux")]
+const LINUX_ACTION_SCRIPT: &str = r#"
+clicked=$(notify-send --icon=system-software-update --action=run="$2" --wait -- "$0" "$1") \
+    || exec notify-send --icon=system-software-update -- "$0" "$1"
+[ "$clicked" = run ] && exec sh -c "$3"
+"#;
+
+/// $0 title, $1 message, $2 action label, $3 command. Notification Center
+/// only offers actions through terminal-notifier; without it, ask in a dialog
+/// that gives up after an hour.
+#[cfg(target_os = "macos")]
+const MACOS_ACTION_SCRIPT: &str = r#"
+if command -v terminal-notifier >/dev/null 2>&1; then
+    exec terminal-notifier -title "$0" -message "$1" -execute "$3"
+fi
+clicked=$(osascript \
+    -e 'on run argv' \
+    -e 'display dialog (item 2 of argv) with title (item 1 of argv) buttons {"Later", item 3 of argv} default button 2 giving up after 3600' \
+    -e 'end run' "$0" "$1" "$2")
+case "$clicked" in *"button returned:$2"*) exec sh -c "$3" ;; esac
+"#;
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn spawn_detached(
+    script: &str,
+    title: &str,
+    message: &str,
+    action: &str,
+    command: &str,
+) -> Result<()> {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(script)
+        .args([title, message, action, command])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // Keep it out of our process group so it outlives the terminal's signals
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    cmd.spawn()?;
+    Ok(())
+}
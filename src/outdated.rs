@@ -0,0 +1,68 @@
+use crate::config;
+use crate::detect::{DetectedManager, RunState};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Pending update counts from the last outdated check, so quick commands like
+/// `spn status` don't have to query every manager.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OutdatedCache {
+    /// Unix time of the check.
+    #[serde(default)]
+    pub checked_at: Option<u64>,
+    /// Number of outdated packages per manager.
+    #[serde(default)]
+    pub managers: BTreeMap<String, usize>,
+}
+
+impl OutdatedCache {
+    /// Whether the last check is recent enough to skip checking again.
+    pub fn is_fresh(&self, ttl_minutes: u64, now: u64) -> bool {
+        self.checked_at
+            .is_some_and(|checked_at| now.saturating_sub(checked_at) < ttl_minutes * 60)
+    }
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("outdated.toml"))
+}
+
+pub async fn load_cache() -> OutdatedCache {
+    let Some(path) = cache_path() else {
+        return OutdatedCache::default();
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => toml::from_str(&content).unwrap_or_default(),
+        Err(_) => OutdatedCache::default(),
+    }
+}
+
+pub async fn save_cache(cache: &OutdatedCache) -> Result<()> {
+    let path = cache_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(cache)?).await?;
+    Ok(())
+}
+
+/// Managers that just upgraded successfully have nothing pending any more.
+pub async fn clear_upgraded(managers: &[DetectedManager]) -> Result<()> {
+    let mut cache = load_cache().await;
+    let mut changed = false;
+    for manager in managers {
+        if manager.status.state == RunState::Success {
+            if let Some(count) = cache.managers.get_mut(&manager.name) {
+                changed |= *count != 0;
+                *count = 0;
+            }
+        }
+    }
+    if changed {
+        save_cache(&cache).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use crate::a11y;
+use crate::config::{Config, ManagerConfig};
+use crate::detect::{self, DetectedManager};
+use crate::execute::{run_captured, run_interactive, shell_quote, RunAs};
+use crate::trust;
+use anyhow::Result;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// How long one manager gets to answer an ownership query.
+const OWNS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fill in a per-package command template such as `brew uninstall {package}`.
+pub fn package_command(template: &str, package: &str) -> String {
+    template.replace("{package}", &shell_quote(package))
+}
+
+/// Fill in a per-file command template such as `pacman -Qoq {path}`.
+fn path_command(template: &str, path: &Path) -> String {
+    template.replace("{path}", &shell_quote(&path.to_string_lossy()))
+}
+
+/// Run one query per manager that has it, all at once, and return the
+/// managers whose query succeeded with what it printed, in the order given.
+async fn query(
+    managers: &[DetectedManager],
+    command: impl Fn(&ManagerConfig) -> Option<String>,
+) -> Vec<(&DetectedManager, String)> {
+    let mut queries = JoinSet::new();
+    for (index, manager) in managers.iter().enumerate() {
+        let Some(command) = command(&manager.config) else {
+            continue;
+        };
+        let config = manager.config.clone();
+        queries.spawn(async move {
+            let output = run_captured(&command, RunAs::manager_query(&config), OWNS_TIMEOUT).await;
+            (index, output)
+        });
+    }
+
+    let mut answers = Vec::new();
+    while let Some(result) = queries.join_next().await {
+        if let Ok((index, Ok(output))) = result {
+            answers.push((index, output));
+        }
+    }
+    answers.sort_unstable_by_key(|(index, _)| *index);
+    answers
+        .into_iter()
+        .map(|(index, output)| (&managers[index], output))
+        .collect()
+}
+
+/// Managers whose owns_command reports that `package` came from them, in the
+/// order given. All managers are asked at once.
+pub async fn owners<'a>(
+    managers: &'a [DetectedManager],
+    package: &str,
+) -> Vec<&'a DetectedManager> {
+    query(managers, |config| {
+        let template = config.owns_command.as_ref()?;
+        Some(package_command(template, package))
+    })
+    .await
+    .into_iter()
+    .map(|(manager, _)| manager)
+    .collect()
+}
+
+/// Managers whose owns_file_command names the package that installed `path`,
+/// with that package.
+async fn file_owners<'a>(
+    managers: &'a [DetectedManager],
+    path: &Path,
+) -> Vec<(&'a DetectedManager, String)> {
+    query(managers, |config| {
+        let template = config.owns_file_command.as_ref()?;
+        Some(path_command(template, path))
+    })
+    .await
+    .into_iter()
+    .filter_map(|(manager, output)| {
+        let package = output
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())?;
+        Some((manager, package.to_string()))
+    })
+    .collect()
+}
+
+/// `spn which`: report which managers own `target`, taken as a file when it
+/// contains a slash, otherwise as a binary on PATH and as a package name.
+pub async fn run_which(config: &Config, target: &str) -> Result<()> {
+    let path = if target.contains('/') {
+        Some(std::path::absolute(target)?)
+    } else {
+        which::which(target).ok()
+    };
+    let managers = detect::detect_package_managers(config).await?;
+
+    let (files, packages) = tokio::join!(
+        async {
+            match &path {
+                Some(path) => file_owners(&managers, path).await,
+                None => Vec::new(),
+            }
+        },
+        async {
+            if target.contains('/') {
+                Vec::new()
+            } else {
+                owners(&managers, target).await
+            }
+        }
+    );
+
+    if let Some(path) = &path {
+        if path.as_os_str() != target {
+            println!("{target} is {}", path.display());
+        }
+        for (manager, package) in &files {
+            println!(
+                "{}: package {package} from {} ({})",
+                path.display(),
+                manager.config.name,
+                manager.name
+            );
+        }
+    }
+    for manager in &packages {
+        println!(
+            "{target}: package installed with {} ({})",
+            manager.config.name, manager.name
+        );
+    }
+
+    if files.is_empty() && packages.is_empty() {
+        anyhow::bail!("No detected package manager owns {target}");
+    }
+    Ok(())
+}
+
+/// `spn remove`: find the manager that installed `package` (or use `with`)
+/// and run its remove command once the user confirms.
+pub async fn run_remove(
+    config: &Config,
+    package: &str,
+    with: Option<&str>,
+    yes: bool,
+) -> Result<()> {
+    if let Some(name) = with {
+        if !config.managers.contains_key(name) {
+            anyhow::bail!("Unknown package manager '{name}'");
+        }
+    }
+
+    let mut candidates: Vec<DetectedManager> = detect::detect_package_managers(config)
+        .await?
+        .into_iter()
+        .filter(|m| m.config.remove.is_some())
+        .filter(|m| with.is_none_or(|name| m.name == name))
+        .collect();
+    trust::retain_user_level(&mut candidates, false);
+    if candidates.is_empty() {
+        match with {
+            Some(name) => anyhow::bail!("{name} isn't installed here or has no remove command"),
+            None => anyhow::bail!("No detected package manager has a remove command"),
+        }
+    }
+
+    let owners = owners(&candidates, package).await;
+    let manager = match (with, owners.as_slice()) {
+        (Some(_), _) => {
+            let manager = &candidates[0];
+            if manager.config.owns_command.is_some() && owners.is_empty() {
+                anyhow::bail!("{package} is not installed with {}", manager.name);
+            }
+            manager
+        }
+        (None, [owner]) => *owner,
+        (None, []) => {
+            anyhow::bail!("No package manager reports owning {package}. Use --with to choose one")
+        }
+        (None, several) => anyhow::bail!(
+            "{package} is installed with several managers ({}). Use --with to choose one",
+            several
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+
+    let command = package_command(
+        manager.config.remove.as_deref().unwrap_or_default(),
+        package,
+    );
+    if owners.is_empty() {
+        println!(
+            "{} can't tell whether it installed {package}.",
+            manager.config.name
+        );
+    } else {
+        println!(
+            "{package} is installed with {} ({}).",
+            manager.config.name, manager.name
+        );
+    }
+    let run_as = RunAs::manager(&manager.config);
+    match (run_as.uses_sudo(), &manager.config.run_as_user) {
+        (true, Some(user)) => println!("Command: {command} (with sudo, as {user})"),
+        (true, None) => println!("Command: {command} (with sudo)"),
+        (false, _) => println!("Command: {command}"),
+    }
+    if !yes && !confirm("Remove it? [y/N] ")? {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let exit = run_interactive(&command, run_as).await?;
+    if !exit.success() {
+        anyhow::bail!(
+            "Removing {package} with {} failed: {}",
+            manager.name,
+            exit.describe()
+        );
+    }
+    println!(
+        "{}Removed {package} with {}",
+        a11y::mark("✓ "),
+        manager.name
+    );
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question}");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,234 @@
+use crate::detect::{DetectedManager, RunState};
+use crate::execute;
+use crate::i18n::t;
+use crate::sudo;
+use anyhow::Result;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// How long there is to cancel before the machine powers down.
+pub const COUNTDOWN: Duration = Duration::from_secs(60);
+
+/// What to do with the machine once an upgrade finishes
+/// (`--shutdown-after`, `--reboot-after`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    Shutdown,
+    Reboot,
+}
+
+/// How the TUI's countdown ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Countdown {
+    /// It ran out, so the action is due now.
+    Finished,
+    /// The user cancelled it.
+    Cancelled,
+}
+
+impl PowerAction {
+    /// The TUI's status line, `seconds` before the action.
+    pub fn tui_countdown(self, seconds: u64) -> String {
+        match self {
+            PowerAction::Shutdown => t!("tui-power-shutdown", seconds = seconds),
+            PowerAction::Reboot => t!("tui-power-reboot", seconds = seconds),
+        }
+    }
+
+    fn countdown(self, seconds: u64) -> String {
+        match self {
+            PowerAction::Shutdown => t!("power-shutdown-countdown", seconds = seconds),
+            PowerAction::Reboot => t!("power-reboot-countdown", seconds = seconds),
+        }
+    }
+
+    fn skipped(self, failed: &str) -> String {
+        match self {
+            PowerAction::Shutdown => t!("power-shutdown-skipped", managers = failed),
+            PowerAction::Reboot => t!("power-reboot-skipped", managers = failed),
+        }
+    }
+
+    /// The word quiet output uses.
+    fn verb(self) -> &'static str {
+        match self {
+            PowerAction::Shutdown => "shutdown",
+            PowerAction::Reboot => "reboot",
+        }
+    }
+
+    /// Commands to try in turn. systemctl goes through polkit, which lets the
+    /// active session power down without a password; `sudo -n` (or `doas
+    /// -n`) never waits for one, since nobody may be around to type it.
+    fn commands(self) -> Vec<String> {
+        let mut commands = Vec::new();
+        if cfg!(target_os = "macos") {
+            // Asks running apps to quit the way the Apple menu does
+            commands.push(match self {
+                PowerAction::Shutdown => {
+                    "osascript -e 'tell application \"System Events\" to shut down'"
+                }
+                PowerAction::Reboot => {
+                    "osascript -e 'tell application \"System Events\" to restart'"
+                }
+            });
+        } else if which::which("systemctl").is_ok() {
+            commands.push(match self {
+                PowerAction::Shutdown => "systemctl poweroff",
+                PowerAction::Reboot => "systemctl reboot",
+            });
+        }
+        let mut commands: Vec<String> = commands.into_iter().map(String::from).collect();
+
+        // The BSDs' shutdown -h halts without turning the machine off
+        let shutdown = match self {
+            PowerAction::Reboot => "shutdown -r now",
+            PowerAction::Shutdown if cfg!(target_os = "openbsd") => "shutdown -hp now",
+            PowerAction::Shutdown
+                if cfg!(any(
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "dragonfly"
+                )) =>
+            {
+                "shutdown -p now"
+            }
+            PowerAction::Shutdown => "shutdown -h now",
+        };
+        commands.push(match sudo::program() {
+            Some(program) if !execute::is_root() => format!("{program} -n {shutdown}"),
+            _ => shutdown.to_string(),
+        });
+        commands
+    }
+}
+
+/// Whether the run went well enough to power down: every manager finished
+/// without failing, or `force` is set and they all at least finished.
+pub fn allowed(managers: &[DetectedManager], force: bool) -> bool {
+    managers.iter().all(|m| m.status.is_finished()) && (force || failed(managers).is_empty())
+}
+
+fn failed(managers: &[DetectedManager]) -> Vec<&str> {
+    managers
+        .iter()
+        .filter(|m| matches!(m.status.state, RunState::Failed | RunState::Unverified))
+        .map(|m| m.name.as_str())
+        .collect()
+}
+
+/// Shut down or reboot after an upgrade, once the countdown runs out. The
+/// TUI counts down itself and passes how that ended as `counted`; otherwise
+/// the countdown runs here and Enter or Ctrl+C cancels it.
+pub async fn after_run(
+    action: PowerAction,
+    managers: &[DetectedManager],
+    force: bool,
+    counted: Option<Countdown>,
+    quiet: bool,
+) {
+    if !managers.iter().all(|m| m.status.is_finished()) || counted == Some(Countdown::Cancelled) {
+        if quiet {
+            println!("{}: cancelled", action.verb());
+        } else {
+            println!("{}", t!("power-cancelled"));
+        }
+        return;
+    }
+    if !allowed(managers, force) {
+        let failed = failed(managers).join(", ");
+        if quiet {
+            println!("{}: SKIPPED ({failed} failed)", action.verb());
+        } else {
+            println!("{}", action.skipped(&failed));
+        }
+        return;
+    }
+
+    if counted.is_none() && !count_down(action, quiet).await {
+        if quiet {
+            println!("{}: cancelled", action.verb());
+        } else {
+            println!("{}", t!("power-cancelled"));
+        }
+        return;
+    }
+
+    if let Err(e) = perform(action).await {
+        if quiet {
+            println!("{}: FAILED ({e})", action.verb());
+        } else {
+            eprintln!("{}", t!("power-failed", error = e.to_string()));
+        }
+    }
+}
+
+/// Count down on the terminal, returning false when it was cancelled.
+async fn count_down(action: PowerAction, quiet: bool) -> bool {
+    let deadline = tokio::time::Instant::now() + COUNTDOWN;
+    if quiet {
+        println!(
+            "{}: in {}s (Enter or Ctrl+C cancels)",
+            action.verb(),
+            COUNTDOWN.as_secs()
+        );
+    }
+
+    // Without a terminal there's nobody to press Enter, and stdin may be
+    // closed, which would read as a cancel. The read is on its own thread
+    // since tokio's stdin would hold up exiting until a line came in.
+    let (enter_tx, enter_rx) = oneshot::channel();
+    if std::io::stdin().is_terminal() {
+        std::thread::spawn(move || {
+            let mut line = String::new();
+            if std::io::stdin()
+                .read_line(&mut line)
+                .is_ok_and(|read| read > 0)
+            {
+                let _ = enter_tx.send(());
+            }
+        });
+    }
+    let enter = async {
+        if enter_rx.await.is_err() {
+            std::future::pending().await
+        }
+    };
+    let ticks = async {
+        loop {
+            let left = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            if !quiet {
+                print!("\r{} ", action.countdown(left.as_secs_f64().ceil() as u64));
+                let _ = std::io::stdout().flush();
+            }
+            tokio::time::sleep(left.min(Duration::from_secs(1))).await;
+        }
+    };
+
+    let finished = tokio::select! {
+        () = ticks => true,
+        () = enter => false,
+        _ = tokio::signal::ctrl_c() => false,
+    };
+    if !quiet {
+        println!();
+    }
+    finished
+}
+
+/// Shut down or reboot now, trying each way in turn.
+async fn perform(action: PowerAction) -> Result<()> {
+    let mut last_error = anyhow::anyhow!("no shutdown command is available");
+    for command in action.commands() {
+        match execute::run_interactive(&command, false).await {
+            Ok(reason) if reason.success() => return Ok(()),
+            Ok(reason) => last_error = anyhow::anyhow!("{command} {}", reason.describe()),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
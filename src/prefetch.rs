@@ -0,0 +1,86 @@
+use crate::config::{Config, ManagerConfig};
+use crate::detect;
+use crate::execute::{self, run_captured, RunAs};
+use crate::holds;
+use crate::stats::format_duration_secs;
+use crate::trust::{self, Confirm};
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(300);
+const PREFETCH_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// `spn prefetch`: refresh each manager's metadata and download the packages
+/// its upgrade would install, without installing them, at up to `limit_rate`
+/// KB/s. Sudo managers that are new or changed in the config are skipped
+/// until `spn upgrade` accepts them. Returns the number of managers whose
+/// download failed.
+pub async fn run_prefetch(
+    config: &Config,
+    quiet: bool,
+    limit_rate: Option<u64>,
+    local: bool,
+) -> Result<usize> {
+    let holds = holds::load_holds().await;
+    let mut managers: Vec<_> = detect::detect_package_managers(config)
+        .await?
+        .into_iter()
+        .filter(|m| m.config.prefetch.is_some() && !holds.managers.contains(&m.name))
+        .collect();
+    trust::retain_trusted(config, &mut managers, Confirm::Skip, local, quiet).await?;
+    trust::retain_user_level(&mut managers, quiet);
+    if managers.is_empty() {
+        if !quiet {
+            println!("No detected package manager has a prefetch command configured.");
+        }
+        return Ok(0);
+    }
+    if limit_rate.is_some() {
+        execute::warn_rate_unsupported(&managers);
+    }
+
+    // Each manager downloads into its own cache, so they can all go at once
+    let mut downloads = JoinSet::new();
+    for manager in managers {
+        downloads.spawn(async move {
+            let started = Instant::now();
+            let result = prefetch(&manager.config, limit_rate).await;
+            (manager.name, result, started.elapsed())
+        });
+    }
+
+    let mut failures = 0;
+    while let Some(joined) = downloads.join_next().await {
+        let (name, result, elapsed) = joined?;
+        match result {
+            Ok(()) if !quiet => println!(
+                "{name}: downloaded in {}",
+                format_duration_secs(elapsed.as_secs_f64())
+            ),
+            Ok(()) => {}
+            Err(e) => {
+                eprintln!("{name}: prefetch failed: {e}");
+                failures += 1;
+            }
+        }
+    }
+    Ok(failures)
+}
+
+/// Refresh the manager's metadata so it sees the latest versions, then run
+/// its prefetch command.
+async fn prefetch(config: &ManagerConfig, limit_rate: Option<u64>) -> Result<()> {
+    let limited = |command: &str| match limit_rate {
+        Some(kbps) => execute::limit_rate(command, config, kbps),
+        None => command.to_string(),
+    };
+    if let Some(refresh) = config.standalone_refresh() {
+        run_captured(&limited(refresh), RunAs::manager(config), REFRESH_TIMEOUT).await?;
+    }
+    let Some(command) = &config.prefetch else {
+        return Ok(());
+    };
+    run_captured(&limited(command), RunAs::manager(config), PREFETCH_TIMEOUT).await?;
+    Ok(())
+}
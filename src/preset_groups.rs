@@ -0,0 +1,42 @@
+use crate::config::{Config, ManagerConfig};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Sets of managers that are only worth having together, each switched on
+/// by adding its name to `preset_groups` rather than left in backbone.toml
+/// for everyone.
+const GROUPS: &[(&str, &str)] = &[("kubernetes", include_str!("../presets/kubernetes.toml"))];
+
+#[derive(Deserialize)]
+struct Group {
+    managers: HashMap<String, ManagerConfig>,
+}
+
+/// Add the managers of each group named in `preset_groups`. Managers
+/// defined in the config under the same names take precedence.
+pub fn add_managers(config: &mut Config) {
+    for name in &config.preset_groups {
+        let Some((_, definitions)) = GROUPS.iter().find(|(group, _)| group == name) else {
+            eprintln!(
+                "Warning: unknown preset group {name} (available: {})",
+                names().join(", ")
+            );
+            continue;
+        };
+        let group: Group = match toml::from_str(definitions) {
+            Ok(group) => group,
+            Err(e) => {
+                eprintln!("Warning: could not add the {name} preset group: {e}");
+                continue;
+            }
+        };
+        for (key, manager) in group.managers {
+            config.managers.entry(key).or_insert(manager);
+        }
+    }
+}
+
+/// The groups `preset_groups` can name.
+fn names() -> Vec<&'static str> {
+    GROUPS.iter().map(|(name, _)| *name).collect()
+}
@@ -0,0 +1,77 @@
+use crate::config;
+use crate::config_edit;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Sets of settings `spn init --profile` writes into the config, so the
+/// usual setup for a kind of machine doesn't have to be put together by
+/// hand.
+const PROFILES: &[(&str, &str)] = &[("laptop", include_str!("../presets/laptop.toml"))];
+
+/// The profiles `spn init --profile` accepts.
+pub fn names() -> Vec<&'static str> {
+    PROFILES.iter().map(|(name, _)| *name).collect()
+}
+
+/// `spn init`: create the config if there isn't one yet, then apply
+/// `profile` to it, printing each setting it changed.
+pub async fn run_init(path: &Path, profile: Option<&str>) -> Result<()> {
+    let Some(profile) = profile else {
+        println!("Configuration: {}", path.display());
+        return Ok(());
+    };
+    let (_, settings) = PROFILES
+        .iter()
+        .find(|(name, _)| *name == profile)
+        .with_context(|| format!("No profile named {profile}"))?;
+    let settings: toml::Table = toml::from_str(settings)?;
+
+    let mut leaves = Vec::new();
+    collect_leaves("", &settings, &mut leaves);
+    println!("Applying the {profile} profile to {}:", path.display());
+    for (key, value) in leaves {
+        let value = match (value, current(path, &key).await?) {
+            (toml::Value::Array(added), Some(toml::Value::Array(mut existing))) => {
+                for entry in added {
+                    if !existing.contains(&entry) {
+                        existing.push(entry);
+                    }
+                }
+                toml::Value::Array(existing)
+            }
+            (value, _) => value,
+        };
+        let written = config_edit::set_value(path, &key, &value.to_string()).await?;
+        println!("  {key} = {written}");
+    }
+    println!("Run 'spn auto --enable' to schedule updates with these settings.");
+    Ok(())
+}
+
+/// Every value in `table` with its dotted key, e.g. `auto_update.blackout`.
+fn collect_leaves(prefix: &str, table: &toml::Table, leaves: &mut Vec<(String, toml::Value)>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        match value {
+            toml::Value::Table(table) => collect_leaves(&key, table, leaves),
+            value => leaves.push((key, value.clone())),
+        }
+    }
+}
+
+/// The value in effect for `key`, including defaults the file leaves out.
+async fn current(path: &Path, key: &str) -> Result<Option<toml::Value>> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Couldn't read {}", path.display()))?;
+    let config = config::parse_config(&content, path)?;
+    let effective = toml::Value::try_from(config)?;
+    Ok(key
+        .split('.')
+        .try_fold(&effective, |value, part| value.get(part))
+        .cloned())
+}
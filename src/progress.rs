@@ -0,0 +1,80 @@
+use crate::config::ManagerConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Extracts a completion ratio from a manager's output using the patterns in
+/// its `progress_patterns`.
+///
+/// A pattern reports progress through named groups: either `percent`
+/// (e.g. `(?P<percent>\d+)%`) or `current` and `total`
+/// (e.g. `\((?P<current>\d+)/(?P<total>\d+)\)`).
+///
+/// It also counts upgraded packages: every line matching the manager's
+/// `package_pattern` is one package.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressParser {
+    patterns: Vec<Regex>,
+    package: Option<Regex>,
+}
+
+impl ProgressParser {
+    pub fn new(config: &ManagerConfig) -> Result<Self> {
+        let patterns = config
+            .progress_patterns
+            .iter()
+            .map(|pattern| {
+                let regex = Regex::new(pattern)
+                    .with_context(|| format!("Invalid progress pattern '{pattern}'"))?;
+                let groups: Vec<&str> = regex.capture_names().flatten().collect();
+                let usable = groups.contains(&"percent")
+                    || (groups.contains(&"current") && groups.contains(&"total"));
+                if !usable {
+                    anyhow::bail!(
+                        "Progress pattern '{pattern}' needs a 'percent' group or 'current' and 'total' groups"
+                    );
+                }
+                Ok(regex)
+            })
+            .collect::<Result<_>>()?;
+        let package = config
+            .package_pattern
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).with_context(|| format!("Invalid package pattern '{pattern}'"))
+            })
+            .transpose()?;
+        Ok(Self { patterns, package })
+    }
+
+    /// Whether this manager's upgraded packages can be counted.
+    pub fn counts_packages(&self) -> bool {
+        self.package.is_some()
+    }
+
+    /// Whether `line` reports one upgraded package.
+    pub fn is_package(&self, line: &str) -> bool {
+        self.package
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(line))
+    }
+
+    /// Progress reported by `line` as a ratio between 0 and 1, from the first
+    /// pattern that matches.
+    pub fn parse(&self, line: &str) -> Option<f64> {
+        // Progress bars redraw with carriage returns; only the last frame counts
+        let line = line.rsplit('\r').find(|frame| !frame.trim().is_empty())?;
+        self.patterns.iter().find_map(|regex| {
+            let captures = regex.captures(line)?;
+            let number =
+                |name: &str| -> Option<f64> { captures.name(name)?.as_str().trim().parse().ok() };
+            let ratio = match number("percent") {
+                Some(percent) => percent / 100.0,
+                None => {
+                    let total = number("total").filter(|total| *total > 0.0)?;
+                    number("current")? / total
+                }
+            };
+            Some(ratio.clamp(0.0, 1.0))
+        })
+    }
+}
@@ -0,0 +1,99 @@
+use crate::config::ReportingConfig;
+use crate::detect::{DetectedManager, RunState};
+use crate::execute::ExitReason;
+use crate::redact::redact;
+use crate::secrets;
+use crate::webhook;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A finished run as JSON: the host and user, when it started and how long
+/// it took, and each manager's outcome, duration and phases. It's what
+/// `[reporting]` posts and `[hooks]` commands read on stdin.
+pub fn run_summary(
+    started_at: SystemTime,
+    finished: &[DetectedManager],
+    job: Option<&str>,
+) -> Value {
+    json!({
+        "host": webhook::hostname(),
+        "user": user_name(),
+        "os": std::env::consts::OS,
+        "version": env!("CARGO_PKG_VERSION"),
+        "started_at": started_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        "duration_secs": started_at.elapsed().unwrap_or_default().as_secs_f64(),
+        "job": job,
+        "managers": finished.iter().map(manager_summary).collect::<Vec<_>>(),
+    })
+}
+
+/// POST a run's summary to `[reporting]`'s endpoint. Does nothing without
+/// an endpoint.
+pub async fn report_run(config: &ReportingConfig, summary: &Value) -> Result<()> {
+    if config.endpoint.is_empty() {
+        return Ok(());
+    }
+    // The token would otherwise cross the network in the clear
+    if !config.endpoint.starts_with("https://") {
+        anyhow::bail!("reporting.endpoint must be an https:// URL");
+    }
+    let endpoint = secrets::expand(&config.endpoint).await?;
+    let token = secrets::expand(&config.token).await?;
+
+    let authorization = format!("Bearer {token}");
+    let mut headers = Vec::new();
+    if !token.is_empty() {
+        headers.push(("Authorization", authorization.as_str()));
+    }
+    webhook::request("POST", &endpoint, &headers, &summary.to_string()).await
+}
+
+fn manager_summary(manager: &DetectedManager) -> Value {
+    let status = &manager.status;
+    let outcome = match status.state {
+        RunState::Success | RunState::Failed | RunState::Unverified => status.state.label(),
+        _ => "skipped",
+    };
+    json!({
+        "name": manager.name,
+        "outcome": outcome,
+        "duration_secs": manager.duration.unwrap_or_default().as_secs_f64(),
+        "packages": status.packages,
+        // Errors can quote command output
+        "error": status.error.as_deref().map(|error| redact(error).into_owned()),
+        "phases": status.phases.iter().map(|result| json!({
+            "phase": result.phase,
+            "duration_secs": result.duration.as_secs_f64(),
+            "exit_code": match result.exit {
+                Some(ExitReason::Code(code)) => Some(code),
+                _ => None,
+            },
+            "skipped": result.skipped,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// The name of the user spn runs as.
+#[cfg(unix)]
+fn user_name() -> String {
+    // SAFETY: getpwuid returns null or a pointer to a static passwd entry,
+    // whose pw_name is NUL-terminated
+    unsafe {
+        let entry = libc::getpwuid(libc::geteuid());
+        if entry.is_null() {
+            return std::env::var("USER").unwrap_or_default();
+        }
+        std::ffi::CStr::from_ptr((*entry).pw_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+#[cfg(not(unix))]
+fn user_name() -> String {
+    std::env::var("USERNAME").unwrap_or_default()
+}
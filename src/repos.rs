@@ -0,0 +1,86 @@
+use crate::config::{Config, ExtraPhase, ManagerConfig, RepoConfig};
+use crate::detect::{DetectedManager, Phase, Stage};
+use crate::execute;
+use crate::trust;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Where the upgrade step notes the commit it pulled from, inside the
+/// checkout's git directory, so the build step can tell whether anything
+/// new came in.
+const PULLED_FROM: &str = "\"$(git rev-parse --git-path spine-pulled-from)\"";
+
+/// The enabled `[repos]`, ready to run alongside the detected managers.
+pub fn repo_managers(config: &Config) -> Result<Vec<DetectedManager>> {
+    let mut repos = Vec::new();
+    for (name, repo) in config.repos.iter().filter(|(_, repo)| repo.enabled) {
+        if config.managers.contains_key(name) || config.tasks.contains_key(name) {
+            anyhow::bail!("repo {name} has the same name as a manager or task");
+        }
+        repos.push(DetectedManager::new(
+            name.clone(),
+            manager_config(name, repo)?,
+            &config.logs,
+            Stage::Managers,
+        ));
+    }
+    Ok(repos)
+}
+
+/// A manager definition that fast-forwards the checkout in its upgrade
+/// step, then runs the build only if that brought new commits. Uncommitted
+/// changes to tracked files leave it needing attention rather than failing
+/// halfway through a pull.
+fn manager_config(name: &str, repo: &RepoConfig) -> Result<ManagerConfig> {
+    let phases: Vec<ExtraPhase> = repo
+        .build
+        .iter()
+        .map(|build| ExtraPhase {
+            name: "build".to_string(),
+            command: build.clone(),
+            timeout_secs: repo.timeout_secs,
+            after: Some(Phase::Upgrade),
+            optional: false,
+            network: false,
+            skip_if: Some(format!(
+                "[ \"$(git rev-parse HEAD)\" = \"$(cat {PULLED_FROM})\" ]"
+            )),
+        })
+        .collect();
+    serde_json::from_value(serde_json::json!({
+        "name": repo.name.as_deref().unwrap_or(name),
+        "check_command": "true",
+        "upgrade_all": format!("git rev-parse HEAD > {PULLED_FROM} && git pull --ff-only"),
+        "attention_command": "git diff --quiet HEAD -- || \
+            echo \"Uncommitted changes to $(git diff --name-only HEAD -- | paste -sd ' ' -)\"",
+        "network_phases": ["upgrade"],
+        "requires_sudo": false,
+        // Checkouts belong to a user; pulling as root would leave root-owned
+        // files in them
+        "user_level": true,
+        "workdir": expand_home(&repo.path),
+        "phases": phases,
+    }))
+    .with_context(|| format!("Invalid repo {name}"))
+}
+
+/// `path` with a leading `~` replaced by the home directory, that of the
+/// user who ran `sudo spn` when there is one, since repos run as that user.
+fn expand_home(path: &str) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => return path.to_string(),
+    };
+    let home = match trust::sudo_user() {
+        Some(user) => execute::user_home(&user),
+        None => dirs::home_dir(),
+    };
+    match home {
+        Some(home) => {
+            let mut home = home.into_os_string();
+            home.push(rest);
+            PathBuf::from(home).display().to_string()
+        }
+        None => path.to_string(),
+    }
+}
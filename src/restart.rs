@@ -0,0 +1,70 @@
+use crate::config::RestartsConfig;
+use crate::execute::{run_captured, shell_quote};
+use crate::holds::glob_match;
+use anyhow::Result;
+use std::time::Duration;
+
+/// Lists systemd services with a process that still maps a shared library
+/// deleted by an upgrade. Used when needrestart isn't installed.
+const SCAN_SCRIPT: &str = r#"for p in /proc/[0-9]*; do
+  grep -qE '\.so[.0-9]* \(deleted\)$' "$p/maps" 2>/dev/null &&
+    sed -n 's|^0::.*/\([^/]*\.service\)$|\1|p' "$p/cgroup" 2>/dev/null
+done | sort -u"#;
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(120);
+const RESTART_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What the post-upgrade restart phase did.
+#[derive(Debug, Default)]
+pub struct RestartReport {
+    pub restarted: Vec<String>,
+    /// Services that failed to restart, with the error.
+    pub failed: Vec<(String, String)>,
+    /// Services still running old libraries that allow/deny kept spine from
+    /// restarting.
+    pub skipped: Vec<String>,
+}
+
+/// Whether spine may restart `service`: it must not match `deny` and, when
+/// `allow` is set, must match one of its patterns.
+fn permitted(config: &RestartsConfig, service: &str) -> bool {
+    let listed = |patterns: &[String]| patterns.iter().any(|p| glob_match(p, service));
+    !listed(&config.deny) && (config.allow.is_empty() || listed(&config.allow))
+}
+
+/// Services running outdated libraries, from needrestart's batch mode when
+/// it is installed and a scan of /proc otherwise. Both need root to see
+/// every process, so they run through sudo.
+async fn outdated_services() -> Result<Vec<String>> {
+    if which::which("needrestart").is_ok() {
+        let output = run_captured("needrestart -b -r l", true, CHECK_TIMEOUT).await?;
+        return Ok(output
+            .lines()
+            .filter_map(|line| line.strip_prefix("NEEDRESTART-SVC:"))
+            .map(|service| service.trim().to_string())
+            .collect());
+    }
+    let output = run_captured(SCAN_SCRIPT, true, CHECK_TIMEOUT).await?;
+    Ok(output.lines().map(str::to_string).collect())
+}
+
+/// Restart the services left running outdated libraries after an upgrade.
+pub async fn restart_services(config: &RestartsConfig) -> Result<RestartReport> {
+    let mut report = RestartReport::default();
+    if !cfg!(target_os = "linux") || which::which("systemctl").is_err() {
+        return Ok(report);
+    }
+
+    for service in outdated_services().await? {
+        if !permitted(config, &service) {
+            report.skipped.push(service);
+            continue;
+        }
+        let command = format!("systemctl restart {}", shell_quote(&service));
+        match run_captured(&command, true, RESTART_TIMEOUT).await {
+            Ok(_) => report.restarted.push(service),
+            Err(e) => report.failed.push((service, e.to_string())),
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,27 @@
+use crate::execute::{run_captured, shell_quote};
+use anyhow::Result;
+use std::time::Duration;
+
+/// Checking for unseen build file changes fetches every package's git clone.
+const REVIEW_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Packages whose build files changed since they were last reviewed, one
+/// per line of the manager's review_command.
+pub async fn pending(command: &str) -> Result<Vec<String>> {
+    let output = run_captured(command, false, REVIEW_TIMEOUT).await?;
+    let mut packages: Vec<String> = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect();
+    packages.sort();
+    packages.dedup();
+    Ok(packages)
+}
+
+/// The changes to review for one package, from review_diff_command with
+/// `{package}` replaced by its quoted name.
+pub async fn diff(template: &str, package: &str) -> Result<String> {
+    let command = template.replace("{package}", &shell_quote(package));
+    run_captured(&command, false, REVIEW_TIMEOUT).await
+}
@@ -0,0 +1,378 @@
+use crate::battery;
+use crate::config::{self, AutoUpdateConfig};
+use crate::notify;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Timelike, Weekday};
+use std::fs::File;
+
+/// A recurring period during which scheduled runs must not start, written as
+/// `"Mon 09:00-18:00"`, `"Mon-Fri 09:00-18:00"` or `"22:00-06:00"` (every day).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlackoutWindow {
+    pub spec: String,
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl BlackoutWindow {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let parts: Vec<&str> = spec.split_whitespace().collect();
+        let (days, times) = match parts.as_slice() {
+            [times] => (ALL_DAYS.to_vec(), *times),
+            [days, times] => (parse_days(days)?, *times),
+            _ => anyhow::bail!("Invalid blackout window '{spec}'. Use e.g. \"Mon 09:00-18:00\""),
+        };
+
+        let Some((start, end)) = times.split_once('-') else {
+            anyhow::bail!("Invalid blackout window '{spec}': expected HH:MM-HH:MM");
+        };
+
+        Ok(Self {
+            spec: spec.to_string(),
+            days,
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        })
+    }
+
+    /// If `now` falls inside this window, return when the window ends.
+    fn end_if_active(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        // A window that wraps past midnight may have started yesterday
+        for days_back in [0, 1] {
+            let day = now.date_naive() - Duration::days(days_back);
+            if !self.days.contains(&day.weekday()) {
+                continue;
+            }
+
+            let start = to_local(day.and_time(self.start))?;
+            let end_day = if self.end <= self.start {
+                day + Duration::days(1)
+            } else {
+                day
+            };
+            let end = to_local(end_day.and_time(self.end))?;
+
+            if start <= now && now < end {
+                return Some(end);
+            }
+        }
+        None
+    }
+}
+
+const ALL_DAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+fn parse_weekday(day: &str) -> Result<Weekday> {
+    day.parse::<Weekday>()
+        .map_err(|_| anyhow::anyhow!("Invalid day '{day}'. Use Mon, Tue, ... Sun"))
+}
+
+fn parse_days(days: &str) -> Result<Vec<Weekday>> {
+    match days.split_once('-') {
+        Some((first, last)) => {
+            let mut day = parse_weekday(first)?;
+            let last = parse_weekday(last)?;
+            let mut range = vec![day];
+            while day != last {
+                day = day.succ();
+                range.push(day);
+            }
+            Ok(range)
+        }
+        None => days.split(',').map(parse_weekday).collect(),
+    }
+}
+
+pub fn parse_time(time: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| anyhow::anyhow!("Invalid time '{time}'. Use HH:MM (e.g., 18:00)"))
+}
+
+/// Parse an interval such as `90m`, `24h`, `7d` or `2w`. A bare number is in
+/// hours.
+pub fn parse_interval(interval: &str) -> Result<std::time::Duration> {
+    let interval = interval.trim();
+    let (number, unit) = match interval.char_indices().last() {
+        Some((end, unit @ ('s' | 'm' | 'h' | 'd' | 'w'))) => (&interval[..end], unit),
+        _ => (interval, 'h'),
+    };
+    let scale = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => 7 * 86400,
+    };
+    match number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|number| *number > 0)
+        .and_then(|number| number.checked_mul(scale))
+    {
+        Some(secs) => Ok(std::time::Duration::from_secs(secs)),
+        None => anyhow::bail!("Invalid interval '{interval}'. Use e.g. 90m, 24h or 7d"),
+    }
+}
+
+fn to_local(naive: chrono::NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+pub fn parse_blackouts(config: &AutoUpdateConfig) -> Result<Vec<BlackoutWindow>> {
+    config
+        .blackout
+        .iter()
+        .map(|spec| BlackoutWindow::parse(spec))
+        .collect()
+}
+
+/// Find the window covering `now` and the time at which runs may start again,
+/// following back-to-back windows.
+fn blackout_end(
+    windows: &[BlackoutWindow],
+    now: DateTime<Local>,
+) -> Option<(&BlackoutWindow, DateTime<Local>)> {
+    let (window, mut end) = windows
+        .iter()
+        .find_map(|w| w.end_if_active(now).map(|end| (w, end)))?;
+
+    // Bounded so overlapping windows covering the whole week can't loop forever
+    for _ in 0..windows.len() * 8 {
+        match windows.iter().find_map(|w| w.end_if_active(end)) {
+            Some(next_end) if next_end > end => end = next_end,
+            _ => break,
+        }
+    }
+
+    Some((window, end))
+}
+
+/// When the installed schedule fires next after `now`, pushed past any
+/// blackout window it would start in. None for macOS interval schedules,
+/// which count from when launchd loaded the job.
+pub fn next_run(
+    config: &AutoUpdateConfig,
+    now: DateTime<Local>,
+) -> Result<Option<DateTime<Local>>> {
+    let next = match config.schedule.as_str() {
+        "daily" => next_at(now, parse_time(&config.time)?, |_| true),
+        "interval" if cfg!(target_os = "macos") => return Ok(None),
+        "interval" => {
+            // cron's `0 */N`: on the hour, every hour divisible by N
+            let hours = config.interval_hours.max(1);
+            (1..=24)
+                .map(|offset| now + Duration::hours(offset))
+                .filter_map(|t| to_local(t.date_naive().and_hms_opt(t.hour(), 0, 0)?))
+                .find(|t| *t > now && t.hour() % hours == 0)
+        }
+        _ => {
+            let day = parse_weekday(&config.day)?;
+            next_at(
+                now,
+                NaiveTime::from_hms_opt(18, 0, 0).unwrap_or_default(),
+                |d| d == day,
+            )
+        }
+    };
+
+    let windows = parse_blackouts(config)?;
+    Ok(next.map(|next| blackout_end(&windows, next).map_or(next, |(_, end)| end)))
+}
+
+/// Time left until a run, to the minute: `2d 4h`, `3h 12m` or `12m`.
+pub fn format_countdown(left: Duration) -> String {
+    let minutes = (left.num_seconds().max(0) + 59) / 60;
+    match (minutes / 1440, minutes / 60 % 24, minutes % 60) {
+        (0, 0, minutes) => format!("{minutes}m"),
+        (0, hours, minutes) => format!("{hours}h {minutes}m"),
+        (days, hours, _) => format!("{days}d {hours}h"),
+    }
+}
+
+/// The first `time` after `now` on a day accepted by `on_day`.
+fn next_at(
+    now: DateTime<Local>,
+    time: NaiveTime,
+    on_day: impl Fn(Weekday) -> bool,
+) -> Option<DateTime<Local>> {
+    (0..=7)
+        .map(|offset| now.date_naive() + Duration::days(offset))
+        .filter(|day| on_day(day.weekday()))
+        .filter_map(|day| to_local(day.and_time(time)))
+        .find(|t| *t > now)
+}
+
+/// Delay a scheduled run by a random offset of up to `jitter_minutes`, so a
+/// fleet of machines sharing a schedule doesn't hit mirrors all at once.
+pub async fn apply_jitter(config: &AutoUpdateConfig) {
+    if config.jitter_minutes == 0 {
+        return;
+    }
+
+    let delay_secs = fastrand::u64(0..=u64::from(config.jitter_minutes) * 60);
+    let start_at = Local::now() + Duration::seconds(delay_secs as i64);
+    log_event(&format!(
+        "Applying schedule jitter: starting at {} ({}m {:02}s from now)",
+        start_at.format("%H:%M:%S"),
+        delay_secs / 60,
+        delay_secs % 60
+    ));
+    tokio::time::sleep(std::time::Duration::from_secs(delay_secs)).await;
+}
+
+/// Defer a scheduled run until any active blackout window has passed,
+/// logging and optionally notifying about the new start time.
+async fn wait_for_blackouts(config: &AutoUpdateConfig, notify_on_defer: bool) -> Result<()> {
+    let windows = parse_blackouts(config)?;
+    let Some((window, resume_at)) = blackout_end(&windows, Local::now()) else {
+        return Ok(());
+    };
+
+    let message = format!(
+        "Scheduled update deferred: blackout window \"{}\" is active. Will run at {}.",
+        window.spec,
+        resume_at.format("%a %H:%M")
+    );
+    log_event(&message);
+    if notify_on_defer {
+        let _ = notify::send_notification("Spine Update Deferred", &message);
+    }
+
+    let wait = (resume_at - Local::now()).to_std().unwrap_or_default();
+    tokio::time::sleep(wait).await;
+
+    log_event("Blackout window ended, starting deferred update");
+    Ok(())
+}
+
+/// How often a run held for battery power checks for a charger.
+const BATTERY_POLL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// How long a run waits for a charger before leaving it to the next
+/// scheduled one.
+const BATTERY_WAIT_LIMIT: std::time::Duration = std::time::Duration::from_secs(6 * 3600);
+
+/// With `defer_on_battery`, hold a scheduled run until the machine is
+/// plugged in. False when it still isn't after BATTERY_WAIT_LIMIT, in which
+/// case the run should be skipped.
+async fn wait_for_ac_power(config: &AutoUpdateConfig, notify_on_defer: bool) -> bool {
+    if !config.defer_on_battery || !battery::on_battery() {
+        return true;
+    }
+
+    let message = "Scheduled update deferred: running on battery. Will run once plugged in.";
+    log_event(message);
+    if notify_on_defer {
+        let _ = notify::send_notification("Spine Update Deferred", message);
+    }
+
+    let started = std::time::Instant::now();
+    while started.elapsed() < BATTERY_WAIT_LIMIT {
+        tokio::time::sleep(BATTERY_POLL).await;
+        if !battery::on_battery() {
+            log_event("On AC power, starting deferred update");
+            return true;
+        }
+    }
+    log_event("Still on battery; skipping this scheduled update");
+    false
+}
+
+/// Hold a scheduled run until it's outside every blackout window and, with
+/// `defer_on_battery`, on AC power, checking both again after each wait: a
+/// blackout can start while the run waits for a charger. False when the run
+/// should be skipped.
+pub async fn wait_until_clear(config: &AutoUpdateConfig, notify_on_defer: bool) -> bool {
+    loop {
+        if let Err(e) = wait_for_blackouts(config, notify_on_defer).await {
+            eprintln!("Warning: ignoring blackout windows: {e}");
+        }
+        if !wait_for_ac_power(config, notify_on_defer).await {
+            return false;
+        }
+        match parse_blackouts(config) {
+            Ok(windows) if blackout_end(&windows, Local::now()).is_some() => continue,
+            _ => return true,
+        }
+    }
+}
+
+/// Held for the whole of a scheduled upgrade or prefetch, waits included,
+/// so runs whose schedules overlap take turns instead of running managers
+/// at the same time. Released when dropped.
+pub struct RunLock {
+    _file: File,
+}
+
+/// Take the scheduled-run lock in the data directory, waiting for the run
+/// that holds it to finish first. None, after a warning, when it can't be
+/// taken at all.
+pub async fn lock_scheduled_run() -> Option<RunLock> {
+    let path = config::data_dir()?.join("scheduled-run.lock");
+    let locked = async {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        if lock(&file, false)? {
+            return Ok(file);
+        }
+        log_event("Another scheduled run is in progress; waiting for it to finish");
+        tokio::task::spawn_blocking(move || lock(&file, true).map(|_| file)).await?
+    };
+    match locked.await {
+        Ok(file) => Some(RunLock { _file: file }),
+        Err(e) => {
+            eprintln!("Warning: couldn't lock {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Take an exclusive lock on `file`, blocking until it's free if `wait`.
+/// False when it's held elsewhere and `wait` isn't set.
+#[cfg(unix)]
+fn lock(file: &File, wait: bool) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+    let operation = if wait {
+        libc::LOCK_EX
+    } else {
+        libc::LOCK_EX | libc::LOCK_NB
+    };
+    loop {
+        // SAFETY: the descriptor stays open for as long as `file` lives
+        if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+            return Ok(true);
+        }
+        let error = std::io::Error::last_os_error();
+        match error.kind() {
+            std::io::ErrorKind::Interrupted => continue,
+            std::io::ErrorKind::WouldBlock => return Ok(false),
+            _ => return Err(error),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn lock(_file: &File, _wait: bool) -> std::io::Result<bool> {
+    Ok(true)
+}
+
+/// Timestamped line for the scheduled-run log.
+fn log_event(message: &str) {
+    println!("[{}] {message}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+}
@@ -0,0 +1,222 @@
+use crate::redact;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::Stdio;
+use std::sync::{LazyLock, Mutex};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// The service secrets are filed under in the keychain or Secret Service.
+const SERVICE: &str = "spine";
+
+/// `${secret:NAME}` in a config value.
+static REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{secret:([A-Za-z0-9_.-]+)\}").expect("secret reference"));
+
+/// Secrets already read during this run, by name.
+static CACHE: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Mutex::default);
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        anyhow::bail!("Secret names may only use letters, digits, '_', '.' and '-'");
+    }
+    Ok(())
+}
+
+/// `spn secret set`: store a secret typed at a hidden prompt, or piped in on
+/// stdin, in the OS keyring.
+pub async fn run_set(name: &str) -> Result<()> {
+    check_name(name)?;
+    let value = if io::stdin().is_terminal() {
+        print!("Secret for {name}: ");
+        io::stdout().flush()?;
+        read_hidden()?
+    } else {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        input.trim_end_matches(['\r', '\n']).to_string()
+    };
+    if value.is_empty() {
+        anyhow::bail!("No secret given");
+    }
+    store(name, &value).await?;
+    println!("Stored {name}; refer to it in the config as ${{secret:{name}}}");
+    Ok(())
+}
+
+/// `spn secret delete`: remove a secret from the OS keyring.
+pub async fn run_delete(name: &str) -> Result<()> {
+    check_name(name)?;
+    let output = if cfg!(target_os = "macos") {
+        command("security")
+            .args(["delete-generic-password", "-s", SERVICE, "-a", name])
+            .output()
+            .await
+    } else {
+        command("secret-tool")
+            .args(["clear", "service", SERVICE, "name", name])
+            .output()
+            .await
+    }
+    .with_context(|| format!("Couldn't run {}", keyring_tool()))?;
+    if !output.status.success() {
+        anyhow::bail!("Couldn't delete {name}: {}", stderr(&output));
+    }
+    println!("Deleted {name}");
+    Ok(())
+}
+
+/// Read a line from the terminal without echoing it.
+#[cfg(unix)]
+fn read_hidden() -> Result<String> {
+    let fd = libc::STDIN_FILENO;
+    // SAFETY: termios is plain data, filled in by tcgetattr before it's used
+    let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+    let hidden = unsafe { libc::tcgetattr(fd, &mut saved) } == 0;
+    if hidden {
+        let mut quiet = saved;
+        quiet.c_lflag &= !libc::ECHO;
+        // SAFETY: quiet is a valid termios copied from the terminal's own
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &quiet) };
+    }
+    let mut input = String::new();
+    let read = io::stdin().read_line(&mut input);
+    if hidden {
+        // SAFETY: saved holds the settings tcgetattr returned
+        unsafe { libc::tcsetattr(fd, libc::TCSANOW, &saved) };
+    }
+    println!();
+    read?;
+    Ok(input.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(not(unix))]
+fn read_hidden() -> Result<String> {
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn store(name: &str, value: &str) -> Result<()> {
+    // The secret goes in on stdin, where other users' ps can't see it
+    let (mut cmd, input) = if cfg!(target_os = "macos") {
+        let quoted = value.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut cmd = command("security");
+        cmd.arg("-i");
+        let line = format!(
+            "add-generic-password -U -s {SERVICE} -a {name} -l \"spine: {name}\" -w \"{quoted}\"\n"
+        );
+        (cmd, line)
+    } else {
+        let mut cmd = command("secret-tool");
+        cmd.args(["store", "--label", &format!("spine: {name}")])
+            .args(["service", SERVICE, "name", name]);
+        (cmd, value.to_string())
+    };
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Couldn't run {}", keyring_tool()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        anyhow::bail!("Couldn't store {name}: {}", stderr(&output));
+    }
+    Ok(())
+}
+
+/// The secret stored as `name`. It's masked in output from then on.
+pub async fn lookup(name: &str) -> Result<String> {
+    if let Some(value) = CACHE.lock().unwrap_or_else(|e| e.into_inner()).get(name) {
+        return Ok(value.clone());
+    }
+    let output = if cfg!(target_os = "macos") {
+        command("security")
+            .args(["find-generic-password", "-s", SERVICE, "-a", name, "-w"])
+            .output()
+            .await
+    } else {
+        command("secret-tool")
+            .args(["lookup", "service", SERVICE, "name", name])
+            .output()
+            .await
+    }
+    .with_context(|| format!("Couldn't run {} to read secret {name}", keyring_tool()))?;
+    let value = String::from_utf8_lossy(&output.stdout);
+    // security ends the secret with a newline; secret-tool doesn't
+    let value = value.strip_suffix('\n').unwrap_or(&value).to_string();
+    if !output.status.success() || value.is_empty() {
+        anyhow::bail!("Secret {name} isn't in the keyring; add it with 'spn secret set {name}'");
+    }
+    redact::add_secret(&value);
+    CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_string(), value.clone());
+    Ok(value)
+}
+
+/// Replace each `${secret:NAME}` in `text` with the secret stored as NAME.
+pub async fn expand(text: &str) -> Result<String> {
+    let references: Vec<(usize, usize, String)> = REFERENCE
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let whole = captures.get(0)?;
+            Some((whole.start(), whole.end(), captures[1].to_string()))
+        })
+        .collect();
+    let mut expanded = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, end, name) in references {
+        expanded.push_str(&text[copied..start]);
+        expanded.push_str(&lookup(&name).await?);
+        copied = end;
+    }
+    expanded.push_str(&text[copied..]);
+    Ok(expanded)
+}
+
+/// The names `text` refers to with `${secret:NAME}`.
+pub fn references(text: &str) -> BTreeSet<String> {
+    REFERENCE
+        .captures_iter(text)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+fn keyring_tool() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "security"
+    } else {
+        "secret-tool (from libsecret)"
+    }
+}
+
+fn command(program: &str) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.stdin(Stdio::null()).kill_on_drop(true);
+    // Cron jobs don't get the session bus the Secret Service is reached
+    // over, though the user's is usually running
+    #[cfg(unix)]
+    if !cfg!(target_os = "macos") && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+        let bus = format!("/run/user/{}/bus", unsafe { libc::geteuid() });
+        if std::path::Path::new(&bus).exists() {
+            cmd.env("DBUS_SESSION_BUS_ADDRESS", format!("unix:path={bus}"));
+        }
+    }
+    cmd
+}
+
+fn stderr(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stderr).trim().to_string()
+}
@@ -0,0 +1,285 @@
+use crate::config::{self, Config};
+use crate::history;
+use crate::status;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Longest request head accepted; only GETs without a body are served.
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// How long a client may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Random bytes in the bearer token clients must send.
+const TOKEN_BYTES: usize = 32;
+
+struct Response {
+    status: &'static str,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self {
+            status: "200 OK",
+            body,
+        }
+    }
+
+    fn error(status: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+/// `spn serve`: answer read-only JSON requests about spine's state until
+/// interrupted. Nothing served can change anything, and only clients that
+/// send the install's bearer token get an answer.
+pub async fn run_server(config: Config, addr: SocketAddr) -> Result<()> {
+    let (token_path, token) = load_token()?;
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Couldn't listen on {addr}"))?;
+    println!("Serving spine's status on http://{addr}");
+    println!("Endpoints: /status, /runs/last, /history?limit=N, /logs, /logs/<name>");
+    println!(
+        "Requests need 'Authorization: Bearer TOKEN', with the token from {}",
+        token_path.display()
+    );
+    if !addr.ip().is_loopback() {
+        println!("Note: anyone who can reach {addr} and has the token can read run history and failure logs");
+    }
+
+    let config = Arc::new(config);
+    let token = Arc::new(token);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let config = Arc::clone(&config);
+        let token = Arc::clone(&token);
+        tokio::spawn(async move {
+            // A client that hangs up early isn't worth reporting
+            let _ = handle_connection(stream, &config, &token).await;
+        });
+    }
+}
+
+/// The token clients must send, generated into the data directory the first
+/// time `spn serve` runs and kept readable only by its owner.
+fn load_token() -> Result<(PathBuf, String)> {
+    let dir = config::data_dir().context("Couldn't determine the data directory")?;
+    let path = dir.join("serve-token");
+    match std::fs::read_to_string(&path) {
+        Ok(token) if !token.trim().is_empty() => return Ok((path, token.trim().to_string())),
+        Ok(_) => anyhow::bail!("{} is empty; delete it to get a new token", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e).with_context(|| format!("Couldn't read {}", path.display())),
+    }
+
+    let mut bytes = [0; TOKEN_BYTES];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .context("Couldn't generate a token")?;
+    let token: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+
+    std::fs::create_dir_all(&dir).with_context(|| format!("Couldn't create {}", dir.display()))?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{token}"))
+        .with_context(|| format!("Couldn't write {}", path.display()))?;
+    Ok((path, token))
+}
+
+async fn handle_connection(mut stream: TcpStream, config: &Config, token: &str) -> Result<()> {
+    let head = match tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await {
+        Ok(head) => head?,
+        Err(_) => return Ok(()),
+    };
+    let mut words = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, target) = (
+        words.next().unwrap_or_default(),
+        words.next().unwrap_or("/"),
+    );
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let header = |wanted: &str| {
+        head.lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(wanted))
+            .map(|(_, value)| value.trim())
+    };
+
+    let response = if !allowed_host(header("Host")) {
+        // A page on another site that resolves its own name to this
+        // machine still sends that name as the Host
+        Response::error("403 Forbidden", "Host not allowed")
+    } else if !matches!(method, "GET" | "HEAD") {
+        Response::error("405 Method Not Allowed", "Only GET requests are served")
+    } else if !header("Authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|sent| same_token(sent.trim(), token))
+    {
+        Response::error("401 Unauthorized", "Missing or wrong bearer token")
+    } else {
+        route(path, query, config).await
+    };
+    let body = response.body.to_string();
+    let challenge = if response.status.starts_with("401") {
+        "WWW-Authenticate: Bearer\r\n"
+    } else {
+        ""
+    };
+    let mut reply = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{challenge}Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        body.len()
+    );
+    if method != "HEAD" {
+        reply.push_str(&body);
+    }
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Whether a request's Host is `localhost` or an IP address, never some
+/// other name that happens to resolve to this machine.
+fn allowed_host(host: Option<&str>) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let name = match host.strip_prefix('[') {
+        // [::1]:7878
+        Some(rest) => rest.split_once(']').map_or(rest, |(name, _)| name),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost") || name.parse::<IpAddr>().is_ok()
+}
+
+/// Compare tokens without returning early at the first differing byte.
+fn same_token(sent: &str, token: &str) -> bool {
+    sent.len() == token.len()
+        && sent
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |differ, (a, b)| differ | (a ^ b))
+            == 0
+}
+
+/// Read up to the blank line ending the request head.
+async fn read_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_BYTES {
+            anyhow::bail!("Request too large");
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+async fn route(path: &str, query: &str, config: &Config) -> Response {
+    match path.trim_end_matches('/') {
+        "" => Response::ok(json!({
+            "endpoints": ["/status", "/runs/last", "/history?limit=N", "/logs", "/logs/<name>"],
+        })),
+        "/status" => Response::ok(status::json(config).await),
+        "/runs/last" => match history::load_history().await.runs.pop() {
+            Some(run) => Response::ok(json!(run)),
+            None => Response::error("404 Not Found", "No runs recorded yet"),
+        },
+        "/history" => {
+            let runs = history::load_history().await.runs;
+            // ?limit=N keeps the most recent N runs
+            let limit = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("limit="))
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(runs.len());
+            Response::ok(json!(runs[runs.len().saturating_sub(limit)..]))
+        }
+        "/logs" => match list_logs().await {
+            Ok(logs) => Response::ok(json!(logs)),
+            Err(e) => Response::error("500 Internal Server Error", format!("{e:#}")),
+        },
+        path => match path.strip_prefix("/logs/") {
+            Some(name) => read_log(name).await,
+            None => Response::error("404 Not Found", format!("No endpoint {path}")),
+        },
+    }
+}
+
+/// Saved failure logs, newest first.
+async fn list_logs() -> Result<Vec<Value>> {
+    let Some(dir) = history::failure_log_dir() else {
+        return Ok(Vec::new());
+    };
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Couldn't read {}", dir.display())),
+    };
+
+    let mut logs = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|age| age.as_secs());
+        logs.push((
+            modified,
+            json!({
+                "name": entry.file_name().to_string_lossy(),
+                "size": metadata.len(),
+                "modified": modified,
+            }),
+        ));
+    }
+    logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(logs.into_iter().map(|(_, log)| log).collect())
+}
+
+async fn read_log(name: &str) -> Response {
+    // Only plain file names from the log directory
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Response::error("400 Bad Request", "Invalid log name");
+    }
+    let Some(dir) = history::failure_log_dir() else {
+        return Response::error("404 Not Found", "No log directory");
+    };
+    match tokio::fs::read(dir.join(name)).await {
+        Ok(content) => Response::ok(json!({
+            "name": name,
+            "content": String::from_utf8_lossy(&content),
+        })),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Response::error("404 Not Found", format!("No log named {name}"))
+        }
+        Err(e) => Response::error("500 Internal Server Error", e.to_string()),
+    }
+}
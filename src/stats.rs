@@ -0,0 +1,162 @@
+use crate::config;
+use crate::detect::{DetectedManager, RunState};
+use crate::i18n::t;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Cumulative run statistics for every manager, persisted between runs.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Stats {
+    #[serde(default)]
+    pub managers: HashMap<String, ManagerStats>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ManagerStats {
+    pub total_runs: u64,
+    pub successes: u64,
+    pub total_duration_secs: f64,
+    pub last_run: Option<u64>,
+    pub last_failure: Option<String>,
+    pub last_failure_at: Option<u64>,
+}
+
+impl ManagerStats {
+    pub fn success_rate(&self) -> f64 {
+        if self.total_runs == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.total_runs as f64 * 100.0
+    }
+
+    pub fn average_duration_secs(&self) -> f64 {
+        if self.total_runs == 0 {
+            return 0.0;
+        }
+        self.total_duration_secs / self.total_runs as f64
+    }
+
+    /// One-line summary used by `spn list --stats` and the TUI detail view.
+    pub fn summary(&self) -> String {
+        t!(
+            "stats-summary",
+            runs = self.total_runs,
+            rate = format!("{:.1}", self.success_rate()),
+            duration = format_duration_secs(self.average_duration_secs())
+        )
+    }
+}
+
+pub fn format_duration_secs(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Local date and time for a Unix timestamp as stored in the stats and
+/// history files.
+pub fn format_timestamp(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown time".to_string())
+}
+
+/// Marker for a recorded manager that is not in the current configuration.
+pub fn removed_marker(name: &str, configured: Option<&HashSet<String>>) -> &'static str {
+    match configured {
+        Some(names) if !names.contains(name) => " (no longer configured)",
+        _ => "",
+    }
+}
+
+fn stats_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("stats.toml"))
+}
+
+pub async fn load_stats() -> Stats {
+    let Some(path) = stats_path() else {
+        return Stats::default();
+    };
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(content) => toml::from_str(&content).unwrap_or_default(),
+        Err(_) => Stats::default(),
+    }
+}
+
+async fn save_stats(stats: &Stats) -> Result<()> {
+    let path = stats_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(stats)?).await?;
+    Ok(())
+}
+
+/// Print the statistics of every manager that has ever run, including ones
+/// that have since been removed from the configuration.
+pub async fn print_stats(configured: Option<&HashSet<String>>) {
+    let stats = load_stats().await;
+    if stats.managers.is_empty() {
+        println!("No runs recorded yet.");
+        return;
+    }
+
+    let mut names: Vec<&String> = stats.managers.keys().collect();
+    names.sort();
+
+    println!("Run statistics:");
+    for name in names {
+        let manager_stats = &stats.managers[name];
+        println!("  {name}{}", removed_marker(name, configured));
+        println!("    {}", manager_stats.summary());
+        if let Some(last_run) = manager_stats.last_run {
+            println!("    Last run:      {}", format_timestamp(last_run));
+        }
+        if let Some(reason) = &manager_stats.last_failure {
+            println!("    Last failure:  {reason}");
+        }
+        println!();
+    }
+}
+
+/// Fold the outcome of every finished manager into the persisted statistics.
+pub async fn record_run(managers: &[DetectedManager]) -> Result<()> {
+    let mut stats = load_stats().await;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    for manager in managers {
+        let failure = match manager.status.state {
+            RunState::Success => None,
+            RunState::Failed | RunState::Unverified => manager.status.error.clone(),
+            _ => continue,
+        };
+
+        let entry = stats.managers.entry(manager.name.clone()).or_default();
+        entry.total_runs += 1;
+        entry.total_duration_secs += manager.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        entry.last_run = Some(now);
+        match failure {
+            None => entry.successes += 1,
+            Some(reason) => {
+                entry.last_failure = Some(reason);
+                entry.last_failure_at = Some(now);
+            }
+        }
+    }
+
+    save_stats(&stats).await
+}
@@ -0,0 +1,424 @@
+use crate::auto_update::{self, ScheduledJob};
+use crate::config::{AutoUpdateConfig, Config};
+use crate::detect;
+use crate::history;
+use crate::holds;
+use crate::i18n::t;
+use crate::outdated;
+use crate::schedule;
+use crate::stats::format_timestamp;
+use chrono::Local;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Output formats for `spn status`: plain text for people, or what status bar
+/// modules expect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatusFormat {
+    Text,
+    /// JSON for a waybar `custom` module with `return-type: json`.
+    Waybar,
+    /// A single line for a polybar `custom/script` module.
+    Polybar,
+    /// xbar plugin output with a dropdown.
+    Xbar,
+    /// xbar output with an SF Symbol icon.
+    Swiftbar,
+    /// Everything above as one JSON object, for scripts and dashboards.
+    Json,
+}
+
+/// Everything `spn status` reports, read from saved state.
+struct Snapshot {
+    now: u64,
+    last_run: Option<LastRun>,
+    holds: Vec<String>,
+    pending: Option<Pending>,
+    detected: Option<Detected>,
+}
+
+/// Installed managers from the detection cache.
+struct Detected {
+    detected_at: u64,
+    managers: Vec<String>,
+}
+
+struct LastRun {
+    started_at: u64,
+    duration_secs: Option<f64>,
+    ran: usize,
+    failed: Vec<String>,
+    /// Each manager's time, in total and per phase, for JSON output.
+    managers: Vec<serde_json::Value>,
+}
+
+struct Pending {
+    checked_at: u64,
+    fresh: bool,
+    managers: Vec<(String, usize)>,
+}
+
+impl Pending {
+    fn total(&self) -> usize {
+        self.managers.iter().map(|(_, count)| count).sum()
+    }
+}
+
+async fn snapshot(config: &Config) -> Snapshot {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let history = history::load_history().await;
+    let last_run = history.runs.last().map(|run| LastRun {
+        started_at: run.started_at,
+        duration_secs: run.duration_secs,
+        ran: run
+            .managers
+            .iter()
+            .filter(|m| m.outcome != "skipped")
+            .count(),
+        failed: run
+            .managers
+            .iter()
+            .filter(|m| m.failed())
+            .map(|m| m.name.clone())
+            .collect(),
+        managers: run
+            .managers
+            .iter()
+            .map(|m| {
+                serde_json::json!({
+                    "name": m.name,
+                    "outcome": m.outcome,
+                    "duration_secs": m.duration_secs,
+                    "phases": m.phases.iter().map(|phase| serde_json::json!({
+                        "phase": phase.phase,
+                        "duration_secs": phase.duration_secs,
+                        "skipped": phase.skipped,
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect(),
+    });
+
+    let cache = outdated::load_cache().await;
+    let managers: Vec<(String, usize)> = cache
+        .managers
+        .iter()
+        .filter(|(name, _)| config.managers.contains_key(*name))
+        .map(|(name, count)| (name.clone(), *count))
+        .collect();
+    let pending = match cache.checked_at {
+        Some(checked_at) if !managers.is_empty() => Some(Pending {
+            checked_at,
+            fresh: cache.is_fresh(config.check_ttl_minutes, now),
+            managers,
+        }),
+        _ => None,
+    };
+
+    let cache = detect::load_cache().await;
+    let detected = cache.detected_at.map(|detected_at| Detected {
+        detected_at,
+        managers: cache
+            .installed()
+            .filter(|name| config.managers.contains_key(*name))
+            .map(str::to_string)
+            .collect(),
+    });
+
+    Snapshot {
+        now,
+        last_run,
+        holds: holds::load_holds().await.managers.into_iter().collect(),
+        pending,
+        detected,
+    }
+}
+
+/// `spn status`: when spine last ran, what's scheduled next and how many
+/// updates are waiting, all from saved state without running any manager.
+pub async fn print_status(config: &Config, local: bool, format: StatusFormat) {
+    let snapshot = snapshot(config).await;
+    match format {
+        StatusFormat::Text => print_text(&snapshot, config, local),
+        StatusFormat::Waybar => print_waybar(&snapshot),
+        StatusFormat::Polybar => println!("{}", bar_text(&snapshot)),
+        StatusFormat::Xbar => print_xbar(&snapshot, None),
+        StatusFormat::Swiftbar => print_xbar(&snapshot, Some("shippingbox")),
+        StatusFormat::Json => println!("{}", status_json(&snapshot)),
+    }
+}
+
+/// The status as JSON, as served by `spn serve` at `/status`.
+pub async fn json(config: &Config) -> serde_json::Value {
+    status_json(&snapshot(config).await)
+}
+
+fn status_json(snapshot: &Snapshot) -> serde_json::Value {
+    serde_json::json!({
+        "now": snapshot.now,
+        "state": bar_class(snapshot),
+        "last_run": snapshot.last_run.as_ref().map(|run| serde_json::json!({
+            "started_at": run.started_at,
+            "duration_secs": run.duration_secs,
+            "ran": run.ran,
+            "failed": run.failed,
+            "managers": run.managers,
+        })),
+        "pending": snapshot.pending.as_ref().map(|pending| serde_json::json!({
+            "checked_at": pending.checked_at,
+            "fresh": pending.fresh,
+            "total": pending.total(),
+            "managers": pending.managers.iter().cloned().collect::<std::collections::BTreeMap<_, _>>(),
+        })),
+        "holds": snapshot.holds,
+        "detected": snapshot.detected.as_ref().map(|detected| serde_json::json!({
+            "detected_at": detected.detected_at,
+            "managers": detected.managers,
+        })),
+    })
+}
+
+fn print_text(snapshot: &Snapshot, config: &Config, local: bool) {
+    // Labels are padded so the values line up whatever the language
+    let field = |label: &str, value: String| println!("{:<13} {value}", t!(label));
+
+    match &snapshot.last_run {
+        Some(run) => {
+            field(
+                "status-last-run",
+                format!(
+                    "{} ({})",
+                    format_timestamp(run.started_at),
+                    format_age(snapshot.now.saturating_sub(run.started_at))
+                ),
+            );
+            if run.failed.is_empty() {
+                field("status-result", t!("status-all-succeeded", count = run.ran));
+            } else {
+                field(
+                    "status-result",
+                    t!(
+                        "status-some-failed",
+                        failed = run.failed.len(),
+                        ran = run.ran,
+                        managers = run.failed.join(", ")
+                    ),
+                );
+            }
+        }
+        None => field("status-last-run", t!("status-never")),
+    }
+
+    if !local {
+        field(
+            "status-auto-update",
+            auto_update_summary(&config.auto_update),
+        );
+    }
+
+    if let Some(detected) = &snapshot.detected {
+        field(
+            "status-managers",
+            t!(
+                "status-managers-detected",
+                managers = detected.managers.join(", "),
+                age = format_age(snapshot.now.saturating_sub(detected.detected_at))
+            ),
+        );
+    }
+
+    if !snapshot.holds.is_empty() {
+        field("status-on-hold", snapshot.holds.join(", "));
+    }
+
+    match &snapshot.pending {
+        Some(pending) => {
+            field(
+                "status-pending",
+                t!(
+                    if pending.fresh {
+                        "status-pending-count"
+                    } else {
+                        "status-pending-stale"
+                    },
+                    count = pending.total(),
+                    time = format_timestamp(pending.checked_at),
+                    age = format_age(snapshot.now.saturating_sub(pending.checked_at))
+                ),
+            );
+            for (name, count) in &pending.managers {
+                println!("  {name:<20} {count}");
+            }
+        }
+        None => field("status-pending", t!("status-pending-unknown")),
+    }
+}
+
+/// The short label shown in a bar: the pending update count, or `?` if
+/// nothing has been checked yet.
+fn bar_text(snapshot: &Snapshot) -> String {
+    match &snapshot.pending {
+        Some(pending) => format!("📦 {}", pending.total()),
+        None => "📦 ?".to_string(),
+    }
+}
+
+/// Per-manager breakdown and last run, one item per line.
+fn bar_details(snapshot: &Snapshot) -> Vec<String> {
+    let mut lines = Vec::new();
+    match &snapshot.pending {
+        Some(pending) => {
+            for (name, count) in &pending.managers {
+                lines.push(format!("{name}: {count}"));
+            }
+            lines.push(t!(
+                if pending.fresh {
+                    "bar-checked"
+                } else {
+                    "bar-checked-stale"
+                },
+                age = format_age(snapshot.now.saturating_sub(pending.checked_at))
+            ));
+        }
+        None => lines.push(t!("bar-unknown")),
+    }
+    match &snapshot.last_run {
+        Some(run) if run.failed.is_empty() => lines.push(t!(
+            "bar-last-run-ok",
+            age = format_age(snapshot.now.saturating_sub(run.started_at))
+        )),
+        Some(run) => lines.push(t!(
+            "bar-last-run-failed",
+            age = format_age(snapshot.now.saturating_sub(run.started_at)),
+            managers = run.failed.join(", ")
+        )),
+        None => lines.push(t!("bar-never-run")),
+    }
+    if !snapshot.holds.is_empty() {
+        lines.push(t!("bar-on-hold", managers = snapshot.holds.join(", ")));
+    }
+    lines
+}
+
+/// CSS class for styling the bar module.
+fn bar_class(snapshot: &Snapshot) -> &'static str {
+    match (&snapshot.last_run, &snapshot.pending) {
+        (Some(run), _) if !run.failed.is_empty() => "failed",
+        (_, None) => "unknown",
+        (_, Some(pending)) if pending.total() > 0 => "pending",
+        _ => "updated",
+    }
+}
+
+fn print_waybar(snapshot: &Snapshot) {
+    let module = serde_json::json!({
+        "text": bar_text(snapshot),
+        "alt": bar_class(snapshot),
+        "class": bar_class(snapshot),
+        "tooltip": bar_details(snapshot).join("\n"),
+    });
+    println!("{module}");
+}
+
+/// xbar/SwiftBar plugin output: the title line, then a dropdown with the
+/// breakdown and actions.
+fn print_xbar(snapshot: &Snapshot, sf_symbol: Option<&str>) {
+    match (sf_symbol, &snapshot.pending) {
+        (Some(symbol), Some(pending)) => println!("{} | sfimage={symbol}", pending.total()),
+        (Some(symbol), None) => println!("? | sfimage={symbol}"),
+        (None, _) => println!("{}", bar_text(snapshot)),
+    }
+    println!("---");
+    for line in bar_details(snapshot) {
+        println!("{}", line.replace('|', "/"));
+    }
+
+    if let Ok(exe) = std::env::current_exe() {
+        let exe = exe.to_string_lossy();
+        println!("---");
+        println!(
+            "{} | bash=\"{exe}\" param1=upgrade terminal=true",
+            t!("bar-upgrade")
+        );
+        println!(
+            "{} | bash=\"{exe}\" param1=check param2=--force terminal=false refresh=true",
+            t!("bar-check")
+        );
+    }
+}
+
+fn auto_update_summary(config: &AutoUpdateConfig) -> String {
+    let state = auto_update::inspect(config);
+    if !state.installed() {
+        return if config.enabled {
+            t!("status-auto-not-scheduled")
+        } else {
+            t!("status-auto-off")
+        };
+    }
+
+    // Report what the scheduler will do, flagging jobs that no longer match
+    // the config
+    let installed: Vec<(&ScheduledJob, &AutoUpdateConfig)> = state
+        .jobs
+        .iter()
+        .filter(|job| job.installed.is_some())
+        .map(|job| (&job.job, job.timing.as_ref().unwrap_or(&job.job.config)))
+        .collect();
+    if installed.is_empty() {
+        return t!("status-auto-stale");
+    }
+    let mut schedule = installed
+        .iter()
+        .map(|(job, timing)| {
+            let schedule = match timing.schedule.as_str() {
+                "daily" => t!("status-auto-daily", time = timing.time.as_str()),
+                "interval" => t!("status-auto-interval", hours = timing.interval_hours),
+                _ => t!("status-auto-weekly", day = timing.day.as_str()),
+            };
+            format!("{}{schedule}", job.prefix())
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    if state.drifted() {
+        schedule = t!("status-auto-drifted", schedule = schedule);
+    }
+
+    // The soonest of the upgrade jobs' next runs
+    let now = Local::now();
+    let mut next = None;
+    let mut error = None;
+    for (_, timing) in installed.into_iter().filter(|(job, _)| !job.prefetch) {
+        match schedule::next_run(timing, now) {
+            Ok(Some(run)) => next = Some(next.map_or(run, |next| run.min(next))),
+            Ok(None) => {}
+            Err(e) => error = error.or(Some(e)),
+        }
+    }
+    match (next, error) {
+        (Some(next), _) => t!(
+            "status-auto-next",
+            schedule = schedule,
+            next = next.format("%a %Y-%m-%d %H:%M").to_string()
+        ),
+        (None, None) => t!("status-auto-on", schedule = schedule),
+        (None, Some(e)) => t!(
+            "status-auto-next-unknown",
+            schedule = schedule,
+            error = e.to_string()
+        ),
+    }
+}
+
+/// Rough time since an event, e.g. `3h ago`.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => t!("age-just-now"),
+        60..=3599 => t!("age-minutes", count = secs / 60),
+        3600..=86_399 => t!("age-hours", count = secs / 3600),
+        _ => t!("age-days", count = secs / 86_400),
+    }
+}
@@ -0,0 +1,111 @@
+use crate::execute::check_sudo_availability;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::{Mutex, MutexGuard};
+use tokio::task::JoinHandle;
+
+/// How often the cached credentials are refreshed during a run; sudo's
+/// default timestamp_timeout is 5 minutes.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whether sudo is known to run without a password in this run.
+static VALIDATED: Mutex<bool> = Mutex::const_new(false);
+
+/// The program commands run through as root or another user: sudo, or doas
+/// where that's all there is, as on OpenBSD. None when neither is installed.
+pub fn program() -> Option<&'static str> {
+    static PROGRAM: OnceLock<Option<&'static str>> = OnceLock::new();
+    *PROGRAM.get_or_init(|| {
+        ["sudo", "doas"]
+            .into_iter()
+            .find(|program| which::which(program).is_ok())
+    })
+}
+
+/// Refreshes sudo's cached credentials in the background until dropped, so
+/// a long run doesn't outlast them.
+pub struct KeepWarm {
+    task: JoinHandle<()>,
+}
+
+impl Drop for KeepWarm {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Validate sudo once before the managers start: quietly when its
+/// credentials are cached, otherwise by asking for the password here when
+/// `interactive`, before the TUI takes over the terminal. Returns None when
+/// that failed, in which case sudo commands run one at a time until one
+/// succeeds; sudoers may still allow them without a password. doas has no
+/// `-v`, so asking only helps when doas.conf has `persist`.
+pub async fn prewarm(interactive: bool) -> Option<KeepWarm> {
+    let mut validated = VALIDATED.lock().await;
+    *validated = check_sudo_availability().await
+        || (interactive && validate().await && check_sudo_availability().await);
+    if !*validated {
+        return None;
+    }
+    Some(KeepWarm {
+        task: tokio::spawn(async {
+            let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let refreshed = refresh().await;
+                *VALIDATED.lock().await = refreshed;
+            }
+        }),
+    })
+}
+
+/// Ask for the password on the terminal.
+async fn validate() -> bool {
+    let Some(program) = program() else {
+        return false;
+    };
+    // doas can't just validate; running anything asks for the password
+    let arg = if program == "sudo" { "-v" } else { "true" };
+    Command::new(program)
+        .arg(arg)
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
+}
+
+/// Extend the cached credentials without prompting.
+async fn refresh() -> bool {
+    if program() != Some("sudo") {
+        return check_sudo_availability().await;
+    }
+    Command::new("sudo")
+        .args(["-n", "-v"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
+}
+
+/// Held while a sudo command runs before sudo is known to work without a
+/// password, so parallel managers take turns instead of each failing (or
+/// prompting) at once.
+pub struct FirstUse(MutexGuard<'static, bool>);
+
+impl FirstUse {
+    /// Record how the command went; once one succeeds, the rest run in
+    /// parallel.
+    pub fn finish(mut self, success: bool) {
+        *self.0 = success;
+    }
+}
+
+/// Wait for a turn to run a sudo command, which is only needed until
+/// credentials are validated ([`prewarm`]) or a sudo command succeeds.
+pub async fn first_use() -> Option<FirstUse> {
+    let validated = VALIDATED.lock().await;
+    (!*validated).then_some(FirstUse(validated))
+}
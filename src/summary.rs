@@ -0,0 +1,348 @@
+use crate::a11y;
+use crate::detect::{DetectedManager, ManagerStatus, RunState};
+use crate::diagnose::{Cause, Recovery};
+use crate::i18n::t;
+use crate::kept_back;
+use crate::stats::format_duration_secs;
+use comfy_table::{modifiers, presets, Cell, CellAlignment, Color, ContentArrangement, Table};
+use std::io::IsTerminal;
+
+/// Width of the rules around the summary.
+const RULE_WIDTH: usize = 70;
+
+/// Characters the summary is drawn with: Unicode on terminals, ASCII for
+/// dumb terminals and output redirected to a log, and words alone for
+/// screen readers.
+struct Glyphs {
+    unicode: bool,
+    color: bool,
+    /// `--a11y`: no symbols, rules or table, only lines of text.
+    spoken: bool,
+}
+
+impl Glyphs {
+    fn detect() -> Self {
+        let spoken = a11y::enabled();
+        let unicode = !spoken
+            && std::io::stdout().is_terminal()
+            && std::env::var("TERM").map_or(true, |term| term != "dumb");
+        Self {
+            unicode,
+            color: unicode && std::env::var_os("NO_COLOR").is_none(),
+            spoken,
+        }
+    }
+
+    /// `text` after the symbol for `state`, if symbols are shown.
+    fn label(&self, state: RunState, text: &str) -> String {
+        if self.spoken {
+            text.to_string()
+        } else {
+            format!("{} {text}", self.state(state))
+        }
+    }
+
+    fn pick(&self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        if self.unicode {
+            unicode
+        } else {
+            ascii
+        }
+    }
+
+    fn print_rule(&self) {
+        if !self.spoken {
+            println!("{}", self.pick("━", "=").repeat(RULE_WIDTH));
+        }
+    }
+
+    fn state(&self, state: RunState) -> &'static str {
+        match state {
+            RunState::Success => self.pick("✓", "+"),
+            RunState::Failed => self.pick("✗", "x"),
+            RunState::Unverified => self.pick("⚠", "!"),
+            _ => "?",
+        }
+    }
+}
+
+/// Print the end-of-run summary: totals, a table with a row per manager,
+/// then the notes, causes and errors of the managers that have any.
+pub fn print_summary(managers: &[DetectedManager]) {
+    let glyphs = Glyphs::detect();
+    let count = |state: RunState| managers.iter().filter(|m| m.status.state == state).count();
+    let total = managers.len();
+    let successful = count(RunState::Success);
+    let failed = count(RunState::Failed);
+    let unverified = count(RunState::Unverified);
+    let incomplete = total - successful - failed - unverified;
+    let percent = |n: usize| (n as f32 / total as f32) * 100.0;
+
+    glyphs.print_rule();
+    if glyphs.spoken {
+        println!("{}", t!("summary-title"));
+    } else {
+        println!(
+            "{}",
+            format!("{:^RULE_WIDTH$}", t!("summary-title")).trim_end()
+        );
+    }
+    glyphs.print_rule();
+
+    println!("\n{}", t!("summary-overall"));
+    println!("  {:<20} {total}", t!("summary-total"));
+    let successful_label = glyphs.label(RunState::Success, &t!("summary-successful"));
+    println!(
+        "  {successful_label:<20} {successful} ({:.1}%)",
+        percent(successful)
+    );
+    let failed_label = glyphs.label(RunState::Failed, &t!("summary-failed"));
+    println!("  {failed_label:<20} {failed} ({:.1}%)", percent(failed));
+    if unverified > 0 {
+        let label = glyphs.label(RunState::Unverified, &t!("summary-unverified-count"));
+        println!("  {label:<20} {unverified}");
+    }
+    if incomplete > 0 {
+        let label = glyphs.label(RunState::Pending, &t!("summary-incomplete"));
+        println!("  {label:<20} {incomplete} ({:.1}%)", percent(incomplete));
+    }
+
+    println!("\n{}", t!("summary-details"));
+    if glyphs.spoken {
+        for manager in managers {
+            println!("{}", spoken_row(manager));
+        }
+    } else {
+        println!("{}", table(managers, &glyphs));
+    }
+    for manager in managers {
+        print_details(manager, &glyphs);
+    }
+
+    let warning = if glyphs.spoken {
+        ""
+    } else {
+        glyphs.pick("⚠️  ", "!! ")
+    };
+    if unverified > 0 {
+        println!("\n{warning}{}", t!("summary-security-warning"));
+        println!("   {}", t!("summary-allow-unverified-hint"));
+    }
+    if failed > 0 {
+        println!("\n{warning}{}", t!("summary-some-failed"));
+        println!("   {}", t!("summary-retry-hint"));
+        println!("   {}", t!("summary-sudo-hint"));
+    } else if successful > 0 && unverified == 0 {
+        println!(
+            "\n{}{}",
+            glyphs.pick("🎉 ", ""),
+            t!("summary-all-succeeded")
+        );
+        let kept_back: usize = managers.iter().map(|m| m.status.kept_back.len()).sum();
+        if kept_back > 0 {
+            println!("   {}", t!("summary-kept-back-hint", count = kept_back));
+        } else {
+            println!("   {}", t!("summary-up-to-date"));
+        }
+    }
+
+    glyphs.print_rule();
+}
+
+fn table(managers: &[DetectedManager], glyphs: &Glyphs) -> Table {
+    let mut table = Table::new();
+    if glyphs.unicode {
+        table
+            .load_preset(presets::UTF8_FULL_CONDENSED)
+            .apply_modifier(modifiers::UTF8_ROUND_CORNERS);
+    } else {
+        table.load_preset(presets::ASCII_FULL_CONDENSED);
+    }
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("summary-column-manager"),
+            t!("summary-column-result"),
+            t!("summary-column-duration"),
+            t!("summary-column-packages"),
+            t!("summary-column-phases"),
+        ]);
+
+    for manager in managers {
+        let state = manager.status.state;
+        let (result, color) = result(&manager.status);
+        let (duration, packages, phases) = row(manager, glyphs);
+
+        let mut status_cells = [
+            Cell::new(format!("{} {}", glyphs.state(state), manager.name)),
+            Cell::new(result),
+        ];
+        if glyphs.color {
+            status_cells = status_cells.map(|cell| cell.fg(color));
+        }
+        let [name, result] = status_cells;
+        table.add_row(vec![
+            name,
+            result,
+            Cell::new(duration).set_alignment(CellAlignment::Right),
+            Cell::new(packages).set_alignment(CellAlignment::Right),
+            Cell::new(phases),
+        ]);
+    }
+    table
+}
+
+/// A manager's result, in words and as the color it's shown in.
+fn result(status: &ManagerStatus) -> (String, Color) {
+    match status.state {
+        RunState::Success if status.attention.is_some() => {
+            (t!("summary-needs-attention"), Color::Yellow)
+        }
+        RunState::Success => (t!("summary-success"), Color::Green),
+        RunState::Failed => (t!("summary-failure"), Color::Red),
+        RunState::Unverified => (t!("summary-unverified"), Color::Yellow),
+        _ => (t!("summary-unfinished"), Color::DarkGrey),
+    }
+}
+
+/// A manager's table row as a sentence, for screen readers.
+fn spoken_row(manager: &DetectedManager) -> String {
+    let glyphs = Glyphs {
+        unicode: false,
+        color: false,
+        spoken: true,
+    };
+    let (duration, packages, phases) = row(manager, &glyphs);
+    let none = |value: String| {
+        if value == "-" {
+            t!("stats-none")
+        } else {
+            value
+        }
+    };
+    let mut parts = vec![
+        format!("{}: {}", manager.name, result(&manager.status).0),
+        format!("{}: {}", t!("summary-column-duration"), none(duration)),
+        format!("{}: {}", t!("summary-column-packages"), none(packages)),
+    ];
+    if !phases.is_empty() {
+        parts.push(format!("{}: {phases}", t!("summary-column-phases")));
+    }
+    format!("{}.", parts.join(". "))
+}
+
+/// The duration, package count and phase results columns of a manager's
+/// row, with "-" for what isn't known.
+fn row(manager: &DetectedManager, glyphs: &Glyphs) -> (String, String, String) {
+    let duration = manager
+        .duration
+        .map(|duration| format_duration_secs(duration.as_secs_f64()))
+        .unwrap_or_else(|| "-".to_string());
+    // An exact record of the changes beats counting output lines
+    let packages = manager
+        .changes
+        .as_ref()
+        .map(|changes| changes.upgraded.len() + changes.installed.len() + changes.removed.len())
+        .or(manager.status.packages)
+        .map(|count| count.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let phases = manager
+        .status
+        .phases
+        .iter()
+        .map(|result| {
+            let outcome = if result.skipped {
+                glyphs.pick("↷", "skipped")
+            } else if result.success() {
+                glyphs.pick("✓", "ok")
+            } else {
+                glyphs.pick("✗", "failed")
+            };
+            if result.skipped {
+                format!("{} {outcome}", result.phase.title())
+            } else {
+                let duration = format_duration_secs(result.duration.as_secs_f64());
+                format!("{} {outcome} {duration}", result.phase.title())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    (duration, packages, phases)
+}
+
+/// Everything about a manager that doesn't fit in its table row.
+fn print_details(manager: &DetectedManager, glyphs: &Glyphs) {
+    let status = &manager.status;
+    let mut lines: Vec<String> = status
+        .recoveries
+        .iter()
+        .map(Recovery::describe)
+        .chain(manager.changes.as_ref().map(|changes| {
+            t!(
+                "summary-changes",
+                upgraded = changes.upgraded.len(),
+                installed = changes.installed.len(),
+                removed = changes.removed.len()
+            )
+        }))
+        .chain(
+            (!status.deferred.is_empty())
+                .then(|| t!("summary-deferred", casks = status.deferred.join(", "))),
+        )
+        .chain((!status.needs_review.is_empty()).then(|| {
+            t!(
+                "summary-needs-review",
+                packages = status.needs_review.join(", ")
+            )
+        }))
+        .chain(
+            status
+                .attention
+                .as_ref()
+                .map(|reason| t!("summary-attention", reason = reason.as_str())),
+        )
+        .chain((!status.kept_back.is_empty()).then(|| {
+            t!(
+                "summary-kept-back",
+                packages = kept_back::describe(&status.kept_back)
+            )
+        }))
+        .collect();
+    let mut timeline = manager.timeline_summary();
+    if glyphs.spoken {
+        timeline = timeline.replace(" → ", ", ");
+    } else if !glyphs.unicode {
+        timeline = timeline.replace('→', "->");
+    }
+    match status.state {
+        RunState::Failed => {
+            lines.push(t!("summary-timeline", timeline = timeline));
+            lines.extend(status.cause.map(Cause::describe).unwrap_or_default());
+            lines.push(t!("summary-error", error = manager.failure_details()));
+        }
+        RunState::Unverified => {
+            lines.push(t!("summary-timeline", timeline = timeline));
+            lines.push(t!(
+                "summary-error",
+                error = status.error.as_deref().unwrap_or_default()
+            ));
+        }
+        _ => {}
+    }
+    let Some(last) = lines.pop() else {
+        return;
+    };
+
+    println!("\n  {}", glyphs.label(status.state, &manager.name));
+    if glyphs.spoken {
+        for line in lines.iter().chain([&last]) {
+            println!("    {line}");
+        }
+        return;
+    }
+    for line in lines {
+        println!("    {} {line}", glyphs.pick("├─", "|-"));
+    }
+    println!("    {} {last}", glyphs.pick("└─", "`-"));
+}
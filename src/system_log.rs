@@ -0,0 +1,240 @@
+use crate::config::SystemLog;
+use crate::detect::{DetectedManager, RunState};
+use crate::stats::format_duration_secs;
+use anyhow::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Priorities from syslog(3), which journald uses too
+const LOG_ERR: i32 = 3;
+const LOG_WARNING: i32 = 4;
+const LOG_INFO: i32 = 6;
+
+/// Lines of output read from a manager's logs at a time with `SystemLog::Full`.
+const OUTPUT_CHUNK: usize = 1000;
+
+/// Output lines longer than this many bytes are cut short if the logger
+/// won't take them whole.
+const MAX_LINE: usize = 4096;
+
+/// Mirror a finished run to the system logger: an entry per manager and one
+/// for the run, plus each line of output with `SystemLog::Full`. Every
+/// journald entry carries `SPINE_RUN`, the run's start time, so
+/// `journalctl SPINE_RUN=...` shows one run. Sending can block, so it runs
+/// off the async workers.
+pub async fn record_run(
+    mode: SystemLog,
+    started_at: SystemTime,
+    finished: &[DetectedManager],
+) -> Result<()> {
+    if mode == SystemLog::Off {
+        return Ok(());
+    }
+    let finished = finished.to_vec();
+    tokio::task::spawn_blocking(move || record(mode, started_at, &finished)).await?
+}
+
+fn record(mode: SystemLog, started_at: SystemTime, finished: &[DetectedManager]) -> Result<()> {
+    let logger = Logger::open();
+    let run = started_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .to_string();
+
+    let mut last_error = None;
+    for manager in finished {
+        let fields = |extra: Vec<(&'static str, String)>| {
+            let mut fields = vec![
+                ("SPINE_RUN", run.clone()),
+                ("SPINE_MANAGER", manager.name.clone()),
+            ];
+            fields.extend(extra);
+            fields
+        };
+
+        if mode == SystemLog::Full {
+            // A chunk at a time, since the output can be far bigger than memory
+            // should hold at once; a line the logger refuses is cut short or
+            // skipped rather than losing the entries after it
+            let mut skipped = 0;
+            let mut start = 0;
+            loop {
+                let chunk = manager.logs.lines(start, OUTPUT_CHUNK);
+                if chunk.is_empty() {
+                    break;
+                }
+                start += chunk.len();
+                for line in chunk.iter().filter(|line| !line.trim().is_empty()) {
+                    let message = format!("[{}] {line}", manager.name);
+                    if logger
+                        .send(LOG_INFO, &message, &fields(Vec::new()))
+                        .is_err()
+                        && logger
+                            .send(LOG_INFO, truncate(&message), &fields(Vec::new()))
+                            .is_err()
+                    {
+                        skipped += 1;
+                    }
+                }
+            }
+            if skipped > 0 {
+                let message = format!(
+                    "[{}] {skipped} line(s) of output couldn't be logged",
+                    manager.name
+                );
+                last_error = logger
+                    .send(LOG_WARNING, &message, &fields(Vec::new()))
+                    .err()
+                    .or(last_error);
+            }
+        }
+
+        let status = &manager.status;
+        let duration = manager.duration.unwrap_or_default().as_secs();
+        let mut message = format!(
+            "{}: {} in {}",
+            manager.name,
+            status.state.label(),
+            format_duration_secs(duration as f64)
+        );
+        if let Some(packages) = status.packages {
+            message.push_str(&format!(", {packages} packages"));
+        }
+        if let Some(error) = &status.error {
+            message.push_str(&format!(": {error}"));
+        }
+        let priority = match status.state {
+            RunState::Failed => LOG_ERR,
+            RunState::Unverified => LOG_WARNING,
+            _ => LOG_INFO,
+        };
+        let mut extra = vec![
+            ("SPINE_STATE", status.state.label().to_string()),
+            ("SPINE_DURATION_SECS", duration.to_string()),
+        ];
+        if let Some(packages) = status.packages {
+            extra.push(("SPINE_PACKAGES", packages.to_string()));
+        }
+        if let Some(error) = &status.error {
+            extra.push(("SPINE_ERROR", error.clone()));
+        }
+        last_error = logger
+            .send(priority, &message, &fields(extra))
+            .err()
+            .or(last_error);
+    }
+
+    let failed: Vec<&str> = finished
+        .iter()
+        .filter(|m| matches!(m.status.state, RunState::Failed | RunState::Unverified))
+        .map(|m| m.name.as_str())
+        .collect();
+    let duration = started_at.elapsed().unwrap_or_default().as_secs();
+    let mut message = format!(
+        "Run finished: {} manager(s) in {}",
+        finished.len(),
+        format_duration_secs(duration as f64)
+    );
+    if !failed.is_empty() {
+        message.push_str(&format!(", failed: {}", failed.join(", ")));
+    }
+    let managers: Vec<&str> = finished.iter().map(|m| m.name.as_str()).collect();
+    let fields = vec![
+        ("SPINE_RUN", run),
+        ("SPINE_MANAGERS", managers.join(" ")),
+        ("SPINE_FAILED", failed.join(" ")),
+        ("SPINE_DURATION_SECS", duration.to_string()),
+    ];
+    let priority = if failed.is_empty() { LOG_INFO } else { LOG_ERR };
+    logger.send(priority, &message, &fields)?;
+    // One entry that couldn't be sent doesn't keep the others out
+    match last_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// `message` cut down to MAX_LINE bytes, for a line too long to log whole.
+fn truncate(message: &str) -> &str {
+    let mut end = message.len().min(MAX_LINE);
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    &message[..end]
+}
+
+enum Logger {
+    /// journald's native socket, which keeps the structured fields.
+    #[cfg(target_os = "linux")]
+    Journal(std::os::unix::net::UnixDatagram),
+    /// syslog(3), which on macOS goes to unified logging.
+    Syslog,
+}
+
+#[cfg(target_os = "linux")]
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+impl Logger {
+    fn open() -> Self {
+        #[cfg(target_os = "linux")]
+        if std::path::Path::new(JOURNAL_SOCKET).exists() {
+            if let Ok(socket) = std::os::unix::net::UnixDatagram::unbound() {
+                return Logger::Journal(socket);
+            }
+        }
+        #[cfg(unix)]
+        // SAFETY: the identifier is a static C string, as openlog requires
+        unsafe {
+            libc::openlog(c"spine".as_ptr(), libc::LOG_PID, libc::LOG_USER);
+        }
+        Logger::Syslog
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    fn send(&self, priority: i32, message: &str, fields: &[(&str, String)]) -> Result<()> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Logger::Journal(socket) => {
+                let mut entry = Vec::new();
+                append_field(&mut entry, "MESSAGE", message);
+                append_field(&mut entry, "PRIORITY", &priority.to_string());
+                append_field(&mut entry, "SYSLOG_IDENTIFIER", "spine");
+                for (key, value) in fields {
+                    append_field(&mut entry, key, value);
+                }
+                socket.send_to(&entry, JOURNAL_SOCKET)?;
+                Ok(())
+            }
+            // syslog has no structured fields; the message names the manager
+            Logger::Syslog => {
+                #[cfg(unix)]
+                {
+                    let message = std::ffi::CString::new(message.replace('\0', ""))?;
+                    // SAFETY: both strings are NUL-terminated and the format
+                    // takes exactly one string argument
+                    unsafe {
+                        libc::syslog(priority, c"%s".as_ptr(), message.as_ptr());
+                    }
+                }
+                #[cfg(not(unix))]
+                let _ = (priority, message);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Add `KEY=value` to a journal entry, in the length-prefixed form when the
+/// value spans lines.
+#[cfg(target_os = "linux")]
+fn append_field(entry: &mut Vec<u8>, key: &str, value: &str) {
+    entry.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
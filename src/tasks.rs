@@ -0,0 +1,46 @@
+use crate::config::{Config, ExtraPhase, ManagerConfig, TaskConfig, TaskWhen};
+use crate::detect::{DetectedManager, Stage};
+use anyhow::{Context, Result};
+
+/// The enabled `[tasks]`, ready to run alongside the detected managers.
+pub fn task_managers(config: &Config) -> Result<Vec<DetectedManager>> {
+    let mut tasks = Vec::new();
+    for (name, task) in config.tasks.iter().filter(|(_, task)| task.enabled) {
+        if config.managers.contains_key(name) {
+            anyhow::bail!("task {name} has the same name as a manager");
+        }
+        let stage = match task.when {
+            TaskWhen::Before => Stage::Before,
+            TaskWhen::After => Stage::After,
+        };
+        tasks.push(DetectedManager::new(
+            name.clone(),
+            manager_config(name, task)?,
+            &config.logs,
+            stage,
+        ));
+    }
+    Ok(tasks)
+}
+
+/// A manager definition whose only step is the task's command, so the
+/// workflow runs it like any other phase (sudo, timeout, skip_if, logs).
+fn manager_config(name: &str, task: &TaskConfig) -> Result<ManagerConfig> {
+    let phase = ExtraPhase {
+        name: name.to_string(),
+        command: task.command.clone(),
+        timeout_secs: task.timeout_secs,
+        after: None,
+        optional: false,
+        network: task.network,
+        skip_if: task.skip_if.clone(),
+    };
+    serde_json::from_value(serde_json::json!({
+        "name": task.name.as_deref().unwrap_or(name),
+        "check_command": "true",
+        "upgrade_all": "",
+        "requires_sudo": task.requires_sudo,
+        "phases": [phase],
+    }))
+    .with_context(|| format!("Invalid task {name}"))
+}
@@ -0,0 +1,56 @@
+use crate::config::ManagerConfig;
+use crate::detect::RunState;
+use crate::execute::{run_captured, shell_quote, RunAs};
+use anyhow::Result;
+use std::time::Duration;
+
+const LIST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A package a manager upgrades on its own, such as a global npm package or
+/// a cargo binary, and how upgrading it went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolUpgrade {
+    pub name: String,
+    pub installed: Option<String>,
+    pub latest: Option<String>,
+    pub state: RunState,
+}
+
+impl ToolUpgrade {
+    /// `name 1.0 → 1.1`, leaving out the versions that aren't known.
+    pub fn describe(&self) -> String {
+        match (&self.installed, &self.latest) {
+            (Some(installed), Some(latest)) => format!("{} {installed} → {latest}", self.name),
+            (Some(version), None) | (None, Some(version)) => format!("{} {version}", self.name),
+            (None, None) => self.name.clone(),
+        }
+    }
+}
+
+/// The tools a manager's tools_command prints, one per line as `name`,
+/// `name installed` or `name installed latest`.
+pub async fn list(command: &str, config: &ManagerConfig) -> Result<Vec<ToolUpgrade>> {
+    let output = run_captured(command, RunAs::manager_query(config), LIST_TIMEOUT).await?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().map(str::to_string);
+            Some(ToolUpgrade {
+                name: fields.next()?,
+                installed: fields.next(),
+                latest: fields.next(),
+                state: RunState::Pending,
+            })
+        })
+        .collect())
+}
+
+/// `template` with `{package}` and, when it's known, `{version}` filled in
+/// for one tool.
+pub fn command(template: &str, tool: &ToolUpgrade) -> String {
+    let command = template.replace("{package}", &shell_quote(&tool.name));
+    match &tool.latest {
+        Some(version) => command.replace("{version}", &shell_quote(version)),
+        None => command.replace("{version}", "latest"),
+    }
+}
@@ -0,0 +1,93 @@
+use crate::config::ManagerConfig;
+use crate::execute::{run_captured, RunAs};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Installed packages by name, with their versions.
+pub type Inventory = BTreeMap<String, String>;
+
+/// What a run actually changed, from the installed packages before and
+/// after it rather than from parsing the upgrade output.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct PackageChanges {
+    #[serde(default)]
+    pub installed: Vec<Package>,
+    #[serde(default)]
+    pub upgraded: Vec<Upgrade>,
+    #[serde(default)]
+    pub removed: Vec<Package>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+}
+
+/// A package whose version changed. Downgrades are listed here too.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Upgrade {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl PackageChanges {
+    pub fn is_empty(&self) -> bool {
+        self.installed.is_empty() && self.upgraded.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The manager's installed packages from its installed_command, or None if
+/// it has none or the command failed.
+pub async fn snapshot(config: &ManagerConfig) -> Option<Inventory> {
+    let command = config.installed_command.as_ref()?;
+    let output = run_captured(command, RunAs::manager_query(config), SNAPSHOT_TIMEOUT)
+        .await
+        .ok()?;
+    Some(parse_inventory(&output))
+}
+
+/// One `name version` pair per line; everything after the name is the
+/// version, so e.g. `brew list --versions` keeping several works too.
+fn parse_inventory(output: &str) -> Inventory {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (name, version) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            (name.to_string(), version.trim().to_string())
+        })
+        .collect()
+}
+
+pub fn diff(before: &Inventory, after: &Inventory) -> PackageChanges {
+    let mut changes = PackageChanges::default();
+    for (name, version) in after {
+        match before.get(name) {
+            None => changes.installed.push(Package {
+                name: name.clone(),
+                version: version.clone(),
+            }),
+            Some(old) if old != version => changes.upgraded.push(Upgrade {
+                name: name.clone(),
+                from: old.clone(),
+                to: version.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    changes.removed = before
+        .iter()
+        .filter(|(name, _)| !after.contains_key(*name))
+        .map(|(name, version)| Package {
+            name: name.clone(),
+            version: version.clone(),
+        })
+        .collect();
+    changes
+}
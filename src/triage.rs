@@ -0,0 +1,257 @@
+use crate::a11y;
+use crate::clipboard;
+use crate::config::Config;
+use crate::detect::{self, RunOrder, RunState};
+use crate::diagnose::{diagnose, Cause};
+use crate::execute::{execute_manager_workflow_simple, RunOptions};
+use crate::history::{self, ManagerRecord, RunRecord};
+use crate::holds;
+use crate::stats::{self, format_timestamp};
+use anyhow::Result;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Number of log lines shown as the key excerpt of a failure.
+const EXCERPT_LINES: usize = 8;
+
+const UNKNOWN_CAUSE: Cause = Cause {
+    id: "unknown",
+    fix: None,
+    remedy: None,
+};
+
+/// The last lines that look like errors, or the tail of the log.
+fn key_excerpt(log: &str) -> Vec<&str> {
+    let lines: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+    let errors: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            ["error", "failed", "fatal", "denied", "e: "]
+                .iter()
+                .any(|k| line.contains(k))
+        })
+        .collect();
+    let source = if errors.is_empty() { lines } else { errors };
+    source[source.len().saturating_sub(EXCERPT_LINES)..].to_vec()
+}
+
+fn read_log(record: &ManagerRecord) -> String {
+    record
+        .log_file
+        .as_deref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+fn report(run: &RunRecord, record: &ManagerRecord, cause: Cause, error: &str, log: &str) -> String {
+    let mut report = format!(
+        "Spine failure report\nManager: {}\nRun: {}\nCause: {}\nError: {error}\n",
+        record.name,
+        format_timestamp(run.started_at),
+        cause.english_title(),
+    );
+    if let Some(fix) = cause.fix {
+        report.push_str(&format!("Suggested fix: {fix}\n"));
+    }
+    report.push_str("\nLog excerpt:\n");
+    for line in key_excerpt(log) {
+        report.push_str(line);
+        report.push('\n');
+    }
+    report
+}
+
+/// Ask a question on the terminal. None means stdin was closed.
+fn prompt(question: &str) -> Result<Option<String>> {
+    print!("{question}");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(input.trim().to_lowercase()))
+}
+
+/// `spn triage`: walk through the managers whose last run failed and offer
+/// a fix for each.
+pub async fn run_triage(config: &Config) -> Result<()> {
+    let history = history::load_history().await;
+    let failures = history::latest_failures(&history);
+
+    if failures.is_empty() {
+        println!("No recent failures - everything is green! 🎉");
+        return Ok(());
+    }
+
+    let total = failures.len();
+    for (index, (run, record)) in failures.into_iter().enumerate() {
+        let configured = config.managers.contains_key(&record.name);
+        let mut error = record.error.clone().unwrap_or_default();
+        let mut log = read_log(record);
+
+        if !a11y::enabled() {
+            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        }
+        println!(
+            "[{}/{total}] {}{} - failed {}",
+            index + 1,
+            record.name,
+            if configured {
+                ""
+            } else {
+                " (no longer configured)"
+            },
+            format_timestamp(run.started_at)
+        );
+
+        let mut show_details = true;
+        loop {
+            let cause = diagnose(&record.name, &error, &log)
+                .copied()
+                .unwrap_or(UNKNOWN_CAUSE);
+            if show_details {
+                print_details(cause, &error, &log);
+                show_details = false;
+            }
+
+            let Some(choice) = prompt(
+                "[r] retry now  [h] hold  [l] open logs  [c] copy report  [n] next  [q] quit: ",
+            )?
+            else {
+                return Ok(());
+            };
+
+            match choice.as_str() {
+                "r" | "retry" if config.report_only => {
+                    println!("Retrying is off in report-only mode.\n");
+                }
+                "r" | "retry" if configured => match retry(config, &record.name).await? {
+                    None => {
+                        println!(
+                            "{}{} upgraded successfully\n",
+                            a11y::mark("✓ "),
+                            record.name
+                        );
+                        break;
+                    }
+                    Some((new_error, new_log)) => {
+                        println!("{}{} failed again\n", a11y::mark("✗ "), record.name);
+                        error = new_error;
+                        log = new_log;
+                        show_details = true;
+                    }
+                },
+                "r" | "retry" => {
+                    println!(
+                        "{} is no longer configured and can't be retried.\n",
+                        record.name
+                    );
+                }
+                "h" | "hold" => {
+                    holds::set_hold(&record.name, true).await?;
+                    println!(
+                        "{}{} is on hold. Release it with 'spn hold --release {}'\n",
+                        a11y::mark("✓ "),
+                        record.name,
+                        record.name
+                    );
+                    break;
+                }
+                "l" | "logs" => match &record.log_file {
+                    Some(path) if path.exists() => open_in_pager(path),
+                    _ => println!("No saved logs for this run.\n"),
+                },
+                "c" | "copy" => {
+                    let text = report(run, record, cause, &error, &log);
+                    if clipboard::copy(&text).await.is_ok() {
+                        println!("{}Report copied to the clipboard\n", a11y::mark("✓ "));
+                    } else {
+                        println!("Couldn't reach the clipboard; here is the report:\n\n{text}");
+                    }
+                }
+                "" | "n" | "next" => break,
+                "q" | "quit" => return Ok(()),
+                _ => println!("Unknown choice '{choice}'\n"),
+            }
+        }
+    }
+
+    println!("Triage complete.");
+    Ok(())
+}
+
+fn print_details(cause: Cause, error: &str, log: &str) {
+    println!("Cause: {}", cause.title());
+    println!("Fix:   {}", cause.hint());
+    if let Some(fix) = cause.fix {
+        println!("Try:   {fix}");
+    }
+    if !error.is_empty() {
+        println!("Error: {error}");
+    }
+    let excerpt = key_excerpt(log);
+    if !excerpt.is_empty() {
+        println!("Log excerpt:");
+        for line in excerpt {
+            println!("  {}{line}", a11y::mark("│ "));
+        }
+    }
+    println!();
+}
+
+/// Run one manager again. Returns None on success, or the new error headline
+/// and logs on failure.
+async fn retry(config: &Config, name: &str) -> Result<Option<(String, String)>> {
+    let detected = detect::detect_package_managers(config).await?;
+    let Some(mut manager) = detected.into_iter().find(|m| m.name == name) else {
+        return Ok(Some((
+            format!("{name} is not available on this system"),
+            String::new(),
+        )));
+    };
+
+    println!("Retrying {name}...");
+    let options = RunOptions {
+        noninteractive: true,
+        exclusions: config.exclusions.packages.clone(),
+        recover: config.recovery.enabled,
+        ..RunOptions::default()
+    };
+    let started_at = std::time::SystemTime::now();
+    execute_manager_workflow_simple(&mut manager, &options).await?;
+
+    let finished = std::slice::from_ref(&manager);
+    if let Err(e) = stats::record_run(finished).await {
+        eprintln!("Warning: could not save run statistics: {e}");
+    }
+    if let Err(e) = history::record_run(
+        started_at,
+        RunOrder::Alphabetical,
+        None,
+        finished,
+        &config.history,
+        None,
+    )
+    .await
+    {
+        eprintln!("Warning: could not save run history: {e}");
+    }
+
+    Ok(match manager.status.state {
+        RunState::Failed | RunState::Unverified => Some((
+            manager.status.error.clone().unwrap_or_default(),
+            manager.logs.contents(),
+        )),
+        _ => None,
+    })
+}
+
+fn open_in_pager(path: &Path) {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    let status = std::process::Command::new(&pager).arg(path).status();
+    if !status.is_ok_and(|s| s.success()) {
+        println!("Logs saved at {}\n", path.display());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::config::{self, Config, ManagerConfig};
+use crate::detect::DetectedManager;
+use crate::execute::{self, RunAs};
+use crate::i18n::t;
+use crate::tasks;
+use crate::wsl;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Fingerprints of the sudo manager definitions the user has accepted, by
+/// manager name. Managers that run as another user through `run_as_user`
+/// count as sudo managers too.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct Trusted {
+    #[serde(default)]
+    managers: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// What to do about a sudo manager whose definition hasn't been accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirm {
+    /// Ask on the terminal, or skip it when there's no terminal.
+    Ask,
+    /// Accept it without asking (`--accept-new-managers`).
+    Accept,
+    /// Skip it; background commands leave accepting to `spn upgrade`.
+    Skip,
+}
+
+fn trusted_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("trusted.toml"))
+}
+
+/// The accepted definitions, or None when nothing has been accepted yet. A
+/// file that can't be read or parsed trusts nothing, so every sudo manager
+/// has to be accepted again rather than the current config being taken as
+/// accepted.
+async fn load_trusted() -> Option<Trusted> {
+    let path = trusted_path()?;
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!("Warning: couldn't read {}: {e}", path.display());
+            return Some(Trusted::default());
+        }
+    };
+    match toml::from_str(&content) {
+        Ok(trusted) => Some(trusted),
+        Err(e) => {
+            eprintln!("Warning: couldn't parse {}: {e}", path.display());
+            Some(Trusted::default())
+        }
+    }
+}
+
+async fn save_trusted(trusted: &Trusted) -> Result<()> {
+    let path = trusted_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(trusted)?).await?;
+    Ok(())
+}
+
+/// A hash of everything about a manager that decides what it runs, and as
+/// whom, where and with which environment (`run_as_user`, `workdir`, `env`).
+/// Unset keys are left out so that keys added in later versions don't change
+/// it, and so is `enabled`, which `spn list --interactive` toggles.
+fn fingerprint(name: &str, config: &ManagerConfig) -> String {
+    fn prune(value: &mut Value) -> bool {
+        match value {
+            Value::Null | Value::Bool(false) => false,
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(map) => {
+                map.retain(|_, value| prune(value));
+                !map.is_empty()
+            }
+            _ => true,
+        }
+    }
+
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    if let Value::Object(map) = &mut value {
+        map.remove("enabled");
+    }
+    prune(&mut value);
+    let hash = Sha256::digest(format!("{name}\n{value}"));
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Whether a manager's commands go through sudo, as root or as its
+/// run_as_user, or enter a WSL distro as root.
+fn uses_sudo(config: &ManagerConfig) -> bool {
+    RunAs::manager(config).uses_sudo() || wsl::runs_as_root(config)
+}
+
+/// The commands a manager runs with sudo, for the confirmation prompt.
+fn sudo_commands(config: &ManagerConfig) -> Vec<&str> {
+    [
+        config.refresh.as_deref(),
+        config.self_update.as_deref(),
+        Some(config.upgrade_all.as_str()),
+        config.full_upgrade.as_deref(),
+        config.cleanup.as_deref(),
+        config.prefetch.as_deref(),
+        config.remove.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|command| !command.is_empty())
+    .chain(config.phases.iter().map(|extra| extra.command.as_str()))
+    .collect()
+}
+
+/// Drop the managers that would run commands with sudo under a definition
+/// the user hasn't accepted: one that's new in the config, or whose commands
+/// changed. The first time this runs against the system config, its sudo
+/// managers and tasks are accepted as they are; a project's `spine.toml`
+/// (`local`) never is.
+pub async fn retain_trusted(
+    config: &Config,
+    managers: &mut Vec<DetectedManager>,
+    confirm: Confirm,
+    local: bool,
+    quiet: bool,
+) -> Result<()> {
+    let mut trusted = match load_trusted().await {
+        Some(trusted) => trusted,
+        None if local => Trusted::default(),
+        None => {
+            let mut trusted = Trusted::default();
+            let tasks = tasks::task_managers(config)?;
+            let definitions = config
+                .managers
+                .iter()
+                .chain(tasks.iter().map(|task| (&task.name, &task.config)));
+            for (name, manager) in definitions.filter(|(_, m)| uses_sudo(m)) {
+                trusted
+                    .managers
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(fingerprint(name, manager));
+            }
+            save_trusted(&trusted).await?;
+            return Ok(());
+        }
+    };
+
+    let ask = confirm == Confirm::Ask && io::stdin().is_terminal();
+    let mut changed = false;
+    let mut kept = Vec::with_capacity(managers.len());
+    for manager in managers.drain(..) {
+        let hash = fingerprint(&manager.name, &manager.config);
+        let accepted = !uses_sudo(&manager.config)
+            || trusted
+                .managers
+                .get(&manager.name)
+                .is_some_and(|hashes| hashes.contains(&hash));
+        let accept = accepted
+            || confirm == Confirm::Accept
+            || (ask && ask_to_trust(&manager.name, &manager.config)?);
+        if !accept {
+            if quiet {
+                println!("{}: SKIPPED (new sudo commands not accepted)", manager.name);
+            } else {
+                println!(
+                    "{}",
+                    t!("upgrade-skip-untrusted", name = manager.name.as_str())
+                );
+            }
+            continue;
+        }
+        if !accepted {
+            trusted
+                .managers
+                .entry(manager.name.clone())
+                .or_default()
+                .insert(hash);
+            changed = true;
+        }
+        kept.push(manager);
+    }
+    *managers = kept;
+
+    if changed {
+        save_trusted(&trusted).await?;
+    }
+    Ok(())
+}
+
+/// Keep user-level managers from running as root, where they'd leave
+/// root-owned files in someone's home directory. When spn runs as root they
+/// run as their run_as_user, or else as the user who ran sudo (or doas), and
+/// are skipped when there's neither.
+pub fn retain_user_level(managers: &mut Vec<DetectedManager>, quiet: bool) {
+    if !execute::is_root() {
+        return;
+    }
+    let sudo_user = sudo_user();
+    managers.retain_mut(|manager| {
+        if !manager.config.user_level || manager.config.run_as_user.is_some() {
+            return true;
+        }
+        if let Some(user) = &sudo_user {
+            manager.config.run_as_user = Some(user.clone());
+            return true;
+        }
+        if quiet {
+            println!(
+                "{}: SKIPPED (user-level manager, spn runs as root)",
+                manager.name
+            );
+        } else {
+            println!("{}", t!("upgrade-skip-root", name = manager.name.as_str()));
+        }
+        false
+    });
+}
+
+/// The user who ran spn through sudo (or doas), when spn runs as root and
+/// that user wasn't root too.
+pub fn sudo_user() -> Option<String> {
+    if !execute::is_root() {
+        return None;
+    }
+    ["SUDO_USER", "DOAS_USER"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|user| !user.is_empty() && user != "root")
+}
+
+/// Show what a manager would run with sudo and ask whether to allow it.
+fn ask_to_trust(name: &str, config: &ManagerConfig) -> Result<bool> {
+    println!("{}", t!("trust-new-manager", name = name));
+    for command in sudo_commands(config) {
+        println!("  {command}");
+    }
+    // Who they run as, where and with what environment are part of what's
+    // accepted, so show those as well
+    if let Some(user) = &config.run_as_user {
+        println!("  run_as_user = {user:?}");
+    }
+    if let Some(workdir) = &config.workdir {
+        println!("  workdir = {workdir:?}");
+    }
+    let env: BTreeMap<_, _> = config.env.iter().collect();
+    for (key, value) in env {
+        println!("  env.{key} = {value:?}");
+    }
+    print!("{} ", t!("trust-prompt", name = name));
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    println!();
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
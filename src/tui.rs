@@ -1,7 +1,20 @@
-use crate::config::Config;
-use crate::detect::{DetectedManager, ManagerStatus};
-use crate::execute::execute_manager_workflow;
+use crate::auto_update;
+use crate::clipboard;
+use crate::config::{Config, ListSort};
+use crate::detect::{DetectedManager, ManagerStatus, Phase, RunState, Stage};
+use crate::execute::{
+    execute_manager_workflow, ControlSender, EventSender, ManagerControl, ManagerEvent, RunOptions,
+};
+use crate::i18n::t;
+use crate::outdated;
+use crate::power::{self, Countdown, PowerAction};
+use crate::schedule;
+use crate::stats::{format_duration_secs, load_stats, Stats};
+use crate::summary;
+use crate::tui_prefs::{self, View, MAX_INFO_HEIGHT, MIN_INFO_HEIGHT};
+use ansi_to_tui::IntoText;
 use anyhow::Result;
+use chrono::{DateTime, Local};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
@@ -9,17 +22,32 @@ use crossterm::{
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Margin},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
-use std::io;
+use regex::{Regex, RegexSet};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::task::JoinSet;
 
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const COMPLETION_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+/// How often the countdown to the next scheduled run is redrawn.
+const COUNTDOWN_INTERVAL: Duration = Duration::from_secs(60);
+/// Smallest terminal the TUI is drawn in.
+const MIN_COLUMNS: u16 = 40;
+const MIN_ROWS: u16 = 12;
+
 #[derive(Debug, Clone, PartialEq)]
 enum AppState {
     ManagerList,
@@ -27,41 +55,167 @@ enum AppState {
     LogsView(usize),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct LogsViewState {
-    scroll_offset: u16,
+    scroll_offset: usize,
+    /// Keep the end of the output in view as it comes in.
+    follow: bool,
+    /// Offset the view was last drawn at, since scroll_offset can point past
+    /// the end.
+    shown_offset: Cell<usize>,
+    /// Line the next-error key last jumped to, highlighted while visible.
+    error_line: Option<usize>,
+    /// The last search for an error line found none.
+    no_errors: bool,
+    /// Outcome of the last copy or save, shown until the next key press.
+    notice: Option<String>,
+}
+
+/// A line being typed at the bottom of the logs view.
+#[derive(Debug, Clone)]
+enum LogsPrompt {
+    /// Sent to the running command's stdin.
+    Input(String),
+    /// Where to save the logs.
+    SavePath(String),
+}
+
+impl LogsPrompt {
+    fn text_mut(&mut self) -> &mut String {
+        match self {
+            LogsPrompt::Input(text) | LogsPrompt::SavePath(text) => text,
+        }
+    }
+}
+
+/// Commands the `:` palette accepts, with their argument and description.
+const COMMANDS: [(&str, &str, &str); 6] = [
+    ("quit", "", "tui-command-quit"),
+    ("retry", "[manager]", "tui-command-retry"),
+    ("cancel", "[manager]", "tui-command-cancel"),
+    ("filter", "[text]", "tui-command-filter"),
+    ("logs", "[manager]", "tui-command-logs"),
+    ("help", "", "tui-command-help"),
+];
+
+/// A command typed into the palette, with the manager it applies to.
+#[derive(Debug, Clone, PartialEq)]
+enum PaletteCommand {
+    Quit,
+    Retry(usize),
+    Cancel(usize),
+    Filter(String),
+    Logs(usize),
+    Help,
 }
 
+/// How the logs view presents output, toggled from the logs view.
+#[derive(Debug, Clone, Copy)]
+struct LogsDisplay {
+    colors: bool,
+    wrap: bool,
+    line_numbers: bool,
+    /// Whether logs views follow new output unless scrolled back.
+    follow: bool,
+}
+
+/// Why the TUI can't be drawn here, if it can't: output doesn't go to a
+/// terminal, the terminal can't move the cursor, or it's too small.
+pub fn unavailable() -> Option<String> {
+    if !io::stdout().is_terminal() {
+        return Some("output isn't a terminal".to_string());
+    }
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return Some("TERM is dumb".to_string());
+    }
+    match crossterm::terminal::size() {
+        Ok((columns, rows)) if columns < MIN_COLUMNS || rows < MIN_ROWS => Some(format!(
+            "the terminal is {columns}x{rows}, smaller than {MIN_COLUMNS}x{MIN_ROWS}"
+        )),
+        Ok(_) => None,
+        Err(e) => Some(format!("the terminal's size can't be read: {e}")),
+    }
+}
+
+/// Run the upgrade in the TUI. With `--shutdown-after` or `--reboot-after`,
+/// also returns how the countdown to that ended, if it started.
 pub async fn run_tui(
     managers: Vec<DetectedManager>,
-    _config: Config,
+    config: Config,
     selective: bool,
-) -> Result<()> {
+    options: RunOptions,
+) -> Result<(Vec<DetectedManager>, Option<Countdown>)> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    let stats = load_stats().await;
+
+    let stages: Vec<Stage> = managers.iter().map(|m| m.stage).collect();
+
+    // Open where the TUI was left last time, if that manager is still here
+    let mut prefs = tui_prefs::load_prefs().await;
+    let restored = prefs
+        .manager
+        .as_deref()
+        .and_then(|name| managers.iter().position(|m| m.name == name));
+
     // Convert managers to shared Arc<Mutex<>> for real-time updates
     let shared_managers: Vec<Arc<Mutex<DetectedManager>>> = managers
         .into_iter()
         .map(|m| Arc::new(Mutex::new(m)))
         .collect();
 
-    let mut selected = 0;
+    let mut selected = restored.unwrap_or(0);
     let mut list_state = ListState::default();
-    list_state.select(Some(0));
-    let mut app_state = AppState::ManagerList;
+    list_state.select(Some(selected));
+    let mut app_state = match (prefs.view, restored) {
+        (View::Detail, Some(index)) => AppState::DetailView(index),
+        (View::Logs, Some(index)) => AppState::LogsView(index),
+        _ => AppState::ManagerList,
+    };
+    let mut info_height = prefs.info_height;
+    let mut sort = prefs.sort.unwrap_or(config.tui.sort);
+    // Counts from the last check, for sorting by pending updates
+    let pending = outdated::load_cache().await.managers;
 
     // Track scroll state for each manager's logs view
     let mut logs_scroll_states: Vec<LogsViewState> = (0..shared_managers.len())
-        .map(|_| LogsViewState { scroll_offset: 0 })
+        .map(|_| LogsViewState {
+            follow: prefs.follow,
+            ..LogsViewState::default()
+        })
         .collect();
 
     // Track which managers have started their workflows
     let mut started_workflows: Vec<bool> = vec![false; shared_managers.len()];
 
+    // Channels for answering a stalled manager, one per started workflow
+    let mut controls: Vec<Option<ControlSender>> = vec![None; shared_managers.len()];
+
+    // Line being typed into the logs view, for the command's stdin or as a
+    // path to save the logs to
+    let mut prompt: Option<LogsPrompt> = None;
+
+    // The `?` overlay, the `:` command being typed, the outcome of the last
+    // command, and the text the manager list is narrowed to
+    let mut show_help = false;
+    let mut palette: Option<String> = None;
+    let mut notice: Option<String> = None;
+    let mut filter = String::new();
+
+    let mut logs_display = LogsDisplay {
+        colors: config.logs.colors,
+        wrap: config.logs.wrap,
+        line_numbers: config.logs.line_numbers,
+        follow: prefs.follow,
+    };
+    // Invalid patterns are reported by `spn doctor`
+    let error_patterns =
+        RegexSet::new(&config.logs.error_patterns).unwrap_or_else(|_| RegexSet::empty());
+
     // Track whether user manually quit to avoid showing summary
     #[allow(unused_assignments)]
     let mut user_quit = false;
@@ -69,18 +223,26 @@ pub async fn run_tui(
     // Track when all operations completed for timed message display
     let mut completion_time: Option<std::time::Instant> = None;
 
-    // Start all manager workflows in parallel (only if not in selective mode)
+    // The title counts down to the next run the scheduler has installed
+    let installed_schedule = auto_update::inspect(&config.auto_update);
+    let counting_down = installed_schedule.next_run(Local::now()).is_some();
+    let mut countdown_at = tokio::time::Instant::now() + COUNTDOWN_INTERVAL;
+
+    // When the machine shuts down or reboots, once everything succeeded
+    let mut power_at: Option<tokio::time::Instant> = None;
+    let mut power_countdown: Option<Countdown> = None;
+
+    // Workflows publish status and log changes here; the loop below only redraws
+    // when one of these (or terminal input) arrives
+    let (events_tx, mut events_rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let mut input_rx = spawn_input_reader();
+
     let mut join_set = JoinSet::new();
-    if !selective {
-        for (i, manager_ref) in shared_managers.iter().enumerate() {
-            let manager_ref = manager_ref.clone();
-            started_workflows[i] = true;
-            join_set.spawn(async move {
-                let _ = execute_manager_workflow(manager_ref).await;
-                i
-            });
-        }
-    }
+
+    // Local copy of manager state for rendering; logs are shared with the
+    // workflows, statuses are kept current from events
+    let mut managers_snapshot = snapshot_managers(&shared_managers).await;
+    let mut needs_redraw = true;
 
     loop {
         // Check for completed tasks
@@ -97,226 +259,690 @@ pub async fn run_tui(
             }
         }
 
-        // Check if all managers are done
-        let all_done = if selective {
-            // In selective mode, only check started workflows
-            let mut all_complete = true;
-            for (i, m) in shared_managers.iter().enumerate() {
-                if started_workflows[i] {
-                    let manager = m.lock().await;
-                    if !matches!(
-                        manager.status,
-                        ManagerStatus::Success | ManagerStatus::Failed(_)
-                    ) {
-                        all_complete = false;
-                        break;
-                    }
+        // Start manager workflows in parallel (only if not in selective
+        // mode), a stage at a time: tasks run before or after the managers
+        if !selective
+            && !managers_snapshot
+                .iter()
+                .zip(&started_workflows)
+                .any(|(manager, started)| *started && !manager.status.is_finished())
+        {
+            let next_stage = stages
+                .iter()
+                .zip(&started_workflows)
+                .filter(|(_, started)| !**started)
+                .map(|(stage, _)| *stage)
+                .min();
+            for (i, manager_ref) in shared_managers.iter().enumerate() {
+                if Some(stages[i]) == next_stage {
+                    started_workflows[i] = true;
+                    controls[i] = Some(spawn_workflow(
+                        &mut join_set,
+                        i,
+                        manager_ref.clone(),
+                        &events_tx,
+                        &options,
+                    ));
                 }
             }
-            all_complete
-        } else {
-            // In non-selective mode, check all managers
-            let mut all_complete = true;
-            for m in shared_managers.iter() {
-                let manager = m.lock().await;
-                if !matches!(
-                    manager.status,
-                    ManagerStatus::Success | ManagerStatus::Failed(_)
-                ) {
-                    all_complete = false;
-                    break;
-                }
-            }
-            all_complete
-        };
+        }
+
+        // Check if all started managers are done
+        let all_done = managers_snapshot
+            .iter()
+            .zip(&started_workflows)
+            .filter(|(_, started)| **started)
+            .all(|(manager, _)| manager.status.is_finished());
 
         // Set completion time when all done for the first time
         if all_done && completion_time.is_none() {
             completion_time = Some(std::time::Instant::now());
+            needs_redraw = true;
+        }
+
+        // Count down to the shutdown or reboot, unless it was cancelled, and
+        // stop if a manager was started in the meantime
+        match options.power {
+            Some(_) if !all_done => power_at = None,
+            Some(_)
+                if power_at.is_none()
+                    && power_countdown.is_none()
+                    && power::allowed(&managers_snapshot, options.power_force) =>
+            {
+                power_at = Some(tokio::time::Instant::now() + power::COUNTDOWN);
+                needs_redraw = true;
+            }
+            _ => {}
         }
 
         // Check if completion message should still be shown (5 seconds)
         let show_completion_message = if let Some(time) = completion_time {
-            time.elapsed().as_secs() < 5
+            time.elapsed() < COMPLETION_MESSAGE_DURATION
         } else {
             false
         };
 
-        // Clone manager data for rendering to avoid blocking in draw
-        let managers_snapshot: Vec<DetectedManager> = {
-            let mut snapshot = Vec::new();
-            for m in shared_managers.iter() {
-                snapshot.push(m.lock().await.clone());
+        // Keep the selection on a manager the filter shows
+        let mut visible = visible_indices(&managers_snapshot, &filter);
+        sort_indices(&mut visible, &managers_snapshot, sort, &stats, &pending);
+        if !visible.contains(&selected) {
+            if let Some(&first) = visible.first() {
+                selected = first;
             }
-            snapshot
-        };
+        }
+        list_state.select(visible.iter().position(|&index| index == selected));
 
-        terminal.draw(|f| {
-            ui(
-                f,
-                &managers_snapshot,
-                &mut list_state,
-                &app_state,
-                &logs_scroll_states,
+        if needs_redraw {
+            let view = ViewContext {
+                managers_snapshot: &managers_snapshot,
+                app_state: &app_state,
+                logs_scroll_states: &logs_scroll_states,
+                stats: &stats,
                 selective,
-                all_done && show_completion_message,
-            )
-        })?;
+                show_completion_message: all_done && show_completion_message,
+                prompt: prompt.as_ref(),
+                visible: &visible,
+                filter: &filter,
+                sort,
+                show_help,
+                palette: palette.as_deref(),
+                notice: notice.as_deref(),
+                info_height,
+                logs_display,
+                next_auto_update: installed_schedule.next_run(Local::now()),
+                power: options.power.zip(power_at).map(|(action, at)| {
+                    let left = at.saturating_duration_since(tokio::time::Instant::now());
+                    (action, left.as_secs_f64().ceil() as u64)
+                }),
+            };
+            terminal.draw(|f| ui(f, &view, &mut list_state))?;
+            needs_redraw = false;
+        }
+
+        // Wake up once more when the completion message expires
+        let message_expiry = async {
+            match completion_time {
+                Some(time) if show_completion_message => {
+                    tokio::time::sleep_until((time + COMPLETION_MESSAGE_DURATION).into()).await
+                }
+                _ => std::future::pending().await,
+            }
+        };
+        let countdown = async {
+            if counting_down {
+                tokio::time::sleep_until(countdown_at).await
+            } else {
+                std::future::pending().await
+            }
+        };
+
+        // Redraw the power countdown every second
+        let power_tick = async {
+            match power_at {
+                Some(at) => {
+                    tokio::time::sleep_until(
+                        at.min(tokio::time::Instant::now() + Duration::from_secs(1)),
+                    )
+                    .await
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        let input = tokio::select! {
+            event = events_rx.recv() => {
+                match event {
+                    Ok(event) => {
+                        needs_redraw |= apply_manager_event(&mut managers_snapshot, &app_state, event);
+                        // Coalesce bursts of output into a single redraw
+                        loop {
+                            match events_rx.try_recv() {
+                                Ok(event) => {
+                                    needs_redraw |= apply_manager_event(&mut managers_snapshot, &app_state, event);
+                                }
+                                Err(broadcast::error::TryRecvError::Lagged(_)) => {
+                                    managers_snapshot = snapshot_managers(&shared_managers).await;
+                                    needs_redraw = true;
+                                }
+                                Err(_) => break,
+                            }
+                        }
+                    }
+                    Err(_) => {
+                        // Missed events - resynchronise from the shared state
+                        managers_snapshot = snapshot_managers(&shared_managers).await;
+                        needs_redraw = true;
+                    }
+                }
+                None
+            }
+            Some(input) = input_rx.recv() => Some(input),
+            () = message_expiry => {
+                needs_redraw = true;
+                None
+            }
+            () = countdown => {
+                countdown_at += COUNTDOWN_INTERVAL;
+                needs_redraw = true;
+                None
+            }
+            () = power_tick => {
+                if power_at.is_some_and(|at| at <= tokio::time::Instant::now()) {
+                    power_countdown = Some(Countdown::Finished);
+                    break;
+                }
+                needs_redraw = true;
+                None
+            }
+        };
 
         // Handle input
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match (&app_state, key.code) {
-                        // Global quit commands
-                        (_, KeyCode::Char('q')) => {
-                            user_quit = true;
-                            break;
+        let Some(input) = input else {
+            continue;
+        };
+
+        match input {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                needs_redraw = true;
+                notice = None;
+                for scroll_state in &mut logs_scroll_states {
+                    scroll_state.notice = None;
+                }
+                match (&app_state, key.code) {
+                    // Typing into the prompt takes every key
+                    (AppState::LogsView(manager_index), code) if prompt.is_some() => match code {
+                        KeyCode::Char(c) => {
+                            if let Some(prompt) = &mut prompt {
+                                prompt.text_mut().push(c);
+                            }
                         }
-                        (AppState::DetailView(_) | AppState::LogsView(_), KeyCode::Esc) => {
-                            app_state = AppState::ManagerList;
+                        KeyCode::Backspace => {
+                            if let Some(prompt) = &mut prompt {
+                                prompt.text_mut().pop();
+                            }
                         }
-                        // Manager list navigation
-                        (AppState::ManagerList, KeyCode::Down | KeyCode::Char('j')) => {
-                            if selected < shared_managers.len() - 1 {
-                                selected += 1;
-                                list_state.select(Some(selected));
+                        KeyCode::Enter => match prompt.take() {
+                            Some(LogsPrompt::Input(line)) => {
+                                if let Some(Some(control)) = controls.get(*manager_index) {
+                                    let _ = control.send(ManagerControl::Input(line));
+                                }
+                            }
+                            Some(LogsPrompt::SavePath(path)) => {
+                                if let (Some(manager), Some(scroll_state)) = (
+                                    managers_snapshot.get(*manager_index),
+                                    logs_scroll_states.get_mut(*manager_index),
+                                ) {
+                                    scroll_state.notice = Some(save_logs(manager, &path).await);
+                                }
+                            }
+                            None => {}
+                        },
+                        KeyCode::Esc => prompt = None,
+                        _ => {}
+                    },
+                    // So does the command palette
+                    (_, code) if palette.is_some() => match code {
+                        KeyCode::Char(c) => {
+                            if let Some(line) = &mut palette {
+                                line.push(c);
                             }
                         }
-                        (AppState::ManagerList, KeyCode::Up | KeyCode::Char('k')) => {
-                            if selected > 0 {
-                                selected -= 1;
-                                list_state.select(Some(selected));
+                        KeyCode::Backspace => {
+                            if let Some(line) = &mut palette {
+                                line.pop();
                             }
                         }
-                        (AppState::ManagerList, KeyCode::Enter) => {
-                            app_state = AppState::DetailView(selected);
+                        KeyCode::Tab => {
+                            if let Some(line) = &mut palette {
+                                *line = complete_command(line, &managers_snapshot);
+                            }
                         }
-                        // Selective mode: start workflow for selected manager
-                        (AppState::ManagerList, KeyCode::Char(' ')) if selective => {
-                            if selected < shared_managers.len() && !started_workflows[selected] {
-                                let manager_ref = shared_managers[selected].clone();
-                                let index = selected;
-                                started_workflows[selected] = true;
-                                join_set.spawn(async move {
-                                    let _ = execute_manager_workflow(manager_ref).await;
-                                    index
-                                });
+                        KeyCode::Esc => palette = None,
+                        KeyCode::Enter => {
+                            let line = palette.take().unwrap_or_default();
+                            let current = match app_state {
+                                AppState::DetailView(index) | AppState::LogsView(index) => index,
+                                AppState::ManagerList => selected,
+                            };
+                            match parse_command(&line, &managers_snapshot, current) {
+                                None => {}
+                                Some(Err(message)) => notice = Some(message),
+                                Some(Ok(PaletteCommand::Quit)) => {
+                                    user_quit = true;
+                                    break;
+                                }
+                                Some(Ok(PaletteCommand::Help)) => show_help = true,
+                                Some(Ok(PaletteCommand::Filter(text))) => {
+                                    filter = text;
+                                    app_state = AppState::ManagerList;
+                                }
+                                Some(Ok(PaletteCommand::Logs(index))) => {
+                                    app_state = AppState::LogsView(index);
+                                }
+                                Some(Ok(PaletteCommand::Cancel(index))) => {
+                                    let manager = &managers_snapshot[index];
+                                    notice = Some(
+                                        if matches!(
+                                            manager.status.state,
+                                            RunState::Running | RunState::Stalled
+                                        ) {
+                                            if let Some(Some(control)) = controls.get(index) {
+                                                let _ = control.send(ManagerControl::Kill);
+                                            }
+                                            t!(
+                                                "tui-palette-cancelling",
+                                                name = manager.name.as_str()
+                                            )
+                                        } else {
+                                            t!(
+                                                "tui-palette-not-running",
+                                                name = manager.name.as_str()
+                                            )
+                                        },
+                                    );
+                                }
+                                Some(Ok(PaletteCommand::Retry(index))) => {
+                                    let name = managers_snapshot[index].name.clone();
+                                    if !matches!(
+                                        managers_snapshot[index].status.state,
+                                        RunState::Failed | RunState::Unverified
+                                    ) {
+                                        notice = Some(t!("tui-palette-not-failed", name = name));
+                                    } else {
+                                        // Start over from a clean status, keeping the
+                                        // earlier attempt's logs above a separator
+                                        {
+                                            let mut manager = shared_managers[index].lock().await;
+                                            manager.logs.push_line("--- Retrying from the TUI ---");
+                                            manager.set_status(
+                                                ManagerStatus::default(),
+                                                Instant::now(),
+                                            );
+                                            manager.duration = None;
+                                            manager.changes = None;
+                                            managers_snapshot[index] = manager.clone();
+                                        }
+                                        started_workflows[index] = true;
+                                        controls[index] = Some(spawn_workflow(
+                                            &mut join_set,
+                                            index,
+                                            shared_managers[index].clone(),
+                                            &events_tx,
+                                            &options,
+                                        ));
+                                        completion_time = None;
+                                        notice = Some(t!("tui-palette-retrying", name = name));
+                                    }
+                                }
                             }
                         }
-                        // Detail view navigation
-                        (AppState::DetailView(manager_index), KeyCode::Char('l')) => {
-                            app_state = AppState::LogsView(*manager_index);
+                        _ => {}
+                    },
+                    // Any key closes the help overlay
+                    _ if show_help => show_help = false,
+                    (_, KeyCode::Char('?')) => show_help = true,
+                    (_, KeyCode::Char(':')) => palette = Some(String::new()),
+                    // Cancel the shutdown or reboot
+                    (_, KeyCode::Char('c')) if power_at.is_some() => {
+                        power_at = None;
+                        power_countdown = Some(Countdown::Cancelled);
+                    }
+                    // Global quit commands
+                    (_, KeyCode::Char('q')) => {
+                        user_quit = true;
+                        break;
+                    }
+                    (AppState::DetailView(_) | AppState::LogsView(_), KeyCode::Esc) => {
+                        app_state = AppState::ManagerList;
+                    }
+                    // Manager list navigation
+                    (AppState::ManagerList, KeyCode::Down | KeyCode::Char('j')) => {
+                        let position = visible.iter().position(|&index| index == selected);
+                        if let Some(&next) = position.and_then(|position| visible.get(position + 1))
+                        {
+                            selected = next;
                         }
-                        (
-                            AppState::DetailView(_) | AppState::LogsView(_),
-                            KeyCode::Char('h') | KeyCode::Left,
-                        ) => {
-                            app_state = AppState::ManagerList;
+                    }
+                    (AppState::ManagerList, KeyCode::Up | KeyCode::Char('k')) => {
+                        let position = visible.iter().position(|&index| index == selected);
+                        if let Some(&previous) = position
+                            .and_then(|position| position.checked_sub(1))
+                            .and_then(|position| visible.get(position))
+                        {
+                            selected = previous;
                         }
-                        // Logs view scrolling
-                        (AppState::LogsView(manager_index), KeyCode::Up | KeyCode::Char('k')) => {
-                            if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
-                                scroll_state.scroll_offset =
-                                    scroll_state.scroll_offset.saturating_sub(1);
-                            }
+                    }
+                    (AppState::ManagerList, KeyCode::Char('s')) => {
+                        sort = next_sort(sort);
+                        prefs.sort = Some(sort);
+                    }
+                    (AppState::ManagerList, KeyCode::Enter) if visible.contains(&selected) => {
+                        app_state = AppState::DetailView(selected);
+                    }
+                    // Selective mode: start workflow for selected manager
+                    (AppState::ManagerList, KeyCode::Char(' '))
+                        if selective
+                            && visible.contains(&selected)
+                            && !started_workflows[selected] =>
+                    {
+                        started_workflows[selected] = true;
+                        controls[selected] = Some(spawn_workflow(
+                            &mut join_set,
+                            selected,
+                            shared_managers[selected].clone(),
+                            &events_tx,
+                            &options,
+                        ));
+                    }
+                    // Open the input box for a running command that accepts stdin
+                    (AppState::LogsView(manager_index), KeyCode::Char('i'))
+                        if managers_snapshot
+                            .get(*manager_index)
+                            .is_some_and(accepts_input) =>
+                    {
+                        prompt = Some(LogsPrompt::Input(String::new()));
+                    }
+                    // Stalled manager: keep waiting, kill it, or answer its prompt
+                    (
+                        AppState::DetailView(manager_index) | AppState::LogsView(manager_index),
+                        KeyCode::Char('w' | 'x'),
+                    ) if managers_snapshot
+                        .get(*manager_index)
+                        .is_some_and(|m| m.status.state == RunState::Stalled) =>
+                    {
+                        let request = match key.code {
+                            KeyCode::Char('w') => ManagerControl::Wait,
+                            _ => ManagerControl::Kill,
+                        };
+                        if let Some(Some(control)) = controls.get(*manager_index) {
+                            let _ = control.send(request);
                         }
-                        (AppState::LogsView(manager_index), KeyCode::Down | KeyCode::Char('j')) => {
-                            if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
-                                scroll_state.scroll_offset =
-                                    scroll_state.scroll_offset.saturating_add(1);
-                            }
+                    }
+                    (
+                        AppState::DetailView(manager_index) | AppState::LogsView(manager_index),
+                        KeyCode::Char('y' | 'n') | KeyCode::Enter,
+                    ) if managers_snapshot.get(*manager_index).is_some_and(|m| {
+                        m.status.state == RunState::Stalled && accepts_input(m)
+                    }) =>
+                    {
+                        let request = match key.code {
+                            KeyCode::Char(answer) => ManagerControl::Input(answer.to_string()),
+                            _ => ManagerControl::Input(String::new()),
+                        };
+                        if let Some(Some(control)) = controls.get(*manager_index) {
+                            let _ = control.send(request);
                         }
-                        (AppState::LogsView(manager_index), KeyCode::PageUp) => {
-                            if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
-                                scroll_state.scroll_offset =
-                                    scroll_state.scroll_offset.saturating_sub(10);
-                            }
+                    }
+                    // Logs view display toggles
+                    (AppState::LogsView(_), KeyCode::Char('c')) => {
+                        logs_display.colors = !logs_display.colors;
+                    }
+                    (AppState::LogsView(_), KeyCode::Char('W')) => {
+                        logs_display.wrap = !logs_display.wrap;
+                    }
+                    (AppState::LogsView(_), KeyCode::Char('N')) => {
+                        logs_display.line_numbers = !logs_display.line_numbers;
+                    }
+                    (AppState::LogsView(_), KeyCode::Char('f')) => {
+                        logs_display.follow = !logs_display.follow;
+                        for scroll_state in &mut logs_scroll_states {
+                            scroll_state.follow = logs_display.follow;
                         }
-                        (AppState::LogsView(manager_index), KeyCode::PageDown) => {
-                            if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
-                                scroll_state.scroll_offset =
-                                    scroll_state.scroll_offset.saturating_add(10);
-                            }
+                    }
+                    // Move the split between the config and the status below it
+                    (AppState::DetailView(_), KeyCode::Char('+' | '=')) => {
+                        info_height = (info_height + 1).min(MAX_INFO_HEIGHT);
+                    }
+                    (AppState::DetailView(_), KeyCode::Char('-')) => {
+                        info_height = info_height.saturating_sub(1).max(MIN_INFO_HEIGHT);
+                    }
+                    // Copy the logs, or save them to a file
+                    (AppState::LogsView(manager_index), KeyCode::Char('y')) => {
+                        if let (Some(manager), Some(scroll_state)) = (
+                            managers_snapshot.get(*manager_index),
+                            logs_scroll_states.get_mut(*manager_index),
+                        ) {
+                            scroll_state.notice = Some(copy_logs(manager).await);
                         }
-                        (AppState::LogsView(manager_index), KeyCode::Home) => {
-                            if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
-                                scroll_state.scroll_offset = 0;
-                            }
+                    }
+                    (AppState::LogsView(manager_index), KeyCode::Char('S')) => {
+                        if let Some(manager) = managers_snapshot.get(*manager_index) {
+                            prompt = Some(LogsPrompt::SavePath(default_save_path(manager)));
                         }
-                        (AppState::LogsView(manager_index), KeyCode::End) => {
-                            if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
-                                // Set to a high value - the render function will clamp it appropriately
-                                scroll_state.scroll_offset = u16::MAX;
-                            }
+                    }
+                    (AppState::LogsView(manager_index), KeyCode::Char('e')) => {
+                        if let (Some(manager), Some(scroll_state)) = (
+                            managers_snapshot.get(*manager_index),
+                            logs_scroll_states.get_mut(*manager_index),
+                        ) {
+                            jump_to_next_error(manager, scroll_state, &error_patterns);
+                        }
+                    }
+                    // Detail view navigation
+                    (AppState::DetailView(manager_index), KeyCode::Char('l')) => {
+                        app_state = AppState::LogsView(*manager_index);
+                    }
+                    (
+                        AppState::DetailView(_) | AppState::LogsView(_),
+                        KeyCode::Char('h') | KeyCode::Left,
+                    ) => {
+                        app_state = AppState::ManagerList;
+                    }
+                    // Logs view scrolling
+                    (AppState::LogsView(manager_index), KeyCode::Up | KeyCode::Char('k')) => {
+                        if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
+                            scroll_state.follow = false;
+                            scroll_state.scroll_offset =
+                                scroll_state.shown_offset.get().saturating_sub(1);
+                        }
+                    }
+                    (AppState::LogsView(manager_index), KeyCode::Down | KeyCode::Char('j')) => {
+                        if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
+                            scroll_state.scroll_offset =
+                                scroll_state.shown_offset.get().saturating_add(1);
+                        }
+                    }
+                    (AppState::LogsView(manager_index), KeyCode::PageUp) => {
+                        if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
+                            scroll_state.follow = false;
+                            scroll_state.scroll_offset =
+                                scroll_state.shown_offset.get().saturating_sub(10);
                         }
-                        _ => {}
                     }
+                    (AppState::LogsView(manager_index), KeyCode::PageDown) => {
+                        if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
+                            scroll_state.scroll_offset =
+                                scroll_state.shown_offset.get().saturating_add(10);
+                        }
+                    }
+                    (AppState::LogsView(manager_index), KeyCode::Home) => {
+                        if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
+                            scroll_state.follow = false;
+                            scroll_state.scroll_offset = 0;
+                        }
+                    }
+                    (AppState::LogsView(manager_index), KeyCode::End) => {
+                        if let Some(scroll_state) = logs_scroll_states.get_mut(*manager_index) {
+                            // Set to a high value - the render function will clamp it appropriately
+                            scroll_state.scroll_offset = usize::MAX;
+                            scroll_state.follow = logs_display.follow;
+                        }
+                    }
+                    _ => {}
                 }
             }
+            Event::Resize(_, _) => {
+                needs_redraw = true;
+            }
+            _ => {}
         }
 
         // No auto-exit - let user decide when to quit
     }
 
+    // Stop the input reader thread before leaving raw mode
+    drop(input_rx);
+
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
+    let final_managers = snapshot_managers(&shared_managers).await;
+
+    let (view, index) = match app_state {
+        AppState::ManagerList => (View::List, selected),
+        AppState::DetailView(index) => (View::Detail, index),
+        AppState::LogsView(index) => (View::Logs, index),
+    };
+    prefs.view = view;
+    prefs.manager = final_managers.get(index).map(|m| m.name.clone());
+    prefs.info_height = info_height;
+    prefs.follow = logs_display.follow;
+    if let Err(e) = tui_prefs::save_prefs(&prefs).await {
+        eprintln!("Warning: could not save the TUI layout: {e}");
+    }
+
     // Only show summary if user didn't manually quit
     if !user_quit {
-        let mut final_managers = Vec::new();
-        for m in shared_managers.iter() {
-            final_managers.push(m.lock().await.clone());
-        }
-
-        print_summary(&final_managers);
+        println!();
+        summary::print_summary(&final_managers);
     }
 
-    Ok(())
+    Ok((final_managers, power_countdown))
 }
 
-fn ui(
-    f: &mut Frame,
-    managers_snapshot: &[DetectedManager],
-    list_state: &mut ListState,
+/// Start a manager's workflow, returning the channel used to answer it if
+/// it stalls.
+fn spawn_workflow(
+    join_set: &mut JoinSet<usize>,
+    index: usize,
+    manager_ref: Arc<Mutex<DetectedManager>>,
+    events_tx: &EventSender,
+    options: &RunOptions,
+) -> ControlSender {
+    let (control_tx, control_rx) = mpsc::unbounded_channel();
+    let events = Some(events_tx.clone());
+    let options = options.clone();
+    join_set.spawn(async move {
+        let _ = execute_manager_workflow(manager_ref, events, Some(control_rx), options).await;
+        index
+    });
+    control_tx
+}
+
+async fn snapshot_managers(
+    shared_managers: &[Arc<Mutex<DetectedManager>>],
+) -> Vec<DetectedManager> {
+    let mut snapshot = Vec::with_capacity(shared_managers.len());
+    for m in shared_managers {
+        snapshot.push(m.lock().await.clone());
+    }
+    snapshot
+}
+
+/// Fold a workflow event into the render snapshot, returning whether the
+/// current view needs to be redrawn.
+fn apply_manager_event(
+    snapshot: &mut [DetectedManager],
     app_state: &AppState,
-    logs_scroll_states: &[LogsViewState],
+    event: ManagerEvent,
+) -> bool {
+    match event {
+        ManagerEvent::Status {
+            manager,
+            status,
+            at,
+        } => {
+            if let Some(m) = snapshot.iter_mut().find(|m| m.name == manager) {
+                // A snapshot taken after the change already includes it
+                if m.updated_at < at {
+                    m.set_status(*status, at);
+                }
+            }
+            true
+        }
+        // Log lines are already in the shared buffer; only the logs view of
+        // that manager shows them
+        ManagerEvent::Log { manager } => match app_state {
+            AppState::LogsView(index) => snapshot.get(*index).is_some_and(|m| m.name == manager),
+            _ => false,
+        },
+    }
+}
+
+/// Read terminal input on a blocking thread and forward it to the async loop.
+/// The thread exits once the receiver is dropped.
+fn spawn_input_reader() -> mpsc::UnboundedReceiver<Event> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while !tx.is_closed() {
+            match event::poll(INPUT_POLL_INTERVAL) {
+                Ok(true) => match event::read() {
+                    Ok(input) => {
+                        if tx.send(input).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                },
+                Ok(false) => {}
+                Err(_) => break,
+            }
+        }
+    });
+    rx
+}
+
+/// Read-only state needed to render a frame.
+struct ViewContext<'a> {
+    managers_snapshot: &'a [DetectedManager],
+    app_state: &'a AppState,
+    logs_scroll_states: &'a [LogsViewState],
+    stats: &'a Stats,
     selective: bool,
     show_completion_message: bool,
-) {
-    match app_state {
-        AppState::ManagerList => {
-            render_manager_list(
-                f,
-                managers_snapshot,
-                list_state,
-                selective,
-                show_completion_message,
-            );
-        }
+    prompt: Option<&'a LogsPrompt>,
+    /// Indices of the managers the list filter lets through.
+    visible: &'a [usize],
+    filter: &'a str,
+    sort: ListSort,
+    show_help: bool,
+    palette: Option<&'a str>,
+    /// Outcome of the last palette command, shown until the next key press.
+    notice: Option<&'a str>,
+    /// Height of the detail view's config pane.
+    info_height: u16,
+    logs_display: LogsDisplay,
+    next_auto_update: Option<DateTime<Local>>,
+    /// The pending shutdown or reboot and the seconds left before it.
+    power: Option<(PowerAction, u64)>,
+}
+
+fn ui(f: &mut Frame, view: &ViewContext, list_state: &mut ListState) {
+    match view.app_state {
+        AppState::ManagerList => render_manager_list(f, view, list_state),
         AppState::DetailView(manager_index) => {
-            if let Some(manager) = managers_snapshot.get(*manager_index) {
-                render_detail_view(f, manager);
+            if let Some(manager) = view.managers_snapshot.get(*manager_index) {
+                render_detail_view(f, manager, view);
             }
         }
         AppState::LogsView(manager_index) => {
-            if let Some(manager) = managers_snapshot.get(*manager_index) {
-                if let Some(scroll_state) = logs_scroll_states.get(*manager_index) {
-                    render_logs_view(f, manager, scroll_state);
+            if let Some(manager) = view.managers_snapshot.get(*manager_index) {
+                if let Some(scroll_state) = view.logs_scroll_states.get(*manager_index) {
+                    render_logs_view(f, manager, scroll_state, view);
                 }
             }
         }
     }
+    if view.show_help {
+        render_help_overlay(f, view.selective);
+    }
+    if let Some(line) = view.palette {
+        render_palette(f, line, view.managers_snapshot);
+    }
 }
 
-fn render_manager_list(
-    f: &mut Frame,
-    managers_snapshot: &[DetectedManager],
-    list_state: &mut ListState,
-    selective: bool,
-    show_completion_message: bool,
-) {
+fn render_manager_list(f: &mut Frame, view: &ViewContext, list_state: &mut ListState) {
     let area = f.area().inner(Margin {
         horizontal: 2,
         vertical: 1,
@@ -327,58 +953,121 @@ fn render_manager_list(
         .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
         .split(area);
 
-    let items: Vec<ListItem> = managers_snapshot
+    let mut items: Vec<ListItem> = view
+        .visible
         .iter()
+        .filter_map(|&index| view.managers_snapshot.get(index))
         .map(|manager| {
-            let status_style = match manager.status {
-                ManagerStatus::Success => Style::default().fg(Color::Green),
-                ManagerStatus::Failed(_) => Style::default().fg(Color::Red),
+            let status_style = match manager.status.state {
+                RunState::Success if manager.status.attention.is_some() => {
+                    Style::default().fg(Color::Yellow)
+                }
+                RunState::Success => Style::default().fg(Color::Green),
+                RunState::Failed => Style::default().fg(Color::Red),
+                RunState::Unverified => Style::default().fg(Color::LightRed),
+                RunState::Stalled => Style::default().fg(Color::Magenta),
                 _ => Style::default().fg(Color::Yellow),
             };
 
-            let status_text = match &manager.status {
-                ManagerStatus::Pending => "Pending".to_string(),
-                ManagerStatus::Running(operation) => format!("{operation}..."),
-                ManagerStatus::Success => "✓ Complete".to_string(),
-                ManagerStatus::Failed(_err) => "✗ Failed".to_string(),
+            let operation = manager.status.operation();
+            let status_text = match manager.status.state {
+                RunState::Pending => t!("tui-pending"),
+                RunState::Running => t!("tui-running", operation = operation),
+                RunState::Stalled => t!("tui-stalled", operation = operation),
+                RunState::Success if manager.status.attention.is_some() => t!("tui-attention"),
+                RunState::Success => t!("tui-complete"),
+                RunState::Failed => t!("tui-failed"),
+                RunState::Unverified => t!("tui-unverified"),
             };
 
-            ListItem::new(Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!("{:<20}", manager.name), Style::default()),
                 Span::styled(status_text, status_style),
-            ]))
+            ];
+            if let (RunState::Running | RunState::Stalled, Some(progress)) =
+                (manager.status.state, manager.status.progress)
+            {
+                spans.push(Span::styled(
+                    format!(" {}", progress_bar(progress)),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+
+            // Tools upgraded one at a time go under their manager
+            let mut lines = vec![Line::from(spans)];
+            lines.extend(tool_lines(manager).into_iter().map(|line| {
+                Line::styled(format!("  {line}"), Style::default().fg(Color::DarkGray))
+            }));
+            ListItem::new(Text::from(lines))
         })
         .collect();
+    if items.is_empty() && !view.filter.is_empty() {
+        items.push(ListItem::new(Span::styled(
+            t!("tui-list-no-match", filter = view.filter),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
 
+    let mut title = match view.next_auto_update {
+        Some(next) => t!(
+            "tui-list-title-next",
+            countdown = schedule::format_countdown(next - Local::now()),
+            time = next.format("%a %H:%M").to_string()
+        ),
+        None => t!("tui-list-title"),
+    };
+    title = format!(
+        "{title} - {}",
+        t!("tui-list-sort", sort = sort_label(view.sort))
+    );
+    if !view.filter.is_empty() {
+        title = format!("{title} - {}", t!("tui-list-filter", filter = view.filter));
+    }
     let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Package Managers - Spine"),
-        )
+        .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
 
     f.render_stateful_widget(list, chunks[0], list_state);
 
-    // Help text or completion message
-    let help_text = if show_completion_message {
-        Paragraph::new("All operations completed! Press 'q' to quit or navigate to view details.")
-            .block(Block::default().borders(Borders::ALL).title("Status"))
+    // Help text, completion message or the countdown to a shutdown
+    let help_text = if let Some((action, seconds)) = view.power {
+        Paragraph::new(action.tui_countdown(seconds))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t!("tui-status")),
+            )
+            .style(Style::default().fg(Color::Yellow))
+    } else if view.show_completion_message {
+        Paragraph::new(t!("tui-all-done"))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(t!("tui-status")),
+            )
             .style(Style::default().fg(Color::Green))
-    } else if selective {
-        Paragraph::new("Navigate: ↑↓/j k | Start: Space | Detail: Enter | Quit: q")
-            .block(Block::default().borders(Borders::ALL).title("Help"))
-            .style(Style::default().fg(Color::Cyan))
     } else {
-        Paragraph::new("Navigate: ↑↓/j k | Detail: Enter | Quit: q")
-            .block(Block::default().borders(Borders::ALL).title("Help"))
+        let mut keys: Vec<String> = view.notice.map(str::to_string).into_iter().collect();
+        keys.push(t!("tui-help-navigate"));
+        if view.selective {
+            keys.push(t!("tui-help-start"));
+        }
+        keys.extend([
+            t!("tui-help-detail"),
+            t!("tui-help-sort"),
+            t!("tui-help-help"),
+            t!("tui-help-quit"),
+        ]);
+        Paragraph::new(keys.join(" | "))
+            .block(Block::default().borders(Borders::ALL).title(t!("tui-help")))
             .style(Style::default().fg(Color::Cyan))
     };
 
     f.render_widget(help_text, chunks[1]);
 }
 
-fn render_detail_view(f: &mut Frame, manager: &DetectedManager) {
+fn render_detail_view(f: &mut Frame, manager: &DetectedManager, view: &ViewContext) {
+    let stats = view.stats.managers.get(&manager.name);
     let area = f.area().inner(Margin {
         horizontal: 2,
         vertical: 1,
@@ -388,7 +1077,8 @@ fn render_detail_view(f: &mut Frame, manager: &DetectedManager) {
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Length(7),
+                Constraint::Length(view.info_height),
+                Constraint::Length(4),
                 Constraint::Min(0),
                 Constraint::Length(3),
             ]
@@ -397,58 +1087,220 @@ fn render_detail_view(f: &mut Frame, manager: &DetectedManager) {
         .split(area);
 
     // Manager info block
-    let info_text = format!(
-        "Name: {}\nCheck Command: {}\nRefresh: {}\nSelf-Update: {}\nUpgrade: {}\nCleanup: {}",
-        manager.config.name,
-        manager.config.check_command,
-        manager.config.refresh.as_deref().unwrap_or("N/A"),
-        manager.config.self_update.as_deref().unwrap_or("N/A"),
-        manager.config.upgrade_all,
-        manager.config.cleanup.as_deref().unwrap_or("N/A")
-    );
+    let unset = t!("tui-config-unset");
+    let info_text = [
+        t!("tui-config-name", value = manager.config.name.as_str()),
+        t!(
+            "tui-config-check",
+            value = manager.config.check_command.as_str()
+        ),
+    ]
+    .into_iter()
+    .chain(Phase::ALL.into_iter().map(|phase| {
+        format!(
+            "{}: {}",
+            phase.title(),
+            manager.config.command(&phase).unwrap_or(&unset)
+        )
+    }))
+    .chain(
+        manager
+            .config
+            .phases
+            .iter()
+            .map(|extra| format!("{}: {}", extra.name, extra.command)),
+    )
+    .collect::<Vec<_>>()
+    .join("\n");
 
     let info_block = Paragraph::new(info_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Manager Configuration"),
+                .title(t!("tui-config")),
         )
         .wrap(Wrap { trim: true });
 
     f.render_widget(info_block, chunks[0]);
 
+    // Cumulative statistics from previous runs
+    let stats_text = match stats {
+        Some(stats) => format!(
+            "{}\n{}",
+            stats.summary(),
+            t!(
+                "stats-last-failure",
+                error = stats
+                    .last_failure
+                    .clone()
+                    .unwrap_or_else(|| t!("stats-none"))
+            )
+        ),
+        None => t!("stats-no-runs"),
+    };
+
+    let stats_block = Paragraph::new(stats_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t!("tui-statistics")),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(stats_block, chunks[1]);
+
     // Status and logs
-    let status_color = match manager.status {
-        ManagerStatus::Success => Color::Green,
-        ManagerStatus::Failed(_) => Color::Red,
+    let status_color = match manager.status.state {
+        RunState::Success if manager.status.attention.is_some() => Color::Yellow,
+        RunState::Success => Color::Green,
+        RunState::Failed => Color::Red,
+        RunState::Unverified => Color::LightRed,
+        RunState::Stalled => Color::Magenta,
         _ => Color::Yellow,
     };
 
-    let status_text = match &manager.status {
-        ManagerStatus::Pending => "Status: Pending".to_string(),
-        ManagerStatus::Running(operation) => {
-            format!("Status: {operation}...")
-        }
-        ManagerStatus::Success => "Status: ✓ All operations completed successfully".to_string(),
-        ManagerStatus::Failed(err) => format!("Status: ✗ Failed - {err}"),
+    let operation = manager.status.operation();
+    let status = match manager.status.state {
+        RunState::Pending => t!("tui-pending"),
+        RunState::Running => t!("tui-running", operation = operation),
+        RunState::Stalled => t!("tui-status-stalled", operation = operation.to_lowercase()),
+        RunState::Success => match &manager.status.attention {
+            Some(reason) => t!("tui-status-attention", reason = reason.as_str()),
+            None => t!("tui-status-success"),
+        },
+        RunState::Failed => t!("tui-status-failed", details = manager.failure_details()),
+        RunState::Unverified => t!("tui-status-unverified", details = manager.failure_details()),
+    };
+    let status_text = t!("tui-status-line", status = status);
+
+    // Finished phases, then the one still running
+    let mut phases: Vec<String> = manager
+        .status
+        .phases
+        .iter()
+        .map(|result| format!("  {}", result.summary()))
+        .collect();
+    if let (RunState::Running | RunState::Stalled, Some(phase), Some(started)) = (
+        manager.status.state,
+        &manager.status.phase,
+        manager.status.phase_started,
+    ) {
+        let progress = manager
+            .status
+            .progress
+            .map(|progress| format!(" {}", progress_bar(progress)))
+            .unwrap_or_default();
+        phases.push(format!(
+            "  … {} {}{progress}",
+            phase.title(),
+            format_duration_secs(started.elapsed().as_secs_f64())
+        ));
+    }
+
+    // A recognised failure goes above the logs that follow the status
+    let status_text = match &manager.status.cause {
+        Some(cause) => format!("{}\n\n{status_text}", cause.describe().join("\n")),
+        None => status_text,
+    };
+
+    let timeline = t!("tui-timeline", timeline = manager.timeline_summary());
+    let mut sections = Vec::new();
+    if !phases.is_empty() {
+        sections.push(format!("{}\n{}", t!("tui-phases"), phases.join("\n")));
+    }
+    let tools = tool_lines(manager);
+    if !tools.is_empty() {
+        sections.push(format!("{}\n{}", t!("tui-tools"), tools.join("\n")));
+    }
+    let status_text = if sections.is_empty() {
+        format!("{timeline}\n\n{status_text}")
+    } else {
+        format!("{timeline}\n{}\n\n{status_text}", sections.join("\n"))
     };
 
     let status_block = Paragraph::new(Text::from(status_text))
-        .block(Block::default().borders(Borders::ALL).title("Status"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t!("tui-status")),
+        )
         .style(Style::default().fg(status_color))
         .wrap(Wrap { trim: true });
 
-    f.render_widget(status_block, chunks[1]);
+    f.render_widget(status_block, chunks[2]);
 
     // Help text for detail view
-    let help_text = Paragraph::new("Back: Esc/h/← | Logs: l | Quit: q")
-        .block(Block::default().borders(Borders::ALL).title("Help"))
+    let mut keys: Vec<String> = view.notice.map(str::to_string).into_iter().collect();
+    keys.extend(stalled_help(manager));
+    keys.extend([
+        t!("tui-help-back"),
+        t!("tui-help-logs"),
+        t!("tui-help-resize"),
+        t!("tui-help-help"),
+        t!("tui-help-quit"),
+    ]);
+    let help_text = Paragraph::new(keys.join(" | "))
+        .block(Block::default().borders(Borders::ALL).title(t!("tui-help")))
         .style(Style::default().fg(Color::Cyan));
 
-    f.render_widget(help_text, chunks[2]);
+    f.render_widget(help_text, chunks[3]);
 }
 
-fn render_logs_view(f: &mut Frame, manager: &DetectedManager, scroll_state: &LogsViewState) {
+/// One line per tool the manager upgrades one at a time, e.g.
+/// `✓ ripgrep 14.0.0 → 14.1.0`.
+fn tool_lines(manager: &DetectedManager) -> Vec<String> {
+    manager
+        .status
+        .tools
+        .iter()
+        .map(|tool| {
+            let glyph = match tool.state {
+                RunState::Success => "✓",
+                RunState::Failed => "✗",
+                RunState::Running | RunState::Stalled => "…",
+                _ => "·",
+            };
+            format!("  {glyph} {}", tool.describe())
+        })
+        .collect()
+}
+
+/// Text gauge for a progress ratio, e.g. `[██████░░░░░░░░░░░░░░]  30%`.
+fn progress_bar(progress: f64) -> String {
+    const WIDTH: usize = 20;
+    let filled = ((progress * WIDTH as f64).round() as usize).min(WIDTH);
+    format!(
+        "[{}{}] {:>3.0}%",
+        "█".repeat(filled),
+        "░".repeat(WIDTH - filled),
+        progress * 100.0
+    )
+}
+
+/// Whether the manager's current command reads lines typed in the TUI.
+fn accepts_input(manager: &DetectedManager) -> bool {
+    manager.config.allow_stdin
+        && matches!(manager.status.state, RunState::Running | RunState::Stalled)
+}
+
+/// Key hints for answering a stalled manager, or nothing if it isn't stalled.
+fn stalled_help(manager: &DetectedManager) -> Vec<String> {
+    match manager.status.state {
+        RunState::Stalled if manager.config.allow_stdin => {
+            vec![t!("tui-help-stalled"), t!("tui-help-answer")]
+        }
+        RunState::Stalled => vec![t!("tui-help-stalled")],
+        _ => Vec::new(),
+    }
+}
+
+fn render_logs_view(
+    f: &mut Frame,
+    manager: &DetectedManager,
+    scroll_state: &LogsViewState,
+    view: &ViewContext,
+) {
+    let display = view.logs_display;
     let area = f.area().inner(Margin {
         horizontal: 2,
         vertical: 1,
@@ -467,126 +1319,531 @@ fn render_logs_view(f: &mut Frame, manager: &DetectedManager, scroll_state: &Log
         .split(area);
 
     // Title block
-    let title_text = format!("{} - Live Logs", manager.name);
+    let dropped = manager.logs.dropped();
+    let title_text = if dropped > 0 {
+        t!(
+            "tui-logs-title-dropped",
+            name = manager.name.as_str(),
+            dropped = dropped
+        )
+    } else {
+        t!("tui-logs-title", name = manager.name.as_str())
+    };
     let title_block = Paragraph::new(title_text)
-        .block(Block::default().borders(Borders::ALL).title("Logs"))
+        .block(Block::default().borders(Borders::ALL).title(t!("tui-logs")))
         .style(Style::default().fg(Color::Cyan));
 
     f.render_widget(title_block, chunks[0]);
 
-    // Raw logs content - show actual package manager output
-    let logs_text = if manager.logs.is_empty() {
-        match &manager.status {
-            ManagerStatus::Pending => "Process not started yet...".to_string(),
-            ManagerStatus::Running(_) => "No output yet...".to_string(),
-            ManagerStatus::Success => {
-                "Command completed successfully - no output captured".to_string()
-            }
-            ManagerStatus::Failed(err) => err.clone(),
-        }
-    } else {
-        manager.logs.clone()
-    };
-
-    let status_color = match manager.status {
-        ManagerStatus::Success => Color::Green,
-        ManagerStatus::Failed(_) => Color::Red,
+    let status_color = match manager.status.state {
+        RunState::Success => Color::Green,
+        RunState::Failed => Color::Red,
+        RunState::Unverified => Color::LightRed,
         _ => Color::Yellow,
     };
 
-    // Calculate scroll bounds
-    let content_height = logs_text.lines().count() as u16;
-    let display_height = chunks[1].height.saturating_sub(2); // Subtract borders
-    let max_scroll = content_height.saturating_sub(display_height);
-    let scroll_offset = scroll_state.scroll_offset.min(max_scroll);
+    let display_height = chunks[1].height.saturating_sub(2) as usize; // Subtract borders
 
-    let logs_block = Paragraph::new(Text::from(logs_text))
+    // Raw logs content - only the visible window is copied out of the buffer
+    let (content_height, scroll_offset, max_scroll, logs_block) = if manager.logs.is_empty() {
+        let placeholder = match manager.status.state {
+            RunState::Pending => t!("tui-logs-not-started"),
+            RunState::Running | RunState::Stalled => t!("tui-logs-no-output"),
+            RunState::Success => t!("tui-logs-success"),
+            RunState::Failed | RunState::Unverified => manager.failure_details(),
+        };
+
+        let content_height = placeholder.lines().count();
+        let max_scroll = content_height.saturating_sub(display_height);
+        let scroll_offset = shown_offset(scroll_state, max_scroll);
+        let block = Paragraph::new(log_text(&placeholder, display.colors))
+            .scroll((scroll_offset.min(u16::MAX as usize) as u16, 0));
+        (content_height, scroll_offset, max_scroll, block)
+    } else {
+        let content_height = manager.logs.len();
+        let max_scroll = content_height.saturating_sub(display_height);
+        let scroll_offset = shown_offset(scroll_state, max_scroll);
+        let visible = manager.logs.lines(scroll_offset, display_height).join("\n");
+        let mut text = log_text(&visible, display.colors);
+        let width = content_height.to_string().len();
+        for (row, line) in text.lines.iter_mut().enumerate() {
+            let number = scroll_offset + row;
+            if scroll_state.error_line == Some(number) {
+                line.style = line.style.add_modifier(Modifier::REVERSED);
+            }
+            if display.line_numbers {
+                line.spans.insert(
+                    0,
+                    Span::styled(
+                        format!("{:>width$} ", number + 1),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                );
+            }
+        }
+        (
+            content_height,
+            scroll_offset,
+            max_scroll,
+            Paragraph::new(text),
+        )
+    };
+
+    let logs_block = logs_block
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(status_color))
-        .wrap(Wrap { trim: true })
-        .scroll((scroll_offset, 0));
+        .style(Style::default().fg(status_color));
+    let logs_block = if display.wrap {
+        logs_block.wrap(Wrap { trim: true })
+    } else {
+        logs_block
+    };
 
     f.render_widget(logs_block, chunks[1]);
 
     // Help text for logs view with scroll indicator
-    let scroll_indicator = if content_height > display_height {
-        format!(
-            " | Scroll: ↑↓/jk PgUp/PgDn Home/End ({}/{})",
-            scroll_offset + 1,
-            max_scroll + 1
+    let scroll_indicator = (content_height > display_height).then(|| {
+        t!(
+            "tui-help-scroll",
+            position = scroll_offset + 1,
+            total = max_scroll + 1
         )
-    } else {
-        String::new()
-    };
+    });
+
+    // The prompt replaces the help line while typing
+    if let Some(prompt) = view.prompt {
+        let (title, line) = match prompt {
+            LogsPrompt::Input(line) => (t!("tui-input-title"), line),
+            LogsPrompt::SavePath(path) => (t!("tui-save-title"), path),
+        };
+        let input_box = Paragraph::new(format!("> {line}█"))
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .style(Style::default().fg(Color::Yellow));
+        f.render_widget(input_box, chunks[2]);
+        return;
+    }
 
-    let help_text = Paragraph::new(format!("Back: Esc/h/← | Quit: q{scroll_indicator}"))
-        .block(Block::default().borders(Borders::ALL).title("Help"))
+    let mut keys: Vec<String> = scroll_state.notice.iter().cloned().collect();
+    keys.extend(view.notice.map(str::to_string));
+    if scroll_state.follow {
+        keys.push(t!("tui-logs-following"));
+    }
+    if scroll_state.no_errors {
+        keys.push(t!("tui-logs-no-errors"));
+    }
+    keys.extend(stalled_help(manager));
+    if accepts_input(manager) {
+        keys.push(t!("tui-help-input"));
+    }
+    keys.extend([
+        t!("tui-help-view"),
+        t!("tui-help-export"),
+        t!("tui-help-back"),
+        t!("tui-help-help"),
+        t!("tui-help-quit"),
+    ]);
+    keys.extend(scroll_indicator);
+    let help_text = Paragraph::new(keys.join(" | "))
+        .block(Block::default().borders(Borders::ALL).title(t!("tui-help")))
         .style(Style::default().fg(Color::Cyan));
 
     f.render_widget(help_text, chunks[2]);
 }
 
-fn print_summary(managers: &[DetectedManager]) {
-    let total = managers.len();
-    let successful = managers
+/// Where to draw the logs from: the end while following, otherwise the
+/// scroll position clamped to the output. Remembered for the scroll keys.
+fn shown_offset(scroll_state: &LogsViewState, max_scroll: usize) -> usize {
+    let offset = if scroll_state.follow {
+        max_scroll
+    } else {
+        scroll_state.scroll_offset.min(max_scroll)
+    };
+    scroll_state.shown_offset.set(offset);
+    offset
+}
+
+/// A `width` by `height` area centred in `area`, shrunk to fit.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Every key, view by view, and the palette's commands, over whatever view
+/// is open.
+fn render_help_overlay(f: &mut Frame, selective: bool) {
+    let heading = |id: &str| {
+        Line::from(Span::styled(
+            t!(id),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+    };
+    let keys = |keys: Vec<String>| keys.into_iter().map(|key| Line::from(format!("  {key}")));
+
+    let mut lines = vec![heading("tui-keys-everywhere")];
+    lines.extend(keys(vec![
+        t!("tui-help-help"),
+        t!("tui-help-palette"),
+        t!("tui-help-quit"),
+    ]));
+    lines.push(heading("tui-keys-list"));
+    let mut list_keys = vec![
+        t!("tui-help-navigate"),
+        t!("tui-help-detail"),
+        t!("tui-help-sort"),
+    ];
+    if selective {
+        list_keys.push(t!("tui-help-start"));
+    }
+    lines.extend(keys(list_keys));
+    lines.push(heading("tui-keys-detail"));
+    lines.extend(keys(vec![
+        t!("tui-help-logs"),
+        t!("tui-help-resize"),
+        t!("tui-help-back"),
+        t!("tui-help-stalled"),
+        t!("tui-help-answer"),
+    ]));
+    lines.push(heading("tui-keys-logs"));
+    lines.extend(keys(vec![
+        t!("tui-help-scroll-keys"),
+        t!("tui-help-view"),
+        t!("tui-help-export"),
+        t!("tui-help-input"),
+        t!("tui-help-back"),
+    ]));
+    lines.push(heading("tui-keys-commands"));
+    lines.extend(COMMANDS.iter().map(|(name, argument, description)| {
+        Line::from(vec![
+            Span::styled(
+                format!("  :{:<18}", format!("{name} {argument}")),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(t!(description)),
+        ])
+    }));
+    lines.push(Line::from(format!("  {}", t!("tui-command-target"))));
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        t!("tui-help-close"),
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let area = centered(f.area(), 90, lines.len() as u16 + 2);
+    let overlay = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t!("tui-keys-title")),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+/// The command being typed, with what it could complete to, over the help
+/// line at the bottom.
+fn render_palette(f: &mut Frame, line: &str, managers: &[DetectedManager]) {
+    let area = f.area().inner(Margin {
+        horizontal: 2,
+        vertical: 1,
+    });
+    let height = 4.min(area.height);
+    let area = Rect {
+        y: area.y + area.height - height,
+        height,
+        ..area
+    };
+    let text = vec![
+        Line::from(format!(":{line}█")),
+        Line::from(Span::styled(
+            palette_suggestions(line, managers).join(" · "),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let palette = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t!("tui-palette-title")),
+        )
+        .style(Style::default().fg(Color::Yellow));
+    f.render_widget(Clear, area);
+    f.render_widget(palette, area);
+}
+
+/// Indices of the managers whose key, name or state contains `filter`,
+/// ignoring case; all of them when it's empty.
+fn visible_indices(managers: &[DetectedManager], filter: &str) -> Vec<usize> {
+    let filter = filter.to_lowercase();
+    managers
         .iter()
-        .filter(|m| matches!(m.status, ManagerStatus::Success))
-        .count();
-    let failed = managers
+        .enumerate()
+        .filter(|(_, manager)| {
+            [
+                manager.name.as_str(),
+                manager.config.name.as_str(),
+                manager.status.state.label(),
+            ]
+            .iter()
+            .any(|text| text.to_lowercase().contains(&filter))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Put `indices` in the list's sort order, keeping the run order among
+/// managers that compare equal.
+fn sort_indices(
+    indices: &mut [usize],
+    managers: &[DetectedManager],
+    sort: ListSort,
+    stats: &Stats,
+    pending: &BTreeMap<String, usize>,
+) {
+    match sort {
+        ListSort::Config => {}
+        ListSort::Name => indices.sort_by_cached_key(|&index| managers[index].name.to_lowercase()),
+        ListSort::Status => indices.sort_by_key(|&index| match managers[index].status.state {
+            RunState::Failed => 0,
+            RunState::Unverified => 1,
+            RunState::Stalled => 2,
+            RunState::Running => 3,
+            RunState::Pending => 4,
+            RunState::Success => 5,
+        }),
+        ListSort::Duration => {
+            // This run's time once it's known, so the order doesn't shift
+            // while managers run
+            let secs = |index: usize| {
+                let manager = &managers[index];
+                manager.duration.map_or_else(
+                    || {
+                        stats
+                            .managers
+                            .get(&manager.name)
+                            .map_or(0.0, |stats| stats.average_duration_secs())
+                    },
+                    |duration| duration.as_secs_f64(),
+                )
+            };
+            indices.sort_by(|&a, &b| secs(b).total_cmp(&secs(a)));
+        }
+        // Managers never checked go last
+        ListSort::Pending => {
+            indices.sort_by_key(|&index| Reverse(pending.get(&managers[index].name).copied()))
+        }
+    }
+}
+
+/// The sort `s` switches to after `sort`.
+fn next_sort(sort: ListSort) -> ListSort {
+    match sort {
+        ListSort::Config => ListSort::Name,
+        ListSort::Name => ListSort::Status,
+        ListSort::Status => ListSort::Duration,
+        ListSort::Duration => ListSort::Pending,
+        ListSort::Pending => ListSort::Config,
+    }
+}
+
+fn sort_label(sort: ListSort) -> String {
+    match sort {
+        ListSort::Config => t!("tui-sort-config"),
+        ListSort::Name => t!("tui-sort-name"),
+        ListSort::Status => t!("tui-sort-status"),
+        ListSort::Duration => t!("tui-sort-duration"),
+        ListSort::Pending => t!("tui-sort-pending"),
+    }
+}
+
+/// Split a palette line into its command word and the rest.
+fn split_command(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    match line.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (line, ""),
+    }
+}
+
+/// Commands starting with `word`, or only the one it names exactly.
+fn matching_commands(word: &str) -> Vec<&'static (&'static str, &'static str, &'static str)> {
+    let word = word.to_lowercase();
+    match COMMANDS.iter().find(|(name, ..)| *name == word) {
+        Some(exact) => vec![exact],
+        None => COMMANDS
+            .iter()
+            .filter(|(name, ..)| name.starts_with(&word))
+            .collect(),
+    }
+}
+
+/// Managers whose key starts with `prefix`, ignoring case, or only the one
+/// it names exactly.
+fn matching_managers(managers: &[DetectedManager], prefix: &str) -> Vec<usize> {
+    let prefix = prefix.to_lowercase();
+    let exact = managers
         .iter()
-        .filter(|m| matches!(m.status, ManagerStatus::Failed(_)))
-        .count();
-    let incomplete = total - successful - failed;
-
-    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("                           SPINE UPGRADE SUMMARY");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-
-    println!("\nOverall Results:");
-    println!("  Total Managers:    {total}");
-    println!(
-        "  ✓ Successful:      {} ({:.1}%)",
-        successful,
-        (successful as f32 / total as f32) * 100.0
-    );
-    println!(
-        "  ✗ Failed:          {} ({:.1}%)",
-        failed,
-        (failed as f32 / total as f32) * 100.0
-    );
+        .position(|manager| manager.name.to_lowercase() == prefix);
+    match exact {
+        Some(index) => vec![index],
+        None => managers
+            .iter()
+            .enumerate()
+            .filter(|(_, manager)| manager.name.to_lowercase().starts_with(&prefix))
+            .map(|(index, _)| index)
+            .collect(),
+    }
+}
 
-    if incomplete > 0 {
-        println!(
-            "  ? Incomplete:      {} ({:.1}%)",
-            incomplete,
-            (incomplete as f32 / total as f32) * 100.0
-        );
+/// What the palette line could complete to: commands while the first word
+/// is being typed, then managers for the commands that take one.
+fn palette_suggestions(line: &str, managers: &[DetectedManager]) -> Vec<String> {
+    let (word, argument) = split_command(line);
+    let commands = matching_commands(word);
+    let typing_argument = line.trim_start().contains(char::is_whitespace);
+    match commands.as_slice() {
+        [(_, "[manager]", _)] if typing_argument => matching_managers(managers, argument)
+            .into_iter()
+            .map(|index| managers[index].name.clone())
+            .collect(),
+        _ if typing_argument => Vec::new(),
+        _ => commands
+            .iter()
+            .map(|(name, argument, _)| format!("{name} {argument}").trim_end().to_string())
+            .collect(),
     }
+}
 
-    println!("\nDetailed Results:");
-    for manager in managers {
-        match &manager.status {
-            ManagerStatus::Success => {
-                println!("  ✓ {:<20} Success", manager.name);
-            }
-            ManagerStatus::Failed(err) => {
-                println!("  ✗ {:<20} Failed", manager.name);
-                println!("    └─ Error: {err}");
-            }
-            _ => {
-                println!("  ? {:<20} Incomplete", manager.name);
-            }
+/// The palette line with its last word completed, if only one thing fits.
+fn complete_command(line: &str, managers: &[DetectedManager]) -> String {
+    let (word, argument) = split_command(line);
+    let commands = matching_commands(word);
+    let [(name, takes, _)] = commands.as_slice() else {
+        return line.to_string();
+    };
+    if !line.trim_start().contains(char::is_whitespace) {
+        return if takes.is_empty() {
+            name.to_string()
+        } else {
+            format!("{name} ")
+        };
+    }
+    match matching_managers(managers, argument).as_slice() {
+        [index] if *takes == "[manager]" => format!("{name} {}", managers[*index].name),
+        _ => line.to_string(),
+    }
+}
+
+/// Resolve a palette line, with `current` as the manager commands apply to
+/// when none is named. Nothing for an empty line, or the message to show
+/// for one that can't run.
+fn parse_command(
+    line: &str,
+    managers: &[DetectedManager],
+    current: usize,
+) -> Option<Result<PaletteCommand, String>> {
+    let (word, argument) = split_command(line);
+    if word.is_empty() {
+        return None;
+    }
+    let manager = || {
+        if argument.is_empty() {
+            return Ok(current);
+        }
+        match matching_managers(managers, argument).as_slice() {
+            [index] => Ok(*index),
+            _ => Err(t!("tui-palette-no-manager", name = argument)),
         }
+    };
+    let command = match matching_commands(word).as_slice() {
+        [(name, ..)] => match *name {
+            "quit" => Ok(PaletteCommand::Quit),
+            "retry" => manager().map(PaletteCommand::Retry),
+            "cancel" => manager().map(PaletteCommand::Cancel),
+            "filter" => Ok(PaletteCommand::Filter(argument.to_string())),
+            "logs" => manager().map(PaletteCommand::Logs),
+            _ => Ok(PaletteCommand::Help),
+        },
+        _ => Err(t!("tui-palette-unknown", command = word)),
+    };
+    Some(command)
+}
+
+/// Scroll to the next line matching an error pattern after the one last
+/// jumped to, starting over from the top after the last match.
+fn jump_to_next_error(manager: &DetectedManager, state: &mut LogsViewState, patterns: &RegexSet) {
+    let is_error = |line: &str| patterns.is_match(line);
+    let from = state.error_line.map_or(0, |line| line + 1);
+    let found = manager
+        .logs
+        .find_line(from, is_error)
+        .or_else(|| manager.logs.find_line(0, is_error));
+    state.no_errors = found.is_none();
+    state.error_line = found;
+    if let Some(line) = found {
+        state.follow = false;
+        state.scroll_offset = line;
     }
+}
 
-    if failed > 0 {
-        println!("\n⚠️  Some package managers failed to upgrade completely.");
-        println!("   Check the error details above and consider running 'spn upgrade' again.");
-        println!("   You may also need to run the failed managers manually with sudo privileges.");
-    } else if successful > 0 {
-        println!("\n🎉 All package managers upgraded successfully!");
-        println!("   Your system is now up to date.");
+/// A manager's complete output as plain text, for pasting or saving.
+fn export_text(manager: &DetectedManager) -> String {
+    let text = manager.logs.full_text();
+    match Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]") {
+        Ok(escapes) => escapes.replace_all(&text, "").into_owned(),
+        Err(_) => text,
     }
+}
+
+async fn copy_logs(manager: &DetectedManager) -> String {
+    match clipboard::copy(&export_text(manager)).await {
+        Ok(_) => t!("tui-logs-copied", name = manager.name.as_str()),
+        Err(e) => t!("tui-logs-copy-failed", error = format!("{e:#}")),
+    }
+}
 
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+/// A file in the current directory named after the manager and the time.
+fn default_save_path(manager: &DetectedManager) -> String {
+    format!(
+        "spine-{}-{}.log",
+        manager.name,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+async fn save_logs(manager: &DetectedManager, path: &str) -> String {
+    let path = match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    };
+    match tokio::fs::write(&path, export_text(manager)).await {
+        Ok(()) => t!("tui-logs-saved", path = path.display().to_string()),
+        Err(e) => t!(
+            "tui-logs-save-failed",
+            path = path.display().to_string(),
+            error = e.to_string()
+        ),
+    }
+}
+
+/// Style captured output from its ANSI escape sequences, or with `colors`
+/// off drop them so the text takes the view's own color.
+fn log_text(raw: &str, colors: bool) -> Text<'static> {
+    let Ok(mut text) = raw.into_text() else {
+        return Text::from(raw.to_string());
+    };
+    if !colors {
+        for line in &mut text.lines {
+            line.style = Style::default();
+            for span in &mut line.spans {
+                span.style = Style::default();
+            }
+        }
+    }
+    text
 }
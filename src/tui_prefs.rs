@@ -0,0 +1,82 @@
+use crate::config::{self, ListSort};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Height of the detail view's config pane unless it's been resized.
+pub const DEFAULT_INFO_HEIGHT: u16 = 8;
+/// Smallest and largest the config pane can be resized to.
+pub const MIN_INFO_HEIGHT: u16 = 3;
+pub const MAX_INFO_HEIGHT: u16 = 30;
+
+/// The view the TUI was left in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    #[default]
+    List,
+    Detail,
+    Logs,
+}
+
+/// How the TUI was left, restored the next time it opens.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TuiPrefs {
+    #[serde(default)]
+    pub view: View,
+    /// The manager the detail or logs view was showing, or the one selected
+    /// in the list.
+    #[serde(default)]
+    pub manager: Option<String>,
+    /// Height of the detail view's config pane, above the status and logs.
+    #[serde(default = "default_info_height")]
+    pub info_height: u16,
+    /// Whether logs views start following new output.
+    #[serde(default)]
+    pub follow: bool,
+    /// The list order last picked with `s`, used over `[tui] sort`.
+    #[serde(default)]
+    pub sort: Option<ListSort>,
+}
+
+fn default_info_height() -> u16 {
+    DEFAULT_INFO_HEIGHT
+}
+
+impl Default for TuiPrefs {
+    fn default() -> Self {
+        TuiPrefs {
+            view: View::List,
+            manager: None,
+            info_height: DEFAULT_INFO_HEIGHT,
+            follow: false,
+            sort: None,
+        }
+    }
+}
+
+fn prefs_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("tui.toml"))
+}
+
+pub async fn load_prefs() -> TuiPrefs {
+    let Some(path) = prefs_path() else {
+        return TuiPrefs::default();
+    };
+
+    let mut prefs: TuiPrefs = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => toml::from_str(&content).unwrap_or_default(),
+        Err(_) => TuiPrefs::default(),
+    };
+    prefs.info_height = prefs.info_height.clamp(MIN_INFO_HEIGHT, MAX_INFO_HEIGHT);
+    prefs
+}
+
+pub async fn save_prefs(prefs: &TuiPrefs) -> Result<()> {
+    let path = prefs_path().ok_or_else(|| anyhow::anyhow!("No data directory available"))?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, toml::to_string(prefs)?).await?;
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use crate::apprise;
+use crate::config::{NotifyConfig, WebhookConfig, WebhookEvent};
+use crate::redact::redact;
+use crate::secrets;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::task::JoinSet;
+
+/// How long one webhook request may take; a slow receiver shouldn't hold up
+/// the run for long.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl WebhookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::RunStarted => "run_started",
+            WebhookEvent::ManagerFinished => "manager_finished",
+            WebhookEvent::ManagerFailed => "manager_failed",
+            WebhookEvent::RunFinished => "run_finished",
+        }
+    }
+}
+
+/// Send `event` with `fields` to every webhook subscribed to it, and to the
+/// notification URLs if it's one of their `url_events`, all at once.
+/// Returns a message for each request that failed.
+pub async fn fire(
+    notify: &NotifyConfig,
+    event: WebhookEvent,
+    fields: Vec<(&str, Value)>,
+) -> Vec<String> {
+    let hooks: Vec<&WebhookConfig> = notify
+        .webhooks
+        .iter()
+        .filter(|hook| hook.events.contains(&event))
+        .collect();
+    let urls = if notify.url_events.contains(&event) {
+        notify.urls.as_slice()
+    } else {
+        &[]
+    };
+    if hooks.is_empty() && urls.is_empty() {
+        return Vec::new();
+    }
+
+    let mut payload = Map::new();
+    payload.insert("event".to_string(), event.name().into());
+    payload.insert("host".to_string(), hostname().into());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    payload.insert("time".to_string(), now.into());
+    // Errors can quote command output
+    for (key, value) in fields {
+        let value = match value {
+            Value::String(text) => redact(&text).into_owned().into(),
+            other => other,
+        };
+        payload.insert(key.to_string(), value);
+    }
+
+    let mut requests = JoinSet::new();
+    let mut errors = Vec::new();
+    let mut expanded = Vec::with_capacity(urls.len());
+    for url in urls {
+        match secrets::expand(url).await {
+            Ok(url) => expanded.push(url),
+            Err(e) => errors.push(format!("notification URL skipped: {e:#}")),
+        }
+    }
+    if !expanded.is_empty() {
+        let urls = expanded;
+        let (title, message) = apprise::message(event, &payload);
+        requests.spawn(async move {
+            let errors = apprise::send(&urls, &title, &message).await;
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors.join("; "))
+            }
+        });
+    }
+    for hook in hooks {
+        let url = hook.url.clone();
+        let hook = match with_secrets(hook).await {
+            Ok(hook) => hook,
+            Err(e) => {
+                errors.push(format!("webhook {url} skipped: {e:#}"));
+                continue;
+            }
+        };
+        let body = match &hook.body {
+            Some(template) => fill_template(template, &payload),
+            None => Value::Object(payload.clone()).to_string(),
+        };
+        requests.spawn(async move {
+            send(&hook, &body)
+                .await
+                .map_err(|e| format!("webhook {url} failed: {e:#}"))
+        });
+    }
+    while let Some(joined) = requests.join_next().await {
+        if let Ok(Err(e)) = joined {
+            errors.push(e);
+        }
+    }
+    errors
+}
+
+/// The webhook with the `${secret:NAME}` references in its URL, headers and
+/// body template filled in. The template is filled before the payload goes
+/// in, so text from a run can't pull in a secret.
+async fn with_secrets(hook: &WebhookConfig) -> Result<WebhookConfig> {
+    let mut hook = hook.clone();
+    hook.url = secrets::expand(&hook.url).await?;
+    for value in hook.headers.values_mut() {
+        *value = secrets::expand(value).await?;
+    }
+    if let Some(body) = &hook.body {
+        hook.body = Some(secrets::expand(body).await?);
+    }
+    Ok(hook)
+}
+
+/// Replace each `{field}` with its value, escaped for use inside a JSON
+/// string. Unset fields become empty.
+fn fill_template(template: &str, payload: &Map<String, Value>) -> String {
+    payload
+        .iter()
+        .fold(template.to_string(), |body, (key, value)| {
+            let text = match value {
+                Value::String(text) => {
+                    let quoted = Value::String(text.clone()).to_string();
+                    quoted[1..quoted.len() - 1].to_string()
+                }
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            body.replace(&format!("{{{key}}}"), &text)
+        })
+}
+
+async fn send(hook: &WebhookConfig, body: &str) -> Result<()> {
+    let mut headers: Vec<(&str, &str)> = hook
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    headers.sort();
+    request(&hook.method, &hook.url, &headers, body).await
+}
+
+/// Send an HTTP request through curl, which is on every system spine
+/// supports. The URL, headers and body go to curl as a config file on its
+/// stdin rather than as arguments, which any user could read from the
+/// process list along with the tokens in them. The body is JSON unless
+/// `headers` set a Content-Type.
+pub async fn request(method: &str, url: &str, headers: &[(&str, &str)], body: &str) -> Result<()> {
+    let mut config = format!("url = {}\n", curl_quote(url));
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+    {
+        config.push_str("header = \"Content-Type: application/json\"\n");
+    }
+    for (name, value) in headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            curl_quote(&format!("{name}: {value}"))
+        ));
+    }
+    config.push_str(&format!("data-raw = {}\n", curl_quote(body)));
+
+    let mut cmd = Command::new("curl");
+    cmd.args(["--silent", "--show-error", "--fail", "--max-time"])
+        .arg(WEBHOOK_TIMEOUT.as_secs().to_string())
+        .args(["--request", method])
+        .args(["--output", "/dev/null", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = cmd.spawn().context("Couldn't run curl")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).await?;
+    }
+    let output = tokio::time::timeout(WEBHOOK_TIMEOUT * 2, child.wait_with_output())
+        .await
+        .context("curl didn't finish")??;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// `value` as a double-quoted string in a curl config file, where a line
+/// break would end the option.
+pub(crate) fn curl_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buffer = [0u8; 256];
+        // SAFETY: gethostname writes at most buffer.len() bytes into it
+        if unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) } == 0 {
+            let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+            return String::from_utf8_lossy(&buffer[..end]).into_owned();
+        }
+    }
+    String::new()
+}
@@ -0,0 +1,99 @@
+use crate::config::{Config, ManagerConfig};
+use crate::execute::shell_quote;
+use regex::Regex;
+use serde_json::json;
+use std::sync::LazyLock;
+
+/// `wsl.exe ... -u root`, which enters a distro as root without sudo.
+static AS_ROOT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bwsl(\.exe)?\s[^|;&]*?(-u|--user)\s+root\b").expect("wsl root pattern")
+});
+
+/// Whether spn runs inside Windows Subsystem for Linux.
+pub fn inside_wsl() -> bool {
+    std::env::var_os("WSL_DISTRO_NAME").is_some()
+        || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+/// Whether any of the manager's commands enter a WSL distro as root. That
+/// takes no sudo, but needs the same confirmation as a sudo manager.
+pub fn runs_as_root(config: &ManagerConfig) -> bool {
+    serde_json::to_string(config).is_ok_and(|json| AS_ROOT.is_match(&json))
+}
+
+/// Add the managers `[wsl]` asks for when running inside WSL: winget and
+/// `wsl --update` on the Windows side, and APT in each of the listed distros.
+/// They go through WSL's interop with Windows executables, and managers
+/// defined in the config under the same names take precedence.
+pub fn add_managers(config: &mut Config) {
+    if (!config.wsl.windows && config.wsl.distros.is_empty()) || !inside_wsl() {
+        return;
+    }
+
+    // wsl.exe writes UTF-16 unless told otherwise, and only variables listed
+    // in WSLENV cross over to Windows
+    let utf8 = json!({ "WSL_UTF8": "1", "WSLENV": "WSL_UTF8" });
+    let mut managers = Vec::new();
+    if config.wsl.windows {
+        managers.push((
+            "winget".to_string(),
+            json!({
+                "name": "winget (Windows)",
+                "check_command": "winget.exe --version",
+                "refresh": "winget.exe source update",
+                "upgrade_all": "winget.exe upgrade --all --silent --accept-package-agreements --accept-source-agreements --disable-interactivity",
+                "outdated_command": "winget.exe upgrade --disable-interactivity | tr -d '\\r' | awk '/^-+$/ { list = 1; next } list && NF && !/upgrades? available/'",
+                "requires_sudo": false,
+            }),
+        ));
+        managers.push((
+            "wsl".to_string(),
+            json!({
+                "name": "WSL",
+                "check_command": "wsl.exe --version",
+                "upgrade_all": "wsl.exe --update",
+                "env": utf8.clone(),
+                "requires_sudo": false,
+            }),
+        ));
+    }
+
+    let current = std::env::var("WSL_DISTRO_NAME").unwrap_or_default();
+    for distro in config
+        .wsl
+        .distros
+        .iter()
+        .filter(|distro| **distro != current)
+    {
+        // Only what changes packages runs as root
+        let query = format!("wsl.exe -d {} --", shell_quote(distro));
+        let run = format!("wsl.exe -d {} -u root --", shell_quote(distro));
+        managers.push((
+            format!("apt@{distro}"),
+            json!({
+                "name": format!("APT ({distro})"),
+                "instance": distro,
+                "check_command": format!("{query} apt-get --version"),
+                "refresh": format!("{run} apt-get update"),
+                "upgrade_all": format!("{run} env DEBIAN_FRONTEND=noninteractive apt-get upgrade -y"),
+                "cleanup": format!("{run} apt-get autoremove -y"),
+                "outdated_command": format!("{query} apt list --upgradable 2>/dev/null | grep / || true"),
+                "env": utf8.clone(),
+                "requires_sudo": false,
+            }),
+        ));
+    }
+
+    for (key, definition) in managers {
+        if config.managers.contains_key(&key) {
+            continue;
+        }
+        match serde_json::from_value::<ManagerConfig>(definition) {
+            Ok(manager) => {
+                config.managers.insert(key, manager);
+            }
+            Err(e) => eprintln!("Warning: could not add WSL manager {key}: {e}"),
+        }
+    }
+}